@@ -285,10 +285,13 @@ pub(crate) fn generate_schema(
     let mut num_funcs = 0;
     let mut num_types = 0;
     let mut num_enums = 0;
+    let mut num_ranges = 0;
     let mut num_sqls = 0;
     let mut num_ords = 0;
     let mut num_hashes = 0;
     let mut num_aggregates = 0;
+    let mut num_access_methods = 0;
+    let mut num_foreign_data_wrappers = 0;
     for func in &fns_to_call {
         if func.starts_with("__pgx_internals_schema_") {
             let schema = func
@@ -303,6 +306,8 @@ pub(crate) fn generate_schema(
             num_types += 1;
         } else if func.starts_with("__pgx_internals_enum_") {
             num_enums += 1;
+        } else if func.starts_with("__pgx_internals_range_") {
+            num_ranges += 1;
         } else if func.starts_with("__pgx_internals_sql_") {
             num_sqls += 1;
         } else if func.starts_with("__pgx_internals_ord_") {
@@ -311,11 +316,15 @@ pub(crate) fn generate_schema(
             num_hashes += 1;
         } else if func.starts_with("__pgx_internals_aggregate_") {
             num_aggregates += 1;
+        } else if func.starts_with("__pgx_internals_am_") {
+            num_access_methods += 1;
+        } else if func.starts_with("__pgx_internals_fdw_") {
+            num_foreign_data_wrappers += 1;
         }
     }
 
     println!(
-        "{} {} SQL entities: {} schemas ({} unique), {} functions, {} types, {} enums, {} sqls, {} ords, {} hashes, {} aggregates",
+        "{} {} SQL entities: {} schemas ({} unique), {} functions, {} types, {} enums, {} ranges, {} sqls, {} ords, {} hashes, {} aggregates, {} access methods, {} foreign data wrappers",
         "  Discovered".bold().green(),
         fns_to_call.len().to_string().bold().cyan(),
         seen_schemas.iter().count().to_string().bold().cyan(),
@@ -323,10 +332,13 @@ pub(crate) fn generate_schema(
         num_funcs.to_string().bold().cyan(),
         num_types.to_string().bold().cyan(),
         num_enums.to_string().bold().cyan(),
+        num_ranges.to_string().bold().cyan(),
         num_sqls.to_string().bold().cyan(),
         num_ords.to_string().bold().cyan(),
         num_hashes.to_string().bold().cyan(),
         num_aggregates.to_string().bold().cyan(),
+        num_access_methods.to_string().bold().cyan(),
+        num_foreign_data_wrappers.to_string().bold().cyan(),
     );
 
     // Now run the generator with the correct symbol table
@@ -583,6 +583,161 @@ fn rewrite_item_fn(
     }
 }
 
+/**
+Declare a function as a trigger function, callable from `CREATE TRIGGER`.
+
+It must take a single `&PgTrigger` argument and return `Option<PgHeapTuple>`.  The returned tuple
+replaces the row a `BEFORE ... FOR EACH ROW` trigger fired for, and `None` skips the operation for
+that row.  Other kinds of triggers should return `None`, as Postgres ignores their result.
+
+The generated SQL declares the function as `RETURNS trigger`.  This macro accepts the same
+arguments as `#[pg_extern]`.
+
+```rust,ignore
+use pgx::*;
+
+#[pg_trigger]
+fn uppercase_name(trigger: &PgTrigger) -> Option<PgHeapTuple<'_>> {
+    let new = trigger.new()?;
+    warning!("{} fired for {:?}", trigger.name(), new.get_by_name::<&str>("name"));
+    Some(new)
+}
+```
+
+*/
+#[proc_macro_attribute]
+pub fn pg_trigger(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::Item);
+    match ast {
+        Item::Fn(mut func) => {
+            if func.sig.inputs.len() != 1 {
+                panic!("#[pg_trigger] functions must take a single `&PgTrigger` argument");
+            }
+
+            // the trigger function is nested inside a function with the calling convention
+            // Postgres expects, which `#[pg_extern]` then wraps as usual.  Its attributes, such as
+            // doc comments, belong to the outer function
+            let ident = func.sig.ident.clone();
+            let vis = std::mem::replace(&mut func.vis, syn::Visibility::Inherited);
+            let attrs = std::mem::take(&mut func.attrs);
+            let func_span = func.span();
+
+            let wrapper = quote_spanned! {func_span=>
+                #(#attrs)*
+                #vis unsafe fn #ident(fcinfo: pgx::pg_sys::FunctionCallInfo) -> pgx::pg_sys::Datum {
+                    #func
+
+                    let trigger = pgx::PgTrigger::from_fcinfo(fcinfo);
+                    let tuple = #ident(&trigger);
+
+                    // a NULL pointer, not a NULL Datum, tells Postgres to skip the operation
+                    tuple.map_or(0, |tuple| tuple.into_pg() as pgx::pg_sys::Datum)
+                }
+            };
+
+            pg_extern(attr, wrapper.into())
+        }
+        _ => panic!("#[pg_trigger] can only be applied to top-level functions"),
+    }
+}
+
+/**
+Declare a function as an event trigger function, callable from `CREATE EVENT TRIGGER`.
+
+It must take a single `&PgEventTrigger` argument and return nothing.  The generated SQL declares the
+function as `RETURNS event_trigger`.
+
+Optionally, give an `event` (`ddl_command_start`, `ddl_command_end`, `sql_drop` or
+`table_rewrite`), and any command `tags` to filter on, to also generate the
+`CREATE EVENT TRIGGER`, which is named after the function.  Any other arguments are passed on to
+`#[pg_extern]`:
+
+```rust,ignore
+use pgx::*;
+
+#[pg_event_trigger(event = "ddl_command_end", tags = ["CREATE TABLE", "ALTER TABLE"])]
+fn audit_ddl(trigger: &PgEventTrigger) {
+    for command in trigger.ddl_commands() {
+        log!("{} {}", command.command_tag, command.object_identity);
+    }
+}
+```
+
+*/
+#[proc_macro_attribute]
+pub fn pg_event_trigger(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::Item);
+    match ast {
+        Item::Fn(mut func) => {
+            if func.sig.inputs.len() != 1 {
+                panic!(
+                    "#[pg_event_trigger] functions must take a single `&PgEventTrigger` argument"
+                );
+            }
+
+            // as with `#[pg_trigger]`, the function is nested inside one with the calling
+            // convention Postgres expects.  `#[event_trigger]` tells `#[pg_extern]` it's an event
+            // trigger, and carries the `CREATE EVENT TRIGGER` options, while everything else is
+            // for `#[pg_extern]` itself
+            let ident = func.sig.ident.clone();
+            let vis = std::mem::replace(&mut func.vis, syn::Visibility::Inherited);
+            let attrs = std::mem::take(&mut func.attrs);
+            let (event_trigger_attr, extern_attr) = split_event_trigger_args(attr.into());
+            let func_span = func.span();
+
+            let wrapper = quote_spanned! {func_span=>
+                #(#attrs)*
+                #[pgx::event_trigger(#event_trigger_attr)]
+                #vis unsafe fn #ident(fcinfo: pgx::pg_sys::FunctionCallInfo) -> pgx::pg_sys::Datum {
+                    #func
+
+                    let trigger = pgx::PgEventTrigger::from_fcinfo(fcinfo);
+                    #ident(&trigger);
+                    0
+                }
+            };
+
+            pg_extern(extern_attr.into(), wrapper.into())
+        }
+        _ => panic!("#[pg_event_trigger] can only be applied to top-level functions"),
+    }
+}
+
+/// Separate the `event` and `tags` arguments of `#[pg_event_trigger]` from the rest, which are
+/// `#[pg_extern]` arguments
+fn split_event_trigger_args(
+    attr: proc_macro2::TokenStream,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let mut args = vec![proc_macro2::TokenStream::new()];
+    for token in attr {
+        match &token {
+            proc_macro2::TokenTree::Punct(punct) if punct.as_char() == ',' => {
+                args.push(proc_macro2::TokenStream::new())
+            }
+            _ => args.last_mut().unwrap().extend(std::iter::once(token)),
+        }
+    }
+
+    let (event_trigger_args, extern_args): (Vec<_>, Vec<_>) = args
+        .into_iter()
+        .filter(|arg| !arg.is_empty())
+        .partition(|arg| match arg.clone().into_iter().next() {
+            Some(proc_macro2::TokenTree::Ident(ident)) => ident == "event" || ident == "tags",
+            _ => false,
+        });
+    (
+        quote! { #(#event_trigger_args),* },
+        quote! { #(#extern_args),* },
+    )
+}
+
+/// Associated macro for `#[pg_event_trigger]`.  Used by the SQL generator, and not meant to be
+/// applied by hand
+#[proc_macro_attribute]
+pub fn event_trigger(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
 /**
 Generate necessary bindings for using the enum with PostgreSQL.
 
@@ -666,6 +821,101 @@ fn impl_postgres_enum(ast: DeriveInput) -> proc_macro2::TokenStream {
     stream
 }
 
+/**
+Generate necessary bindings for using a `Range<T>` newtype as a user-defined Postgres range type.
+
+```rust,ignore
+use pgx::*;
+#[derive(Debug, PostgresRange)]
+#[pgx(subdiff = float_range_subdiff)]
+struct FloatRange(Range<f64>);
+
+fn float_range_subdiff(a: f64, b: f64) -> f64 {
+    a - b
+}
+```
+
+This emits `CREATE TYPE FloatRange AS RANGE (SUBTYPE = double precision, ...)`.
+
+Optionally accepts the following attributes:
+
+* `canonical`: A `fn(Range<T>) -> Range<T>` used as the range type's `CANONICAL` function.
+* `subdiff`: A `fn(T, T) -> f64` used as the range type's `SUBTYPE_DIFF` function.
+* `sql`: Same arguments as [`#[pgx(sql = ..)]`](macro@pgx).
+*/
+#[proc_macro_derive(PostgresRange, attributes(pgx))]
+pub fn postgres_range(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+
+    impl_postgres_range(ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn impl_postgres_range(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let sql_graph_entity_item = sql_entity_graph::PostgresRange::from_derive_input(ast)?;
+    let name = sql_graph_entity_item.name();
+    let name_string = name.to_string();
+    let subtype = sql_graph_entity_item.subtype();
+
+    let mut stream = quote! {
+        impl pgx::FromDatum for #name {
+            #[inline]
+            unsafe fn from_datum(datum: pgx::pg_sys::Datum, is_null: bool, typoid: pgx::pg_sys::Oid) -> Option<#name> {
+                <pgx::Range<#subtype> as pgx::FromDatum>::from_datum(datum, is_null, typoid).map(#name)
+            }
+        }
+
+        impl pgx::IntoDatum for #name {
+            #[inline]
+            fn into_datum(self) -> Option<pgx::pg_sys::Datum> {
+                self.0.into_range_datum(Self::type_oid())
+            }
+
+            fn type_oid() -> pgx::pg_sys::Oid {
+                pgx::regtypein(#name_string)
+            }
+        }
+    };
+
+    let wrappers = [
+        sql_graph_entity_item.canonical().map(|canonical| {
+            (
+                sql_graph_entity_item.canonical_sql_name(),
+                quote! { pgx::call_range_canonical::<#subtype>(fcinfo, #canonical) },
+            )
+        }),
+        sql_graph_entity_item.subdiff().map(|subdiff| {
+            (
+                sql_graph_entity_item.subdiff_sql_name(),
+                quote! { pgx::call_range_subdiff::<#subtype>(fcinfo, #subdiff) },
+            )
+        }),
+    ];
+    for (sql_name, call) in wrappers.iter().flatten() {
+        let wrapper_ident = Ident::new(&format!("{}_wrapper", sql_name), name.span());
+        let finfo_ident = Ident::new(&format!("pg_finfo_{}_wrapper", sql_name), name.span());
+        stream.extend(quote! {
+            #[no_mangle]
+            #[doc(hidden)]
+            pub extern "C" fn #finfo_ident() -> &'static pgx::pg_sys::Pg_finfo_record {
+                const V1_API: pgx::pg_sys::Pg_finfo_record = pgx::pg_sys::Pg_finfo_record { api_version: 1 };
+                &V1_API
+            }
+
+            #[pgx::pg_guard]
+            #[doc(hidden)]
+            pub unsafe extern "C" fn #wrapper_ident(fcinfo: pgx::pg_sys::FunctionCallInfo) -> pgx::pg_sys::Datum {
+                #call
+            }
+        });
+    }
+
+    sql_graph_entity_item.to_tokens(&mut stream);
+
+    Ok(stream)
+}
+
 /**
 Generate necessary bindings for using the type with PostgreSQL.
 
@@ -980,6 +1230,98 @@ pub fn postgres_hash(input: TokenStream) -> TokenStream {
         .into()
 }
 
+/**
+Generate a `pgx::FromSpiRow` implementation, mapping each field of a struct onto the SPI result
+column of the same name.
+
+Fields of type `Option<T>` accept NULL values.  Column types are checked against each field's
+type, and mismatches are returned as a `pgx::SpiError` naming the column.
+
+```rust,ignore
+use pgx::*;
+#[derive(FromSpiRow)]
+struct Dog {
+    name: String,
+    age: Option<i32>,
+}
+```
+*/
+#[proc_macro_derive(FromSpiRow)]
+pub fn from_spi_row(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    impl_from_spi_row(ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn impl_from_spi_row(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &ast.data {
+        Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => {
+            return Err(syn::Error::new(
+                ast.span(),
+                "#[derive(FromSpiRow)] can only be applied to structs with named fields",
+            ))
+        }
+    };
+
+    let mut field_values = proc_macro2::TokenStream::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field has no ident");
+        let column = ident.to_string();
+        let column = column.strip_prefix("r#").unwrap_or(&column);
+
+        let value = match option_inner_type(&field.ty) {
+            Some(inner) => quote_spanned! {field.ty.span()=>
+                row.get_by_name::<#inner>(#column)?
+            },
+            None => {
+                let ty = &field.ty;
+                quote_spanned! {field.ty.span()=>
+                    row.get_by_name::<#ty>(#column)?.ok_or_else(|| pgx::SpiError::UnexpectedNull {
+                        column: #column.to_string(),
+                    })?
+                }
+            }
+        };
+        field_values.extend(quote! { #ident: #value, });
+    }
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics pgx::FromSpiRow for #name #ty_generics #where_clause {
+            fn from_spi_row(row: &pgx::SpiHeapTupleData) -> std::result::Result<Self, pgx::SpiError> {
+                Ok(#name {
+                    #field_values
+                })
+            }
+        }
+    })
+}
+
+/// If `ty` is an `Option<T>`, return `T`
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let path = match ty {
+        syn::Type::Path(type_path) if type_path.qself.is_none() => &type_path.path,
+        _ => return None,
+    };
+    let last = path.segments.last()?;
+    if last.ident != "Option" {
+        return None;
+    }
+    match &last.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(syn::GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 /**
 Declare a `pgx::Aggregate` implentation on a type as able to used by Postgres as an aggregate.
 
@@ -1006,6 +1348,89 @@ pub fn pg_aggregate(_attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
+/**
+Declare a `pgx::IndexAm` implementation on a type as an index access method.
+
+Generates the `index_am_handler` function and the `CREATE ACCESS METHOD ... TYPE INDEX` SQL.
+
+```rust,ignore
+use pgx::*;
+
+pub struct BloomAm;
+
+#[pg_index_am]
+impl IndexAm for BloomAm {
+    const NAME: &'static str = "bloom";
+    // ...
+}
+```
+
+Optionally accepts the following attributes on the `impl`:
+
+* `sql`: Same arguments as [`#[pgx(sql = ..)]`](macro@pgx).
+*/
+#[proc_macro_attribute]
+pub fn pg_index_am(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    fn wrapped(item_impl: ItemImpl) -> Result<TokenStream, syn::Error> {
+        let sql_graph_entity_item = sql_entity_graph::PgIndexAm::new(item_impl)?;
+
+        Ok(sql_graph_entity_item.to_token_stream().into())
+    }
+
+    let parsed_base = parse_macro_input!(item as syn::ItemImpl);
+    match wrapped(parsed_base) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let msg = e.to_string();
+            TokenStream::from(quote! {
+              compile_error!(#msg);
+            })
+        }
+    }
+}
+
+/**
+Declare a `pgx::TableAm` implementation on a type as a table access method.
+
+Generates the `table_am_handler` function and the `CREATE ACCESS METHOD ... TYPE TABLE` SQL.
+Table access methods require Postgres 12 or newer.
+
+```rust,ignore
+use pgx::*;
+
+pub struct ColumnarAm;
+
+#[pg_table_am]
+impl TableAm for ColumnarAm {
+    const NAME: &'static str = "columnar";
+    // ...
+}
+```
+
+Optionally accepts the following attributes on the `impl`:
+
+* `sql`: Same arguments as [`#[pgx(sql = ..)]`](macro@pgx).
+*/
+#[proc_macro_attribute]
+pub fn pg_table_am(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    fn wrapped(item_impl: ItemImpl) -> Result<TokenStream, syn::Error> {
+        let sql_graph_entity_item = sql_entity_graph::PgTableAm::new(item_impl)?;
+
+        Ok(sql_graph_entity_item.to_token_stream().into())
+    }
+
+    let parsed_base = parse_macro_input!(item as syn::ItemImpl);
+    match wrapped(parsed_base) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let msg = e.to_string();
+            TokenStream::from(quote! {
+              compile_error!(#msg);
+            })
+        }
+    }
+}
+
 /**
 A helper attribute for various contexts.
 
@@ -1030,3 +1455,109 @@ Currently `sql` can be provided one of the following:
 pub fn pgx(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
+
+/**
+Declare a `pgx::ForeignDataWrapper` implementation on a type as a foreign data wrapper.
+
+Generates the `fdw_handler` function, the options validator function, and the
+`CREATE FOREIGN DATA WRAPPER` SQL.
+
+```rust,ignore
+use pgx::*;
+
+pub struct CsvFdw;
+
+#[pg_foreign_data_wrapper]
+impl ForeignDataWrapper for CsvFdw {
+    type ScanState = std::io::Lines<std::io::BufReader<std::fs::File>>;
+    type Row = (String,);
+    // ...
+}
+```
+
+Optionally accepts the following attributes on the `impl`:
+
+* `sql`: Same arguments as [`#[pgx(sql = ..)]`](macro@pgx).
+*/
+#[proc_macro_attribute]
+pub fn pg_foreign_data_wrapper(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    fn wrapped(item_impl: ItemImpl) -> Result<TokenStream, syn::Error> {
+        let sql_graph_entity_item = sql_entity_graph::PgForeignDataWrapper::new(item_impl)?;
+
+        Ok(sql_graph_entity_item.to_token_stream().into())
+    }
+
+    let parsed_base = parse_macro_input!(item as syn::ItemImpl);
+    match wrapped(parsed_base) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let msg = e.to_string();
+            TokenStream::from(quote! {
+              compile_error!(#msg);
+            })
+        }
+    }
+}
+
+/**
+Declare a `pgx::OutputPlugin` implementation on a type as the extension's logical decoding output
+plugin.
+
+Generates the `_PG_output_plugin_init` function Postgres calls when a replication slot is using
+the extension's shared library as its output plugin, so only one per extension is possible.
+
+```rust,ignore
+use pgx::*;
+
+pub struct JsonChanges;
+
+#[pg_output_plugin]
+impl OutputPlugin for JsonChanges {
+    // ...
+}
+```
+*/
+#[proc_macro_attribute]
+pub fn pg_output_plugin(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    fn wrapped(item_impl: ItemImpl) -> Result<TokenStream, syn::Error> {
+        match &item_impl.trait_ {
+            Some((_, path, _))
+                if path
+                    .segments
+                    .last()
+                    .map(|last| last.ident == "OutputPlugin")
+                    .unwrap_or(false) => {}
+            _ => {
+                return Err(syn::Error::new(
+                    item_impl.span(),
+                    "`#[pg_output_plugin]` only works with the `OutputPlugin` trait.",
+                ))
+            }
+        }
+        let target = &item_impl.self_ty;
+
+        Ok(quote! {
+            #item_impl
+
+            #[no_mangle]
+            #[doc(hidden)]
+            pub unsafe extern "C" fn _PG_output_plugin_init(
+                callbacks: *mut pgx::pg_sys::OutputPluginCallbacks,
+            ) {
+                pgx::output_plugin_init::<#target>(callbacks)
+            }
+        }
+        .into())
+    }
+
+    let parsed_base = parse_macro_input!(item as syn::ItemImpl);
+    match wrapped(parsed_base) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let msg = e.to_string();
+            TokenStream::from(quote! {
+              compile_error!(#msg);
+            })
+        }
+    }
+}
@@ -13,6 +13,8 @@
 #include "access/htup.h"
 #include "access/htup_details.h"
 #include "catalog/pg_type.h"
+#include "commands/explain.h"
+#include "executor/instrument.h"
 #if IS_PG_10 || IS_PG_11
 #include "nodes/relation.h"
 #else
@@ -20,6 +22,9 @@
 #endif
 #include "nodes/pg_list.h"
 #include "parser/parsetree.h"
+#include "portability/instr_time.h"
+#include "replication/logical.h"
+#include "tcop/tcopprot.h"
 #include "utils/memutils.h"
 #include "utils/builtins.h"
 
@@ -107,3 +112,60 @@ PGDLLEXPORT char *pgx_GETSTRUCT(HeapTuple tuple);
 char *pgx_GETSTRUCT(HeapTuple tuple) {
     return GETSTRUCT(tuple);
 }
+
+PGDLLEXPORT StringInfo pgx_LogicalDecodingContext_out(LogicalDecodingContext *ctx);
+StringInfo pgx_LogicalDecodingContext_out(LogicalDecodingContext *ctx) {
+    return ctx->out;
+}
+
+PGDLLEXPORT List *pgx_LogicalDecodingContext_output_plugin_options(LogicalDecodingContext *ctx);
+List *pgx_LogicalDecodingContext_output_plugin_options(LogicalDecodingContext *ctx) {
+    return ctx->output_plugin_options;
+}
+
+PGDLLEXPORT void *pgx_LogicalDecodingContext_output_plugin_private(LogicalDecodingContext *ctx);
+void *pgx_LogicalDecodingContext_output_plugin_private(LogicalDecodingContext *ctx) {
+    return ctx->output_plugin_private;
+}
+
+PGDLLEXPORT void pgx_LogicalDecodingContext_set_output_plugin_private(LogicalDecodingContext *ctx, void *private_data);
+void pgx_LogicalDecodingContext_set_output_plugin_private(LogicalDecodingContext *ctx, void *private_data) {
+    ctx->output_plugin_private = private_data;
+}
+
+/*
+ * What ExplainOneQuery() does when there's no ExplainOneQuery_hook.  It's static in explain.c, so
+ * this follows it line for line.  Postgres 10's hook has no `queryEnv`, so it's given NULL there
+ */
+PGDLLEXPORT void pgx_standard_ExplainOneQuery(Query *query, int cursorOptions, IntoClause *into, ExplainState *es, const char *queryString, ParamListInfo params, QueryEnvironment *queryEnv);
+void pgx_standard_ExplainOneQuery(Query *query, int cursorOptions, IntoClause *into, ExplainState *es, const char *queryString, ParamListInfo params, QueryEnvironment *queryEnv) {
+    PlannedStmt *plan;
+    instr_time planstart, planduration;
+#if PG_VERSION_NUM >= 130000
+    BufferUsage bufusage_start, bufusage;
+
+    if (es->buffers)
+        bufusage_start = pgBufferUsage;
+#endif
+    INSTR_TIME_SET_CURRENT(planstart);
+
+#if PG_VERSION_NUM >= 130000
+    plan = pg_plan_query(query, queryString, cursorOptions, params);
+#else
+    plan = pg_plan_query(query, cursorOptions, params);
+#endif
+
+    INSTR_TIME_SET_CURRENT(planduration);
+    INSTR_TIME_SUBTRACT(planduration, planstart);
+
+#if PG_VERSION_NUM >= 130000
+    if (es->buffers) {
+        memset(&bufusage, 0, sizeof(BufferUsage));
+        BufferUsageAccumDiff(&bufusage, &pgBufferUsage, &bufusage_start);
+    }
+
+    ExplainOnePlan(plan, into, es, queryString, params, queryEnv, &planduration, (es->buffers ? &bufusage : NULL));
+#else
+    ExplainOnePlan(plan, into, es, queryString, params, queryEnv, &planduration);
+#endif
+}
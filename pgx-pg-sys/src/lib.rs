@@ -211,6 +211,39 @@ mod all_versions {
         pub fn pgx_GETSTRUCT(tuple: pg_sys::HeapTuple) -> *mut std::os::raw::c_char;
     }
 
+    // `LogicalDecodingContext` is opaque to bindgen, so its fields are reached through the cshim
+    #[pgx_macros::pg_guard]
+    extern "C" {
+        pub fn pgx_LogicalDecodingContext_out(
+            ctx: *mut super::LogicalDecodingContext,
+        ) -> super::StringInfo;
+        pub fn pgx_LogicalDecodingContext_output_plugin_options(
+            ctx: *mut super::LogicalDecodingContext,
+        ) -> *mut super::List;
+        pub fn pgx_LogicalDecodingContext_output_plugin_private(
+            ctx: *mut super::LogicalDecodingContext,
+        ) -> *mut std::os::raw::c_void;
+        pub fn pgx_LogicalDecodingContext_set_output_plugin_private(
+            ctx: *mut super::LogicalDecodingContext,
+            private_data: *mut std::os::raw::c_void,
+        );
+    }
+
+    // Postgres' own `ExplainOneQuery()` is private to explain.c, so the cshim has a copy of what it
+    // does when there's no `ExplainOneQuery_hook`
+    #[pgx_macros::pg_guard]
+    extern "C" {
+        pub fn pgx_standard_ExplainOneQuery(
+            query: *mut super::Query,
+            cursor_options: i32,
+            into: *mut super::IntoClause,
+            es: *mut super::ExplainState,
+            query_string: *const std::os::raw::c_char,
+            params: super::ParamListInfo,
+            query_env: *mut super::QueryEnvironment,
+        );
+    }
+
     #[inline]
     pub fn VARHDRSZ_EXTERNAL() -> usize {
         offset_of!(super::varattrib_1b_e, va_data)
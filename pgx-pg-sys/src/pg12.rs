@@ -2982,6 +2982,14 @@ pub const TYPECACHE_DOMAIN_BASE_INFO: u32 = 4096;
 pub const TYPECACHE_DOMAIN_CONSTR_INFO: u32 = 8192;
 pub const TYPECACHE_HASH_EXTENDED_PROC: u32 = 16384;
 pub const TYPECACHE_HASH_EXTENDED_PROC_FINFO: u32 = 32768;
+pub const RANGE_EMPTY: u32 = 1;
+pub const RANGE_LB_INC: u32 = 2;
+pub const RANGE_UB_INC: u32 = 4;
+pub const RANGE_LB_INF: u32 = 8;
+pub const RANGE_UB_INF: u32 = 16;
+pub const RANGE_LB_NULL: u32 = 32;
+pub const RANGE_UB_NULL: u32 = 64;
+pub const RANGE_CONTAIN_EMPTY: u32 = 128;
 pub type Oid = ::std::os::raw::c_uint;
 pub type pg_int64 = ::std::os::raw::c_long;
 pub type va_list = __builtin_va_list;
@@ -35847,6 +35855,439 @@ pub type ExplainOneQuery_hook_type = ::std::option::Option<
 extern "C" {
     pub static mut ExplainOneQuery_hook: ExplainOneQuery_hook_type;
 }
+pub type post_parse_analyze_hook_type =
+    ::std::option::Option<unsafe extern "C" fn(pstate: *mut ParseState, query: *mut Query)>;
+#[pg_guard]
+extern "C" {
+    pub static mut post_parse_analyze_hook: post_parse_analyze_hook_type;
+}
+#[pg_guard]
+extern "C" {
+    pub fn parse_analyze(
+        parseTree: *mut RawStmt,
+        sourceText: *const ::std::os::raw::c_char,
+        paramTypes: *mut Oid,
+        numParams: ::std::os::raw::c_int,
+        queryEnv: *mut QueryEnvironment,
+    ) -> *mut Query;
+}
+#[pg_guard]
+extern "C" {
+    pub fn parse_analyze_varparams(
+        parseTree: *mut RawStmt,
+        sourceText: *const ::std::os::raw::c_char,
+        paramTypes: *mut *mut Oid,
+        numParams: *mut ::std::os::raw::c_int,
+    ) -> *mut Query;
+}
+#[pg_guard]
+extern "C" {
+    pub fn parse_sub_analyze(
+        parseTree: *mut Node,
+        parentParseState: *mut ParseState,
+        parentCTE: *mut CommonTableExpr,
+        locked_from_parent: bool,
+        resolve_unknowns: bool,
+    ) -> *mut Query;
+}
+#[pg_guard]
+extern "C" {
+    pub fn transformTopLevelStmt(pstate: *mut ParseState, parseTree: *mut RawStmt) -> *mut Query;
+}
+#[pg_guard]
+extern "C" {
+    pub fn transformStmt(pstate: *mut ParseState, parseTree: *mut Node) -> *mut Query;
+}
+#[pg_guard]
+extern "C" {
+    pub fn analyze_requires_snapshot(parseTree: *mut RawStmt) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn LCS_asString(strength: LockClauseStrength) -> *const ::std::os::raw::c_char;
+}
+#[pg_guard]
+extern "C" {
+    pub fn CheckSelectLocking(qry: *mut Query, strength: LockClauseStrength);
+}
+#[pg_guard]
+extern "C" {
+    pub fn applyLockingClause(
+        qry: *mut Query,
+        rtindex: Index,
+        strength: LockClauseStrength,
+        waitPolicy: LockWaitPolicy,
+        pushedDown: bool,
+    );
+}
+#[pg_guard]
+extern "C" {
+    pub fn BuildOnConflictExcludedTargetlist(targetrel: Relation, exclRelIndex: Index) -> *mut List;
+}
+pub type ClientAuthentication_hook_type =
+    ::std::option::Option<unsafe extern "C" fn(arg1: *mut Port, arg2: ::std::os::raw::c_int)>;
+#[pg_guard]
+extern "C" {
+    pub static mut ClientAuthentication_hook: ClientAuthentication_hook_type;
+}
+#[pg_guard]
+extern "C" {
+    pub static mut pg_krb_server_keyfile: *mut ::std::os::raw::c_char;
+}
+#[pg_guard]
+extern "C" {
+    pub static mut pg_krb_caseins_users: bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn ClientAuthentication(port: *mut Port);
+}
+pub const ObjectAccessType_OAT_POST_CREATE: ObjectAccessType = 0;
+pub const ObjectAccessType_OAT_DROP: ObjectAccessType = 1;
+pub const ObjectAccessType_OAT_POST_ALTER: ObjectAccessType = 2;
+pub const ObjectAccessType_OAT_NAMESPACE_SEARCH: ObjectAccessType = 3;
+pub const ObjectAccessType_OAT_FUNCTION_EXECUTE: ObjectAccessType = 4;
+pub type ObjectAccessType = ::std::os::raw::c_uint;
+pub type object_access_hook_type = ::std::option::Option<
+    unsafe extern "C" fn(
+        access: ObjectAccessType,
+        classId: Oid,
+        objectId: Oid,
+        subId: ::std::os::raw::c_int,
+        arg: *mut ::std::os::raw::c_void,
+    ),
+>;
+#[pg_guard]
+extern "C" {
+    pub static mut object_access_hook: object_access_hook_type;
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ObjectAccessPostCreate {
+    pub is_internal: bool,
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ObjectAccessDrop {
+    pub dropflags: ::std::os::raw::c_int,
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ObjectAccessPostAlter {
+    pub auxiliary_id: Oid,
+    pub is_internal: bool,
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ObjectAccessNamespaceSearch {
+    pub ereport_on_violation: bool,
+    pub result: bool,
+}
+#[pg_guard]
+extern "C" {
+    pub fn RunObjectPostCreateHook(
+        classId: Oid,
+        objectId: Oid,
+        subId: ::std::os::raw::c_int,
+        is_internal: bool,
+    );
+}
+#[pg_guard]
+extern "C" {
+    pub fn RunObjectDropHook(
+        classId: Oid,
+        objectId: Oid,
+        subId: ::std::os::raw::c_int,
+        dropflags: ::std::os::raw::c_int,
+    );
+}
+#[pg_guard]
+extern "C" {
+    pub fn RunObjectPostAlterHook(
+        classId: Oid,
+        objectId: Oid,
+        subId: ::std::os::raw::c_int,
+        auxiliaryId: Oid,
+        is_internal: bool,
+    );
+}
+#[pg_guard]
+extern "C" {
+    pub fn RunNamespaceSearchHook(objectId: Oid, ereport_on_violation: bool) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn RunFunctionExecuteHook(objectId: Oid);
+}
+pub type get_relation_info_hook_type = ::std::option::Option<
+    unsafe extern "C" fn(
+        root: *mut PlannerInfo,
+        relationObjectId: Oid,
+        inhparent: bool,
+        rel: *mut RelOptInfo,
+    ),
+>;
+#[pg_guard]
+extern "C" {
+    pub static mut get_relation_info_hook: get_relation_info_hook_type;
+}
+#[pg_guard]
+extern "C" {
+    pub fn get_relation_info(
+        root: *mut PlannerInfo,
+        relationObjectId: Oid,
+        inhparent: bool,
+        rel: *mut RelOptInfo,
+    );
+}
+#[pg_guard]
+extern "C" {
+    pub fn infer_arbiter_indexes(root: *mut PlannerInfo) -> *mut List;
+}
+#[pg_guard]
+extern "C" {
+    pub fn estimate_rel_size(
+        rel: Relation,
+        attr_widths: *mut int32,
+        pages: *mut BlockNumber,
+        tuples: *mut f64,
+        allvisfrac: *mut f64,
+    );
+}
+#[pg_guard]
+extern "C" {
+    pub fn get_rel_data_width(rel: Relation, attr_widths: *mut int32) -> int32;
+}
+#[pg_guard]
+extern "C" {
+    pub fn get_relation_data_width(relid: Oid, attr_widths: *mut int32) -> int32;
+}
+#[pg_guard]
+extern "C" {
+    pub fn relation_excluded_by_constraints(
+        root: *mut PlannerInfo,
+        rel: *mut RelOptInfo,
+        rte: *mut RangeTblEntry,
+    ) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn build_physical_tlist(root: *mut PlannerInfo, rel: *mut RelOptInfo) -> *mut List;
+}
+#[pg_guard]
+extern "C" {
+    pub fn has_unique_index(rel: *mut RelOptInfo, attno: AttrNumber) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn restriction_selectivity(
+        root: *mut PlannerInfo,
+        operatorid: Oid,
+        args: *mut List,
+        inputcollid: Oid,
+        varRelid: ::std::os::raw::c_int,
+    ) -> Selectivity;
+}
+#[pg_guard]
+extern "C" {
+    pub fn join_selectivity(
+        root: *mut PlannerInfo,
+        operatorid: Oid,
+        args: *mut List,
+        inputcollid: Oid,
+        jointype: JoinType,
+        sjinfo: *mut SpecialJoinInfo,
+    ) -> Selectivity;
+}
+#[pg_guard]
+extern "C" {
+    pub fn has_row_triggers(root: *mut PlannerInfo, rti: Index, event: CmdType) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn has_stored_generated_columns(root: *mut PlannerInfo, rti: Index) -> bool;
+}
+pub type TYPCATEGORY = ::std::os::raw::c_char;
+pub const CoercionPathType_COERCION_PATH_NONE: CoercionPathType = 0;
+pub const CoercionPathType_COERCION_PATH_FUNC: CoercionPathType = 1;
+pub const CoercionPathType_COERCION_PATH_RELABELTYPE: CoercionPathType = 2;
+pub const CoercionPathType_COERCION_PATH_ARRAYCOERCE: CoercionPathType = 3;
+pub const CoercionPathType_COERCION_PATH_COERCEVIAIO: CoercionPathType = 4;
+pub type CoercionPathType = ::std::os::raw::c_uint;
+#[pg_guard]
+extern "C" {
+    pub fn IsBinaryCoercible(srctype: Oid, targettype: Oid) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn IsPreferredType(category: TYPCATEGORY, type_: Oid) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn TypeCategory(type_: Oid) -> TYPCATEGORY;
+}
+#[pg_guard]
+extern "C" {
+    pub fn coerce_to_target_type(
+        pstate: *mut ParseState,
+        expr: *mut Node,
+        exprtype: Oid,
+        targettype: Oid,
+        targettypmod: int32,
+        ccontext: CoercionContext,
+        cformat: CoercionForm,
+        location: ::std::os::raw::c_int,
+    ) -> *mut Node;
+}
+#[pg_guard]
+extern "C" {
+    pub fn can_coerce_type(
+        nargs: ::std::os::raw::c_int,
+        input_typeids: *const Oid,
+        target_typeids: *const Oid,
+        ccontext: CoercionContext,
+    ) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn coerce_type(
+        pstate: *mut ParseState,
+        node: *mut Node,
+        inputTypeId: Oid,
+        targetTypeId: Oid,
+        targetTypeMod: int32,
+        ccontext: CoercionContext,
+        cformat: CoercionForm,
+        location: ::std::os::raw::c_int,
+    ) -> *mut Node;
+}
+#[pg_guard]
+extern "C" {
+    pub fn find_coercion_pathway(
+        targetTypeId: Oid,
+        sourceTypeId: Oid,
+        ccontext: CoercionContext,
+        funcid: *mut Oid,
+    ) -> CoercionPathType;
+}
+#[pg_guard]
+extern "C" {
+    pub fn find_typmod_coercion_function(typeId: Oid, funcid: *mut Oid) -> CoercionPathType;
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RangeType {
+    pub vl_len_: int32,
+    pub rangetypid: Oid,
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RangeBound {
+    pub val: Datum,
+    pub infinite: bool,
+    pub inclusive: bool,
+    pub lower: bool,
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_contains_elem_internal(
+        typcache: *mut TypeCacheEntry,
+        r: *mut RangeType,
+        val: Datum,
+    ) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_eq_internal(
+        typcache: *mut TypeCacheEntry,
+        r1: *mut RangeType,
+        r2: *mut RangeType,
+    ) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_contains_internal(
+        typcache: *mut TypeCacheEntry,
+        r1: *mut RangeType,
+        r2: *mut RangeType,
+    ) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_overlaps_internal(
+        typcache: *mut TypeCacheEntry,
+        r1: *mut RangeType,
+        r2: *mut RangeType,
+    ) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_get_typcache(fcinfo: FunctionCallInfo, rngtypid: Oid) -> *mut TypeCacheEntry;
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_serialize(
+        typcache: *mut TypeCacheEntry,
+        lower: *mut RangeBound,
+        upper: *mut RangeBound,
+        empty: bool,
+    ) -> *mut RangeType;
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_deserialize(
+        typcache: *mut TypeCacheEntry,
+        range: *mut RangeType,
+        lower: *mut RangeBound,
+        upper: *mut RangeBound,
+        empty: *mut bool,
+    );
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_get_flags(range: *mut RangeType) -> ::std::os::raw::c_char;
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_set_contain_empty(range: *mut RangeType);
+}
+#[pg_guard]
+extern "C" {
+    pub fn make_range(
+        typcache: *mut TypeCacheEntry,
+        lower: *mut RangeBound,
+        upper: *mut RangeBound,
+        empty: bool,
+    ) -> *mut RangeType;
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_cmp_bounds(
+        typcache: *mut TypeCacheEntry,
+        b1: *const RangeBound,
+        b2: *const RangeBound,
+    ) -> ::std::os::raw::c_int;
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_cmp_bound_values(
+        typcache: *mut TypeCacheEntry,
+        b1: *const RangeBound,
+        b2: *const RangeBound,
+    ) -> ::std::os::raw::c_int;
+}
+#[pg_guard]
+extern "C" {
+    pub fn bounds_adjacent(
+        typcache: *mut TypeCacheEntry,
+        bound1: RangeBound,
+        bound2: RangeBound,
+    ) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn make_empty_range(typcache: *mut TypeCacheEntry) -> *mut RangeType;
+}
 pub type explain_get_index_name_hook_type =
     ::std::option::Option<unsafe extern "C" fn(indexId: Oid) -> *const ::std::os::raw::c_char>;
 #[pg_guard]
@@ -116,6 +116,16 @@ pub fn register_pg_guard_panic_handler() {
     }))
 }
 
+/// An error caught by `pg_try()` and taken with [`PgTryResult::catch_error()`]
+#[derive(Debug)]
+pub enum CaughtError {
+    /// A Postgres ERROR, copied out of Postgres' error state with `CopyErrorData()`
+    PostgresError(*mut crate::ErrorData),
+
+    /// A Rust `panic!()`, with its message
+    RustPanic(String),
+}
+
 /// A `std::result::Result`-type value returned from `pg_try()` that allows for performing cleanup
 /// work after a closure raised an error and before it is possibly rethrown
 #[must_use = "this `PgTryResult` may be be holding a Postgres ERROR.  It must be consumed or rethrown"]
@@ -166,6 +176,33 @@ impl<T> PgTryResult<T> {
         }
     }
 
+    /// Take the error the try block raised, if any, instead of rethrowing it.
+    ///
+    /// A Postgres ERROR is copied into the `CurrentMemoryContext`, which must not be Postgres'
+    /// `ErrorContext`, and Postgres' error state is then flushed.
+    ///
+    /// ## Safety
+    ///
+    /// This function does not rethrow a caught ERROR.  As with `unwrap_or()`, ignoring a caught
+    /// error can leave Postgres in an undefined state.  Callers are expected to clean up by
+    /// rolling back a subtransaction started before the try block.
+    pub unsafe fn catch_error(self) -> Result<T, CaughtError> {
+        match self.0 {
+            Ok(result) => Ok(result),
+            Err(e) => match downcast_err(e) {
+                Ok(message) => {
+                    take_panic_location();
+                    Err(CaughtError::RustPanic(message))
+                }
+                Err(_) => {
+                    let edata = crate::CopyErrorData();
+                    FlushErrorState();
+                    Err(CaughtError::PostgresError(edata))
+                }
+            },
+        }
+    }
+
     /// Perform some operation cleanup operation after the try block if an error was thrown.
     ///
     /// In the event an error was caught, it is rethrown.
@@ -17,6 +17,7 @@ pub mod pg_test {
     }
 
     pub fn postgresql_conf_options() -> Vec<&'static str> {
-        vec![]
+        // logical decoding is needed by the output plugin tests
+        vec!["wal_level = logical"]
     }
 }
@@ -46,6 +46,34 @@ fn count_true_sliced(values: Array<bool>) -> i32 {
     values.as_slice().iter().filter(|b| **b).count() as i32
 }
 
+#[pg_extern]
+fn dot_product_sliced(a: Array<f32>, b: Array<f32>) -> f32 {
+    a.as_slice()
+        .iter()
+        .zip(b.as_slice().iter())
+        .map(|(a, b)| a * b)
+        .sum()
+}
+
+#[pg_extern]
+fn slice_checked_contains_nulls(values: Array<i16>) -> bool {
+    values.as_slice_checked() == Err(ArraySliceError::ContainsNulls)
+}
+
+#[pg_extern]
+fn slice_checked_incompatible(values: AnyArray) -> bool {
+    let as_bool = AnyArray::into::<Array<bool>>(&values).unwrap();
+    let as_u32 = AnyArray::into::<Array<u32>>(&values).unwrap();
+    let as_f32 = AnyArray::into::<Array<f32>>(&values).unwrap();
+    [
+        as_bool.as_slice_checked().err(),
+        as_u32.as_slice_checked().err(),
+        as_f32.as_slice_checked().err(),
+    ]
+    .iter()
+    .all(|e| *e == Some(ArraySliceError::IncompatibleElementType))
+}
+
 #[pg_extern]
 fn count_nulls(values: Array<i32>) -> i32 {
     values.iter().map(|v| v.is_none()).filter(|v| *v).count() as i32
@@ -93,6 +121,45 @@ fn return_zero_length_vec() -> Vec<i32> {
     Vec::new()
 }
 
+#[pg_extern]
+fn array_dims(values: Array<i32>) -> Vec<i32> {
+    values.dims().to_vec()
+}
+
+#[pg_extern]
+fn array_lower_bounds(values: Array<i32>) -> Vec<i32> {
+    values.lower_bounds().to_vec()
+}
+
+#[pg_extern]
+fn array_get_md(values: Array<i32>, i: i32, j: i32) -> Option<i32> {
+    values.get_md(&[i as usize, j as usize]).flatten()
+}
+
+#[pg_extern]
+fn build_multiplication_table(n: i32) -> Array<'static, i32> {
+    let mut builder = ArrayBuilder::<i32>::new(&[n as usize, n as usize]);
+    for i in 1..=n {
+        for j in 1..=n {
+            if i == j {
+                builder.push_null();
+            } else {
+                builder.push(i * j);
+            }
+        }
+    }
+    builder.build()
+}
+
+#[pg_extern]
+fn build_array_with_lower_bound() -> Array<'static, &'static str> {
+    let mut builder = ArrayBuilder::<&str>::new(&[3]).with_lower_bounds(&[0]);
+    builder.push("a");
+    builder.push("b");
+    builder.push("c");
+    builder.build()
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgx::pg_schema]
 mod tests {
@@ -234,4 +301,123 @@ mod tests {
         .expect("Failed to return json even though it's right there ^^");
         assert_eq!(json.0, json! {{"values": [1, 2, 3, null, 4]}});
     }
+
+    #[pg_test]
+    fn test_array_dims() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT array_dims(ARRAY[[1,2,3],[4,5,6]]) = ARRAY[2,3] \
+                AND array_dims(ARRAY[1,2]) = ARRAY[2] \
+                AND array_dims(ARRAY[]::integer[]) = ARRAY[]::integer[];",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_array_lower_bounds() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT array_lower_bounds('[0:1][5:6]={{1,2},{3,4}}'::integer[]) = ARRAY[0,5];",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_array_get_md() {
+        let value = Spi::get_one::<i32>("SELECT array_get_md(ARRAY[[1,2,3],[4,5,6]], 1, 2);")
+            .expect("failed to get SPI result");
+        assert_eq!(value, 6);
+
+        let value = Spi::get_one::<i32>("SELECT array_get_md(ARRAY[[1,2,3],[4,5,6]], 2, 0);");
+        assert_eq!(value, None);
+    }
+
+    #[pg_test]
+    fn test_array_builder_md() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT build_multiplication_table(3) = ARRAY[[NULL,2,3],[2,NULL,6],[3,6,NULL]];",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_array_builder_lower_bounds() {
+        let text = Spi::get_one::<&str>("SELECT build_array_with_lower_bound()::text;")
+            .expect("failed to get SPI result");
+        assert_eq!(text, "[0:2]={a,b,c}");
+    }
+
+    #[pg_test]
+    fn test_array_builder_without_nulls() {
+        let mut builder = ArrayBuilder::<i64>::new(&[2, 2]);
+        for i in 0..4 {
+            builder.push(i);
+        }
+        let array = builder.build();
+        assert_eq!(array.ndim(), 2);
+        assert_eq!(array.dims(), &[2, 2]);
+        assert_eq!(array.get_md(&[1, 0]), Some(Some(2)));
+        assert!(!unsafe { pg_sys::array_contains_nulls(array.into_array_type() as *mut _) });
+    }
+
+    #[pg_test(error = "array needs 4 elements but only 3 were pushed")]
+    fn test_array_builder_too_few_elements() {
+        let mut builder = ArrayBuilder::<i32>::new(&[2, 2]);
+        for i in 0..3 {
+            builder.push(i);
+        }
+        builder.build();
+    }
+
+    #[pg_test]
+    fn test_dot_product_sliced() {
+        let product = Spi::get_one::<f32>(
+            "SELECT dot_product_sliced(ARRAY[1,2,3]::real[], ARRAY[4,5,6]::real[]);",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!(product, 32f32);
+    }
+
+    #[pg_test]
+    fn test_sum_array_md_sliced() {
+        let sum = Spi::get_one::<i64>("SELECT sum_array_sliced(ARRAY[[1,2],[3,4]]::bigint[]);")
+            .expect("failed to get SPI result");
+        assert_eq!(sum, 10);
+    }
+
+    #[pg_test]
+    fn test_slice_checked_incompatible() {
+        for array in &["ARRAY[1, 2]::integer[]", "ARRAY['a', 'b']::\"char\"[]"] {
+            let rc = Spi::get_one::<bool>(&format!("SELECT slice_checked_incompatible({})", array))
+                .expect("failed to get SPI result");
+            assert!(rc, "{}", array);
+        }
+    }
+
+    #[pg_test]
+    fn test_slice_checked_contains_nulls() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT slice_checked_contains_nulls(ARRAY[1, NULL, 3]::smallint[]) \
+                AND NOT slice_checked_contains_nulls(ARRAY[1, 2, 3]::smallint[]);",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test(error = "array contains NULL")]
+    fn test_slice_with_nulls() {
+        Spi::get_one::<f32>(
+            "SELECT dot_product_sliced(ARRAY[1,NULL,3]::real[], ARRAY[4,5,6]::real[]);",
+        );
+    }
+
+    #[pg_test]
+    fn test_slice_of_built_array() {
+        let mut builder = ArrayBuilder::<i32>::new(&[3]);
+        for i in 1..=3 {
+            builder.push(i);
+        }
+        assert_eq!(builder.build().as_slice(), &[1, 2, 3]);
+    }
 }
@@ -0,0 +1,57 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::bgworkers::*;
+use pgx::*;
+
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn bgworker_tests_noop_main(_arg: pg_sys::Datum) {}
+
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn bgworker_tests_wait_main(_arg: pg_sys::Datum) {
+    BackgroundWorker::attach_signal_handlers(SignalWakeFlags::SIGTERM);
+    while BackgroundWorker::wait_latch(None) {}
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::bgworkers::*;
+    use pgx::*;
+
+    #[pg_test]
+    fn test_dynamic_bgworker_runs_to_completion() {
+        let worker = BackgroundWorkerBuilder::new("pgx_tests noop worker")
+            .set_function("bgworker_tests_noop_main")
+            .set_library("pgx_tests")
+            .enable_shmem_access(None)
+            .load_dynamic();
+
+        assert_eq!(worker.notify_pid(), unsafe { pg_sys::MyProcPid });
+        assert_eq!(worker.wait_for_shutdown(), Ok(()));
+        assert_eq!(worker.status(), BackgroundWorkerStatus::Stopped);
+        assert_eq!(worker.pid(), None);
+    }
+
+    #[pg_test]
+    fn test_dynamic_bgworker_terminate() {
+        let worker = BackgroundWorkerBuilder::new("pgx_tests waiting worker")
+            .set_function("bgworker_tests_wait_main")
+            .set_library("pgx_tests")
+            .enable_shmem_access(None)
+            .load_dynamic();
+
+        let pid = worker.wait_for_startup().expect("worker did not start");
+        assert!(pid > 0);
+        assert_eq!(worker.pid(), Some(pid));
+
+        worker.terminate();
+        assert_eq!(worker.wait_for_shutdown(), Ok(()));
+        assert_eq!(worker.status(), BackgroundWorkerStatus::Stopped);
+    }
+}
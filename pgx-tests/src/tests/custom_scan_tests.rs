@@ -0,0 +1,101 @@
+use pgx::*;
+
+pub struct CountingScan;
+
+impl CustomScan for CountingScan {
+    const NAME: &'static str = "counting_scan";
+    type State = i32;
+    type Row = (i32,);
+
+    fn plan_path(
+        _root: &mut pg_sys::PlannerInfo,
+        rel: &mut pg_sys::RelOptInfo,
+        _rti: pg_sys::Index,
+        rte: &mut pg_sys::RangeTblEntry,
+    ) -> Option<PgBox<pg_sys::CustomPath>> {
+        if rte.rtekind != pg_sys::RTEKind_RTE_RELATION {
+            return None;
+        }
+        let relation = unsafe { PgRelation::open(rte.relid) };
+        if relation.name() != "custom_scan_table" {
+            return None;
+        }
+        Some(custom_path::<Self>(rel, 3.0, 0.0, 0.0))
+    }
+
+    fn begin(
+        _node: &mut pg_sys::CustomScanState,
+        _estate: &mut pg_sys::EState,
+        _eflags: i32,
+    ) -> Self::State {
+        0
+    }
+
+    fn exec(state: &mut Self::State) -> Option<Self::Row> {
+        *state += 1;
+        if *state <= 3 {
+            Some((*state,))
+        } else {
+            None
+        }
+    }
+
+    fn rescan(state: &mut Self::State) {
+        *state = 0;
+    }
+}
+
+struct CountingScanHook;
+
+impl PgHooks for CountingScanHook {
+    fn set_rel_pathlist(
+        &mut self,
+        mut root: PgBox<pg_sys::PlannerInfo>,
+        mut rel: PgBox<pg_sys::RelOptInfo>,
+        rti: pg_sys::Index,
+        mut rte: PgBox<pg_sys::RangeTblEntry>,
+        prev_hook: fn(
+            PgBox<pg_sys::PlannerInfo>,
+            PgBox<pg_sys::RelOptInfo>,
+            pg_sys::Index,
+            PgBox<pg_sys::RangeTblEntry>,
+        ) -> HookResult<()>,
+    ) -> HookResult<()> {
+        add_custom_scan_path::<CountingScan>(&mut root, &mut rel, rti, &mut rte);
+        prev_hook(root, rel, rti, rte)
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+    use pgx::*;
+
+    static mut HOOK: super::CountingScanHook = super::CountingScanHook;
+
+    #[pg_test]
+    unsafe fn custom_scan_replaces_seq_scan() {
+        pgx::hooks::register_hook(&mut HOOK);
+        Spi::run("CREATE TABLE custom_scan_table (id int4);");
+
+        let plan = Spi::get_one::<&str>("EXPLAIN (COSTS OFF) SELECT id FROM custom_scan_table;")
+            .expect("SQL select failed");
+        assert_eq!(plan, "Custom Scan (counting_scan) on custom_scan_table");
+
+        let sum = Spi::get_one::<i64>("SELECT sum(id) FROM custom_scan_table;")
+            .expect("SQL select failed");
+        assert_eq!(sum, 6);
+    }
+
+    #[pg_test]
+    unsafe fn custom_scan_applies_quals() {
+        pgx::hooks::register_hook(&mut HOOK);
+        Spi::run("CREATE TABLE custom_scan_table (id int4);");
+
+        let sum = Spi::get_one::<i64>("SELECT sum(id) FROM custom_scan_table WHERE id > 1;")
+            .expect("SQL select failed");
+        assert_eq!(sum, 5);
+    }
+}
@@ -45,6 +45,11 @@ fn return_3pm_mountain_time() -> TimestampWithTimeZone {
     three_pm
 }
 
+#[pg_extern]
+fn accept_interval(i: Interval) -> Interval {
+    i
+}
+
 #[pg_extern]
 fn timestamptz_to_i64(tstz: pg_sys::TimestampTz) -> i64 {
     tstz
@@ -268,4 +273,97 @@ mod tests {
 
         assert_eq!(result, Duration::from_secs(60).as_micros() as i64);
     }
+
+    #[pg_test]
+    fn test_accept_interval() {
+        let result = Spi::get_one::<bool>(
+            "SELECT accept_interval('1 year 2 months 3 days 04:05:06.789'::interval) = '1 year 2 months 3 days 04:05:06.789'::interval;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_interval_fields() {
+        let interval = Spi::get_one::<Interval>("SELECT '-1 year 2 days 00:00:01.5'::interval")
+            .expect("SPI result was NULL");
+        assert_eq!(-12, interval.months());
+        assert_eq!(2, interval.days());
+        assert_eq!(1_500_000, interval.micros());
+        assert_eq!(Interval::from_days(30), Interval::from_months(1));
+        assert!(Interval::from_days(1) < Interval::from_micros(86_400_000_001));
+    }
+
+    #[pg_test]
+    fn test_interval_duration_conversion() {
+        use std::convert::TryFrom;
+
+        let interval = Interval::try_from(Duration::from_micros(90_061_000_001)).unwrap();
+        assert_eq!(
+            Duration::try_from(interval),
+            Ok(Duration::from_micros(90_061_000_001))
+        );
+        assert_eq!(
+            Interval::try_from(Duration::from_nanos(1)),
+            Err(IntervalConversionError::SubMicrosecond)
+        );
+        assert_eq!(
+            Duration::try_from(Interval::from_days(1)),
+            Err(IntervalConversionError::VariableLength)
+        );
+        assert_eq!(
+            Duration::try_from(Interval::from_micros(-1)),
+            Err(IntervalConversionError::Negative)
+        );
+    }
+
+    #[pg_test]
+    fn test_timestamp_interval_arithmetic() {
+        let timestamp = Spi::get_one::<Timestamp>("SELECT '2020-01-31 12:00:00'::timestamp")
+            .expect("SPI result was NULL");
+
+        // Jan 31st plus one month is clamped to Feb 29th, and then one more day is Mar 1st
+        let later = timestamp + Interval::new(1, 1, 1_000_000);
+        assert_eq!(time::Month::March, later.month());
+        assert_eq!(1, later.day());
+        assert_eq!(12, later.hour());
+        assert_eq!(1, later.second());
+
+        let earlier = later - Interval::from_months(1);
+        assert_eq!(time::Month::February, earlier.month());
+        assert_eq!(1, earlier.day());
+
+        let start = Spi::get_one::<Timestamp>("SELECT '2020-01-01'::timestamp").unwrap();
+        let end = Spi::get_one::<Timestamp>("SELECT '2020-01-02 01:00:00'::timestamp").unwrap();
+        let difference = end - start;
+        assert_eq!(0, difference.months());
+        assert_eq!(1, difference.days());
+        assert_eq!(3_600_000_000, difference.micros());
+    }
+
+    #[pg_test]
+    fn test_timestamptz_interval_arithmetic() {
+        Spi::run("SET LOCAL TIME ZONE 'UTC'");
+        let start =
+            Spi::get_one::<TimestampWithTimeZone>("SELECT '2021-01-31 12:00:00+00'::timestamptz")
+                .expect("SPI result was NULL");
+        let later = start + Interval::new(1, 1, 0);
+        assert_eq!(time::Month::March, later.month());
+        assert_eq!(1, later.day());
+
+        let start =
+            Spi::get_one::<TimestampWithTimeZone>("SELECT '2021-01-31 12:00:00+00'::timestamptz")
+                .unwrap();
+        let difference = later - start;
+        assert_eq!(29, difference.days());
+        assert_eq!(0, difference.micros());
+    }
+
+    #[pg_test]
+    fn test_interval_display() {
+        assert_eq!(
+            "1 year 2 mons 3 days 04:05:06",
+            Interval::new(14, 3, 14_706_000_000).to_string()
+        );
+    }
 }
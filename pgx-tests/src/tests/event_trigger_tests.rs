@@ -0,0 +1,137 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    static ref EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+}
+
+fn record_event(event: String) {
+    EVENTS.lock().unwrap().push(event);
+}
+
+fn take_events() -> Vec<String> {
+    EVENTS.lock().unwrap().drain(..).collect()
+}
+
+/// Created with the extension, and only interested in collations so that it doesn't fire for
+/// the DDL in other tests
+#[pg_event_trigger(event = "ddl_command_end", tags = ["CREATE COLLATION", "ALTER COLLATION"])]
+fn record_collation_ddl(trigger: &PgEventTrigger) {
+    for command in trigger.ddl_commands() {
+        record_event(format!(
+            "{:?} {} {} {}",
+            trigger.event(),
+            command.command_tag,
+            command.object_type,
+            command.object_identity
+        ));
+    }
+}
+
+#[pg_event_trigger]
+fn record_drops(trigger: &PgEventTrigger) {
+    for dropped in trigger.dropped_objects().filter(|dropped| dropped.original) {
+        record_event(format!(
+            "{:?} {} {} {:?} {:?}",
+            trigger.event(),
+            trigger.tag(),
+            dropped.object_type,
+            dropped.schema_name,
+            dropped.object_name
+        ));
+    }
+}
+
+#[pg_event_trigger]
+fn record_parse_tree(trigger: &PgEventTrigger) {
+    let is_create_stmt = trigger
+        .parse_tree()
+        .map(|node| unsafe { is_a(node.as_ptr(), pg_sys::NodeTag_T_CreateStmt) })
+        .unwrap_or(false);
+    record_event(format!(
+        "{:?} {} {}",
+        trigger.event(),
+        trigger.tag(),
+        is_create_stmt
+    ));
+}
+
+/// `#[pg_extern]`'s arguments are accepted too
+#[pg_event_trigger(name = "record_rewrites_renamed")]
+fn record_rewrites(trigger: &PgEventTrigger) {
+    record_event(format!("{:?} {}", trigger.event(), trigger.tag()));
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use super::take_events;
+    use pgx::*;
+
+    #[pg_test]
+    fn test_generated_event_trigger() {
+        take_events();
+        Spi::run(
+            "CREATE COLLATION tests.event_trigger_c FROM \"C\"; \
+             CREATE TABLE tests.event_trigger_ignored (id int);",
+        );
+        assert_eq!(
+            take_events(),
+            vec!["DdlCommandEnd CREATE COLLATION collation tests.event_trigger_c"]
+        );
+    }
+
+    #[pg_test]
+    fn test_dropped_objects() {
+        Spi::run(
+            "CREATE TABLE tests.event_trigger_doomed (id int); \
+             CREATE EVENT TRIGGER record_drops ON sql_drop \
+                EXECUTE PROCEDURE record_drops();",
+        );
+        take_events();
+        Spi::run("DROP TABLE tests.event_trigger_doomed;");
+        assert_eq!(
+            take_events(),
+            vec!["SqlDrop DROP TABLE table Some(\"tests\") Some(\"event_trigger_doomed\")"]
+        );
+    }
+
+    #[pg_test]
+    fn test_parse_tree() {
+        Spi::run(
+            "CREATE EVENT TRIGGER record_parse_tree ON ddl_command_start \
+                WHEN TAG IN ('CREATE TABLE', 'CREATE VIEW') \
+                EXECUTE PROCEDURE record_parse_tree();",
+        );
+        take_events();
+        Spi::run(
+            "CREATE TABLE tests.event_trigger_parsed (id int); \
+             CREATE VIEW tests.event_trigger_view AS SELECT 1;",
+        );
+        assert_eq!(
+            take_events(),
+            vec![
+                "DdlCommandStart CREATE TABLE true",
+                "DdlCommandStart CREATE VIEW false"
+            ]
+        );
+    }
+
+    #[pg_test]
+    fn test_pg_extern_arguments() {
+        Spi::run(
+            "CREATE TABLE tests.event_trigger_rewritten (id int); \
+             CREATE EVENT TRIGGER record_rewrites ON table_rewrite \
+                EXECUTE PROCEDURE record_rewrites_renamed();",
+        );
+        take_events();
+        Spi::run("ALTER TABLE tests.event_trigger_rewritten ALTER COLUMN id TYPE bigint;");
+        assert_eq!(take_events(), vec!["TableRewrite ALTER TABLE"]);
+    }
+}
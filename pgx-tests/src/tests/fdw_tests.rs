@@ -0,0 +1,100 @@
+use pgx::*;
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    static ref COUNTING_FDW_INSERTS: Mutex<Vec<Option<i32>>> = Mutex::new(Vec::new());
+}
+
+pub struct CountingFdw;
+
+#[pg_foreign_data_wrapper]
+impl ForeignDataWrapper for CountingFdw {
+    type ScanState = (i32, i32);
+    type Row = (i32, String);
+
+    fn validate(options: FdwOptions, _catalog: pg_sys::Oid) {
+        if let Some(rows) = options.get("rows") {
+            if rows.parse::<i32>().is_err() {
+                error!("counting_fdw: `rows` must be an integer, got `{}`", rows);
+            }
+        }
+    }
+
+    fn begin_scan(_relation: &PgRelation, options: FdwOptions) -> Self::ScanState {
+        let rows = options
+            .get("rows")
+            .map(|rows| rows.parse().expect("rows option was not validated"))
+            .unwrap_or(3);
+        (0, rows)
+    }
+
+    fn iterate_scan(state: &mut Self::ScanState) -> Option<Self::Row> {
+        if state.0 >= state.1 {
+            return None;
+        }
+        state.0 += 1;
+        Some((state.0, format!("row {}", state.0)))
+    }
+
+    fn rescan(state: &mut Self::ScanState) {
+        state.0 = 0;
+    }
+
+    fn insert(_relation: &PgRelation, _options: &FdwOptions, values: &[Option<pg_sys::Datum>]) {
+        let value = unsafe { i32::from_datum(values[0].unwrap_or(0), values[0].is_none(), 0) };
+        COUNTING_FDW_INSERTS.lock().unwrap().push(value);
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+    use pgx::*;
+
+    #[pg_test]
+    fn fdw_registered() {
+        let registered = Spi::get_one::<bool>(
+            "SELECT EXISTS(SELECT 1 FROM pg_foreign_data_wrapper WHERE fdwname = 'counting_fdw');",
+        )
+        .expect("SQL select failed");
+        assert!(registered);
+    }
+
+    #[pg_test]
+    fn fdw_scan() {
+        Spi::run(
+            "CREATE SERVER counting_server FOREIGN DATA WRAPPER counting_fdw;
+            CREATE FOREIGN TABLE counting_table (id int4, label text)
+                SERVER counting_server OPTIONS (rows '5');",
+        );
+        let sum =
+            Spi::get_one::<i64>("SELECT sum(id) FROM counting_table;").expect("SQL select failed");
+        assert_eq!(sum, 15);
+        let label = Spi::get_one::<&str>("SELECT label FROM counting_table WHERE id = 2;")
+            .expect("SQL select failed");
+        assert_eq!(label, "row 2");
+    }
+
+    #[pg_test(error = "counting_fdw: `rows` must be an integer, got `many`")]
+    fn fdw_validator_rejects_bad_option() {
+        Spi::run(
+            "CREATE SERVER counting_bad_server FOREIGN DATA WRAPPER counting_fdw;
+            CREATE FOREIGN TABLE counting_bad_table (id int4, label text)
+                SERVER counting_bad_server OPTIONS (rows 'many');",
+        );
+    }
+
+    #[pg_test]
+    fn fdw_insert() {
+        Spi::run(
+            "CREATE SERVER counting_insert_server FOREIGN DATA WRAPPER counting_fdw;
+            CREATE FOREIGN TABLE counting_insert_table (id int4, label text)
+                SERVER counting_insert_server;
+            INSERT INTO counting_insert_table VALUES (7, 'seven'), (NULL, 'nothing');",
+        );
+        let inserted = super::COUNTING_FDW_INSERTS.lock().unwrap();
+        assert_eq!(*inserted, vec![Some(7), None]);
+    }
+}
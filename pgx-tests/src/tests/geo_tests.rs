@@ -0,0 +1,156 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern]
+fn path_length(path: Path) -> f64 {
+    let points = path.points();
+    let mut segments = points.windows(2).map(|w| (w[0], w[1])).collect::<Vec<_>>();
+    if path.is_closed() && points.len() > 1 {
+        segments.push((points[points.len() - 1], points[0]));
+    }
+    segments
+        .into_iter()
+        .map(|(a, b)| (a.x - b.x).hypot(a.y - b.y))
+        .sum()
+}
+
+#[pg_extern]
+fn translate_polygon(polygon: Polygon, dx: f64, dy: f64) -> Polygon {
+    let mut polygon = polygon;
+    for point in polygon.points_mut() {
+        point.x += dx;
+        point.y += dy;
+    }
+    polygon
+}
+
+#[pg_extern]
+fn lseg_midpoint(lseg: pg_sys::LSEG) -> pg_sys::Point {
+    pg_sys::Point {
+        x: (lseg.p[0].x + lseg.p[1].x) / 2.0,
+        y: (lseg.p[0].y + lseg.p[1].y) / 2.0,
+    }
+}
+
+#[pg_extern]
+fn scale_circle(circle: pg_sys::CIRCLE, factor: f64) -> pg_sys::CIRCLE {
+    pg_sys::CIRCLE {
+        center: circle.center,
+        radius: circle.radius * factor,
+    }
+}
+
+#[pg_extern]
+fn take_and_return_line(line: pg_sys::LINE) -> pg_sys::LINE {
+    line
+}
+
+#[pg_extern]
+fn take_and_return_box(the_box: pg_sys::BOX) -> pg_sys::BOX {
+    the_box
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_path_points() {
+        let path = Spi::get_one::<Path>("SELECT '[(0,0),(3,4),(3,0)]'::path;")
+            .expect("SPI result was NULL");
+        assert!(!path.is_closed());
+        assert_eq!(path.points().len(), 3);
+        assert_eq!(path.points()[1].x, 3.0);
+        assert_eq!(path.points()[1].y, 4.0);
+    }
+
+    #[pg_test]
+    fn test_path_length() {
+        let matches = Spi::get_one::<bool>(
+            "SELECT path_length('[(0,0),(3,4),(3,0)]') = length('[(0,0),(3,4),(3,0)]'::path) \
+                AND path_length('((0,0),(3,4),(3,0))') = length('((0,0),(3,4),(3,0))'::path);",
+        )
+        .expect("SPI result was NULL");
+        assert!(matches);
+    }
+
+    #[pg_test]
+    fn test_path_into_datum() {
+        let text = Spi::get_one_with_args::<&str>(
+            "SELECT $1::text;",
+            vec![(
+                PgOid::from(Path::type_oid()),
+                Path::closed(vec![
+                    pg_sys::Point { x: 1.0, y: 2.0 },
+                    pg_sys::Point { x: 3.0, y: 4.0 },
+                ])
+                .into_datum(),
+            )],
+        )
+        .expect("SPI result was NULL");
+        assert_eq!(text, "((1,2),(3,4))");
+    }
+
+    #[pg_test]
+    fn test_translate_polygon() {
+        let matches = Spi::get_one::<bool>(
+            "SELECT translate_polygon('((0,0),(1,0),(1,1))', 10, 20) ~= '((10,20),(11,20),(11,21))'::polygon;",
+        )
+        .expect("SPI result was NULL");
+        assert!(matches);
+    }
+
+    #[pg_test]
+    fn test_polygon_bounding_box() {
+        // `box(polygon)` reads the bounding box pgx stored alongside the points
+        let matches = Spi::get_one::<bool>(
+            "SELECT box(translate_polygon('((0,0),(4,1),(2,3))', 1, 1)) ~= '(5,4),(1,1)'::box;",
+        )
+        .expect("SPI result was NULL");
+        assert!(matches);
+
+        let polygon = Spi::get_one::<Polygon>("SELECT '((0,0),(4,1),(2,3))'::polygon;")
+            .expect("SPI result was NULL");
+        let bbox = polygon.bounding_box();
+        assert_eq!((bbox.low.x, bbox.low.y), (0.0, 0.0));
+        assert_eq!((bbox.high.x, bbox.high.y), (4.0, 3.0));
+    }
+
+    #[pg_test]
+    fn test_lseg_midpoint() {
+        let matches =
+            Spi::get_one::<bool>("SELECT lseg_midpoint('[(0,0),(4,2)]') ~= '(2,1)'::point;")
+                .expect("SPI result was NULL");
+        assert!(matches);
+    }
+
+    #[pg_test]
+    fn test_scale_circle() {
+        let circle = Spi::get_one::<pg_sys::CIRCLE>("SELECT scale_circle('<(1,2),3>', 2);")
+            .expect("SPI result was NULL");
+        assert_eq!((circle.center.x, circle.center.y), (1.0, 2.0));
+        assert_eq!(circle.radius, 6.0);
+    }
+
+    #[pg_test]
+    fn test_take_and_return_line() {
+        let line = Spi::get_one::<pg_sys::LINE>("SELECT take_and_return_line('{1,-1,0}');")
+            .expect("SPI result was NULL");
+        assert_eq!((line.A, line.B, line.C), (1.0, -1.0, 0.0));
+    }
+
+    #[pg_test]
+    fn test_take_and_return_box() {
+        let matches = Spi::get_one::<bool>(
+            "SELECT take_and_return_box('(1,1),(0,0)') ~= '(1,1),(0,0)'::box;",
+        )
+        .expect("SPI result was NULL");
+        assert!(matches);
+    }
+}
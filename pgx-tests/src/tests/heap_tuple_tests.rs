@@ -0,0 +1,108 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_trigger]
+fn uppercase_title(trigger: &PgTrigger) -> Option<PgHeapTuple<'_>> {
+    let mut new = trigger.new()?;
+    let title = new.get_by_name::<String>("title");
+    new.set_by_name("title", title.map(|title| title.to_uppercase()));
+    Some(new)
+}
+
+#[pg_extern]
+fn make_heap_tuple_dog(name: &str, age: Option<i32>) -> PgHeapTuple<'static> {
+    let mut dog = PgHeapTuple::new_composite_type(regtypein("tests.heap_tuple_dog"));
+    dog.set_by_name("name", name);
+    dog.set_by_name("age", age);
+    dog
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_modify_in_trigger() {
+        Spi::run(
+            "CREATE TABLE tests.shouting (id int, title varchar(20)); \
+             CREATE TRIGGER shouting_before BEFORE INSERT OR UPDATE ON tests.shouting \
+                FOR EACH ROW EXECUTE PROCEDURE uppercase_title(); \
+             INSERT INTO tests.shouting VALUES (1, 'hello'), (2, NULL); \
+             UPDATE tests.shouting SET title = 'world' WHERE id = 2;",
+        );
+
+        let titles =
+            Spi::get_one::<&str>("SELECT string_agg(title, ',' ORDER BY id) FROM tests.shouting;")
+                .expect("SPI result was NULL");
+        assert_eq!(titles, "HELLO,WORLD");
+    }
+
+    #[pg_test]
+    fn test_return_composite() {
+        Spi::run("CREATE TYPE tests.heap_tuple_dog AS (name text, age int);");
+
+        let text = Spi::get_one::<&str>(
+            "SELECT make_heap_tuple_dog('Rex', 3)::text || ' ' || make_heap_tuple_dog('Tom', NULL)::text;",
+        )
+        .expect("SPI result was NULL");
+        assert_eq!(text, "(Rex,3) (Tom,)");
+    }
+
+    #[pg_test]
+    fn test_from_datums() {
+        Spi::run(
+            "CREATE TYPE tests.heap_tuple_pair AS (id int, dropped int, label text); \
+                  ALTER TYPE tests.heap_tuple_pair DROP ATTRIBUTE dropped;",
+        );
+        let tupdesc = unsafe {
+            PgTupleDesc::from_pg(pg_sys::lookup_rowtype_tupdesc(
+                regtypein("tests.heap_tuple_pair"),
+                -1,
+            ))
+        };
+        let mut pair =
+            unsafe { PgHeapTuple::from_datums(tupdesc, vec![42.into_datum(), None, None]) };
+        assert_eq!(pair.names().collect::<Vec<_>>(), vec!["id", "label"]);
+        assert_eq!(pair.get_by_name::<i32>("id"), Some(42));
+        assert_eq!(pair.get_by_name::<String>("label"), None);
+
+        pair.set_by_index(3, "forty-two");
+        pair.set_by_name("id", Option::<i32>::None);
+        assert_eq!(pair.get_by_index::<i32>(1), None);
+        assert_eq!(
+            pair.get_by_index::<String>(3),
+            Some("forty-two".to_string())
+        );
+
+        let text = Spi::get_one_with_args::<&str>(
+            "SELECT $1::text;",
+            vec![(PgOid::from(pg_sys::RECORDOID), pair.into_datum())],
+        )
+        .expect("SPI result was NULL");
+        assert_eq!(text, "(,forty-two)");
+    }
+
+    #[pg_test]
+    #[should_panic(
+        expected = "attribute \"age\" is of type integer, but the value is of type text"
+    )]
+    fn test_set_wrong_type() {
+        Spi::run("CREATE TYPE tests.heap_tuple_cat AS (name text, age int);");
+        let mut cat = PgHeapTuple::new_composite_type(regtypein("tests.heap_tuple_cat"));
+        cat.set_by_name("age", "three");
+    }
+
+    #[pg_test]
+    #[should_panic(expected = "tuple has no attribute named \"nope\"")]
+    fn test_get_missing_attribute() {
+        Spi::run("CREATE TYPE tests.heap_tuple_bird AS (name text);");
+        let bird = PgHeapTuple::new_composite_type(regtypein("tests.heap_tuple_bird"));
+        bird.get_by_name::<String>("nope");
+    }
+}
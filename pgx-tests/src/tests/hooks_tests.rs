@@ -93,4 +93,298 @@ mod tests {
         // TODO:  it'd be nice to also test that .commit() and .abort() also get called
         //    but I don't see how to do that since we're running *inside* a transaction here
     }
+
+    #[pg_test]
+    unsafe fn test_parser_planner_and_fmgr_hooks() {
+        use pgx::pg_sys::*;
+        use std::ffi::CStr;
+
+        struct TestHook {
+            parses: u32,
+            created: Vec<Oid>,
+            relations: Vec<Oid>,
+            explains: u32,
+            warnings: Vec<String>,
+            watched_fn: Oid,
+            fmgr_events: Vec<FmgrHookEventType>,
+        }
+        impl PgHooks for TestHook {
+            fn post_parse_analyze(
+                &mut self,
+                pstate: PgBox<ParseState>,
+                query: PgBox<Query>,
+                jumble_state: Option<PgBox<JumbleState>>,
+                prev_hook: fn(
+                    PgBox<ParseState>,
+                    PgBox<Query>,
+                    Option<PgBox<JumbleState>>,
+                ) -> HookResult<()>,
+            ) -> HookResult<()> {
+                self.parses += 1;
+                prev_hook(pstate, query, jumble_state)
+            }
+
+            fn object_access(
+                &mut self,
+                access: ObjectAccessType,
+                class_id: Oid,
+                object_id: Oid,
+                sub_id: i32,
+                arg: void_mut_ptr,
+                prev_hook: fn(ObjectAccessType, Oid, Oid, i32, void_mut_ptr) -> HookResult<()>,
+            ) -> HookResult<()> {
+                if access == ObjectAccessType_OAT_POST_CREATE && class_id == RelationRelationId {
+                    self.created.push(object_id);
+                }
+                prev_hook(access, class_id, object_id, sub_id, arg)
+            }
+
+            fn get_relation_info(
+                &mut self,
+                root: PgBox<PlannerInfo>,
+                relation_oid: Oid,
+                inhparent: bool,
+                rel: PgBox<RelOptInfo>,
+                prev_hook: fn(PgBox<PlannerInfo>, Oid, bool, PgBox<RelOptInfo>) -> HookResult<()>,
+            ) -> HookResult<()> {
+                self.relations.push(relation_oid);
+                prev_hook(root, relation_oid, inhparent, rel)
+            }
+
+            fn explain_one_query(
+                &mut self,
+                query: PgBox<Query>,
+                cursor_options: i32,
+                into: PgBox<IntoClause>,
+                es: PgBox<ExplainState>,
+                query_string: *const std::os::raw::c_char,
+                params: PgBox<ParamListInfoData>,
+                query_env: PgBox<QueryEnvironment>,
+                prev_hook: fn(
+                    PgBox<Query>,
+                    i32,
+                    PgBox<IntoClause>,
+                    PgBox<ExplainState>,
+                    *const std::os::raw::c_char,
+                    PgBox<ParamListInfoData>,
+                    PgBox<QueryEnvironment>,
+                ) -> HookResult<()>,
+            ) -> HookResult<()> {
+                self.explains += 1;
+                prev_hook(
+                    query,
+                    cursor_options,
+                    into,
+                    es,
+                    query_string,
+                    params,
+                    query_env,
+                )
+            }
+
+            fn emit_log(
+                &mut self,
+                error_data: PgBox<ErrorData>,
+                prev_hook: fn(PgBox<ErrorData>) -> HookResult<()>,
+            ) -> HookResult<()> {
+                if error_data.elevel == WARNING as i32 {
+                    let message = unsafe { CStr::from_ptr(error_data.message) };
+                    self.warnings.push(message.to_string_lossy().into_owned());
+                }
+                prev_hook(error_data)
+            }
+
+            fn needs_fmgr(
+                &mut self,
+                fn_oid: Oid,
+                prev_hook: fn(Oid) -> HookResult<bool>,
+            ) -> HookResult<bool> {
+                if fn_oid == self.watched_fn {
+                    HookResult::new(true)
+                } else {
+                    prev_hook(fn_oid)
+                }
+            }
+
+            fn fmgr(
+                &mut self,
+                event: FmgrHookEventType,
+                flinfo: PgBox<FmgrInfo>,
+                arg: *mut Datum,
+                prev_hook: fn(FmgrHookEventType, PgBox<FmgrInfo>, *mut Datum) -> HookResult<()>,
+            ) -> HookResult<()> {
+                if flinfo.fn_oid == self.watched_fn {
+                    self.fmgr_events.push(event);
+                }
+                prev_hook(event, flinfo, arg)
+            }
+        }
+
+        static mut HOOK: TestHook = TestHook {
+            parses: 0,
+            created: Vec::new(),
+            relations: Vec::new(),
+            explains: 0,
+            warnings: Vec::new(),
+            watched_fn: InvalidOid,
+            fmgr_events: Vec::new(),
+        };
+        HOOK.watched_fn = Spi::get_one::<Oid>("SELECT 'pg_catalog.upper(text)'::regprocedure::oid")
+            .expect("SPI result was NULL");
+        pgx::hooks::register_hook_points(
+            &mut HOOK,
+            PgHookPoints::POST_PARSE_ANALYZE
+                | PgHookPoints::OBJECT_ACCESS
+                | PgHookPoints::GET_RELATION_INFO
+                | PgHookPoints::EXPLAIN_ONE_QUERY
+                | PgHookPoints::EMIT_LOG
+                | PgHookPoints::FMGR,
+        );
+
+        Spi::run("CREATE TABLE tests.hooked (id int)");
+        let table = Spi::get_one::<Oid>("SELECT 'tests.hooked'::regclass::oid")
+            .expect("SPI result was NULL");
+        assert!(HOOK.parses >= 2);
+        assert!(HOOK.created.contains(&table));
+
+        Spi::run("EXPLAIN SELECT * FROM tests.hooked");
+        assert_eq!(HOOK.explains, 1);
+        assert!(HOOK.relations.contains(&table));
+
+        warning!("hooked warning");
+        assert_eq!(HOOK.warnings, vec!["hooked warning"]);
+
+        let upper = Spi::get_one::<&str>("SELECT upper(id::text) FROM (SELECT 'x' AS id) x")
+            .expect("SPI result was NULL");
+        assert_eq!(upper, "X");
+        assert_eq!(
+            HOOK.fmgr_events,
+            vec![FmgrHookEventType_FHET_START, FmgrHookEventType_FHET_END]
+        );
+    }
+
+    #[pg_test]
+    unsafe fn test_multiple_hooks() {
+        use pgx::pg_sys::*;
+
+        static mut CALLS: Vec<String> = Vec::new();
+
+        struct NamedHook(&'static str);
+        impl PgHooks for NamedHook {
+            fn executor_start(
+                &mut self,
+                query_desc: PgBox<QueryDesc>,
+                eflags: i32,
+                prev_hook: fn(PgBox<QueryDesc>, i32) -> HookResult<()>,
+            ) -> HookResult<()> {
+                unsafe { CALLS.push(self.0.to_string()) };
+                let result = prev_hook(query_desc, eflags);
+                unsafe { CALLS.push(format!("/{}", self.0)) };
+                result
+            }
+        }
+
+        static mut FIRST: NamedHook = NamedHook("first");
+        static mut SECOND: NamedHook = NamedHook("second");
+        let first = pgx::hooks::register_hook(&mut FIRST);
+        let second = pgx::hooks::register_hook(&mut SECOND);
+        assert_ne!(first, second);
+
+        // only the hook points that were registered for are installed
+        assert!(ExecutorStart_hook.is_some());
+        assert!(ExplainOneQuery_hook.is_none());
+        assert!(needs_fmgr_hook.is_none());
+
+        Spi::run("SELECT 1");
+        assert_eq!(
+            std::mem::take(&mut CALLS),
+            vec!["first", "second", "/second", "/first"]
+        );
+
+        assert!(pgx::hooks::unregister_hook(first));
+        assert!(!pgx::hooks::unregister_hook(first));
+        Spi::run("SELECT 1");
+        assert_eq!(std::mem::take(&mut CALLS), vec!["second", "/second"]);
+
+        assert!(pgx::hooks::unregister_hook(second));
+        Spi::run("SELECT 1");
+        assert!(CALLS.is_empty());
+    }
+
+    struct CountingHook(usize);
+    impl PgHooks for CountingHook {
+        fn executor_run(
+            &mut self,
+            query_desc: PgBox<pg_sys::QueryDesc>,
+            direction: pg_sys::ScanDirection,
+            count: u64,
+            execute_once: bool,
+            prev_hook: fn(
+                PgBox<pg_sys::QueryDesc>,
+                pg_sys::ScanDirection,
+                u64,
+                bool,
+            ) -> HookResult<()>,
+        ) -> HookResult<()> {
+            self.0 += 1;
+            prev_hook(query_desc, direction, count, execute_once)
+        }
+    }
+
+    static mut COUNTING_HOOK: CountingHook = CountingHook(0);
+    static mut COUNTING_HOOK_ID: Option<pgx::hooks::HookId> = None;
+
+    #[pg_extern]
+    fn register_counting_hook() {
+        unsafe { COUNTING_HOOK_ID = Some(pgx::hooks::register_hook(&mut COUNTING_HOOK)) };
+    }
+
+    #[pg_extern]
+    fn unregister_counting_hook() -> bool {
+        unsafe { pgx::hooks::unregister_hook(COUNTING_HOOK_ID.take().unwrap()) }
+    }
+
+    #[pg_test]
+    unsafe fn test_unregister_from_later_statement() {
+        Spi::run("SELECT tests.register_counting_hook()");
+        Spi::run("SELECT 1");
+        let calls = COUNTING_HOOK.0;
+        assert!(calls > 0);
+
+        // runs from within the executor run hook of the statement calling it
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT tests.unregister_counting_hook()"),
+            Some(true)
+        );
+        let calls = COUNTING_HOOK.0;
+        Spi::run("SELECT 1");
+        assert_eq!(COUNTING_HOOK.0, calls);
+    }
+
+    #[pg_test]
+    unsafe fn test_unregister_after_error() {
+        use pgx::pg_sys::*;
+
+        struct PassThroughHook;
+        impl PgHooks for PassThroughHook {
+            fn executor_run(
+                &mut self,
+                query_desc: PgBox<QueryDesc>,
+                direction: ScanDirection,
+                count: u64,
+                execute_once: bool,
+                prev_hook: fn(PgBox<QueryDesc>, ScanDirection, u64, bool) -> HookResult<()>,
+            ) -> HookResult<()> {
+                prev_hook(query_desc, direction, count, execute_once)
+            }
+        }
+
+        static mut HOOK: PassThroughHook = PassThroughHook;
+        let id = pgx::hooks::register_hook(&mut HOOK);
+
+        // the division by zero is raised from within the executor run hook, and caught by
+        // `try_run`'s subtransaction
+        assert!(Spi::try_run("SELECT 1 / g FROM generate_series(0, 0) g").is_err());
+        assert!(pgx::hooks::unregister_hook(id));
+    }
 }
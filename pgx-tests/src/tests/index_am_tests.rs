@@ -0,0 +1,99 @@
+use pgx::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DEMO_AM_INSERTS: AtomicUsize = AtomicUsize::new(0);
+
+pub struct DemoAm;
+
+#[pg_index_am]
+impl IndexAm for DemoAm {
+    const STRATEGIES: u16 = 1;
+
+    fn build(
+        _heap_relation: &PgRelation,
+        _index_relation: &PgRelation,
+        _index_info: &mut pg_sys::IndexInfo,
+    ) -> IndexBuildResult {
+        IndexBuildResult::default()
+    }
+
+    fn build_empty(_index_relation: &PgRelation) {}
+
+    fn insert(
+        _index_relation: &PgRelation,
+        values: &[Option<pg_sys::Datum>],
+        _heap_tid: pg_sys::ItemPointerData,
+        _heap_relation: &PgRelation,
+        _check_unique: pg_sys::IndexUniqueCheck,
+        _index_info: &mut pg_sys::IndexInfo,
+    ) -> bool {
+        assert_eq!(values.len(), 1);
+        DEMO_AM_INSERTS.fetch_add(1, Ordering::SeqCst);
+        false
+    }
+
+    fn bulk_delete(
+        _info: &pg_sys::IndexVacuumInfo,
+        stats: Option<PgBox<pg_sys::IndexBulkDeleteResult>>,
+        _callback: BulkDeleteCallback,
+    ) -> Option<PgBox<pg_sys::IndexBulkDeleteResult>> {
+        stats
+    }
+
+    fn vacuum_cleanup(
+        _info: &pg_sys::IndexVacuumInfo,
+        stats: Option<PgBox<pg_sys::IndexBulkDeleteResult>>,
+    ) -> Option<PgBox<pg_sys::IndexBulkDeleteResult>> {
+        stats
+    }
+
+    fn cost_estimate(
+        root: &mut pg_sys::PlannerInfo,
+        path: &mut pg_sys::IndexPath,
+        loop_count: f64,
+    ) -> IndexCosts {
+        IndexCosts::generic(root, path, loop_count)
+    }
+
+    fn begin_scan(_index_relation: &PgRelation, _nkeys: i32, _norderbys: i32) -> Self::ScanState {}
+
+    fn rescan(_scan: &mut PgIndexScan<Self::ScanState>) {}
+
+    fn get_tuple(
+        _scan: &mut PgIndexScan<Self::ScanState>,
+        _direction: pg_sys::ScanDirection,
+    ) -> bool {
+        false
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+    use pgx::*;
+    use std::sync::atomic::Ordering;
+
+    #[pg_test]
+    fn index_am_registered() {
+        let registered = Spi::get_one::<bool>(
+            "SELECT EXISTS(SELECT 1 FROM pg_am WHERE amname = 'demo_am' AND amtype = 'i');",
+        )
+        .expect("SQL select failed");
+        assert!(registered);
+    }
+
+    #[pg_test]
+    fn index_am_insert() {
+        let before = super::DEMO_AM_INSERTS.load(Ordering::SeqCst);
+        Spi::run(
+            "CREATE TABLE demo_am_table (id int4);
+            CREATE OPERATOR CLASS demo_am_int4_ops DEFAULT FOR TYPE int4 USING demo_am AS
+                OPERATOR 1 = (int4, int4);
+            CREATE INDEX demo_am_idx ON demo_am_table USING demo_am (id);
+            INSERT INTO demo_am_table SELECT generate_series(1, 10);",
+        );
+        assert_eq!(super::DEMO_AM_INSERTS.load(Ordering::SeqCst) - before, 10);
+    }
+}
@@ -8,17 +8,26 @@ mod tests {
     use crate as pgx_tests;
 
     use pgx::*;
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::str::FromStr;
 
     #[pg_test]
     fn test_deserialize_inet() {
         let inet =
             serde_json::from_str::<Inet>("\"192.168.0.1\"").expect("failed to deserialize inet");
-        assert_eq!("192.168.0.1", &inet.0)
+        assert_eq!(inet.addr(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
+        assert_eq!(inet.netmask(), 32);
+    }
+
+    #[pg_test]
+    fn test_deserialize_invalid_inet() {
+        assert!(serde_json::from_str::<Inet>("\"192.168.0.1/33\"").is_err());
+        assert!(serde_json::from_str::<Inet>("\"not an address\"").is_err());
     }
 
     #[pg_test]
     fn test_serialize_inet() {
-        let json = serde_json::to_string(&Inet("192.168.0.1".to_owned()))
+        let json = serde_json::to_string(&Inet::from(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1))))
             .expect("failed to serialize inet");
         assert_eq!("\"192.168.0.1\"", &json);
     }
@@ -28,12 +37,118 @@ mod tests {
         inet
     }
 
+    #[pg_extern]
+    fn take_and_return_cidr(cidr: Cidr) -> Cidr {
+        cidr
+    }
+
+    #[pg_extern]
+    fn inet_network(inet: Inet) -> Cidr {
+        inet.network()
+    }
+
+    #[pg_extern]
+    fn take_and_return_macaddr(mac: MacAddr) -> MacAddr {
+        mac
+    }
+
+    #[pg_extern]
+    fn take_and_return_macaddr8(mac: MacAddr8) -> MacAddr8 {
+        mac
+    }
+
     #[pg_test]
     fn test_take_and_return_inet() {
         let rc = Spi::get_one::<bool>(
-            "SELECT tests.take_and_return_inet('192.168.0.1') = '192.168.0.1'::inet;",
+            "SELECT tests.take_and_return_inet('192.168.0.1') = '192.168.0.1'::inet \
+                AND tests.take_and_return_inet('10.1.2.3/8') = '10.1.2.3/8'::inet \
+                AND tests.take_and_return_inet('2001:db8::1/64') = '2001:db8::1/64'::inet;",
         )
         .expect("failed to get SPI result");
         assert!(rc)
     }
+
+    #[pg_test]
+    fn test_decode_inet() {
+        let inet = Spi::get_one::<Inet>("SELECT '2001:db8::1/64'::inet;")
+            .expect("failed to get SPI result");
+        assert_eq!(inet.addr(), IpAddr::from_str("2001:db8::1").unwrap());
+        assert_eq!(inet.netmask(), 64);
+        assert_eq!(inet.to_string(), "2001:db8::1/64");
+    }
+
+    #[pg_test]
+    fn test_inet_network_matches_postgres() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT tests.inet_network('192.168.10.20/16') = network('192.168.10.20/16'::inet);",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_take_and_return_cidr() {
+        let cidr = Spi::get_one::<Cidr>("SELECT tests.take_and_return_cidr('10.1/16');")
+            .expect("failed to get SPI result");
+        assert_eq!(cidr, Cidr::from_str("10.1.0.0/16").unwrap());
+        assert!(cidr.contains(&IpAddr::V4(Ipv4Addr::new(10, 1, 200, 3))));
+        assert!(!cidr.contains(&IpAddr::V4(Ipv4Addr::new(10, 2, 0, 1))));
+    }
+
+    #[pg_test]
+    fn test_cidr_host_bits() {
+        assert_eq!(
+            Cidr::from_str("10.1.0.1/16"),
+            Err(NetworkAddressError::HostBitsSet)
+        );
+    }
+
+    #[pg_test]
+    fn test_inet_ordering_matches_postgres() {
+        let values = [
+            "::1",
+            "10.0.0.1",
+            "10.0.0.0/8",
+            "10.0.0.1/8",
+            "192.168.0.0/16",
+            "9.255.255.255",
+        ];
+        let mut sorted = values
+            .iter()
+            .map(|value| Inet::from_str(value).unwrap())
+            .collect::<Vec<_>>();
+        sorted.sort();
+
+        let expected = Spi::get_one::<Vec<Inet>>(
+            "SELECT array_agg(v ORDER BY v) FROM unnest(ARRAY['::1', '10.0.0.1', '10.0.0.0/8', \
+                '10.0.0.1/8', '192.168.0.0/16', '9.255.255.255']::inet[]) v;",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!(sorted, expected);
+    }
+
+    #[pg_test]
+    fn test_take_and_return_macaddr() {
+        let mac =
+            Spi::get_one::<MacAddr>("SELECT tests.take_and_return_macaddr('08-00-2B-01-02-03');")
+                .expect("failed to get SPI result");
+        assert_eq!(mac, MacAddr([0x08, 0x00, 0x2b, 0x01, 0x02, 0x03]));
+        assert_eq!(mac.to_string(), "08:00:2b:01:02:03");
+    }
+
+    #[pg_test]
+    fn test_take_and_return_macaddr8() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT tests.take_and_return_macaddr8('08:00:2b:01:02:03:04:05') = '08:00:2b:01:02:03:04:05'::macaddr8;",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc);
+
+        let mac = Spi::get_one::<MacAddr8>("SELECT '08:00:2b:01:02:03'::macaddr::macaddr8;")
+            .expect("failed to get SPI result");
+        assert_eq!(
+            mac,
+            MacAddr8::from(MacAddr::from_str("08:00:2b:01:02:03").unwrap())
+        );
+    }
 }
@@ -4,15 +4,22 @@
 mod aggregate_tests;
 mod anyarray_tests;
 mod array_tests;
+mod bgworker_tests;
 mod bytea_tests;
 mod cfg_tests;
+mod custom_scan_tests;
 mod datetime_tests;
 mod default_arg_value_tests;
 mod derive_pgtype_lifetimes;
 mod enum_type_tests;
+mod event_trigger_tests;
 mod fcinfo_tests;
+mod fdw_tests;
+mod geo_tests;
 mod guc_tests;
+mod heap_tuple_tests;
 mod hooks_tests;
+mod index_am_tests;
 mod inet_tests;
 mod internal_tests;
 mod json_tests;
@@ -21,14 +28,22 @@ mod log_tests;
 mod memcxt_tests;
 mod name_tests;
 mod numeric_tests;
+mod output_plugin_tests;
 mod pg_extern_args_tests;
 mod pg_try_tests;
 mod pgbox_tests;
 mod postgres_type_tests;
+mod range_tests;
+mod record_tests;
 mod schema_tests;
+mod shmem_tests;
 mod spi_tests;
 mod srf_tests;
 mod struct_type_tests;
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+mod table_am_tests;
+mod trigger_tests;
+mod tsearch_tests;
 mod uuid_tests;
 mod variadic_tests;
 mod xact_callback_tests;
@@ -9,6 +9,8 @@ mod tests {
 
     use pgx::*;
     use serde::Deserialize;
+    use std::convert::TryFrom;
+    use std::str::FromStr;
 
     #[pg_extern]
     fn return_an_i32_numeric() -> Numeric {
@@ -63,4 +65,139 @@ mod tests {
             .to_string();
         assert_eq!("invalid Numeric value: foo", &error);
     }
+
+    fn numeric(value: &str) -> Numeric {
+        Numeric::from_str(value).expect("invalid numeric")
+    }
+
+    fn sql_numeric(query: &str) -> Numeric {
+        Spi::get_one::<Numeric>(query).expect("SPI result was NULL")
+    }
+
+    #[pg_test]
+    fn test_numeric_roundtrips_through_text() {
+        for value in &[
+            "0",
+            "0.00",
+            "1",
+            "-1",
+            "1.5",
+            "-0.0001",
+            "12345.6789",
+            "10000",
+            "100000000.000000001",
+            "0.000000000000000000000000000000000000000000000000000000000000000000000000000001",
+            "123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890",
+            "-98765432109876543210.123456789012345678901234567890123456789012345678901234567890123456789",
+            "NaN",
+        ] {
+            // decoded directly from the datum Postgres built
+            let decoded = sql_numeric(&format!("SELECT '{}'::numeric", value));
+            assert_eq!(value, &decoded.to_string());
+
+            // and encoded directly into a datum Postgres understands
+            let text = Spi::get_one_with_args::<String>(
+                "SELECT $1::text",
+                vec![(PgBuiltInOids::NUMERICOID.oid(), numeric(value).into_datum())],
+            )
+            .expect("SPI result was NULL");
+            assert_eq!(value, &text);
+        }
+    }
+
+    #[pg_test]
+    fn test_numeric_arithmetic_matches_postgres() {
+        let pairs = [
+            ("1.5", "2.25"),
+            ("-7", "3"),
+            ("10", "-0.3"),
+            ("123456789.123456789", "0.000001"),
+            ("2", "3"),
+            ("1", "7.0000000000000000001"),
+            ("99999999999999999999", "0.5"),
+        ];
+
+        for (left, right) in &pairs {
+            let (l, r) = (numeric(left), numeric(right));
+            for (op, expected) in &[
+                ("+", &l + &r),
+                ("-", &l - &r),
+                ("*", &l * &r),
+                ("/", &l / &r),
+                ("%", &l % &r),
+            ] {
+                let from_sql = sql_numeric(&format!(
+                    "SELECT {}::numeric {} {}::numeric",
+                    left, op, right
+                ));
+                assert_eq!(
+                    from_sql.to_string(),
+                    expected.to_string(),
+                    "{} {} {}",
+                    left,
+                    op,
+                    right
+                );
+            }
+        }
+    }
+
+    #[pg_test]
+    #[should_panic(expected = "division by zero")]
+    fn test_numeric_division_by_zero() {
+        let _ = numeric("1") / numeric("0");
+    }
+
+    #[pg_test]
+    fn test_numeric_comparisons() {
+        assert!(numeric("1.50") == numeric("1.5"));
+        assert!(numeric("-2") < numeric("-1.999"));
+        assert!(numeric("100") > numeric("99.9999"));
+        assert!(Numeric::nan() == Numeric::nan());
+        assert!(Numeric::nan() > numeric("1e100"));
+        assert_eq!(
+            std::cmp::max(numeric("1"), numeric("2")).to_string(),
+            "2".to_string()
+        );
+    }
+
+    #[pg_test]
+    fn test_numeric_conversions() {
+        assert_eq!(i32::try_from(numeric("41.5")), Ok(42));
+        assert_eq!(i32::try_from(numeric("-41.5")), Ok(-42));
+        assert_eq!(i8::try_from(numeric("128")), Err(NumericError::OutOfRange));
+        assert_eq!(u64::try_from(Numeric::nan()), Err(NumericError::NotFinite));
+        assert_eq!(f64::from(numeric("0.25")), 0.25);
+        assert!(f64::from(Numeric::nan()).is_nan());
+        assert!(Numeric::from(f64::NAN).is_nan());
+        assert_eq!(Numeric::from(i128::MIN).to_string(), i128::MIN.to_string());
+
+        let decimal = bigdecimal::BigDecimal::from_str("3.14159").unwrap();
+        let value = Numeric::from(decimal.clone());
+        assert_eq!(bigdecimal::BigDecimal::try_from(value), Ok(decimal));
+    }
+
+    #[pg_test]
+    fn test_numeric_scale_and_precision() {
+        let value = numeric("123.4560");
+        assert_eq!(value.scale(), Some(4));
+        assert_eq!(value.precision(), Some(7));
+        assert_eq!(numeric("0.05").precision(), Some(2));
+        assert_eq!(value.round(2).to_string(), "123.46");
+        assert_eq!(value.round(-2).to_string(), "100");
+        assert_eq!(numeric("-2.5").round(0).to_string(), "-3");
+        assert_eq!(value.trunc(1).to_string(), "123.4");
+
+        assert_eq!(
+            value.with_typmod(5, 2).unwrap().to_string(),
+            sql_numeric("SELECT 123.4560::numeric(5, 2)").to_string()
+        );
+        assert_eq!(
+            numeric("999.995").with_typmod(5, 2),
+            Err(NumericError::FieldOverflow {
+                precision: 5,
+                scale: 2
+            })
+        );
+    }
 }
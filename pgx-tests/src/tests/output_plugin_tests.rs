@@ -0,0 +1,81 @@
+use pgx::*;
+
+pub struct MessageEcho {
+    prefix: Option<String>,
+}
+
+#[pg_output_plugin]
+impl OutputPlugin for MessageEcho {
+    fn startup(ctx: &mut PgLogicalDecodingContext, _is_init: bool) -> Self {
+        MessageEcho {
+            prefix: ctx.options().remove("prefix").flatten(),
+        }
+    }
+
+    fn change(
+        &mut self,
+        ctx: &mut PgLogicalDecodingContext,
+        _txn: &pg_sys::ReorderBufferTXN,
+        relation: &PgRelation,
+        change: &DecodedChange,
+    ) {
+        ctx.write_str(&format!("change {:?} {}", change.action(), relation.name()));
+    }
+
+    fn message(
+        &mut self,
+        ctx: &mut PgLogicalDecodingContext,
+        _txn: Option<&pg_sys::ReorderBufferTXN>,
+        _message_lsn: pg_sys::XLogRecPtr,
+        transactional: bool,
+        prefix: &str,
+        message: &[u8],
+    ) {
+        if self
+            .prefix
+            .as_deref()
+            .map_or(true, |wanted| wanted == prefix)
+        {
+            ctx.write_str(&format!(
+                "message {}: {} (transactional: {})",
+                prefix,
+                String::from_utf8_lossy(message),
+                transactional
+            ));
+        }
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+    use pgx::*;
+
+    #[pg_test]
+    fn output_plugin_creates_slot() {
+        let plugin = Spi::get_one::<&str>(
+            "SELECT plugin::text FROM pg_create_logical_replication_slot('pgx_create_slot', 'pgx_tests', true)
+                JOIN pg_replication_slots USING (slot_name);",
+        )
+        .expect("SQL select failed");
+        assert_eq!(plugin, "pgx_tests");
+    }
+
+    #[pg_test]
+    fn output_plugin_decodes_messages() {
+        Spi::run(
+            "SELECT pg_create_logical_replication_slot('pgx_message_slot', 'pgx_tests', true);
+            SELECT pg_logical_emit_message(false, 'other', 'ignored');
+            SELECT pg_logical_emit_message(false, 'pgx', 'hello');
+            CHECKPOINT;",
+        );
+        let data = Spi::get_one::<&str>(
+            "SELECT string_agg(data, ',') FROM pg_logical_slot_get_changes('pgx_message_slot', NULL, NULL, 'prefix', 'pgx')
+                WHERE data LIKE 'message%';",
+        )
+        .expect("SQL select failed");
+        assert_eq!(data, "message pgx: hello (transactional: false)");
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[derive(PostgresRange, Debug, PartialEq)]
+#[pgx(subdiff = float_range_subdiff)]
+pub struct FloatRange(Range<f64>);
+
+fn float_range_subdiff(a: f64, b: f64) -> f64 {
+    a - b
+}
+
+/// A discrete range over even numbers, canonicalized like `int4range` to `[lower,upper)`
+#[derive(PostgresRange, Debug, PartialEq)]
+#[pgx(canonical = even_range_canonical)]
+pub struct EvenRange(Range<i32>);
+
+fn even_range_canonical(range: Range<i32>) -> Range<i32> {
+    let round_up = |value: i32| value + value.rem_euclid(2);
+    match range.into_bounds() {
+        None => Range::empty(),
+        Some((lower, upper)) => {
+            let lower = match lower {
+                RangeBound::Infinite => RangeBound::Infinite,
+                RangeBound::Inclusive(value) => RangeBound::Inclusive(round_up(value)),
+                RangeBound::Exclusive(value) => RangeBound::Inclusive(round_up(value + 1)),
+            };
+            let upper = match upper {
+                RangeBound::Infinite => RangeBound::Infinite,
+                RangeBound::Inclusive(value) => RangeBound::Exclusive(round_up(value + 1)),
+                RangeBound::Exclusive(value) => RangeBound::Exclusive(round_up(value)),
+            };
+            Range::new(lower, upper)
+        }
+    }
+}
+
+#[pg_extern]
+fn accept_int4range(range: Range<i32>) -> Range<i32> {
+    range
+}
+
+#[pg_extern]
+fn accept_numrange(range: Range<Numeric>) -> Range<Numeric> {
+    range
+}
+
+#[pg_extern]
+fn accept_daterange(range: Range<Date>) -> Range<Date> {
+    range
+}
+
+#[pg_extern]
+fn accept_float_range(range: FloatRange) -> FloatRange {
+    range
+}
+
+#[pg_extern]
+fn make_even_range(lower: i32, upper: i32) -> EvenRange {
+    EvenRange((lower..=upper).into())
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use super::{EvenRange, FloatRange};
+    use pgx::*;
+
+    #[pg_test]
+    fn test_int4range_is_canonicalized() {
+        let range = Spi::get_one::<Range<i32>>("SELECT int4range(1, 3, '[]');")
+            .expect("SPI result was NULL");
+        assert_eq!(range, Range::from(1..4));
+    }
+
+    #[pg_test]
+    fn test_int4range_roundtrip() {
+        let matches = Spi::get_one::<bool>(
+            "SELECT accept_int4range('(,10]') = '(,10]'::int4range \
+                AND accept_int4range('[5,)') = '[5,)'::int4range \
+                AND accept_int4range('empty') = 'empty'::int4range;",
+        )
+        .expect("SPI result was NULL");
+        assert!(matches);
+    }
+
+    #[pg_test]
+    fn test_empty_range() {
+        let range =
+            Spi::get_one::<Range<i32>>("SELECT 'empty'::int4range;").expect("SPI result was NULL");
+        assert!(range.is_empty());
+        assert_eq!(range.lower(), None);
+        assert_eq!(range.upper(), None);
+    }
+
+    #[pg_test]
+    fn test_infinite_bounds() {
+        let range =
+            Spi::get_one::<Range<i64>>("SELECT '(,42]'::int8range;").expect("SPI result was NULL");
+        assert_eq!(range.lower(), Some(&RangeBound::Infinite));
+        assert_eq!(range.upper(), Some(&RangeBound::Exclusive(43)));
+    }
+
+    #[pg_test]
+    fn test_numrange_keeps_inclusivity() {
+        let range = Spi::get_one::<Range<Numeric>>("SELECT accept_numrange('(1.5,2.5]');")
+            .expect("SPI result was NULL");
+        assert_eq!(
+            range,
+            Range::new(
+                RangeBound::Exclusive(Numeric::from(1.5)),
+                RangeBound::Inclusive(Numeric::from(2.5))
+            )
+        );
+    }
+
+    #[pg_test]
+    fn test_daterange_roundtrip() {
+        let text =
+            Spi::get_one::<&str>("SELECT accept_daterange('[2022-01-01,2022-02-01)')::text;")
+                .expect("SPI result was NULL");
+        assert_eq!(text, "[2022-01-01,2022-02-01)");
+    }
+
+    #[pg_test]
+    fn test_into_datum() {
+        let range: Range<i32> = (10..).into();
+        let text = Spi::get_one_with_args::<&str>(
+            "SELECT $1::text;",
+            vec![(PgOid::from(Range::<i32>::type_oid()), range.into_datum())],
+        )
+        .expect("SPI result was NULL");
+        assert_eq!(text, "[10,)");
+    }
+
+    #[pg_test]
+    fn test_derived_range() {
+        let range = Spi::get_one::<FloatRange>("SELECT accept_float_range('[1.5,2.5)');")
+            .expect("SPI result was NULL");
+        assert_eq!(range, FloatRange((1.5..2.5).into()));
+    }
+
+    #[pg_test]
+    fn test_derived_range_subdiff() {
+        // a GiST index on a range type uses its SUBTYPE_DIFF function when splitting pages
+        Spi::run(
+            "CREATE TABLE float_ranges (r FloatRange); \
+             INSERT INTO float_ranges SELECT FloatRange(x, x + 1) FROM generate_series(1, 1000) x; \
+             CREATE INDEX ON float_ranges USING gist (r);",
+        );
+        let count =
+            Spi::get_one::<i64>("SELECT count(*) FROM float_ranges WHERE r && FloatRange(10, 20);")
+                .expect("SPI result was NULL");
+        assert_eq!(count, 10);
+    }
+
+    #[pg_test]
+    fn test_derived_range_canonical() {
+        let range = Spi::get_one::<EvenRange>("SELECT EvenRange(1, 4, '[]');")
+            .expect("SPI result was NULL");
+        assert_eq!(range, EvenRange(Range::from(2..6)));
+
+        let range = Spi::get_one::<EvenRange>("SELECT make_even_range(3, 7);")
+            .expect("SPI result was NULL");
+        assert_eq!(range, EvenRange(Range::from(4..8)));
+    }
+
+    #[pg_test]
+    fn test_borrowed_bounds_of_compressed_range() {
+        // long, repetitive bounds are stored compressed, so decoding them makes a detoasted copy
+        // that the `&str` bounds borrow from
+        Spi::run(
+            "CREATE TYPE textrange AS RANGE (subtype = text); \
+             CREATE TABLE text_ranges (r textrange); \
+             INSERT INTO text_ranges VALUES (textrange(repeat('a', 10000), repeat('b', 10000)));",
+        );
+        let matches = Spi::connect(|client| {
+            let range = client
+                .select("SELECT r FROM text_ranges;", None, None)
+                .first()
+                .get_one::<Range<&str>>()
+                .expect("SPI result was NULL");
+            let matches = match range.into_bounds() {
+                Some((RangeBound::Inclusive(lower), RangeBound::Exclusive(upper))) => {
+                    lower == "a".repeat(10000) && upper == "b".repeat(10000)
+                }
+                _ => false,
+            };
+            Ok(Some(matches))
+        });
+        assert_eq!(matches, Some(true));
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern]
+fn record_names(record: PgRecord) -> Vec<String> {
+    record.names().map(str::to_string).collect()
+}
+
+/// Sum every `integer` attribute, whatever it's called
+#[pg_extern]
+fn record_sum_ints(record: PgRecord) -> i64 {
+    record
+        .iter()
+        .filter(|(_, _, typoid)| *typoid == pg_sys::INT4OID)
+        .filter_map(|(_, datum, typoid)| unsafe { i32::from_datum(datum?, false, typoid) })
+        .map(i64::from)
+        .sum()
+}
+
+#[pg_extern]
+fn record_get_text(record: PgRecord, name: &str) -> Option<String> {
+    record.get::<String>(name)
+}
+
+#[pg_extern]
+fn make_pair(id: i32, label: Option<&str>) -> PgRecord {
+    let mut builder = PgRecordBuilder::new();
+    builder.push("id", id);
+    builder.push("label", label);
+    builder.build()
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_anonymous_record() {
+        let names = Spi::get_one::<Vec<String>>("SELECT record_names(ROW(1, 'two', 3));")
+            .expect("SPI result was NULL");
+        assert_eq!(names, vec!["f1", "f2", "f3"]);
+
+        let sum = Spi::get_one::<i64>("SELECT record_sum_ints(ROW(1, 'two', 3, NULL::int));")
+            .expect("SPI result was NULL");
+        assert_eq!(sum, 4);
+    }
+
+    #[pg_test]
+    fn test_table_row_type() {
+        Spi::run(
+            "CREATE TABLE pets (name text, dropped int, species text); \
+             ALTER TABLE pets DROP COLUMN dropped; \
+             INSERT INTO pets VALUES ('Rex', 'dog'), ('Tom', NULL);",
+        );
+
+        let names = Spi::get_one::<Vec<String>>("SELECT record_names(p) FROM pets p LIMIT 1;")
+            .expect("SPI result was NULL");
+        assert_eq!(names, vec!["name", "species"]);
+
+        let species = Spi::get_one::<&str>(
+            "SELECT string_agg(coalesce(record_get_text(p, 'species'), 'unknown'), ',' ORDER BY name) FROM pets p;",
+        )
+        .expect("SPI result was NULL");
+        assert_eq!(species, "dog,unknown");
+    }
+
+    #[pg_test]
+    fn test_record_from_spi() {
+        let record = Spi::get_one::<PgRecord>("SELECT ROW(42, 'hello')::record;")
+            .expect("SPI result was NULL");
+        assert_eq!(record.len(), 2);
+        assert_eq!(record.type_oid(), pg_sys::RECORDOID);
+        assert_eq!(record.get::<i32>("f1"), Some(42));
+        assert_eq!(record.get::<String>("f2"), Some("hello".to_string()));
+    }
+
+    #[pg_test]
+    #[should_panic(expected = "record has no attribute named \"nope\"")]
+    fn test_record_missing_attribute() {
+        let record =
+            Spi::get_one::<PgRecord>("SELECT ROW(1)::record;").expect("SPI result was NULL");
+        record.get::<i32>("nope");
+    }
+
+    #[pg_test]
+    fn test_build_anonymous_record() {
+        let json = Spi::get_one::<&str>(
+            "SELECT row_to_json(make_pair(1, 'one'))::text || ' ' || row_to_json(make_pair(2, NULL))::text;",
+        )
+        .expect("SPI result was NULL");
+        assert_eq!(json, r#"{"id":1,"label":"one"} {"id":2,"label":null}"#);
+
+        let label = Spi::get_one::<&str>(
+            "SELECT label FROM make_pair(3, 'three') AS t(id int, label text);",
+        )
+        .expect("SPI result was NULL");
+        assert_eq!(label, "three");
+    }
+
+    #[pg_test]
+    fn test_build_named_composite() {
+        Spi::run("CREATE TYPE dog AS (name text, age int, owner text);");
+        let dog_oid = regtypein("dog");
+
+        let mut builder = PgRecordBuilder::new();
+        builder.push("age", 3);
+        builder.push("name", "Rex");
+        let dog = builder.build_as(dog_oid);
+        assert_eq!(dog.type_oid(), dog_oid);
+        assert_eq!(dog.get::<String>("owner"), None);
+
+        let text = Spi::get_one_with_args::<&str>(
+            "SELECT $1::text;",
+            vec![(PgOid::from(dog_oid), dog.into_datum())],
+        )
+        .expect("SPI result was NULL");
+        assert_eq!(text, "(Rex,3,)");
+    }
+
+    #[pg_test]
+    #[should_panic(
+        expected = "attribute \"age\" is of type integer, but the value is of type bigint"
+    )]
+    fn test_build_named_composite_wrong_type() {
+        Spi::run("CREATE TYPE cat AS (name text, age int);");
+        let mut builder = PgRecordBuilder::new();
+        builder.push("age", 3i64);
+        builder.build_as(regtypein("cat"));
+    }
+}
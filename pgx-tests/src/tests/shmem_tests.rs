@@ -0,0 +1,66 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    const MAGIC: u64 = 0x7067_785f_7465_7374;
+    const VALUE_KEY: u64 = 1;
+    const QUEUE_KEY: u64 = 2;
+    const QUEUE_SIZE: usize = 16384;
+
+    fn segment() -> PgDsmSegment {
+        let size = PgShmTocEstimator::new()
+            .add_chunk(std::mem::size_of::<i64>())
+            .add_chunk(QUEUE_SIZE)
+            .add_keys(2)
+            .size();
+        // each test drops its segment before the end of its transaction
+        unsafe { PgDsmSegment::create(size) }
+    }
+
+    #[pg_test]
+    fn test_dsm_toc_roundtrip() {
+        let segment = segment();
+        assert!(segment.len() > QUEUE_SIZE);
+
+        let toc = PgShmToc::create(MAGIC, &segment);
+        toc.insert_value(VALUE_KEY, 42i64);
+
+        let attached = PgShmToc::attach(MAGIC, &segment).expect("toc not found");
+        let value = unsafe { attached.lookup_value::<i64>(VALUE_KEY) };
+        assert_eq!(value, Some(&42));
+        assert!(attached.lookup(QUEUE_KEY).is_none());
+        assert!(PgShmToc::attach(MAGIC + 1, &segment).is_none());
+    }
+
+    #[pg_test]
+    fn test_shm_mq_send_receive() {
+        let segment = segment();
+        let toc = PgShmToc::create(MAGIC, &segment);
+        toc.create_mq(QUEUE_KEY, QUEUE_SIZE);
+
+        let mq = toc.lookup_mq(QUEUE_KEY).expect("queue not found");
+        let mut receiver = mq.attach_as_receiver();
+        let mut sender = mq.attach_as_sender();
+
+        assert_eq!(receiver.try_receive(), Err(ShmMqError::WouldBlock));
+        sender.send(b"hello").expect("send failed");
+        sender
+            .send_serialized(&(String::from("world"), 42i32))
+            .expect("send failed");
+        drop(sender);
+
+        assert_eq!(receiver.receive(), Ok(&b"hello"[..]));
+        assert_eq!(
+            receiver.receive_deserialized::<(String, i32)>(),
+            Ok((String::from("world"), 42))
+        );
+        assert_eq!(receiver.receive(), Err(ShmMqError::Detached));
+    }
+}
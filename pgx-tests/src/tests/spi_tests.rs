@@ -162,4 +162,346 @@ mod tests {
         );
         assert_eq!(result, Some(1));
     }
+
+    #[pg_test]
+    fn test_cursor_iterates_in_batches() {
+        Spi::execute(|client| {
+            let mut cursor = client
+                .open_cursor("SELECT * FROM generate_series(1, 10)", None)
+                .set_batch_size(3);
+            let mut values = vec![];
+            while let Some(row) = cursor.next_row() {
+                values.push(row.by_ordinal(1).unwrap().value::<i32>().unwrap());
+            }
+            assert_eq!(values, (1..=10).collect::<Vec<_>>());
+        });
+    }
+
+    #[derive(FromSpiRow, Debug, PartialEq)]
+    struct SeriesRow {
+        value: i32,
+    }
+
+    #[pg_test]
+    fn test_cursor_rows() {
+        Spi::execute(|client| {
+            let mut cursor = client
+                .open_cursor("SELECT value FROM generate_series(1, 10) value", None)
+                .set_batch_size(3);
+            let rows = cursor
+                .rows::<SeriesRow>()
+                .collect::<Result<Vec<_>, _>>()
+                .expect("failed to convert rows");
+            assert_eq!(
+                rows,
+                (1..=10)
+                    .map(|value| SeriesRow { value })
+                    .collect::<Vec<_>>()
+            );
+            assert!(cursor.next_row().is_none());
+        });
+    }
+
+    #[pg_test]
+    fn test_cursor_fetch_and_move() {
+        Spi::execute(|client| {
+            let mut cursor = client.open_cursor(
+                "SELECT * FROM generate_series(1, $1)",
+                Some(vec![(PgBuiltInOids::INT4OID.oid(), 10.into_datum())]),
+            );
+            assert_eq!(cursor.fetch(3).len(), 3);
+            assert_eq!(cursor.move_forward(2), 2);
+
+            let rest = cursor.fetch(100);
+            assert_eq!(rest.len(), 5);
+            assert_eq!(rest[0].by_ordinal(1).unwrap().value::<i32>(), Some(6));
+            assert!(cursor.fetch(1).is_empty());
+        });
+    }
+
+    #[pg_test]
+    fn test_named_cursor_persists_across_connections() {
+        let name = Spi::connect(|client| {
+            let mut cursor = client.open_named_cursor(
+                "pgx_test_cursor",
+                "SELECT * FROM generate_series(1, 10)",
+                None,
+            );
+            assert_eq!(cursor.fetch(2).len(), 2);
+            Ok(Some(cursor.detach_into_name()))
+        })
+        .unwrap();
+        assert_eq!(name, "pgx_test_cursor");
+
+        let next = Spi::connect(|client| {
+            let mut cursor = client.find_cursor(&name).expect("cursor not found");
+            Ok(cursor.fetch(1)[0].by_ordinal(1).unwrap().value::<i32>())
+        });
+        assert_eq!(next, Some(3));
+
+        Spi::execute(|client| assert!(client.find_cursor(&name).is_none()));
+    }
+
+    #[pg_test]
+    fn test_prepared_statement_reuse() {
+        Spi::execute(|client| {
+            let int4 = PgBuiltInOids::INT4OID.oid();
+            let statement = client.prepare("SELECT $1 + $2", &[int4, int4]);
+            assert_eq!(statement.argtypes(), &[int4, int4]);
+
+            for i in 0..3 {
+                let sum = client
+                    .select_prepared(&statement, None, vec![i.into_datum(), 40.into_datum()])
+                    .first()
+                    .get_one::<i32>();
+                assert_eq!(sum, Some(i + 40));
+            }
+
+            let sum = client
+                .select_prepared(&statement, None, vec![None, 40.into_datum()])
+                .first()
+                .get_one::<i32>();
+            assert_eq!(sum, None);
+        });
+    }
+
+    #[pg_test]
+    fn test_prepared_statement_update() {
+        Spi::execute(|mut client| {
+            client.update("CREATE TABLE tests.prepared_test (id int)", None, None);
+            let statement = client.prepare(
+                "INSERT INTO tests.prepared_test VALUES ($1)",
+                &[PgBuiltInOids::INT4OID.oid()],
+            );
+            for i in 1..=3 {
+                client.update_prepared(&statement, None, vec![i.into_datum()]);
+            }
+        });
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT sum(id) FROM tests.prepared_test"),
+            Some(6)
+        );
+    }
+
+    #[pg_test]
+    fn test_prepared_statement_keep() {
+        let mut kept = None;
+        Spi::connect(|client| {
+            kept = Some(
+                client
+                    .prepare("SELECT $1 * 2", &[PgBuiltInOids::INT8OID.oid()])
+                    .keep(),
+            );
+            Ok(Some(()))
+        });
+
+        let statement = kept.unwrap();
+        let doubled = Spi::connect(|client| {
+            Ok(client
+                .select_prepared(&statement, None, vec![21i64.into_datum()])
+                .first()
+                .get_one::<i64>())
+        });
+        assert_eq!(doubled, Some(42));
+    }
+
+    #[pg_test]
+    fn test_prepared_statement_keep_twice() {
+        let mut kept = None;
+        Spi::connect(|client| {
+            // keeping an already kept statement mustn't keep, or later free, its plan twice
+            kept = Some(client.prepare("SELECT 1", &[]).keep().keep());
+            Ok(Some(()))
+        });
+
+        let statement = kept.unwrap();
+        let one = Spi::connect(|client| {
+            Ok(client
+                .select_prepared(&statement, None, vec![])
+                .first()
+                .get_one::<i32>())
+        });
+        assert_eq!(one, Some(1));
+        drop(statement);
+    }
+
+    #[pg_test(error = "prepared statement expects 1 arguments, but 0 were given")]
+    fn test_prepared_statement_wrong_arity() {
+        Spi::execute(|client| {
+            let statement = client.prepare("SELECT $1", &[PgBuiltInOids::INT4OID.oid()]);
+            client.select_prepared(&statement, None, vec![]);
+        });
+    }
+
+    #[derive(FromSpiRow, Debug, PartialEq)]
+    struct SpiRow {
+        id: i32,
+        name: String,
+        note: Option<String>,
+    }
+
+    #[derive(FromSpiRow, Debug)]
+    struct WrongTypes {
+        #[allow(dead_code)]
+        id: String,
+    }
+
+    #[pg_test]
+    fn test_from_spi_row() {
+        Spi::execute(|client| {
+            let rows = client
+                .select(
+                    "SELECT * FROM (VALUES (1, 'a'::varchar, NULL::text), (2, 'b', 'note')) v(id, name, note)",
+                    None,
+                    None,
+                )
+                .into_rows::<SpiRow>()
+                .collect::<Result<Vec<_>, _>>()
+                .expect("failed to map rows");
+
+            assert_eq!(
+                rows,
+                vec![
+                    SpiRow {
+                        id: 1,
+                        name: "a".into(),
+                        note: None
+                    },
+                    SpiRow {
+                        id: 2,
+                        name: "b".into(),
+                        note: Some("note".into())
+                    },
+                ]
+            );
+        });
+    }
+
+    #[pg_test]
+    fn test_from_spi_row_errors() {
+        Spi::execute(|client| {
+            let mismatch = client
+                .select("SELECT 1 AS id", None, None)
+                .into_rows::<WrongTypes>()
+                .next()
+                .unwrap();
+            assert_eq!(
+                mismatch.unwrap_err(),
+                SpiError::TypeMismatch {
+                    column: "id".into(),
+                    expected: pg_sys::TEXTOID,
+                    found: pg_sys::INT4OID,
+                }
+            );
+
+            let missing = client
+                .select("SELECT 1 AS id, 'a' AS name", None, None)
+                .into_rows::<SpiRow>()
+                .next()
+                .unwrap();
+            assert_eq!(
+                missing.unwrap_err(),
+                SpiError::ColumnNotFound {
+                    column: "note".into()
+                }
+            );
+
+            let null = client
+                .select(
+                    "SELECT 1 AS id, NULL::text AS name, NULL::text AS note",
+                    None,
+                    None,
+                )
+                .into_rows::<SpiRow>()
+                .next()
+                .unwrap();
+            assert_eq!(
+                null.unwrap_err(),
+                SpiError::UnexpectedNull {
+                    column: "name".into()
+                }
+            );
+        });
+    }
+
+    #[pg_test]
+    fn test_try_get_one() {
+        assert_eq!(Spi::try_get_one::<i32>("SELECT 42"), Ok(Some(42)));
+
+        let e = Spi::try_get_one::<i32>("THIS IS NOT A VALID QUERY").unwrap_err();
+        assert!(e.is_sqlstate(PgSqlErrorCode::ERRCODE_SYNTAX_ERROR));
+        assert_eq!(e.sqlstate(), Some("42601"));
+
+        // the transaction is still usable
+        assert_eq!(Spi::get_one::<i32>("SELECT 1"), Some(1));
+    }
+
+    #[pg_test]
+    fn test_try_run_captures_error_data() {
+        let e = Spi::try_run(
+            "DO $$ BEGIN RAISE EXCEPTION 'boom' USING DETAIL = 'some detail', HINT = 'some hint'; END $$",
+        )
+        .unwrap_err();
+        assert_eq!(
+            e,
+            SpiError::Sql {
+                sqlstate: "P0001".into(),
+                message: "boom".into(),
+                detail: Some("some detail".into()),
+                hint: Some("some hint".into()),
+            }
+        );
+    }
+
+    #[pg_test]
+    fn test_try_update_unique_violation() {
+        Spi::execute(|mut client| {
+            client.update(
+                "CREATE TABLE tests.try_update (id int PRIMARY KEY)",
+                None,
+                None,
+            );
+            client.update("INSERT INTO tests.try_update VALUES (1)", None, None);
+
+            let e = client
+                .try_update("INSERT INTO tests.try_update VALUES (1)", None, None)
+                .unwrap_err();
+            assert!(e.is_sqlstate(PgSqlErrorCode::ERRCODE_UNIQUE_VIOLATION));
+
+            // retry with a different key using the same client
+            client
+                .try_update("INSERT INTO tests.try_update VALUES (2)", None, None)
+                .expect("insert failed");
+        });
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT sum(id) FROM tests.try_update"),
+            Some(3)
+        );
+    }
+
+    #[pg_test]
+    fn test_try_connect_rolls_back_on_err() {
+        Spi::run("CREATE TABLE tests.try_connect (id int)");
+        let result = Spi::try_connect::<(), _>(|mut client| {
+            client.update("INSERT INTO tests.try_connect VALUES (1)", None, None);
+            Err(SpiError::Noattribute)
+        });
+        assert_eq!(result, Err(SpiError::Noattribute));
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM tests.try_connect"),
+            Some(0)
+        );
+    }
+
+    #[pg_test]
+    fn test_try_check_status() {
+        assert_eq!(
+            Spi::try_check_status(pg_sys::SPI_OK_SELECT as i32),
+            Ok(SpiOk::Select)
+        );
+        assert_eq!(
+            Spi::try_check_status(pg_sys::SPI_ERROR_ARGUMENT),
+            Err(SpiError::Argument)
+        );
+    }
 }
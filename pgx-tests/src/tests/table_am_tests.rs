@@ -0,0 +1,187 @@
+use pgx::*;
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    static ref MEMORY_AM_ROWS: Mutex<Vec<Option<i32>>> = Mutex::new(Vec::new());
+}
+
+pub struct MemoryAm;
+
+#[pg_table_am]
+impl TableAm for MemoryAm {
+    type ScanState = usize;
+
+    fn scan_begin(
+        _relation: &PgRelation,
+        _snapshot: pg_sys::Snapshot,
+        _keys: &[pg_sys::ScanKeyData],
+        _flags: u32,
+    ) -> Self::ScanState {
+        0
+    }
+
+    fn scan_rescan(scan: &mut PgTableScan<Self::ScanState>) {
+        *scan.state_mut() = 0;
+    }
+
+    fn scan_getnextslot(
+        scan: &mut PgTableScan<Self::ScanState>,
+        _direction: pg_sys::ScanDirection,
+        slot: &mut PgTupleTableSlot,
+    ) -> bool {
+        let rows = MEMORY_AM_ROWS.lock().unwrap();
+        let position = scan.state_mut();
+        match rows.get(*position) {
+            Some(&value) => {
+                *position += 1;
+                slot.store_virtual(&[value.into_datum()]);
+                true
+            }
+            None => {
+                slot.clear();
+                false
+            }
+        }
+    }
+
+    fn tuple_insert(
+        _relation: &PgRelation,
+        slot: &mut PgTupleTableSlot,
+        _cid: pg_sys::CommandId,
+        _options: i32,
+    ) {
+        MEMORY_AM_ROWS.lock().unwrap().push(slot.get::<i32>(1));
+    }
+
+    fn relation_set_new_filenode(
+        _relation: &PgRelation,
+        _new_rnode: &pg_sys::RelFileNode,
+        _persistence: std::os::raw::c_char,
+    ) -> (pg_sys::TransactionId, pg_sys::MultiXactId) {
+        MEMORY_AM_ROWS.lock().unwrap().clear();
+        (pg_sys::InvalidTransactionId, 0)
+    }
+
+    fn relation_estimate_size(_relation: &PgRelation) -> TableSizeEstimate {
+        TableSizeEstimate {
+            pages: 1,
+            tuples: MEMORY_AM_ROWS.lock().unwrap().len() as f64,
+            allvisfrac: 0.0,
+        }
+    }
+}
+
+/// Every table using it holds the integers 1 through 100, whichever process scans it, so that
+/// parallel scans can be checked for missing or duplicated rows
+pub struct SeriesAm;
+
+#[pg_table_am]
+impl TableAm for SeriesAm {
+    type ScanState = i32;
+
+    fn scan_begin(
+        _relation: &PgRelation,
+        _snapshot: pg_sys::Snapshot,
+        _keys: &[pg_sys::ScanKeyData],
+        _flags: u32,
+    ) -> Self::ScanState {
+        0
+    }
+
+    fn scan_rescan(scan: &mut PgTableScan<Self::ScanState>) {
+        *scan.state_mut() = 0;
+    }
+
+    fn scan_getnextslot(
+        scan: &mut PgTableScan<Self::ScanState>,
+        _direction: pg_sys::ScanDirection,
+        slot: &mut PgTupleTableSlot,
+    ) -> bool {
+        let last = scan.state_mut();
+        if *last < 100 {
+            *last += 1;
+            slot.store_virtual(&[(*last).into_datum()]);
+            true
+        } else {
+            slot.clear();
+            false
+        }
+    }
+
+    fn tuple_insert(
+        _relation: &PgRelation,
+        _slot: &mut PgTupleTableSlot,
+        _cid: pg_sys::CommandId,
+        _options: i32,
+    ) {
+        panic!("series_am tables are read-only")
+    }
+
+    fn relation_set_new_filenode(
+        _relation: &PgRelation,
+        _new_rnode: &pg_sys::RelFileNode,
+        _persistence: std::os::raw::c_char,
+    ) -> (pg_sys::TransactionId, pg_sys::MultiXactId) {
+        (pg_sys::InvalidTransactionId, 0)
+    }
+
+    fn relation_estimate_size(_relation: &PgRelation) -> TableSizeEstimate {
+        TableSizeEstimate {
+            pages: 1000,
+            tuples: 100.0,
+            allvisfrac: 0.0,
+        }
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+    use pgx::*;
+
+    #[pg_test]
+    fn table_am_registered() {
+        let registered = Spi::get_one::<bool>(
+            "SELECT EXISTS(SELECT 1 FROM pg_am WHERE amname = 'memory_am' AND amtype = 't');",
+        )
+        .expect("SQL select failed");
+        assert!(registered);
+    }
+
+    #[pg_test]
+    fn table_am_insert_and_scan() {
+        Spi::run(
+            "CREATE TABLE memory_am_table (id int4) USING memory_am;
+            INSERT INTO memory_am_table VALUES (1), (2), (NULL), (3);",
+        );
+        let sum =
+            Spi::get_one::<i64>("SELECT sum(id) FROM memory_am_table;").expect("SQL select failed");
+        assert_eq!(sum, 6);
+        let count = Spi::get_one::<i64>("SELECT count(*) FROM memory_am_table WHERE id IS NULL;")
+            .expect("SQL select failed");
+        assert_eq!(count, 1);
+    }
+
+    #[pg_test]
+    fn table_am_parallel_scan() {
+        Spi::run(
+            "CREATE TABLE series_am_table (id int4) USING series_am;
+            SET LOCAL parallel_setup_cost = 0;
+            SET LOCAL parallel_tuple_cost = 0;
+            SET LOCAL min_parallel_table_scan_size = 0;
+            SET LOCAL max_parallel_workers_per_gather = 2;",
+        );
+        let plan = Spi::get_one::<&str>("EXPLAIN (COSTS OFF) SELECT id FROM series_am_table;")
+            .expect("SQL select failed");
+        assert_eq!(plan, "Gather");
+
+        let sum =
+            Spi::get_one::<i64>("SELECT sum(id) FROM series_am_table;").expect("SQL select failed");
+        assert_eq!(sum, 5050);
+        let count = Spi::get_one::<i64>("SELECT count(*) FROM series_am_table;")
+            .expect("SQL select failed");
+        assert_eq!(count, 100);
+    }
+}
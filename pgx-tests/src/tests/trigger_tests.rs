@@ -0,0 +1,114 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+/// Record how, and for which rows, it fired in `tests.trigger_log`
+#[pg_trigger]
+fn log_trigger_event(trigger: &PgTrigger) -> Option<PgHeapTuple<'_>> {
+    let title =
+        |tuple: Option<PgHeapTuple<'_>>| tuple.and_then(|t| t.get_by_name::<String>("title"));
+    let entry = format!(
+        "{:?} {:?} {:?} {} on {} args={} old={:?} new={:?}",
+        trigger.when(),
+        trigger.level(),
+        trigger.op(),
+        trigger.name(),
+        trigger.relation().name(),
+        trigger.args().join(","),
+        title(trigger.old()),
+        title(trigger.new()),
+    );
+    Spi::execute(|mut client| {
+        client.update(
+            "INSERT INTO tests.trigger_log (entry) VALUES ($1)",
+            None,
+            Some(vec![(PgOid::from(String::type_oid()), entry.into_datum())]),
+        );
+    });
+
+    match (trigger.when(), trigger.level()) {
+        (PgTriggerWhen::Before, PgTriggerLevel::Row) => trigger.new().or_else(|| trigger.old()),
+        _ => None,
+    }
+}
+
+#[pg_trigger]
+fn skip_deletes(trigger: &PgTrigger) -> Option<PgHeapTuple<'_>> {
+    match trigger.op() {
+        PgTriggerOperation::Delete => None,
+        _ => trigger.new(),
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn trigger_log() -> String {
+        Spi::get_one::<String>(
+            "SELECT string_agg(entry, E'\\n' ORDER BY id) FROM tests.trigger_log;",
+        )
+        .expect("SPI result was NULL")
+    }
+
+    #[pg_test]
+    fn test_row_trigger() {
+        Spi::run(
+            "CREATE TABLE tests.trigger_log (id serial, entry text); \
+             CREATE TABLE tests.books (id int, title text); \
+             CREATE TRIGGER books_before BEFORE INSERT OR UPDATE OR DELETE ON tests.books \
+                FOR EACH ROW EXECUTE PROCEDURE log_trigger_event('a', 'b'); \
+             INSERT INTO tests.books VALUES (1, 'Dune'); \
+             UPDATE tests.books SET title = 'Emma'; \
+             DELETE FROM tests.books;",
+        );
+
+        assert_eq!(
+            trigger_log(),
+            "Before Row Insert books_before on books args=a,b old=None new=Some(\"Dune\")\n\
+             Before Row Update books_before on books args=a,b old=Some(\"Dune\") new=Some(\"Emma\")\n\
+             Before Row Delete books_before on books args=a,b old=Some(\"Emma\") new=None"
+        );
+        let count =
+            Spi::get_one::<i64>("SELECT count(*) FROM tests.books;").expect("SPI result was NULL");
+        assert_eq!(count, 0);
+    }
+
+    #[pg_test]
+    fn test_statement_trigger() {
+        Spi::run(
+            "CREATE TABLE tests.trigger_log (id serial, entry text); \
+             CREATE TABLE tests.films (id int, title text); \
+             CREATE TRIGGER films_after AFTER INSERT OR TRUNCATE ON tests.films \
+                FOR EACH STATEMENT EXECUTE PROCEDURE log_trigger_event(); \
+             INSERT INTO tests.films VALUES (1, 'Heat'), (2, 'Ran'); \
+             TRUNCATE tests.films;",
+        );
+
+        assert_eq!(
+            trigger_log(),
+            "After Statement Insert films_after on films args= old=None new=None\n\
+             After Statement Truncate films_after on films args= old=None new=None"
+        );
+    }
+
+    #[pg_test]
+    fn test_skip_row() {
+        Spi::run(
+            "CREATE TABLE tests.keepers (id int); \
+             CREATE TRIGGER keepers_before BEFORE INSERT OR DELETE ON tests.keepers \
+                FOR EACH ROW EXECUTE PROCEDURE skip_deletes(); \
+             INSERT INTO tests.keepers VALUES (1), (2); \
+             DELETE FROM tests.keepers WHERE id = 1;",
+        );
+
+        let count = Spi::get_one::<i64>("SELECT count(*) FROM tests.keepers;")
+            .expect("SPI result was NULL");
+        assert_eq!(count, 2);
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+/// A toy ranking function: the sum of each matching lexeme's best weight
+#[pg_extern]
+fn weight_rank(vector: TsVector, words: Vec<Option<String>>) -> i32 {
+    words
+        .into_iter()
+        .flatten()
+        .filter_map(|word| vector.get(&word))
+        .filter_map(|lexeme| lexeme.positions().iter().map(|pos| pos.weight).max())
+        .map(|weight| weight as i32 + 1)
+        .sum()
+}
+
+/// A toy parser: every whitespace-separated word, lowercased, at its position
+#[pg_extern]
+fn whitespace_tsvector(input: &str) -> TsVector {
+    let mut builder = TsVectorBuilder::new();
+    for (idx, word) in input.split_whitespace().enumerate() {
+        builder.add_position(&word.to_lowercase(), idx as u16 + 1, TsWeight::D);
+    }
+    builder.build()
+}
+
+#[pg_extern]
+fn take_and_return_tsquery(query: TsQuery) -> TsQuery {
+    query
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_decode_tsvector() {
+        let vector = Spi::get_one::<TsVector>(
+            "SELECT to_tsvector('english', 'The fat cats ate the fat rats');",
+        )
+        .expect("SPI result was NULL");
+        let words = vector
+            .lexemes()
+            .iter()
+            .map(|lexeme| lexeme.word())
+            .collect::<Vec<_>>();
+        assert_eq!(words, vec!["ate", "cat", "fat", "rat"]);
+
+        let fat = vector.get("fat").expect("no 'fat' lexeme");
+        assert_eq!(
+            fat.positions(),
+            &[
+                TsPosition {
+                    position: 2,
+                    weight: TsWeight::D
+                },
+                TsPosition {
+                    position: 6,
+                    weight: TsWeight::D
+                }
+            ]
+        );
+    }
+
+    #[pg_test]
+    fn test_decode_weights_and_stripped() {
+        let vector = Spi::get_one::<TsVector>("SELECT 'a:1A,2B b:3C c'::tsvector;")
+            .expect("SPI result was NULL");
+        let weights = vector
+            .get("a")
+            .unwrap()
+            .positions()
+            .iter()
+            .map(|pos| pos.weight)
+            .collect::<Vec<_>>();
+        assert_eq!(weights, vec![TsWeight::A, TsWeight::B]);
+        assert!(vector.get("c").unwrap().positions().is_empty());
+        assert!(vector.get("d").is_none());
+    }
+
+    #[pg_test]
+    fn test_build_tsvector() {
+        let mut builder = TsVectorBuilder::new();
+        builder.add_position("fat", 4, TsWeight::D);
+        builder.add_position("cat", 3, TsWeight::A);
+        builder.add_position("fat", 2, TsWeight::D);
+        builder.add_position("fat", 2, TsWeight::B);
+        builder.add_lexeme("rat");
+
+        let matches = Spi::get_one_with_args::<bool>(
+            "SELECT $1 = 'cat:3A fat:2B,4 rat'::tsvector;",
+            vec![(
+                PgOid::from(TsVector::type_oid()),
+                builder.build().into_datum(),
+            )],
+        )
+        .expect("SPI result was NULL");
+        assert!(matches);
+    }
+
+    #[pg_test]
+    fn test_custom_parser() {
+        let matches = Spi::get_one::<bool>(
+            "SELECT whitespace_tsvector('Fat cats   ate fat rats') = 'fat:1,4 cats:2 ate:3 rats:5'::tsvector \
+                AND whitespace_tsvector('Fat cats ate') @@ 'fat <-> cats'::tsquery;",
+        )
+        .expect("SPI result was NULL");
+        assert!(matches);
+    }
+
+    #[pg_test]
+    fn test_custom_ranking() {
+        let rank = Spi::get_one::<i32>(
+            "SELECT weight_rank(setweight(to_tsvector('english', 'fat cat'), 'A') || to_tsvector('english', 'rat'), ARRAY['cat', 'rat', 'dog']);",
+        )
+        .expect("SPI result was NULL");
+        assert_eq!(rank, 4 + 1);
+    }
+
+    #[pg_test]
+    fn test_decode_tsquery() {
+        let query = Spi::get_one::<TsQuery>("SELECT 'fat & (rat | !cat)'::tsquery;")
+            .expect("SPI result was NULL");
+        let expected = TsQueryNode::lexeme("fat")
+            .and(TsQueryNode::lexeme("rat").or(TsQueryNode::lexeme("cat").negate()));
+        assert_eq!(query, TsQuery::from(expected));
+    }
+
+    #[pg_test]
+    fn test_decode_tsquery_phrase_prefix_weights() {
+        let query = Spi::get_one::<TsQuery>("SELECT 'supern:*AB <2> star'::tsquery;")
+            .expect("SPI result was NULL");
+        let expected = TsQueryNode::from(
+            TsQueryOperand::new("supern")
+                .prefix()
+                .weights(&[TsWeight::B, TsWeight::A]),
+        )
+        .followed_by(TsQueryNode::lexeme("star"), 2);
+        assert_eq!(query, TsQuery::from(expected));
+    }
+
+    #[pg_test]
+    fn test_build_tsquery() {
+        let query = TsQuery::from(
+            TsQueryNode::lexeme("fat")
+                .and(TsQueryNode::lexeme("rat").or(TsQueryNode::lexeme("cat").negate()))
+                .and(
+                    TsQueryNode::from(TsQueryOperand::new("supern").prefix())
+                        .followed_by(TsQueryNode::lexeme("star"), 1),
+                ),
+        );
+        let matches = Spi::get_one_with_args::<bool>(
+            "SELECT $1 = 'fat & (rat | !cat) & supern:* <-> star'::tsquery \
+                AND to_tsvector('simple', 'fat rat supernova star') @@ $1;",
+            vec![(PgOid::from(TsQuery::type_oid()), query.into_datum())],
+        )
+        .expect("SPI result was NULL");
+        assert!(matches);
+    }
+
+    #[pg_test]
+    fn test_tsquery_roundtrip() {
+        let matches = Spi::get_one::<bool>(
+            "SELECT take_and_return_tsquery('a:A & !(b | c) <3> d:*') = 'a:A & !(b | c) <3> d:*'::tsquery;",
+        )
+        .expect("SPI result was NULL");
+        assert!(matches);
+    }
+
+    #[pg_test]
+    fn test_empty_tsquery() {
+        let query = Spi::get_one::<TsQuery>("SELECT to_tsquery('english', 'the');")
+            .expect("SPI result was NULL");
+        assert!(query.is_empty());
+
+        let text = Spi::get_one_with_args::<&str>(
+            "SELECT $1::text;",
+            vec![(
+                PgOid::from(TsQuery::type_oid()),
+                TsQuery::empty().into_datum(),
+            )],
+        )
+        .expect("SPI result was NULL");
+        assert_eq!(text, "");
+    }
+}
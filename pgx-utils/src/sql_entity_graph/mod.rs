@@ -1,25 +1,33 @@
 mod extension_sql;
 mod pg_aggregate;
 mod pg_extern;
+mod pg_foreign_data_wrapper;
+mod pg_index_am;
 mod pg_schema;
+mod pg_table_am;
 mod pgx_attribute;
 mod positioning_ref;
 mod postgres_enum;
 mod postgres_hash;
 mod postgres_ord;
+mod postgres_range;
 mod postgres_type;
 mod to_sql;
 
 pub use super::ExternArgs;
 pub use extension_sql::{ExtensionSql, ExtensionSqlFile, SqlDeclared};
 pub use pg_aggregate::PgAggregate;
-pub use pg_extern::{Argument, PgExtern, PgOperator};
+pub use pg_extern::{Argument, PgEventTrigger, PgExtern, PgOperator};
+pub use pg_foreign_data_wrapper::PgForeignDataWrapper;
+pub use pg_index_am::PgIndexAm;
 pub use pg_schema::Schema;
+pub use pg_table_am::PgTableAm;
 pub use pgx_attribute::{ArgValue, NameValueArg, PgxArg, PgxAttribute};
 pub use positioning_ref::PositioningRef;
 pub use postgres_enum::PostgresEnum;
 pub use postgres_hash::PostgresHash;
 pub use postgres_ord::PostgresOrd;
+pub use postgres_range::PostgresRange;
 pub use postgres_type::PostgresType;
 pub use to_sql::ToSqlConfig;
 
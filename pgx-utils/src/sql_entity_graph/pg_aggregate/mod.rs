@@ -727,7 +727,7 @@ fn pg_extern_attr(item: &ImplItemMethod) -> syn::Attribute {
     }
 }
 
-fn get_impl_type_by_name<'a>(item_impl: &'a ItemImpl, name: &str) -> Option<&'a ImplItemType> {
+pub(crate) fn get_impl_type_by_name<'a>(item_impl: &'a ItemImpl, name: &str) -> Option<&'a ImplItemType> {
     let mut needle = None;
     for impl_item in item_impl.items.iter() {
         match impl_item {
@@ -743,7 +743,7 @@ fn get_impl_type_by_name<'a>(item_impl: &'a ItemImpl, name: &str) -> Option<&'a
     needle
 }
 
-fn get_impl_func_by_name<'a>(item_impl: &'a ItemImpl, name: &str) -> Option<&'a ImplItemMethod> {
+pub(crate) fn get_impl_func_by_name<'a>(item_impl: &'a ItemImpl, name: &str) -> Option<&'a ImplItemMethod> {
     let mut needle = None;
     for impl_item in item_impl.items.iter() {
         match impl_item {
@@ -759,7 +759,7 @@ fn get_impl_func_by_name<'a>(item_impl: &'a ItemImpl, name: &str) -> Option<&'a
     needle
 }
 
-fn get_impl_const_by_name<'a>(item_impl: &'a ItemImpl, name: &str) -> Option<&'a ImplItemConst> {
+pub(crate) fn get_impl_const_by_name<'a>(item_impl: &'a ItemImpl, name: &str) -> Option<&'a ImplItemConst> {
     let mut needle = None;
     for impl_item in item_impl.items.iter() {
         match impl_item {
@@ -0,0 +1,124 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Token,
+};
+
+/// The events a `CREATE EVENT TRIGGER` can fire on
+const EVENTS: &[&str] = &[
+    "ddl_command_start",
+    "ddl_command_end",
+    "sql_drop",
+    "table_rewrite",
+];
+
+/// A parsed `#[pg_event_trigger]` event trigger function.
+///
+/// It is created during [`PgExtern`](crate::sql_entity_graph::PgExtern) parsing, from the
+/// `#[event_trigger]` attribute `#[pg_event_trigger]` leaves on the function.
+///
+/// If it has an `event`, a `CREATE EVENT TRIGGER` is generated for the function.
+#[derive(Debug, Default, Clone)]
+pub struct PgEventTrigger {
+    pub event: Option<syn::LitStr>,
+    pub tags: Vec<syn::LitStr>,
+}
+
+impl Parse for PgEventTrigger {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let mut event_trigger = PgEventTrigger::default();
+        let args = Punctuated::<EventTriggerArg, Token![,]>::parse_terminated(input)?;
+        for arg in args {
+            match arg {
+                EventTriggerArg::Event(event) => {
+                    if !EVENTS.contains(&event.value().as_str()) {
+                        return Err(syn::Error::new(
+                            event.span(),
+                            format!("`event` must be one of: {}", EVENTS.join(", ")),
+                        ));
+                    }
+                    event_trigger.event = Some(event);
+                }
+                EventTriggerArg::Tags(tags) => event_trigger.tags.extend(tags),
+            }
+        }
+
+        if event_trigger.event.is_none() && !event_trigger.tags.is_empty() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`tags` requires an `event`",
+            ));
+        }
+        Ok(event_trigger)
+    }
+}
+
+impl ToTokens for PgEventTrigger {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let event = self.event.iter();
+        let tags = self.tags.iter();
+        let quoted = quote! {
+            pgx::datum::sql_entity_graph::PgEventTriggerEntity {
+                event: None#( .unwrap_or(Some(#event)) )*,
+                tags: vec![#(#tags),*],
+            }
+        };
+        tokens.append_all(quoted);
+    }
+}
+
+enum EventTriggerArg {
+    Event(syn::LitStr),
+    Tags(Punctuated<syn::LitStr, Token![,]>),
+}
+
+impl Parse for EventTriggerArg {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let ident: syn::Ident = input.parse()?;
+        let _eq: Token![=] = input.parse()?;
+        match ident.to_string().as_str() {
+            "event" => Ok(Self::Event(input.parse()?)),
+            "tags" => {
+                let content;
+                let _bracket = syn::bracketed!(content in input);
+                Ok(Self::Tags(
+                    content.parse_terminated(<syn::LitStr as Parse>::parse)?,
+                ))
+            }
+            _ => Err(syn::Error::new(ident.span(), "Invalid option")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PgEventTrigger;
+
+    #[test]
+    fn parse_event_trigger() {
+        let parsed: PgEventTrigger = syn::parse_str("").unwrap();
+        assert!(parsed.event.is_none());
+
+        let parsed: PgEventTrigger =
+            syn::parse_str(r#"event = "ddl_command_end", tags = ["CREATE TABLE", "DROP TABLE"]"#)
+                .unwrap();
+        assert_eq!(parsed.event.unwrap().value(), "ddl_command_end");
+        assert_eq!(
+            parsed
+                .tags
+                .iter()
+                .map(|tag| tag.value())
+                .collect::<Vec<_>>(),
+            vec!["CREATE TABLE", "DROP TABLE"]
+        );
+    }
+
+    #[test]
+    fn reject_bad_event_trigger() {
+        assert!(syn::parse_str::<PgEventTrigger>(r#"event = "whenever""#).is_err());
+        assert!(syn::parse_str::<PgEventTrigger>(r#"tags = ["CREATE TABLE"]"#).is_err());
+        assert!(syn::parse_str::<PgEventTrigger>(r#"name = "audit""#).is_err());
+    }
+}
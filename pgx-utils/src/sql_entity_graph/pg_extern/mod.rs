@@ -1,11 +1,13 @@
 mod argument;
 mod attribute;
+mod event_trigger;
 mod operator;
 mod returning;
 mod search_path;
 
 pub use argument::Argument;
 use attribute::Attribute;
+pub use event_trigger::PgEventTrigger;
 pub use operator::PgOperator;
 use operator::{PgxOperatorAttributeWithIdent, PgxOperatorOpName};
 pub(crate) use returning::NameMacro;
@@ -157,6 +159,21 @@ impl PgExtern {
         skel
     }
 
+    fn event_trigger(&self) -> Option<PgEventTrigger> {
+        self.func
+            .attrs
+            .iter()
+            .find(|attr| attr.path.segments.last().unwrap().ident == "event_trigger")
+            .map(|attr| {
+                if attr.tokens.is_empty() {
+                    PgEventTrigger::default()
+                } else {
+                    attr.parse_args::<PgEventTrigger>()
+                        .expect(&format!("Unable to parse {:?}", &attr.tokens))
+                }
+            })
+    }
+
     fn search_path(&self) -> Option<SearchPathList> {
         self.func
             .attrs
@@ -184,6 +201,9 @@ impl PgExtern {
     }
 
     fn returns(&self) -> Result<Returning, eyre::Error> {
+        if self.event_trigger().is_some() {
+            return Ok(Returning::EventTrigger);
+        }
         Returning::try_from(&self.func.sig.output)
     }
 
@@ -233,6 +253,7 @@ impl ToTokens for PgExtern {
             }
         };
         let operator = self.operator().into_iter();
+        let event_trigger = self.event_trigger().into_iter();
         let to_sql_config = match self.overridden() {
             None => self.to_sql_config.clone(),
             Some(content) => {
@@ -264,6 +285,7 @@ impl ToTokens for PgExtern {
                     fn_args: vec![#(#inputs),*],
                     fn_return: #returns,
                     operator: None#( .unwrap_or(Some(#operator)) )*,
+                    event_trigger: None#( .unwrap_or(Some(#event_trigger)) )*,
                     to_sql_config: #to_sql_config,
                 };
                 pgx::datum::sql_entity_graph::SqlGraphEntity::Function(submission)
@@ -16,6 +16,8 @@ pub enum Returning {
     Iterated(Vec<(syn::Type, Option<String>)>),
     /// `pgx_pg_sys::Datum`
     Trigger,
+    /// A `#[pg_event_trigger]` function
+    EventTrigger,
 }
 
 impl Returning {
@@ -262,6 +264,9 @@ impl ToTokens for Returning {
             Returning::Trigger => quote! {
                 pgx::datum::sql_entity_graph::PgExternReturnEntity::Trigger
             },
+            Returning::EventTrigger => quote! {
+                pgx::datum::sql_entity_graph::PgExternReturnEntity::EventTrigger
+            },
         };
         tokens.append_all(quoted);
     }
@@ -0,0 +1,365 @@
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    spanned::Spanned,
+    ItemFn, ItemImpl, Path,
+};
+
+use super::pg_aggregate::{get_impl_const_by_name, get_impl_func_by_name, get_impl_type_by_name};
+use super::ToSqlConfig;
+
+/** A parsed `#[pg_foreign_data_wrapper]` item.
+
+It should be used with [`syn::parse::Parse`] functions.
+
+Using [`quote::ToTokens`] will output the `impl`, the generated `fdw_handler` and validator
+functions, and the declaration for a `pgx::datum::sql_entity_graph::PgForeignDataWrapperEntity`.
+
+```rust
+use syn::{parse_quote, ItemImpl};
+use quote::ToTokens;
+use pgx_utils::sql_entity_graph::PgForeignDataWrapper;
+
+# fn main() -> eyre::Result<()> {
+let parsed: ItemImpl = parse_quote! {
+    impl ForeignDataWrapper for NoopFdw {
+        type Row = (i32,);
+        fn begin_scan() { todo!() }
+        fn iterate_scan() { todo!() }
+    }
+};
+let fdw = PgForeignDataWrapper::new(parsed)?;
+let sql_graph_entity_tokens = fdw.to_token_stream();
+# Ok(())
+# }
+```
+*/
+#[derive(Debug, Clone)]
+pub struct PgForeignDataWrapper {
+    item_impl: ItemImpl,
+    target_path: Path,
+    snake_case_target_ident: Ident,
+    handler: ItemFn,
+    validator: ItemFn,
+    to_sql_config: ToSqlConfig,
+}
+
+impl PgForeignDataWrapper {
+    pub fn new(mut item_impl: ItemImpl) -> Result<Self, syn::Error> {
+        let to_sql_config =
+            ToSqlConfig::from_attributes(item_impl.attrs.as_slice())?.unwrap_or_default();
+
+        if let Some((_, ref path, _)) = item_impl.trait_ {
+            if let Some(last) = path.segments.last() {
+                if last.ident.to_string() != "ForeignDataWrapper" {
+                    return Err(syn::Error::new(
+                        last.ident.span(),
+                        "`#[pg_foreign_data_wrapper]` only works with the `ForeignDataWrapper` trait.",
+                    ));
+                }
+            }
+        }
+
+        let target_path = match &*item_impl.self_ty {
+            syn::Type::Path(ref type_path) => type_path.path.clone(),
+            something_else => {
+                return Err(syn::Error::new(
+                    something_else.span(),
+                    "`#[pg_foreign_data_wrapper]` only works with types.",
+                ))
+            }
+        };
+        let target_ident = target_path
+            .segments
+            .last()
+            .ok_or_else(|| {
+                syn::Error::new(
+                    target_path.span(),
+                    "`#[pg_foreign_data_wrapper]` only works with types whose path have a final segment.",
+                )
+            })?
+            .ident
+            .clone();
+        let snake_case_target_ident = Ident::new(
+            &target_ident.to_string().to_case(Case::Snake),
+            target_ident.span(),
+        );
+
+        // We want to avoid having multiple borrows, so we take a snapshot to scan from,
+        // and mutate the actual one.
+        let item_impl_snapshot = item_impl.clone();
+
+        // `NAME` is an optional value, we default to the snake cased name of the type.
+        if get_impl_const_by_name(&item_impl_snapshot, "NAME").is_none() {
+            let name = snake_case_target_ident.to_string();
+            item_impl.items.push(parse_quote! {
+                const NAME: &'static str = #name;
+            });
+        }
+
+        // `ScanState` is an optional value, we default to nothing.
+        if get_impl_type_by_name(&item_impl_snapshot, "ScanState").is_none() {
+            item_impl.items.push(parse_quote! {
+                type ScanState = ();
+            });
+        }
+
+        if get_impl_type_by_name(&item_impl_snapshot, "Row").is_none() {
+            return Err(syn::Error::new(
+                item_impl_snapshot.span(),
+                "`#[pg_foreign_data_wrapper]` requires the `Row` type defined.",
+            ));
+        }
+
+        for required in ["begin_scan", "iterate_scan"] {
+            if get_impl_func_by_name(&item_impl_snapshot, required).is_none() {
+                return Err(syn::Error::new(
+                    item_impl_snapshot.span(),
+                    format!(
+                        "`#[pg_foreign_data_wrapper]` requires the `{}` function defined.",
+                        required
+                    ),
+                ));
+            }
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "validate").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn validate(_options: pgx::FdwOptions, _catalog: pgx::pg_sys::Oid) {}
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "get_rel_size").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn get_rel_size(
+                    _root: &mut pgx::pg_sys::PlannerInfo,
+                    _baserel: &mut pgx::pg_sys::RelOptInfo,
+                    _foreign_table_oid: pgx::pg_sys::Oid,
+                ) {}
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "get_paths").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn get_paths(
+                    root: &mut pgx::pg_sys::PlannerInfo,
+                    baserel: &mut pgx::pg_sys::RelOptInfo,
+                    _foreign_table_oid: pgx::pg_sys::Oid,
+                ) {
+                    pgx::default_foreign_paths(root, baserel)
+                }
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "get_plan").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn get_plan(
+                    _root: &mut pgx::pg_sys::PlannerInfo,
+                    baserel: &mut pgx::pg_sys::RelOptInfo,
+                    _foreign_table_oid: pgx::pg_sys::Oid,
+                    _best_path: &mut pgx::pg_sys::ForeignPath,
+                    tlist: pgx::PgList<pgx::pg_sys::TargetEntry>,
+                    scan_clauses: pgx::PgList<pgx::pg_sys::RestrictInfo>,
+                    outer_plan: *mut pgx::pg_sys::Plan,
+                ) -> pgx::PgBox<pgx::pg_sys::ForeignScan> {
+                    pgx::default_foreign_plan(baserel, tlist, scan_clauses, outer_plan)
+                }
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "rescan").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn rescan(_state: &mut Self::ScanState) {}
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "end_scan").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn end_scan(_state: &mut Self::ScanState) {}
+            });
+        }
+
+        // Postgres checks if these callbacks are set to decide which commands are possible.
+        let mut unset_callbacks = Vec::new();
+        if get_impl_func_by_name(&item_impl_snapshot, "insert").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn insert(
+                    _relation: &pgx::PgRelation,
+                    _options: &pgx::FdwOptions,
+                    _values: &[Option<pgx::pg_sys::Datum>],
+                ) {
+                    unimplemented!("Call to insert on a foreign data wrapper which does not support it.")
+                }
+            });
+            unset_callbacks.push(Ident::new("ExecForeignInsert", item_impl_snapshot.span()));
+        }
+        if get_impl_func_by_name(&item_impl_snapshot, "update").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn update(
+                    _relation: &pgx::PgRelation,
+                    _options: &pgx::FdwOptions,
+                    _rowid: Option<pgx::pg_sys::Datum>,
+                    _values: &[Option<pgx::pg_sys::Datum>],
+                ) {
+                    unimplemented!("Call to update on a foreign data wrapper which does not support it.")
+                }
+            });
+            unset_callbacks.push(Ident::new("ExecForeignUpdate", item_impl_snapshot.span()));
+        }
+        if get_impl_func_by_name(&item_impl_snapshot, "delete").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn delete(
+                    _relation: &pgx::PgRelation,
+                    _options: &pgx::FdwOptions,
+                    _rowid: Option<pgx::pg_sys::Datum>,
+                ) {
+                    unimplemented!("Call to delete on a foreign data wrapper which does not support it.")
+                }
+            });
+            unset_callbacks.push(Ident::new("ExecForeignDelete", item_impl_snapshot.span()));
+        }
+        if unset_callbacks.len() == 3 {
+            for callback in [
+                "AddForeignUpdateTargets",
+                "BeginForeignModify",
+                "EndForeignModify",
+            ] {
+                unset_callbacks.push(Ident::new(callback, item_impl_snapshot.span()));
+            }
+        }
+
+        let handler_ident = Ident::new(
+            &format!("{}_handler", snake_case_target_ident),
+            target_ident.span(),
+        );
+        let handler = parse_quote! {
+            #[pg_extern]
+            fn #handler_ident() -> pgx::PgBox<pgx::pg_sys::FdwRoutine> {
+                #[allow(unused_mut)]
+                let mut routine = pgx::fdw_routine::<#target_path>();
+                #( routine.#unset_callbacks = None; )*
+                routine
+            }
+        };
+
+        let validator_ident = Ident::new(
+            &format!("{}_validator", snake_case_target_ident),
+            target_ident.span(),
+        );
+        let validator = parse_quote! {
+            #[pg_extern]
+            fn #validator_ident(options: Vec<String>, catalog: pgx::pg_sys::Oid) {
+                pgx::fdw_validate::<#target_path>(options, catalog)
+            }
+        };
+
+        Ok(Self {
+            item_impl,
+            target_path,
+            snake_case_target_ident,
+            handler,
+            validator,
+            to_sql_config,
+        })
+    }
+
+    fn entity_tokens(&self) -> ItemFn {
+        let target_path = &self.target_path;
+        let handler_ident = &self.handler.sig.ident;
+        let validator_ident = &self.validator.sig.ident;
+        let to_sql_config = &self.to_sql_config;
+        let sql_graph_entity_fn_name = syn::Ident::new(
+            &format!("__pgx_internals_fdw_{}", self.snake_case_target_ident),
+            self.snake_case_target_ident.span(),
+        );
+
+        parse_quote! {
+            #[no_mangle]
+            pub extern "C" fn #sql_graph_entity_fn_name() -> pgx::datum::sql_entity_graph::SqlGraphEntity {
+                let submission = pgx::datum::sql_entity_graph::PgForeignDataWrapperEntity {
+                    name: <#target_path as pgx::ForeignDataWrapper>::NAME,
+                    handler: stringify!(#handler_ident),
+                    validator: stringify!(#validator_ident),
+                    file: file!(),
+                    line: line!(),
+                    full_path: core::any::type_name::<#target_path>(),
+                    module_path: module_path!(),
+                    to_sql_config: #to_sql_config,
+                };
+                pgx::datum::sql_entity_graph::SqlGraphEntity::ForeignDataWrapper(submission)
+            }
+        }
+    }
+}
+
+impl Parse for PgForeignDataWrapper {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        Self::new(input.parse()?)
+    }
+}
+
+impl ToTokens for PgForeignDataWrapper {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let entity_fn = self.entity_tokens();
+        let impl_item = &self.item_impl;
+        let handler = &self.handler;
+        let validator = &self.validator;
+        let inv = quote! {
+            #impl_item
+
+            #handler
+
+            #validator
+
+            #entity_fn
+        };
+        tokens.append_all(inv);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PgForeignDataWrapper;
+    use eyre::Result;
+    use syn::{parse_quote, ItemImpl};
+
+    #[test]
+    fn foreign_data_wrapper_required_only() -> Result<()> {
+        let tokens: ItemImpl = parse_quote! {
+            #[pg_foreign_data_wrapper]
+            impl ForeignDataWrapper for DemoFdw {
+                type Row = (i32, String);
+                fn begin_scan(relation: &PgRelation, options: FdwOptions) -> Self::ScanState { todo!() }
+                fn iterate_scan(state: &mut Self::ScanState) -> Option<Self::Row> { todo!() }
+            }
+        };
+        // It should not error, as it's valid.
+        let fdw = PgForeignDataWrapper::new(tokens);
+        assert!(fdw.is_ok());
+        let fdw = fdw.unwrap();
+        // The handler and validator should be named specifically:
+        assert_eq!(fdw.handler.sig.ident.to_string(), "demo_fdw_handler");
+        assert_eq!(fdw.validator.sig.ident.to_string(), "demo_fdw_validator");
+        // It should be possible to generate entity tokens.
+        let _ = fdw.entity_tokens();
+        Ok(())
+    }
+
+    #[test]
+    fn foreign_data_wrapper_missing_row() -> Result<()> {
+        // This is not valid as it is missing the `Row` type.
+        let tokens: ItemImpl = parse_quote! {
+            #[pg_foreign_data_wrapper]
+            impl ForeignDataWrapper for DemoFdw {
+                fn begin_scan(relation: &PgRelation, options: FdwOptions) -> Self::ScanState { todo!() }
+                fn iterate_scan(state: &mut Self::ScanState) -> Option<Self::Row> { todo!() }
+            }
+        };
+        let fdw = PgForeignDataWrapper::new(tokens);
+        assert!(fdw.is_err());
+        Ok(())
+    }
+}
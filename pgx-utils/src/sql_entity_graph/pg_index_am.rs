@@ -0,0 +1,301 @@
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{quote, TokenStreamExt, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    spanned::Spanned,
+    ItemFn, ItemImpl, Path,
+};
+
+use super::pg_aggregate::{get_impl_const_by_name, get_impl_func_by_name, get_impl_type_by_name};
+use super::ToSqlConfig;
+
+/** A parsed `#[pg_index_am]` item.
+
+It should be used with [`syn::parse::Parse`] functions.
+
+Using [`quote::ToTokens`] will output the `impl`, the generated `index_am_handler` function, and
+the declaration for a `pgx::datum::sql_entity_graph::PgAccessMethodEntity`.
+
+```rust
+use syn::{parse_quote, ItemImpl};
+use quote::ToTokens;
+use pgx_utils::sql_entity_graph::PgIndexAm;
+
+# fn main() -> eyre::Result<()> {
+let parsed: ItemImpl = parse_quote! {
+    impl IndexAm for NoopAm {
+        fn build() { todo!() }
+        fn build_empty() { todo!() }
+        fn insert() { todo!() }
+        fn bulk_delete() { todo!() }
+        fn vacuum_cleanup() { todo!() }
+        fn cost_estimate() { todo!() }
+        fn begin_scan() { todo!() }
+        fn rescan() { todo!() }
+        fn get_tuple() { todo!() }
+    }
+};
+let index_am = PgIndexAm::new(parsed)?;
+let sql_graph_entity_tokens = index_am.to_token_stream();
+# Ok(())
+# }
+```
+*/
+#[derive(Debug, Clone)]
+pub struct PgIndexAm {
+    item_impl: ItemImpl,
+    target_path: Path,
+    snake_case_target_ident: Ident,
+    handler: ItemFn,
+    to_sql_config: ToSqlConfig,
+}
+
+impl PgIndexAm {
+    pub fn new(mut item_impl: ItemImpl) -> Result<Self, syn::Error> {
+        let to_sql_config =
+            ToSqlConfig::from_attributes(item_impl.attrs.as_slice())?.unwrap_or_default();
+
+        if let Some((_, ref path, _)) = item_impl.trait_ {
+            if let Some(last) = path.segments.last() {
+                if last.ident.to_string() != "IndexAm" {
+                    return Err(syn::Error::new(
+                        last.ident.span(),
+                        "`#[pg_index_am]` only works with the `IndexAm` trait.",
+                    ));
+                }
+            }
+        }
+
+        let target_path = match &*item_impl.self_ty {
+            syn::Type::Path(ref type_path) => type_path.path.clone(),
+            something_else => {
+                return Err(syn::Error::new(
+                    something_else.span(),
+                    "`#[pg_index_am]` only works with types.",
+                ))
+            }
+        };
+        let target_ident = target_path
+            .segments
+            .last()
+            .ok_or_else(|| {
+                syn::Error::new(
+                    target_path.span(),
+                    "`#[pg_index_am]` only works with types whose path have a final segment.",
+                )
+            })?
+            .ident
+            .clone();
+        let snake_case_target_ident = Ident::new(
+            &target_ident.to_string().to_case(Case::Snake),
+            target_ident.span(),
+        );
+
+        // We want to avoid having multiple borrows, so we take a snapshot to scan from,
+        // and mutate the actual one.
+        let item_impl_snapshot = item_impl.clone();
+
+        // `NAME` is an optional value, we default to the snake cased name of the type.
+        if get_impl_const_by_name(&item_impl_snapshot, "NAME").is_none() {
+            let name = snake_case_target_ident.to_string();
+            item_impl.items.push(parse_quote! {
+                const NAME: &'static str = #name;
+            });
+        }
+
+        // `ScanState` is an optional value, we default to nothing.
+        if get_impl_type_by_name(&item_impl_snapshot, "ScanState").is_none() {
+            item_impl.items.push(parse_quote! {
+                type ScanState = ();
+            });
+        }
+
+        for required in [
+            "build",
+            "build_empty",
+            "insert",
+            "bulk_delete",
+            "vacuum_cleanup",
+            "cost_estimate",
+            "begin_scan",
+            "rescan",
+        ] {
+            if get_impl_func_by_name(&item_impl_snapshot, required).is_none() {
+                return Err(syn::Error::new(
+                    item_impl_snapshot.span(),
+                    format!(
+                        "`#[pg_index_am]` requires the `{}` function defined.",
+                        required
+                    ),
+                ));
+            }
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "options").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn options(_reloptions: pgx::pg_sys::Datum, _validate: bool) -> Option<pgx::PgBox<pgx::pg_sys::bytea>> {
+                    None
+                }
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "validate").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn validate(_opclass_oid: pgx::pg_sys::Oid) -> bool {
+                    true
+                }
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "end_scan").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn end_scan(_scan: &mut pgx::PgIndexScan<Self::ScanState>) {}
+            });
+        }
+
+        // Postgres checks if these callbacks are set to decide which kinds of scans are possible.
+        let mut unset_callbacks = Vec::new();
+        if get_impl_func_by_name(&item_impl_snapshot, "get_tuple").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn get_tuple(_scan: &mut pgx::PgIndexScan<Self::ScanState>, _direction: pgx::pg_sys::ScanDirection) -> bool {
+                    unimplemented!("Call to get_tuple on an index access method which does not support it.")
+                }
+            });
+            unset_callbacks.push(Ident::new("amgettuple", item_impl_snapshot.span()));
+        }
+        if get_impl_func_by_name(&item_impl_snapshot, "get_bitmap").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn get_bitmap(_scan: &mut pgx::PgIndexScan<Self::ScanState>, _bitmap: &mut pgx::PgTidBitmap) -> i64 {
+                    unimplemented!("Call to get_bitmap on an index access method which does not support it.")
+                }
+            });
+            unset_callbacks.push(Ident::new("amgetbitmap", item_impl_snapshot.span()));
+        }
+        if unset_callbacks.len() == 2 {
+            return Err(syn::Error::new(
+                item_impl_snapshot.span(),
+                "`#[pg_index_am]` requires at least one of the `get_tuple` or `get_bitmap` functions defined.",
+            ));
+        }
+
+        let handler_ident = Ident::new(
+            &format!("{}_handler", snake_case_target_ident),
+            target_ident.span(),
+        );
+        let handler = parse_quote! {
+            #[pg_extern]
+            fn #handler_ident(_internal: pgx::Internal) -> pgx::PgBox<pgx::pg_sys::IndexAmRoutine> {
+                #[allow(unused_mut)]
+                let mut routine = pgx::index_am_routine::<#target_path>();
+                #( routine.#unset_callbacks = None; )*
+                routine
+            }
+        };
+
+        Ok(Self {
+            item_impl,
+            target_path,
+            snake_case_target_ident,
+            handler,
+            to_sql_config,
+        })
+    }
+
+    fn entity_tokens(&self) -> ItemFn {
+        let target_path = &self.target_path;
+        let handler_ident = &self.handler.sig.ident;
+        let to_sql_config = &self.to_sql_config;
+        let sql_graph_entity_fn_name = syn::Ident::new(
+            &format!("__pgx_internals_am_{}", self.snake_case_target_ident),
+            self.snake_case_target_ident.span(),
+        );
+
+        parse_quote! {
+            #[no_mangle]
+            pub extern "C" fn #sql_graph_entity_fn_name() -> pgx::datum::sql_entity_graph::SqlGraphEntity {
+                let submission = pgx::datum::sql_entity_graph::PgAccessMethodEntity {
+                    name: <#target_path as pgx::IndexAm>::NAME,
+                    handler: stringify!(#handler_ident),
+                    kind: pgx::datum::sql_entity_graph::AccessMethodType::Index,
+                    file: file!(),
+                    line: line!(),
+                    full_path: core::any::type_name::<#target_path>(),
+                    module_path: module_path!(),
+                    to_sql_config: #to_sql_config,
+                };
+                pgx::datum::sql_entity_graph::SqlGraphEntity::AccessMethod(submission)
+            }
+        }
+    }
+}
+
+impl Parse for PgIndexAm {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        Self::new(input.parse()?)
+    }
+}
+
+impl ToTokens for PgIndexAm {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let entity_fn = self.entity_tokens();
+        let impl_item = &self.item_impl;
+        let handler = &self.handler;
+        let inv = quote! {
+            #impl_item
+
+            #handler
+
+            #entity_fn
+        };
+        tokens.append_all(inv);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PgIndexAm;
+    use eyre::Result;
+    use syn::{parse_quote, ItemImpl};
+
+    #[test]
+    fn index_am_required_only() -> Result<()> {
+        let tokens: ItemImpl = parse_quote! {
+            #[pg_index_am]
+            impl IndexAm for DemoAm {
+                fn build(heap: &PgRelation, index: &PgRelation, info: &mut pg_sys::IndexInfo) -> IndexBuildResult { todo!() }
+                fn build_empty(index: &PgRelation) { todo!() }
+                fn insert(index: &PgRelation, values: &[Option<pg_sys::Datum>], tid: pg_sys::ItemPointerData, heap: &PgRelation, check_unique: pg_sys::IndexUniqueCheck, info: &mut pg_sys::IndexInfo) -> bool { todo!() }
+                fn bulk_delete(info: &pg_sys::IndexVacuumInfo, stats: Option<PgBox<pg_sys::IndexBulkDeleteResult>>, callback: BulkDeleteCallback) -> Option<PgBox<pg_sys::IndexBulkDeleteResult>> { todo!() }
+                fn vacuum_cleanup(info: &pg_sys::IndexVacuumInfo, stats: Option<PgBox<pg_sys::IndexBulkDeleteResult>>) -> Option<PgBox<pg_sys::IndexBulkDeleteResult>> { todo!() }
+                fn cost_estimate(root: &mut pg_sys::PlannerInfo, path: &mut pg_sys::IndexPath, loop_count: f64) -> IndexCosts { todo!() }
+                fn begin_scan(index: &PgRelation, nkeys: i32, norderbys: i32) -> Self::ScanState { todo!() }
+                fn rescan(scan: &mut PgIndexScan<Self::ScanState>) { todo!() }
+                fn get_tuple(scan: &mut PgIndexScan<Self::ScanState>, direction: pg_sys::ScanDirection) -> bool { todo!() }
+            }
+        };
+        // It should not error, as it's valid.
+        let index_am = PgIndexAm::new(tokens);
+        assert!(index_am.is_ok());
+        let index_am = index_am.unwrap();
+        // The handler should be named specifically:
+        assert_eq!(index_am.handler.sig.ident.to_string(), "demo_am_handler");
+        // It should be possible to generate entity tokens.
+        let _ = index_am.entity_tokens();
+        Ok(())
+    }
+
+    #[test]
+    fn index_am_missing_required() -> Result<()> {
+        // This is not valid as it is missing required functions.
+        let tokens: ItemImpl = parse_quote! {
+            #[pg_index_am]
+            impl IndexAm for DemoAm {
+            }
+        };
+        let index_am = PgIndexAm::new(tokens);
+        assert!(index_am.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,315 @@
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    spanned::Spanned,
+    ItemFn, ItemImpl, Path,
+};
+
+use super::pg_aggregate::{get_impl_const_by_name, get_impl_func_by_name, get_impl_type_by_name};
+use super::ToSqlConfig;
+
+/** A parsed `#[pg_table_am]` item.
+
+It should be used with [`syn::parse::Parse`] functions.
+
+Using [`quote::ToTokens`] will output the `impl`, the generated `table_am_handler` function, and
+the declaration for a `pgx::datum::sql_entity_graph::PgAccessMethodEntity`.
+
+```rust
+use syn::{parse_quote, ItemImpl};
+use quote::ToTokens;
+use pgx_utils::sql_entity_graph::PgTableAm;
+
+# fn main() -> eyre::Result<()> {
+let parsed: ItemImpl = parse_quote! {
+    impl TableAm for NoopAm {
+        fn scan_begin() { todo!() }
+        fn scan_getnextslot() { todo!() }
+        fn tuple_insert() { todo!() }
+        fn relation_set_new_filenode() { todo!() }
+        fn relation_estimate_size() { todo!() }
+    }
+};
+let table_am = PgTableAm::new(parsed)?;
+let sql_graph_entity_tokens = table_am.to_token_stream();
+# Ok(())
+# }
+```
+*/
+#[derive(Debug, Clone)]
+pub struct PgTableAm {
+    item_impl: ItemImpl,
+    target_path: Path,
+    snake_case_target_ident: Ident,
+    handler: ItemFn,
+    to_sql_config: ToSqlConfig,
+}
+
+impl PgTableAm {
+    pub fn new(mut item_impl: ItemImpl) -> Result<Self, syn::Error> {
+        let to_sql_config =
+            ToSqlConfig::from_attributes(item_impl.attrs.as_slice())?.unwrap_or_default();
+
+        if let Some((_, ref path, _)) = item_impl.trait_ {
+            if let Some(last) = path.segments.last() {
+                if last.ident.to_string() != "TableAm" {
+                    return Err(syn::Error::new(
+                        last.ident.span(),
+                        "`#[pg_table_am]` only works with the `TableAm` trait.",
+                    ));
+                }
+            }
+        }
+
+        let target_path = match &*item_impl.self_ty {
+            syn::Type::Path(ref type_path) => type_path.path.clone(),
+            something_else => {
+                return Err(syn::Error::new(
+                    something_else.span(),
+                    "`#[pg_table_am]` only works with types.",
+                ))
+            }
+        };
+        let target_ident = target_path
+            .segments
+            .last()
+            .ok_or_else(|| {
+                syn::Error::new(
+                    target_path.span(),
+                    "`#[pg_table_am]` only works with types whose path have a final segment.",
+                )
+            })?
+            .ident
+            .clone();
+        let snake_case_target_ident = Ident::new(
+            &target_ident.to_string().to_case(Case::Snake),
+            target_ident.span(),
+        );
+
+        // We want to avoid having multiple borrows, so we take a snapshot to scan from,
+        // and mutate the actual one.
+        let item_impl_snapshot = item_impl.clone();
+
+        // `NAME` is an optional value, we default to the snake cased name of the type.
+        if get_impl_const_by_name(&item_impl_snapshot, "NAME").is_none() {
+            let name = snake_case_target_ident.to_string();
+            item_impl.items.push(parse_quote! {
+                const NAME: &'static str = #name;
+            });
+        }
+
+        // `ScanState` is an optional value, we default to nothing.
+        if get_impl_type_by_name(&item_impl_snapshot, "ScanState").is_none() {
+            item_impl.items.push(parse_quote! {
+                type ScanState = ();
+            });
+        }
+
+        for required in [
+            "scan_begin",
+            "scan_getnextslot",
+            "tuple_insert",
+            "relation_set_new_filenode",
+            "relation_estimate_size",
+        ] {
+            if get_impl_func_by_name(&item_impl_snapshot, required).is_none() {
+                return Err(syn::Error::new(
+                    item_impl_snapshot.span(),
+                    format!(
+                        "`#[pg_table_am]` requires the `{}` function defined.",
+                        required
+                    ),
+                ));
+            }
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "scan_rescan").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn scan_rescan(_scan: &mut pgx::PgTableScan<Self::ScanState>) {}
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "scan_end").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn scan_end(_scan: &mut pgx::PgTableScan<Self::ScanState>) {}
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "tuple_delete").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn tuple_delete(
+                    _relation: &pgx::PgRelation,
+                    _tid: pgx::pg_sys::ItemPointerData,
+                    _cid: pgx::pg_sys::CommandId,
+                    _snapshot: pgx::pg_sys::Snapshot,
+                    _wait: bool,
+                    _failure_data: &mut pgx::pg_sys::TM_FailureData,
+                ) -> pgx::pg_sys::TM_Result {
+                    pgx::error!("DELETE is not supported by the {} table access method", <Self as pgx::TableAm>::NAME)
+                }
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "tuple_update").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn tuple_update(
+                    _relation: &pgx::PgRelation,
+                    _old_tid: pgx::pg_sys::ItemPointerData,
+                    _slot: &mut pgx::PgTupleTableSlot,
+                    _cid: pgx::pg_sys::CommandId,
+                    _snapshot: pgx::pg_sys::Snapshot,
+                    _wait: bool,
+                    _failure_data: &mut pgx::pg_sys::TM_FailureData,
+                    _update_indexes: &mut bool,
+                ) -> pgx::pg_sys::TM_Result {
+                    pgx::error!("UPDATE is not supported by the {} table access method", <Self as pgx::TableAm>::NAME)
+                }
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "tuple_lock").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn tuple_lock(
+                    _relation: &pgx::PgRelation,
+                    _tid: pgx::pg_sys::ItemPointerData,
+                    _snapshot: pgx::pg_sys::Snapshot,
+                    _slot: &mut pgx::PgTupleTableSlot,
+                    _cid: pgx::pg_sys::CommandId,
+                    _mode: pgx::pg_sys::LockTupleMode,
+                    _wait_policy: pgx::pg_sys::LockWaitPolicy,
+                    _failure_data: &mut pgx::pg_sys::TM_FailureData,
+                ) -> pgx::pg_sys::TM_Result {
+                    pgx::error!("row locking is not supported by the {} table access method", <Self as pgx::TableAm>::NAME)
+                }
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "relation_vacuum").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn relation_vacuum(_relation: &pgx::PgRelation, _params: &mut pgx::pg_sys::VacuumParams) {}
+            });
+        }
+
+        if get_impl_func_by_name(&item_impl_snapshot, "relation_size").is_none() {
+            item_impl.items.push(parse_quote! {
+                fn relation_size(_relation: &pgx::PgRelation, _fork: pgx::pg_sys::ForkNumber) -> u64 {
+                    0
+                }
+            });
+        }
+
+        let handler_ident = Ident::new(
+            &format!("{}_handler", snake_case_target_ident),
+            target_ident.span(),
+        );
+        let handler = parse_quote! {
+            #[pg_extern]
+            fn #handler_ident(_internal: pgx::Internal) -> pgx::PgBox<pgx::pg_sys::TableAmRoutine> {
+                pgx::table_am_routine::<#target_path>()
+            }
+        };
+
+        Ok(Self {
+            item_impl,
+            target_path,
+            snake_case_target_ident,
+            handler,
+            to_sql_config,
+        })
+    }
+
+    fn entity_tokens(&self) -> ItemFn {
+        let target_path = &self.target_path;
+        let handler_ident = &self.handler.sig.ident;
+        let to_sql_config = &self.to_sql_config;
+        let sql_graph_entity_fn_name = syn::Ident::new(
+            &format!("__pgx_internals_am_{}", self.snake_case_target_ident),
+            self.snake_case_target_ident.span(),
+        );
+
+        parse_quote! {
+            #[no_mangle]
+            pub extern "C" fn #sql_graph_entity_fn_name() -> pgx::datum::sql_entity_graph::SqlGraphEntity {
+                let submission = pgx::datum::sql_entity_graph::PgAccessMethodEntity {
+                    name: <#target_path as pgx::TableAm>::NAME,
+                    handler: stringify!(#handler_ident),
+                    kind: pgx::datum::sql_entity_graph::AccessMethodType::Table,
+                    file: file!(),
+                    line: line!(),
+                    full_path: core::any::type_name::<#target_path>(),
+                    module_path: module_path!(),
+                    to_sql_config: #to_sql_config,
+                };
+                pgx::datum::sql_entity_graph::SqlGraphEntity::AccessMethod(submission)
+            }
+        }
+    }
+}
+
+impl Parse for PgTableAm {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        Self::new(input.parse()?)
+    }
+}
+
+impl ToTokens for PgTableAm {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let entity_fn = self.entity_tokens();
+        let impl_item = &self.item_impl;
+        let handler = &self.handler;
+        let inv = quote! {
+            #impl_item
+
+            #handler
+
+            #entity_fn
+        };
+        tokens.append_all(inv);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PgTableAm;
+    use eyre::Result;
+    use syn::{parse_quote, ItemImpl};
+
+    #[test]
+    fn table_am_required_only() -> Result<()> {
+        let tokens: ItemImpl = parse_quote! {
+            #[pg_table_am]
+            impl TableAm for DemoAm {
+                fn scan_begin(relation: &PgRelation, snapshot: pg_sys::Snapshot, keys: &[pg_sys::ScanKeyData], flags: u32) -> Self::ScanState { todo!() }
+                fn scan_getnextslot(scan: &mut PgTableScan<Self::ScanState>, direction: pg_sys::ScanDirection, slot: &mut PgTupleTableSlot) -> bool { todo!() }
+                fn tuple_insert(relation: &PgRelation, slot: &mut PgTupleTableSlot, cid: pg_sys::CommandId, options: i32) { todo!() }
+                fn relation_set_new_filenode(relation: &PgRelation, new_rnode: &pg_sys::RelFileNode, persistence: std::os::raw::c_char) -> (pg_sys::TransactionId, pg_sys::MultiXactId) { todo!() }
+                fn relation_estimate_size(relation: &PgRelation) -> TableSizeEstimate { todo!() }
+            }
+        };
+        // It should not error, as it's valid.
+        let table_am = PgTableAm::new(tokens);
+        assert!(table_am.is_ok());
+        let table_am = table_am.unwrap();
+        // The handler should be named specifically:
+        assert_eq!(table_am.handler.sig.ident.to_string(), "demo_am_handler");
+        // It should be possible to generate entity tokens.
+        let _ = table_am.entity_tokens();
+        Ok(())
+    }
+
+    #[test]
+    fn table_am_missing_required() -> Result<()> {
+        // This is not valid as it is missing required functions.
+        let tokens: ItemImpl = parse_quote! {
+            #[pg_table_am]
+            impl TableAm for DemoAm {
+            }
+        };
+        let table_am = PgTableAm::new(tokens);
+        assert!(table_am.is_err());
+        Ok(())
+    }
+}
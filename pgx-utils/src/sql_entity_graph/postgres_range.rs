@@ -0,0 +1,234 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    DeriveInput, Fields, GenericArgument, Ident, ItemStruct, PathArguments,
+};
+
+use super::{ArgValue, PgxArg, PgxAttribute, ToSqlConfig};
+
+/// A parsed `#[derive(PostgresRange)]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the declaration for a `pgx::datum::sql_entity_graph::PostgresRangeEntity`.
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PostgresRange;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: PostgresRange = parse_quote! {
+///     #[derive(PostgresRange)]
+///     #[pgx(subdiff = float_range_subdiff)]
+///     struct FloatRange(Range<f64>);
+/// };
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PostgresRange {
+    name: Ident,
+    subtype: syn::Type,
+    canonical: Option<syn::Path>,
+    subdiff: Option<syn::Path>,
+    to_sql_config: ToSqlConfig,
+}
+
+impl PostgresRange {
+    pub fn new(
+        name: Ident,
+        subtype: syn::Type,
+        canonical: Option<syn::Path>,
+        subdiff: Option<syn::Path>,
+        to_sql_config: ToSqlConfig,
+    ) -> Self {
+        Self {
+            name,
+            subtype,
+            canonical,
+            subdiff,
+            to_sql_config,
+        }
+    }
+
+    pub fn from_derive_input(derive_input: DeriveInput) -> Result<Self, syn::Error> {
+        let fields = match derive_input.data {
+            syn::Data::Struct(data_struct) => data_struct.fields,
+            syn::Data::Union(_) | syn::Data::Enum(_) => {
+                return Err(syn::Error::new(
+                    derive_input.ident.span(),
+                    "expected a struct",
+                ))
+            }
+        };
+        Self::from_parts(derive_input.ident, fields, &derive_input.attrs)
+    }
+
+    fn from_parts(
+        name: Ident,
+        fields: Fields,
+        attrs: &[syn::Attribute],
+    ) -> Result<Self, syn::Error> {
+        let to_sql_config = ToSqlConfig::from_attributes(attrs)?.unwrap_or_default();
+        let subtype = range_subtype(&name, fields)?;
+
+        let mut canonical = None;
+        let mut subdiff = None;
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("pgx")) {
+            let attr = attr.parse_args::<PgxAttribute>()?;
+            for arg in attr.args {
+                if let PgxArg::NameValue(nv) = arg {
+                    let slot = if nv.path.is_ident("canonical") {
+                        &mut canonical
+                    } else if nv.path.is_ident("subdiff") {
+                        &mut subdiff
+                    } else {
+                        continue;
+                    };
+                    match nv.value {
+                        ArgValue::Path(path) => *slot = Some(path),
+                        ArgValue::Lit(lit) => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "expected a path to a function",
+                            ))
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(Self::new(name, subtype, canonical, subdiff, to_sql_config))
+    }
+
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+
+    /// The `T` in the struct's `Range<T>` field
+    pub fn subtype(&self) -> &syn::Type {
+        &self.subtype
+    }
+
+    /// The user's `fn(Range<T>) -> Range<T>`, from `#[pgx(canonical = ..)]`
+    pub fn canonical(&self) -> Option<&syn::Path> {
+        self.canonical.as_ref()
+    }
+
+    /// The user's `fn(T, T) -> f64`, from `#[pgx(subdiff = ..)]`
+    pub fn subdiff(&self) -> Option<&syn::Path> {
+        self.subdiff.as_ref()
+    }
+
+    /// The SQL name of the generated `CANONICAL` function.  Its C symbol is this plus `_wrapper`.
+    pub fn canonical_sql_name(&self) -> String {
+        format!("{}_canonical", self.name).to_lowercase()
+    }
+
+    /// The SQL name of the generated `SUBTYPE_DIFF` function.  Its C symbol is this plus `_wrapper`.
+    pub fn subdiff_sql_name(&self) -> String {
+        format!("{}_subdiff", self.name).to_lowercase()
+    }
+}
+
+/// Find `T` in a struct shaped like `struct Name(Range<T>);`
+fn range_subtype(name: &Ident, fields: Fields) -> Result<syn::Type, syn::Error> {
+    let error = || {
+        syn::Error::new(
+            name.span(),
+            "#[derive(PostgresRange)] expects a tuple struct with a single `Range<T>` field",
+        )
+    };
+    let field = match fields {
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+            unnamed.unnamed.into_iter().next().unwrap()
+        }
+        _ => return Err(error()),
+    };
+    let last_segment = match field.ty {
+        syn::Type::Path(type_path) => type_path.path.segments.into_iter().last(),
+        _ => None,
+    }
+    .ok_or_else(error)?;
+    if last_segment.ident != "Range" {
+        return Err(error());
+    }
+    match last_segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            match args.args.into_iter().next().unwrap() {
+                GenericArgument::Type(ty) => Ok(ty),
+                _ => Err(error()),
+            }
+        }
+        _ => Err(error()),
+    }
+}
+
+impl Parse for PostgresRange {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let parsed: ItemStruct = input.parse()?;
+        Self::from_parts(parsed.ident, parsed.fields, &parsed.attrs)
+    }
+}
+
+impl ToTokens for PostgresRange {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let name = &self.name;
+        let subtype = &self.subtype;
+        let sql_graph_entity_fn_name = syn::Ident::new(
+            &format!("__pgx_internals_range_{}", name),
+            Span::call_site(),
+        );
+
+        let canonical = match self.canonical {
+            Some(_) => {
+                let canonical = self.canonical_sql_name();
+                quote! { Some(#canonical) }
+            }
+            None => quote! { None },
+        };
+        let subdiff = match self.subdiff {
+            Some(_) => {
+                let subdiff = self.subdiff_sql_name();
+                quote! { Some(#subdiff) }
+            }
+            None => quote! { None },
+        };
+
+        let to_sql_config = &self.to_sql_config;
+
+        let inv = quote! {
+            #[no_mangle]
+            pub extern "C" fn  #sql_graph_entity_fn_name() -> pgx::datum::sql_entity_graph::SqlGraphEntity {
+                extern crate alloc;
+                use alloc::vec::Vec;
+                use alloc::vec;
+                let mut mappings = Default::default();
+                <#name as pgx::datum::WithTypeIds>::register_with_refs(&mut mappings, stringify!(#name).to_string());
+                pgx::datum::WithSizedTypeIds::<#name>::register_sized_with_refs(&mut mappings, stringify!(#name).to_string());
+                pgx::datum::WithArrayTypeIds::<#name>::register_array_with_refs(&mut mappings, stringify!(#name).to_string());
+                pgx::datum::WithVarlenaTypeIds::<#name>::register_varlena_with_refs(&mut mappings, stringify!(#name).to_string());
+
+                let submission = pgx::datum::sql_entity_graph::PostgresRangeEntity {
+                    name: stringify!(#name),
+                    file: file!(),
+                    line: line!(),
+                    module_path: module_path!(),
+                    full_path: core::any::type_name::<#name>(),
+                    mappings,
+                    subtype_id: core::any::TypeId::of::<#subtype>(),
+                    subtype_source: stringify!(#subtype),
+                    subtype_full_path: core::any::type_name::<#subtype>(),
+                    canonical: #canonical,
+                    subdiff: #subdiff,
+                    to_sql_config: #to_sql_config,
+                };
+                pgx::datum::sql_entity_graph::SqlGraphEntity::Range(submission)
+            }
+        };
+        tokens.append_all(inv);
+    }
+}
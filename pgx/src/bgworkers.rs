@@ -5,6 +5,7 @@
 //!
 //! See: [https://www.postgresql.org/docs/12/bgworker.html](https://www.postgresql.org/docs/12/bgworker.html)
 use crate::pg_sys;
+use crate::{ereport, PgLogLevel, PgSqlErrorCode};
 use std::convert::TryInto;
 use std::ffi::CStr;
 use std::ffi::CString;
@@ -47,6 +48,7 @@ bitflags! {
 }
 
 /// The various points in which a BackgroundWorker can be started by Postgres
+#[derive(Debug, Clone, Copy)]
 pub enum BgWorkerStartTime {
     PostmasterStart = pg_sys::BgWorkerStartTime_BgWorkerStart_PostmasterStart as isize,
     ConsistentState = pg_sys::BgWorkerStartTime_BgWorkerStart_ConsistentState as isize,
@@ -189,7 +191,8 @@ unsafe extern "C" fn worker_spi_sigterm(_signal_args: i32) {
 /// A builder-style interface for creating a new Background Worker
 ///
 /// This must be used from within your extension's `_PG_init()` function,
-/// finishing with the `.load()` function.
+/// finishing with the `.load()` function, unless the worker is started on demand
+/// with `.load_dynamic()`.
 ///
 /// ## Example
 ///
@@ -373,8 +376,67 @@ impl BackgroundWorkerBuilder {
     /// Once properly configured, call `load()` to get the BackgroundWorker registered and
     /// started at the proper time by Postgres.
     pub fn load(self: Self) {
+        let mut bgw = self.to_bgw();
+
+        unsafe {
+            pg_sys::RegisterBackgroundWorker(&mut bgw);
+            if self.bgw_flags.contains(BGWflags::BGWORKER_SHMEM_ACCESS)
+                && self.shared_memory_startup_fn.is_some()
+            {
+                PREV_SHMEM_STARTUP_HOOK = pg_sys::shmem_startup_hook;
+                pg_sys::shmem_startup_hook = self.shared_memory_startup_fn;
+            }
+        };
+    }
+
+    /// Register and start the BackgroundWorker right away, from a regular backend rather than
+    /// from `_PG_init()`, returning a handle that can be used to wait on it or stop it.
+    ///
+    /// If no notify pid has been set, it becomes the current backend's, so that it can wait
+    /// on the worker.  Raises an ERROR if no background worker slots are free.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use pgx::bgworkers::BackgroundWorkerBuilder;
+    ///
+    /// let worker = BackgroundWorkerBuilder::new("One-off Job")
+    ///     .set_function("job_main")
+    ///     .set_library("example")
+    ///     .enable_spi_access()
+    ///     .load_dynamic();
+    /// worker.wait_for_shutdown().expect("postmaster died");
+    /// ```
+    pub fn load_dynamic(mut self: Self) -> DynamicBackgroundWorker {
+        if self.bgw_notify_pid == 0 {
+            self.bgw_notify_pid = unsafe { pg_sys::MyProcPid };
+        }
+        let mut bgw = self.to_bgw();
+        let mut handle = std::ptr::null_mut();
+
+        if !unsafe { pg_sys::RegisterDynamicBackgroundWorker(&mut bgw, &mut handle) } {
+            ereport(
+                PgLogLevel::ERROR,
+                PgSqlErrorCode::ERRCODE_INSUFFICIENT_RESOURCES,
+                &format!(
+                    "could not register background worker \"{}\": you may need to increase max_worker_processes",
+                    self.bgw_name
+                ),
+                file!(),
+                line!(),
+                column!(),
+            );
+        }
+
+        DynamicBackgroundWorker {
+            handle,
+            notify_pid: self.bgw_notify_pid,
+        }
+    }
+
+    fn to_bgw(&self) -> pg_sys::BackgroundWorker {
         #[cfg(feature = "pg10")]
-        let mut bgw = pg_sys::BackgroundWorker {
+        let bgw = pg_sys::BackgroundWorker {
             bgw_name: RpgffiChar::from(&self.bgw_name[..]).0,
             bgw_flags: self.bgw_flags.bits(),
             bgw_start_time: self.bgw_start_time as u32,
@@ -390,7 +452,7 @@ impl BackgroundWorkerBuilder {
         };
 
         #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
-        let mut bgw = pg_sys::BackgroundWorker {
+        let bgw = pg_sys::BackgroundWorker {
             bgw_name: RpgffiChar::from(&self.bgw_name[..]).0,
             bgw_type: RpgffiChar::from(&self.bgw_type[..]).0,
             bgw_flags: self.bgw_flags.bits(),
@@ -406,15 +468,89 @@ impl BackgroundWorkerBuilder {
             bgw_notify_pid: self.bgw_notify_pid,
         };
 
-        unsafe {
-            pg_sys::RegisterBackgroundWorker(&mut bgw);
-            if self.bgw_flags.contains(BGWflags::BGWORKER_SHMEM_ACCESS)
-                && self.shared_memory_startup_fn.is_some()
-            {
-                PREV_SHMEM_STARTUP_HOOK = pg_sys::shmem_startup_hook;
-                pg_sys::shmem_startup_hook = self.shared_memory_startup_fn;
-            }
-        };
+        bgw
+    }
+}
+
+/// The status of a [`DynamicBackgroundWorker`], as seen by the backend that started it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackgroundWorkerStatus {
+    Started,
+    NotYetStarted,
+    Stopped,
+    PostmasterDied,
+}
+
+impl From<pg_sys::BgwHandleStatus> for BackgroundWorkerStatus {
+    fn from(status: pg_sys::BgwHandleStatus) -> Self {
+        match status {
+            pg_sys::BgwHandleStatus_BGWH_STARTED => BackgroundWorkerStatus::Started,
+            pg_sys::BgwHandleStatus_BGWH_NOT_YET_STARTED => BackgroundWorkerStatus::NotYetStarted,
+            pg_sys::BgwHandleStatus_BGWH_STOPPED => BackgroundWorkerStatus::Stopped,
+            pg_sys::BgwHandleStatus_BGWH_POSTMASTER_DIED => BackgroundWorkerStatus::PostmasterDied,
+            unknown => panic!("unrecognized BgwHandleStatus: {}", unknown),
+        }
+    }
+}
+
+/// A handle to a BackgroundWorker started with [`BackgroundWorkerBuilder::load_dynamic()`]
+///
+/// Waiting on the worker only works from the backend whose pid was given as the worker's
+/// notify pid, which by default is the backend that started it.
+pub struct DynamicBackgroundWorker {
+    handle: *mut pg_sys::BackgroundWorkerHandle,
+    notify_pid: pg_sys::pid_t,
+}
+
+impl DynamicBackgroundWorker {
+    /// The worker's current status, without waiting
+    pub fn status(&self) -> BackgroundWorkerStatus {
+        let mut pid = 0;
+        unsafe { pg_sys::GetBackgroundWorkerPid(self.handle, &mut pid) }.into()
+    }
+
+    /// The worker's pid, if it's currently running
+    pub fn pid(&self) -> Option<pg_sys::pid_t> {
+        let mut pid = 0;
+        match unsafe { pg_sys::GetBackgroundWorkerPid(self.handle, &mut pid) }.into() {
+            BackgroundWorkerStatus::Started => Some(pid),
+            _ => None,
+        }
+    }
+
+    /// The pid of the backend that's notified when the worker starts or stops
+    pub fn notify_pid(&self) -> pg_sys::pid_t {
+        self.notify_pid
+    }
+
+    /// Wait for the postmaster to start the worker, returning its pid, or the status it ended
+    /// up in instead (it may have already stopped)
+    pub fn wait_for_startup(&self) -> Result<pg_sys::pid_t, BackgroundWorkerStatus> {
+        let mut pid = 0;
+        match unsafe { pg_sys::WaitForBackgroundWorkerStartup(self.handle, &mut pid) }.into() {
+            BackgroundWorkerStatus::Started => Ok(pid),
+            other => Err(other),
+        }
+    }
+
+    /// Wait for the worker to exit.  Fails only if the postmaster died first
+    pub fn wait_for_shutdown(&self) -> Result<(), BackgroundWorkerStatus> {
+        match unsafe { pg_sys::WaitForBackgroundWorkerShutdown(self.handle) }.into() {
+            BackgroundWorkerStatus::Stopped => Ok(()),
+            other => Err(other),
+        }
+    }
+
+    /// Ask the postmaster to send the worker a SIGTERM, or to not start it at all if it
+    /// hasn't started yet
+    pub fn terminate(&self) {
+        unsafe { pg_sys::TerminateBackgroundWorker(self.handle) }
+    }
+
+    /// The underlying handle, for use with functions such as
+    /// [`PgShmMqHandle::set_worker_handle()`](crate::PgShmMqHandle::set_worker_handle)
+    pub fn as_ptr(&self) -> *mut pg_sys::BackgroundWorkerHandle {
+        self.handle
     }
 }
 
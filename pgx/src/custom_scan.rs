@@ -0,0 +1,357 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+/*!
+
+[Custom Scan Provider](https://www.postgresql.org/docs/current/custom-scan.html) support.
+
+Custom scans are created by implementing [`CustomScan`] for a type, and offering paths for it to
+the planner from a [`PgHooks::set_rel_pathlist`](crate::PgHooks::set_rel_pathlist) (or
+[`PgHooks::set_join_pathlist`](crate::PgHooks::set_join_pathlist)) hook, usually
+through [`add_custom_scan_path`].
+
+If the planner picks the path, [`CustomScan::create_plan`] turns it into a `CustomScan` plan node,
+and the executor calls [`CustomScan::begin`], [`CustomScan::exec`] and [`CustomScan::end`] to
+produce its rows.  Quals and projection are handled by Postgres' `ExecScan()`.
+
+# Example
+
+```rust,no_run
+use pgx::*;
+
+pub struct CountingScan;
+
+impl CustomScan for CountingScan {
+    const NAME: &'static str = "counting_scan";
+    type State = i32;
+    type Row = (i32,);
+
+    fn plan_path(
+        _root: &mut pg_sys::PlannerInfo,
+        rel: &mut pg_sys::RelOptInfo,
+        _rti: pg_sys::Index,
+        _rte: &mut pg_sys::RangeTblEntry,
+    ) -> Option<PgBox<pg_sys::CustomPath>> {
+        Some(custom_path::<Self>(rel, 10.0, 0.0, 0.0))
+    }
+
+    fn begin(
+        _node: &mut pg_sys::CustomScanState,
+        _estate: &mut pg_sys::EState,
+        _eflags: i32,
+    ) -> Self::State {
+        0
+    }
+
+    fn exec(state: &mut Self::State) -> Option<Self::Row> {
+        *state += 1;
+        if *state <= 10 {
+            Some((*state,))
+        } else {
+            None
+        }
+    }
+}
+
+struct CountingHook;
+
+impl PgHooks for CountingHook {
+    fn set_rel_pathlist(
+        &mut self,
+        mut root: PgBox<pg_sys::PlannerInfo>,
+        mut rel: PgBox<pg_sys::RelOptInfo>,
+        rti: pg_sys::Index,
+        mut rte: PgBox<pg_sys::RangeTblEntry>,
+        prev_hook: fn(
+            PgBox<pg_sys::PlannerInfo>,
+            PgBox<pg_sys::RelOptInfo>,
+            pg_sys::Index,
+            PgBox<pg_sys::RangeTblEntry>,
+        ) -> HookResult<()>,
+    ) -> HookResult<()> {
+        add_custom_scan_path::<CountingScan>(&mut root, &mut rel, rti, &mut rte);
+        prev_hook(root, rel, rti, rte)
+    }
+}
+```
+*/
+use crate::{fdw, pg_sys, IntoDatumRow, PgBox, PgList, PgMemoryContexts};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::ffi::{c_void, CString};
+use std::os::raw::c_int;
+
+/// A Rust-implemented executor node, planned as a `CustomPath` and executed as a `CustomScan`.
+pub trait CustomScan: Sized + 'static {
+    /// The name of the custom scan, as shown by `EXPLAIN`.
+    ///
+    /// It must be unique among the custom scans loaded into a backend.
+    const NAME: &'static str;
+
+    /// The state kept between [`CustomScan::begin`] and [`CustomScan::end`].
+    type State;
+
+    /// The type of the rows produced by [`CustomScan::exec`].
+    ///
+    /// Each row must match the scan tuple descriptor: the relation's columns for a scan of a base
+    /// relation, otherwise the plan's `custom_scan_tlist`.
+    type Row: IntoDatumRow;
+
+    /// Offer a path for the base relation `rel`, usually created with [`custom_path`].
+    ///
+    /// Called by [`add_custom_scan_path`], returning `None` means the scan can't handle `rel`.
+    fn plan_path(
+        root: &mut pg_sys::PlannerInfo,
+        rel: &mut pg_sys::RelOptInfo,
+        rti: pg_sys::Index,
+        rte: &mut pg_sys::RangeTblEntry,
+    ) -> Option<PgBox<pg_sys::CustomPath>>;
+
+    /// Turn the chosen `best_path` into a `CustomScan` plan node.
+    ///
+    /// The default, [`default_custom_plan`], leaves all of `clauses` to be checked by the executor
+    /// and only works for base relations.
+    fn create_plan(
+        _root: &mut pg_sys::PlannerInfo,
+        rel: &mut pg_sys::RelOptInfo,
+        best_path: &mut pg_sys::CustomPath,
+        tlist: PgList<pg_sys::TargetEntry>,
+        clauses: PgList<pg_sys::RestrictInfo>,
+        _custom_plans: PgList<pg_sys::Plan>,
+    ) -> PgBox<pg_sys::CustomScan> {
+        default_custom_plan::<Self>(rel, best_path, tlist, clauses)
+    }
+
+    /// Prepare the scan, this is also called for `EXPLAIN` without `ANALYZE`.
+    fn begin(
+        node: &mut pg_sys::CustomScanState,
+        estate: &mut pg_sys::EState,
+        eflags: i32,
+    ) -> Self::State;
+
+    /// Produce the next row, or `None` when the scan is done.
+    fn exec(state: &mut Self::State) -> Option<Self::Row>;
+
+    /// Restart the scan from the beginning.
+    fn rescan(_state: &mut Self::State) {}
+
+    /// Release any resources the scan holds.
+    fn end(_state: &mut Self::State) {}
+
+    /// Add details to the `EXPLAIN` output of the scan, using `pg_sys::ExplainProperty*()`.
+    fn explain(
+        _state: &mut Self::State,
+        _ancestors: PgList<pg_sys::Plan>,
+        _es: &mut pg_sys::ExplainState,
+    ) {
+    }
+}
+
+struct CustomScanMethods {
+    _name: CString,
+    path: pg_sys::CustomPathMethods,
+    scan: pg_sys::CustomScanMethods,
+    exec: pg_sys::CustomExecMethods,
+}
+
+static mut CUSTOM_SCAN_METHODS: Option<HashMap<TypeId, Box<CustomScanMethods>>> = None;
+
+/// The method tables of `T`, created and registered with Postgres on first use.
+///
+/// Postgres keeps pointers to these in plans which might outlive the current query, so they're
+/// never freed.
+fn methods<T: CustomScan>() -> &'static CustomScanMethods {
+    unsafe {
+        let registry = CUSTOM_SCAN_METHODS.get_or_insert_with(HashMap::new);
+        let methods = registry.entry(TypeId::of::<T>()).or_insert_with(|| {
+            let name = CString::new(T::NAME).expect("custom scan name contains a NUL byte");
+            let methods = Box::new(CustomScanMethods {
+                path: pg_sys::CustomPathMethods {
+                    CustomName: name.as_ptr(),
+                    PlanCustomPath: Some(plan_custom_path::<T>),
+                    ..Default::default()
+                },
+                scan: pg_sys::CustomScanMethods {
+                    CustomName: name.as_ptr(),
+                    CreateCustomScanState: Some(create_custom_scan_state::<T>),
+                },
+                exec: pg_sys::CustomExecMethods {
+                    CustomName: name.as_ptr(),
+                    BeginCustomScan: Some(begin_custom_scan::<T>),
+                    ExecCustomScan: Some(exec_custom_scan::<T>),
+                    EndCustomScan: Some(end_custom_scan::<T>),
+                    ReScanCustomScan: Some(rescan_custom_scan::<T>),
+                    ExplainCustomScan: Some(explain_custom_scan::<T>),
+                    ..Default::default()
+                },
+                _name: name,
+            });
+            pg_sys::RegisterCustomScanMethods(&methods.scan);
+            methods
+        });
+        &*(methods.as_ref() as *const CustomScanMethods)
+    }
+}
+
+/// Create an unparameterized and unsorted `CustomPath` for `T` over `rel`, with the given
+/// estimates.
+///
+/// Its `custom_private` list can be used to pass data on to [`CustomScan::create_plan`].
+pub fn custom_path<T: CustomScan>(
+    rel: &mut pg_sys::RelOptInfo,
+    rows: f64,
+    startup_cost: f64,
+    total_cost: f64,
+) -> PgBox<pg_sys::CustomPath> {
+    let mut path = PgBox::<pg_sys::CustomPath>::alloc_node(pg_sys::NodeTag_T_CustomPath);
+    path.path.pathtype = pg_sys::NodeTag_T_CustomScan;
+    path.path.parent = rel;
+    path.path.pathtarget = rel.reltarget;
+    path.path.rows = rows;
+    path.path.startup_cost = startup_cost;
+    path.path.total_cost = total_cost;
+    path.methods = &methods::<T>().path;
+    path.into_pg_boxed()
+}
+
+/// Offer the path returned by [`CustomScan::plan_path`], if any, for the base relation `rel`.
+///
+/// This is meant to be called from a [`PgHooks::set_rel_pathlist`](crate::PgHooks::set_rel_pathlist)
+/// hook.
+pub fn add_custom_scan_path<T: CustomScan>(
+    root: &mut pg_sys::PlannerInfo,
+    rel: &mut pg_sys::RelOptInfo,
+    rti: pg_sys::Index,
+    rte: &mut pg_sys::RangeTblEntry,
+) {
+    if let Some(path) = T::plan_path(root, rel, rti, rte) {
+        unsafe { pg_sys::add_path(rel, path.into_pg() as *mut pg_sys::Path) }
+    }
+}
+
+/// Create a `CustomScan` of the base relation `rel` which leaves all of `clauses` to be checked
+/// by the executor.
+pub fn default_custom_plan<T: CustomScan>(
+    rel: &mut pg_sys::RelOptInfo,
+    best_path: &mut pg_sys::CustomPath,
+    tlist: PgList<pg_sys::TargetEntry>,
+    clauses: PgList<pg_sys::RestrictInfo>,
+) -> PgBox<pg_sys::CustomScan> {
+    let mut scan = PgBox::<pg_sys::CustomScan>::alloc_node(pg_sys::NodeTag_T_CustomScan);
+    scan.scan.plan.targetlist = tlist.into_pg();
+    scan.scan.plan.qual = unsafe { pg_sys::extract_actual_clauses(clauses.into_pg(), false) };
+    scan.scan.scanrelid = rel.relid;
+    scan.custom_private = best_path.custom_private;
+    scan.methods = &methods::<T>().scan;
+    scan.into_pg_boxed()
+}
+
+#[repr(C)]
+struct CustomScanStateWithState {
+    css: pg_sys::CustomScanState,
+    state: *mut c_void,
+}
+
+unsafe fn scan_state<'a, T: CustomScan>(node: *mut pg_sys::CustomScanState) -> &'a mut T::State {
+    &mut *((*(node as *mut CustomScanStateWithState)).state as *mut T::State)
+}
+
+unsafe extern "C" fn plan_custom_path<T: CustomScan>(
+    root: *mut pg_sys::PlannerInfo,
+    rel: *mut pg_sys::RelOptInfo,
+    best_path: *mut pg_sys::CustomPath,
+    tlist: *mut pg_sys::List,
+    clauses: *mut pg_sys::List,
+    custom_plans: *mut pg_sys::List,
+) -> *mut pg_sys::Plan {
+    pg_sys::guard::guard(|| {
+        T::create_plan(
+            &mut *root,
+            &mut *rel,
+            &mut *best_path,
+            PgList::from_pg(tlist),
+            PgList::from_pg(clauses),
+            PgList::from_pg(custom_plans),
+        )
+        .into_pg() as *mut pg_sys::Plan
+    })
+}
+
+unsafe extern "C" fn create_custom_scan_state<T: CustomScan>(
+    _cscan: *mut pg_sys::CustomScan,
+) -> *mut pg_sys::Node {
+    pg_sys::guard::guard(|| {
+        let mut node =
+            PgBox::<CustomScanStateWithState>::alloc_node(pg_sys::NodeTag_T_CustomScanState);
+        node.css.methods = &methods::<T>().exec;
+        node.into_pg() as *mut pg_sys::Node
+    })
+}
+
+unsafe extern "C" fn begin_custom_scan<T: CustomScan>(
+    node: *mut pg_sys::CustomScanState,
+    estate: *mut pg_sys::EState,
+    eflags: c_int,
+) {
+    pg_sys::guard::guard(|| {
+        let state = T::begin(&mut *node, &mut *estate, eflags);
+        (*(node as *mut CustomScanStateWithState)).state =
+            PgMemoryContexts::CurrentMemoryContext.leak_and_drop_on_delete(state) as *mut c_void;
+    })
+}
+
+unsafe extern "C" fn exec_custom_scan<T: CustomScan>(
+    node: *mut pg_sys::CustomScanState,
+) -> *mut pg_sys::TupleTableSlot {
+    pg_sys::ExecScan(
+        &mut (*node).ss,
+        Some(next_custom_scan::<T>),
+        Some(recheck_custom_scan),
+    )
+}
+
+unsafe extern "C" fn next_custom_scan<T: CustomScan>(
+    node: *mut pg_sys::ScanState,
+) -> *mut pg_sys::TupleTableSlot {
+    pg_sys::guard::guard(|| {
+        let slot = (*node).ss_ScanTupleSlot;
+        fdw::clear_slot(slot);
+        if let Some(row) = T::exec(scan_state::<T>(node as *mut pg_sys::CustomScanState)) {
+            fdw::store_row(slot, row.into_datum_row());
+        }
+        slot
+    })
+}
+
+unsafe extern "C" fn recheck_custom_scan(
+    _node: *mut pg_sys::ScanState,
+    _slot: *mut pg_sys::TupleTableSlot,
+) -> bool {
+    // rows are only produced by `CustomScan::exec`, there's nothing to recheck them against
+    true
+}
+
+unsafe extern "C" fn end_custom_scan<T: CustomScan>(node: *mut pg_sys::CustomScanState) {
+    pg_sys::guard::guard(|| {
+        if !(*(node as *mut CustomScanStateWithState)).state.is_null() {
+            T::end(scan_state::<T>(node));
+        }
+    })
+}
+
+unsafe extern "C" fn rescan_custom_scan<T: CustomScan>(node: *mut pg_sys::CustomScanState) {
+    pg_sys::guard::guard(|| {
+        pg_sys::ExecScanReScan(&mut (*node).ss);
+        T::rescan(scan_state::<T>(node));
+    })
+}
+
+unsafe extern "C" fn explain_custom_scan<T: CustomScan>(
+    node: *mut pg_sys::CustomScanState,
+    ancestors: *mut pg_sys::List,
+    es: *mut pg_sys::ExplainState,
+) {
+    pg_sys::guard::guard(|| {
+        T::explain(scan_state::<T>(node), PgList::from_pg(ancestors), &mut *es);
+    })
+}
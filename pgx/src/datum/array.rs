@@ -3,10 +3,14 @@
 
 use crate::{pg_sys, void_mut_ptr, FromDatum, IntoDatum, PgMemoryContexts};
 use serde::Serializer;
+use std::convert::TryFrom;
 use std::marker::PhantomData;
 
 pub type VariadicArray<'a, T> = Array<'a, T>;
 
+/// from "utils/memutils.h"
+pub(crate) const MAX_ALLOC_SIZE: usize = 0x3fffffff;
+
 pub struct Array<'a, T: FromDatum> {
     ptr: *mut pg_sys::varlena,
     array_type: *mut pg_sys::ArrayType,
@@ -14,6 +18,8 @@ pub struct Array<'a, T: FromDatum> {
     nulls: *mut bool,
     typoid: pg_sys::Oid,
     nelems: usize,
+    dims: Vec<i32>,
+    lower_bounds: Vec<i32>,
     elem_slice: &'a [pg_sys::Datum],
     null_slice: &'a [bool],
     _marker: PhantomData<T>,
@@ -59,6 +65,8 @@ impl<'a, T: FromDatum> Array<'a, T> {
             nulls,
             typoid: pg_sys::InvalidOid,
             nelems,
+            dims: vec![nelems as i32],
+            lower_bounds: vec![1],
             elem_slice: std::slice::from_raw_parts(elements, nelems),
             null_slice: std::slice::from_raw_parts(nulls, nelems),
             _marker: PhantomData,
@@ -73,6 +81,12 @@ impl<'a, T: FromDatum> Array<'a, T> {
         typoid: pg_sys::Oid,
         nelems: usize,
     ) -> Self {
+        let ndim = (*array_type).ndim as usize;
+        // the dimensions and lower bounds follow the ArrayType header, as in ARR_DIMS() and ARR_LBOUND()
+        let dims_ptr = array_type.add(1) as *const i32;
+        let dims = std::slice::from_raw_parts(dims_ptr, ndim).to_vec();
+        let lower_bounds = std::slice::from_raw_parts(dims_ptr.add(ndim), ndim).to_vec();
+
         Array::<T> {
             ptr,
             array_type,
@@ -80,6 +94,8 @@ impl<'a, T: FromDatum> Array<'a, T> {
             nulls,
             typoid,
             nelems,
+            dims,
+            lower_bounds,
             elem_slice: std::slice::from_raw_parts(elements, nelems),
             null_slice: std::slice::from_raw_parts(nulls, nelems),
             _marker: PhantomData,
@@ -96,14 +112,58 @@ impl<'a, T: FromDatum> Array<'a, T> {
         ptr
     }
 
-    pub fn as_slice(&self) -> &[T] {
-        let sizeof_type = std::mem::size_of::<T>();
-        let sizeof_datums = std::mem::size_of_val(self.elem_slice);
+    /// Borrow the array's elements directly from its data area, without converting each one
+    /// with [`FromDatum`].
+    ///
+    /// ## Panics
+    ///
+    /// If the array contains a NULL, or in any other case where [`Array::as_slice_checked()`]
+    /// returns an error
+    pub fn as_slice(&self) -> &[T]
+    where
+        T: FixedWidthArrayElement,
+    {
+        match self.as_slice_checked() {
+            Ok(slice) => slice,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Borrow the array's elements directly from its data area, without converting each one
+    /// with [`FromDatum`].
+    ///
+    /// NULL elements take no space in an array's data area, so this returns
+    /// [`ArraySliceError::ContainsNulls`] if there are any.  The array's element type must be
+    /// exactly `T`'s [`IntoDatum::type_oid()`], or this returns
+    /// [`ArraySliceError::IncompatibleElementType`].
+    pub fn as_slice_checked(&self) -> Result<&[T], ArraySliceError>
+    where
+        T: FixedWidthArrayElement,
+    {
+        if self.array_type.is_null() {
+            return Err(ArraySliceError::NoArrayData);
+        }
+
         unsafe {
-            std::slice::from_raw_parts(
-                self.elem_slice.as_ptr() as *const T,
-                sizeof_datums / sizeof_type,
-            )
+            let array = &*self.array_type;
+            if array.dataoffset != 0 {
+                // a nonzero `dataoffset` means there's a null bitmap
+                return Err(ArraySliceError::ContainsNulls);
+            } else if self.nelems == 0 {
+                return Ok(&[]);
+            }
+
+            if array.elemtype != T::type_oid() {
+                return Err(ArraySliceError::IncompatibleElementType);
+            }
+
+            // ARR_DATA_PTR(), for an array without a null bitmap
+            let overhead = maxalign(
+                std::mem::size_of::<pg_sys::ArrayType>()
+                    + 2 * std::mem::size_of::<i32>() * array.ndim as usize,
+            );
+            let data = (self.array_type as *const u8).add(overhead) as *const T;
+            Ok(std::slice::from_raw_parts(data, self.nelems))
         }
     }
 
@@ -150,6 +210,308 @@ impl<'a, T: FromDatum> Array<'a, T> {
             Some(unsafe { T::from_datum(self.elem_slice[i], self.null_slice[i], self.typoid) })
         }
     }
+
+    /// The number of dimensions, which is zero for an empty array
+    #[inline]
+    pub fn ndim(&self) -> usize {
+        self.dims.len()
+    }
+
+    /// The length of each dimension
+    #[inline]
+    pub fn dims(&self) -> &[i32] {
+        &self.dims
+    }
+
+    /// The lower bound of each dimension, which Postgres' subscripts start from.  Usually `1`.
+    #[inline]
+    pub fn lower_bounds(&self) -> &[i32] {
+        &self.lower_bounds
+    }
+
+    /// Get the element at `index` in a multi-dimensional array, with one zero-based index per
+    /// dimension, regardless of the array's [lower bounds](Array::lower_bounds).
+    ///
+    /// Returns `None` if `index` doesn't have [`Array::ndim()`] entries or is out of bounds.
+    #[allow(clippy::option_option)]
+    pub fn get_md(&self, index: &[usize]) -> Option<Option<T>> {
+        if index.len() != self.dims.len() {
+            return None;
+        }
+
+        // elements are stored in row-major order
+        let mut offset = 0;
+        for (&i, &dim) in index.iter().zip(self.dims.iter()) {
+            if i >= dim as usize {
+                return None;
+            }
+            offset = offset * dim as usize + i;
+        }
+        self.get(offset)
+    }
+}
+
+/// An array element type whose values are stored in an array's data area exactly as Rust lays
+/// out `Self`, so that [`Array::as_slice()`] can borrow them in place.
+///
+/// ## Safety
+///
+/// Every value of the Postgres type named by [`IntoDatum::type_oid()`] must be a valid value of
+/// `Self`, and that type must be pass-by-value, with a `typlen` equal to `Self`'s Rust size and a
+/// `typalign` no less strict than `Self`'s alignment.
+pub unsafe trait FixedWidthArrayElement: FromDatum + IntoDatum + Copy {}
+
+unsafe impl FixedWidthArrayElement for bool {}
+unsafe impl FixedWidthArrayElement for i8 {}
+unsafe impl FixedWidthArrayElement for i16 {}
+unsafe impl FixedWidthArrayElement for i32 {}
+unsafe impl FixedWidthArrayElement for i64 {}
+unsafe impl FixedWidthArrayElement for u32 {}
+unsafe impl FixedWidthArrayElement for f32 {}
+unsafe impl FixedWidthArrayElement for f64 {}
+
+/// Why [`Array::as_slice_checked()`] can't borrow an array's elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArraySliceError {
+    /// The array contains at least one NULL
+    ContainsNulls,
+
+    /// The array's element type isn't the Rust type's own [`IntoDatum::type_oid()`]
+    IncompatibleElementType,
+
+    /// The [`Array`] was created with [`Array::over()`], so there is no array data to borrow
+    NoArrayData,
+}
+
+impl std::fmt::Display for ArraySliceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArraySliceError::ContainsNulls => write!(f, "array contains NULL"),
+            ArraySliceError::IncompatibleElementType => {
+                write!(
+                    f,
+                    "array element type is incompatible with the requested slice type"
+                )
+            }
+            ArraySliceError::NoArrayData => write!(f, "array has no data to slice"),
+        }
+    }
+}
+
+impl std::error::Error for ArraySliceError {}
+
+#[inline]
+fn maxalign(len: usize) -> usize {
+    let alignment = pg_sys::MAXIMUM_ALIGNOF as usize;
+    (len + alignment - 1) & !(alignment - 1)
+}
+
+/// Builds a possibly multi-dimensional Postgres array directly from element Datums, without
+/// going through a `Vec<Option<T>>` first.
+///
+/// Elements are pushed in row-major order, meaning the last dimension varies fastest, and the
+/// array is created with `construct_md_array()` once all of them have been pushed.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// // '{{1,2,3},{4,NULL,6}}'
+/// let mut builder = ArrayBuilder::<i32>::new(&[2, 3]);
+/// for i in 1..=6 {
+///     if i == 5 {
+///         builder.push_null();
+///     } else {
+///         builder.push(i);
+///     }
+/// }
+/// let array = builder.build();
+/// assert_eq!(array.get_md(&[1, 2]), Some(Some(6)));
+/// ```
+pub struct ArrayBuilder<T: IntoDatum> {
+    dims: Vec<i32>,
+    lower_bounds: Vec<i32>,
+    elements: *mut pg_sys::Datum,
+    nulls: *mut bool,
+    capacity: usize,
+    len: usize,
+    has_nulls: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<T: IntoDatum> ArrayBuilder<T> {
+    /// Start building an array with the given dimensions, each with a lower bound of `1`
+    ///
+    /// ## Panics
+    ///
+    /// If there are more than `pg_sys::MAXDIM` dimensions, or the array would be too large
+    pub fn new(dims: &[usize]) -> Self {
+        if dims.len() > pg_sys::MAXDIM as usize {
+            panic!(
+                "number of array dimensions ({}) exceeds the maximum allowed ({})",
+                dims.len(),
+                pg_sys::MAXDIM
+            );
+        }
+
+        let capacity = if dims.is_empty() {
+            0
+        } else {
+            dims.iter()
+                .try_fold(1usize, |acc, &dim| acc.checked_mul(dim))
+                .filter(|&capacity| {
+                    capacity <= MAX_ALLOC_SIZE / std::mem::size_of::<pg_sys::Datum>()
+                })
+                .expect("array size exceeds the maximum allowed")
+        };
+        let dims = dims
+            .iter()
+            .map(|&dim| i32::try_from(dim).expect("array dimension too large"))
+            .collect::<Vec<_>>();
+
+        let (elements, nulls) = unsafe {
+            // allocate at least one element so the pointers are never NULL
+            (
+                pg_sys::palloc(capacity.max(1) * std::mem::size_of::<pg_sys::Datum>())
+                    as *mut pg_sys::Datum,
+                pg_sys::palloc0(capacity.max(1) * std::mem::size_of::<bool>()) as *mut bool,
+            )
+        };
+
+        ArrayBuilder {
+            lower_bounds: vec![1; dims.len()],
+            dims,
+            elements,
+            nulls,
+            capacity,
+            len: 0,
+            has_nulls: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Use the given lower bounds rather than starting every dimension at `1`
+    ///
+    /// ## Panics
+    ///
+    /// If there isn't one lower bound per dimension
+    pub fn with_lower_bounds(mut self, lower_bounds: &[i32]) -> Self {
+        if lower_bounds.len() != self.dims.len() {
+            panic!(
+                "array has {} dimensions but {} lower bounds were given",
+                self.dims.len(),
+                lower_bounds.len()
+            );
+        }
+        self.lower_bounds = lower_bounds.to_vec();
+        self
+    }
+
+    /// Append the next element
+    ///
+    /// ## Panics
+    ///
+    /// If the array is already full
+    pub fn push(&mut self, value: T) {
+        match value.into_datum() {
+            Some(datum) => self.push_datum(datum, false),
+            None => self.push_datum(0, true),
+        }
+    }
+
+    /// Append a NULL as the next element
+    ///
+    /// ## Panics
+    ///
+    /// If the array is already full
+    pub fn push_null(&mut self) {
+        self.push_datum(0, true)
+    }
+
+    fn push_datum(&mut self, datum: pg_sys::Datum, is_null: bool) {
+        if self.len == self.capacity {
+            panic!("array already has all {} of its elements", self.capacity);
+        }
+
+        unsafe {
+            *self.elements.add(self.len) = datum;
+            *self.nulls.add(self.len) = is_null;
+        }
+        self.has_nulls |= is_null;
+        self.len += 1;
+    }
+
+    /// The number of elements pushed so far
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Create the array.  It's only given a null bitmap if a NULL was pushed.
+    ///
+    /// ## Panics
+    ///
+    /// If fewer elements were pushed than the dimensions call for
+    pub fn build<'a>(mut self) -> Array<'a, T>
+    where
+        T: FromDatum,
+    {
+        if self.len != self.capacity {
+            panic!(
+                "array needs {} elements but only {} were pushed",
+                self.capacity, self.len
+            );
+        }
+
+        unsafe {
+            let elemtype = T::type_oid();
+            let mut typlen = 0;
+            let mut typbyval = false;
+            let mut typalign = 0;
+            pg_sys::get_typlenbyvalalign(elemtype, &mut typlen, &mut typbyval, &mut typalign);
+
+            let array_type = pg_sys::construct_md_array(
+                self.elements,
+                if self.has_nulls {
+                    self.nulls
+                } else {
+                    std::ptr::null_mut()
+                },
+                self.dims.len() as i32,
+                self.dims.as_mut_ptr(),
+                self.lower_bounds.as_mut_ptr(),
+                elemtype,
+                typlen as i32,
+                typbyval,
+                typalign,
+            );
+
+            // the Array now owns our element and null buffers
+            let array = Array::from_pg(
+                std::ptr::null_mut(),
+                array_type,
+                self.elements,
+                self.nulls,
+                elemtype,
+                self.len,
+            );
+            std::mem::forget(self);
+            array
+        }
+    }
+}
+
+impl<T: IntoDatum> Drop for ArrayBuilder<T> {
+    fn drop(&mut self) {
+        unsafe {
+            pg_sys::pfree(self.elements as void_mut_ptr);
+            pg_sys::pfree(self.nulls as void_mut_ptr);
+        }
+    }
 }
 
 pub struct ArrayTypedIterator<'a, T: 'a + FromDatum> {
@@ -322,6 +684,31 @@ impl<'a, T: FromDatum> FromDatum for Array<'a, T> {
     }
 }
 
+impl<'a, T: FromDatum + IntoDatum> IntoDatum for Array<'a, T> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        if self.array_type.is_null() {
+            // an `Array::over()` some Datums that aren't in an ArrayType yet
+            let mut builder = ArrayBuilder::<T>::new(&[self.nelems]);
+            for (&datum, &is_null) in self.elem_slice.iter().zip(self.null_slice.iter()) {
+                builder.push_datum(datum, is_null);
+            }
+            builder.build().into_datum()
+        } else {
+            unsafe {
+                pg_sys::pfree(self.elements as void_mut_ptr);
+                pg_sys::pfree(self.nulls as void_mut_ptr);
+            }
+            let array_type = self.array_type;
+            std::mem::forget(self);
+            Some(array_type as pg_sys::Datum)
+        }
+    }
+
+    fn type_oid() -> u32 {
+        unsafe { pg_sys::get_array_type(T::type_oid()) }
+    }
+}
+
 impl<T: FromDatum> FromDatum for Vec<T> {
     #[inline]
     unsafe fn from_datum(
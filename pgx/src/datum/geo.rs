@@ -1,9 +1,149 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
-use crate::{direct_function_call_as_datum, pg_sys, FromDatum, IntoDatum};
+use crate::datum::array::MAX_ALLOC_SIZE;
+use crate::{pg_sys, varlena, FromDatum, IntoDatum, PgMemoryContexts};
 
-impl FromDatum for pg_sys::BOX {
+/// Implements `FromDatum` and `IntoDatum` for a fixed-size, pass-by-reference, geometric type,
+/// by copying the struct out of, or into, palloc'd memory
+macro_rules! fixed_size_geo {
+    ($ty:ident, $oid:ident, $name:literal) => {
+        impl FromDatum for pg_sys::$ty {
+            const NEEDS_TYPID: bool = false;
+            unsafe fn from_datum(
+                datum: pg_sys::Datum,
+                is_null: bool,
+                _: pg_sys::Oid,
+            ) -> Option<Self>
+            where
+                Self: Sized,
+            {
+                if is_null {
+                    None
+                } else if datum == 0 {
+                    panic!(concat!(
+                        $name,
+                        " datum declared not null, but datum is zero"
+                    ))
+                } else {
+                    Some((datum as *const pg_sys::$ty).read())
+                }
+            }
+        }
+
+        impl IntoDatum for pg_sys::$ty {
+            fn into_datum(self) -> Option<pg_sys::Datum> {
+                let ptr = PgMemoryContexts::CurrentMemoryContext.palloc_struct::<pg_sys::$ty>();
+                unsafe {
+                    ptr.write(self);
+                }
+                Some(ptr as pg_sys::Datum)
+            }
+
+            fn type_oid() -> pg_sys::Oid {
+                pg_sys::$oid
+            }
+        }
+    };
+}
+
+fixed_size_geo!(Point, POINTOID, "Point");
+fixed_size_geo!(BOX, BOXOID, "BOX");
+fixed_size_geo!(LSEG, LSEGOID, "LSEG");
+fixed_size_geo!(LINE, LINEOID, "LINE");
+fixed_size_geo!(CIRCLE, CIRCLEOID, "CIRCLE");
+
+/// A `path`: a sequence of points, which is either open or closed
+#[derive(Debug, Clone)]
+pub struct Path {
+    points: Vec<pg_sys::Point>,
+    closed: bool,
+}
+
+/// A `polygon`: a closed sequence of points
+#[derive(Debug, Clone)]
+pub struct Polygon {
+    points: Vec<pg_sys::Point>,
+}
+
+impl Path {
+    pub fn new(points: Vec<pg_sys::Point>, closed: bool) -> Self {
+        Path { points, closed }
+    }
+
+    /// An open path through `points`, like `[(x1,y1),...]`
+    pub fn open(points: Vec<pg_sys::Point>) -> Self {
+        Path::new(points, false)
+    }
+
+    /// A closed path through `points`, like `((x1,y1),...)`
+    pub fn closed(points: Vec<pg_sys::Point>) -> Self {
+        Path::new(points, true)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn set_closed(&mut self, closed: bool) {
+        self.closed = closed;
+    }
+
+    pub fn points(&self) -> &[pg_sys::Point] {
+        &self.points
+    }
+
+    pub fn points_mut(&mut self) -> &mut [pg_sys::Point] {
+        &mut self.points
+    }
+
+    pub fn into_points(self) -> Vec<pg_sys::Point> {
+        self.points
+    }
+}
+
+impl Polygon {
+    pub fn new(points: Vec<pg_sys::Point>) -> Self {
+        Polygon { points }
+    }
+
+    pub fn points(&self) -> &[pg_sys::Point] {
+        &self.points
+    }
+
+    pub fn points_mut(&mut self) -> &mut [pg_sys::Point] {
+        &mut self.points
+    }
+
+    pub fn into_points(self) -> Vec<pg_sys::Point> {
+        self.points
+    }
+
+    /// The smallest box containing every point, which Postgres keeps alongside the points.
+    /// An empty polygon has an all-zero box.
+    pub fn bounding_box(&self) -> pg_sys::BOX {
+        let mut points = self.points.iter();
+        let first = match points.next() {
+            Some(first) => *first,
+            None => return pg_sys::BOX::default(),
+        };
+        points.fold(
+            pg_sys::BOX {
+                high: first,
+                low: first,
+            },
+            |mut bbox, point| {
+                bbox.high.x = bbox.high.x.max(point.x);
+                bbox.high.y = bbox.high.y.max(point.y);
+                bbox.low.x = bbox.low.x.min(point.x);
+                bbox.low.y = bbox.low.y.min(point.y);
+                bbox
+            },
+        )
+    }
+}
+
+impl FromDatum for Path {
     const NEEDS_TYPID: bool = false;
     unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<Self>
     where
@@ -12,31 +152,42 @@ impl FromDatum for pg_sys::BOX {
         if is_null {
             None
         } else if datum == 0 {
-            panic!("BOX datum declared not null, but datum is zero")
+            panic!("PATH datum declared not null, but datum is zero")
         } else {
-            let the_box = datum as *mut pg_sys::BOX;
-            Some(the_box.read())
+            let detoasted = pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena);
+            let path = detoasted as *const pg_sys::PATH;
+            let points =
+                std::slice::from_raw_parts((*path).p.as_ptr(), (*path).npts as usize).to_vec();
+            let closed = (*path).closed != 0;
+
+            // don't leak the detoasted copy, if Postgres had to make one
+            if detoasted as usize != datum {
+                pg_sys::pfree(detoasted as *mut std::os::raw::c_void);
+            }
+
+            Some(Path { points, closed })
         }
     }
 }
 
-impl IntoDatum for pg_sys::BOX {
-    fn into_datum(mut self) -> Option<pg_sys::Datum> {
-        let the_box = &mut self;
+impl IntoDatum for Path {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
         unsafe {
-            direct_function_call_as_datum(
-                pg_sys::box_out,
-                vec![Some(the_box as *mut pg_sys::BOX as pg_sys::Datum)],
-            )
+            let path = alloc_points_varlena::<pg_sys::PATH>(self.points.len());
+            (*path).npts = self.points.len() as i32;
+            (*path).closed = self.closed as i32;
+            copy_points(&self.points, (*path).p.as_mut_ptr());
+            Some(path as pg_sys::Datum)
         }
     }
 
     fn type_oid() -> pg_sys::Oid {
-        pg_sys::BOXOID
+        pg_sys::PATHOID
     }
 }
 
-impl FromDatum for pg_sys::Point {
+impl FromDatum for Polygon {
+    const NEEDS_TYPID: bool = false;
     unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<Self>
     where
         Self: Sized,
@@ -44,26 +195,55 @@ impl FromDatum for pg_sys::Point {
         if is_null {
             None
         } else if datum == 0 {
-            panic!("Point datum declared not null, but datum is zero")
+            panic!("POLYGON datum declared not null, but datum is zero")
         } else {
-            let point = datum as *mut pg_sys::Point;
-            Some(point.read())
+            let detoasted = pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena);
+            let polygon = detoasted as *const pg_sys::POLYGON;
+            let points =
+                std::slice::from_raw_parts((*polygon).p.as_ptr(), (*polygon).npts as usize)
+                    .to_vec();
+
+            // don't leak the detoasted copy, if Postgres had to make one
+            if detoasted as usize != datum {
+                pg_sys::pfree(detoasted as *mut std::os::raw::c_void);
+            }
+
+            Some(Polygon { points })
         }
     }
 }
 
-impl IntoDatum for pg_sys::Point {
-    fn into_datum(mut self) -> Option<usize> {
-        let point = &mut self;
+impl IntoDatum for Polygon {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
         unsafe {
-            direct_function_call_as_datum(
-                pg_sys::point_out,
-                vec![Some(point as *mut pg_sys::Point as pg_sys::Datum)],
-            )
+            let polygon = alloc_points_varlena::<pg_sys::POLYGON>(self.points.len());
+            (*polygon).npts = self.points.len() as i32;
+            (*polygon).boundbox = self.bounding_box();
+            copy_points(&self.points, (*polygon).p.as_mut_ptr());
+            Some(polygon as pg_sys::Datum)
         }
     }
 
     fn type_oid() -> pg_sys::Oid {
-        pg_sys::POINTOID
+        pg_sys::POLYGONOID
     }
 }
+
+/// Allocate a zeroed `PATH` or `POLYGON`, with room for `npts` points after its header, and set
+/// its varlena size.  Like Postgres' `offsetof(PATH, p)`, the header size of these structs is
+/// their `size_of`, as their trailing point array has no size.
+unsafe fn alloc_points_varlena<T>(npts: usize) -> *mut T {
+    let size = npts
+        .checked_mul(std::mem::size_of::<pg_sys::Point>())
+        .and_then(|points| points.checked_add(std::mem::size_of::<T>()))
+        .filter(|&size| size <= MAX_ALLOC_SIZE)
+        .expect("too many points requested");
+
+    let ptr = pg_sys::palloc0(size) as *mut T;
+    varlena::set_varsize(ptr as *mut pg_sys::varlena, size as i32);
+    ptr
+}
+
+unsafe fn copy_points(points: &[pg_sys::Point], dest: *mut pg_sys::Point) {
+    std::ptr::copy_nonoverlapping(points.as_ptr(), dest, points.len());
+}
@@ -1,79 +1,274 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
-use crate::{
-    direct_function_call, direct_function_call_as_datum, pg_sys, pg_try, void_mut_ptr, FromDatum,
-    IntoDatum,
-};
+//! Postgres' network address types: `inet`, `cidr`, `macaddr`, and `macaddr8`
+//!
+//! These are decoded directly from their on-disk representations, so no text parsing happens
+//! when moving them in and out of Postgres.
+
+use crate::{pg_sys, varlena, FromDatum, IntoDatum, PgMemoryContexts};
 use serde::de::{Error, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::ffi::CStr;
+use std::cmp::Ordering;
 use std::fmt;
-use std::ops::Deref;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+/// from "utils/inet.h".  Postgres stores `AF_INET` (which is 2 on every platform it supports) and
+/// its own `AF_INET + 1` rather than the platform's `AF_INET6`
+const PGSQL_AF_INET: u8 = 2;
+const PGSQL_AF_INET6: u8 = PGSQL_AF_INET + 1;
+
+/// An `inet` value: a host address, optionally with the netmask of the network it's on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Inet {
+    addr: IpAddr,
+    netmask: u8,
+}
+
+/// A `cidr` value: a network address, whose bits to the right of the netmask are all zero
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cidr {
+    addr: IpAddr,
+    netmask: u8,
+}
+
+/// A `macaddr` value: a 6-byte MAC address
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddr(pub [u8; 6]);
 
-#[derive(Debug, Ord, PartialOrd, Eq, PartialEq)]
-pub struct Inet(pub String);
+/// A `macaddr8` value: an 8-byte, EUI-64, MAC address
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddr8(pub [u8; 8]);
 
-impl Deref for Inet {
-    type Target = str;
+/// Errors constructing or parsing a network address type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkAddressError {
+    /// The netmask is longer than the address family allows (32 for IPv4, 128 for IPv6)
+    InvalidNetmask(u8),
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    /// A `cidr` value has bits set to the right of its netmask
+    HostBitsSet,
+
+    /// The input string isn't a valid value for the type
+    InvalidValue(String),
+}
+
+impl fmt::Display for NetworkAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkAddressError::InvalidNetmask(netmask) => {
+                write!(f, "invalid netmask: {}", netmask)
+            }
+            NetworkAddressError::HostBitsSet => {
+                write!(f, "invalid cidr value: has bits set to right of mask")
+            }
+            NetworkAddressError::InvalidValue(value) => {
+                write!(f, "invalid network address value: {}", value)
+            }
+        }
     }
 }
 
-impl Serialize for Inet {
-    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
-    where
-        S: Serializer,
-    {
-        serializer.serialize_str(&self.0)
+impl std::error::Error for NetworkAddressError {}
+
+impl Inet {
+    /// Create an `inet` for `addr` on a network with a `netmask`-bit prefix
+    pub fn new(addr: IpAddr, netmask: u8) -> Result<Self, NetworkAddressError> {
+        check_netmask(&addr, netmask)?;
+        Ok(Inet { addr, netmask })
+    }
+
+    /// The host address
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// The netmask, as a prefix length in bits
+    pub fn netmask(&self) -> u8 {
+        self.netmask
+    }
+
+    /// The network this address is on, like Postgres' `network(inet)`
+    pub fn network(&self) -> Cidr {
+        Cidr {
+            addr: mask_addr(&self.addr, self.netmask),
+            netmask: self.netmask,
+        }
     }
 }
 
-impl<'de> Deserialize<'de> for Inet {
-    fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct InetVisitor;
-        impl<'de> Visitor<'de> for InetVisitor {
-            type Value = Inet;
+impl Cidr {
+    /// Create a `cidr` for the network at `addr` with a `netmask`-bit prefix.  Like Postgres'
+    /// `cidr_in`, this fails if `addr` has any bits set to the right of the netmask.
+    pub fn new(addr: IpAddr, netmask: u8) -> Result<Self, NetworkAddressError> {
+        check_netmask(&addr, netmask)?;
+        if mask_addr(&addr, netmask) != addr {
+            return Err(NetworkAddressError::HostBitsSet);
+        }
+        Ok(Cidr { addr, netmask })
+    }
 
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("a quoted JSON string in proper inet form")
-            }
+    /// The network address
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
 
-            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
-            where
-                E: Error,
-            {
-                self.visit_string(v.to_owned())
-            }
+    /// The netmask, as a prefix length in bits
+    pub fn netmask(&self) -> u8 {
+        self.netmask
+    }
 
-            fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
-            where
-                E: Error,
-            {
-                // try to convert the provided String value into a Postgres Numeric Datum
-                // if it doesn't raise an ERROR, then we're good
-                unsafe {
-                    pg_try(|| {
-                        // this might throw, but that's okay
-                        let datum = Inet(v.clone()).into_datum().unwrap();
-
-                        // and don't leak the 'inet' datum Postgres created
-                        pg_sys::pfree(datum as void_mut_ptr);
-
-                        // we have it as a valid String
-                        Ok(Inet(v.clone()))
-                    })
-                    .unwrap_or_else(|| Err(Error::custom(format!("invalid inet value: {}", v))))
-                }
-            }
+    /// Is `addr` on this network?
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        addr.is_ipv4() == self.addr.is_ipv4() && mask_addr(addr, self.netmask) == self.addr
+    }
+}
+
+impl From<IpAddr> for Inet {
+    /// A single host, with a full-length netmask
+    fn from(addr: IpAddr) -> Self {
+        Inet {
+            addr,
+            netmask: max_netmask(&addr),
+        }
+    }
+}
+
+impl From<IpAddr> for Cidr {
+    /// A network of one host, with a full-length netmask
+    fn from(addr: IpAddr) -> Self {
+        Cidr {
+            addr,
+            netmask: max_netmask(&addr),
+        }
+    }
+}
+
+impl From<Cidr> for Inet {
+    fn from(cidr: Cidr) -> Self {
+        Inet {
+            addr: cidr.addr,
+            netmask: cidr.netmask,
+        }
+    }
+}
+
+impl From<MacAddr> for MacAddr8 {
+    /// Converts to EUI-64 by inserting `FF:FE` in the middle, like Postgres' `macaddr8(macaddr)`
+    fn from(mac: MacAddr) -> Self {
+        let [a, b, c, d, e, f] = mac.0;
+        MacAddr8([a, b, c, 0xff, 0xfe, d, e, f])
+    }
+}
+
+impl Ord for Inet {
+    /// Orders the same as Postgres' `network_cmp()`
+    fn cmp(&self, other: &Self) -> Ordering {
+        network_cmp(&self.addr, self.netmask, &other.addr, other.netmask)
+    }
+}
+
+impl PartialOrd for Inet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cidr {
+    /// Orders the same as Postgres' `network_cmp()`
+    fn cmp(&self, other: &Self) -> Ordering {
+        network_cmp(&self.addr, self.netmask, &other.addr, other.netmask)
+    }
+}
+
+impl PartialOrd for Cidr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Inet {
+    /// Formats like Postgres' `inet_out`, which leaves off a full-length netmask
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.netmask == max_netmask(&self.addr) {
+            write!(f, "{}", self.addr)
+        } else {
+            write!(f, "{}/{}", self.addr, self.netmask)
         }
+    }
+}
+
+impl fmt::Display for Cidr {
+    /// Formats like Postgres' `cidr_out`, which always includes the netmask
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.netmask)
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_mac(f, &self.0)
+    }
+}
 
-        deserializer.deserialize_str(InetVisitor)
+impl fmt::Display for MacAddr8 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_mac(f, &self.0)
+    }
+}
+
+impl FromStr for Inet {
+    type Err = NetworkAddressError;
+
+    /// Parses `address` or `address/netmask`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, netmask) = parse_addr(s)?;
+        match netmask {
+            Some(netmask) => Inet::new(addr, netmask),
+            None => Ok(Inet::from(addr)),
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = NetworkAddressError;
+
+    /// Parses `address` or `address/netmask`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, netmask) = parse_addr(s)?;
+        match netmask {
+            Some(netmask) => Cidr::new(addr, netmask),
+            None => Ok(Cidr::from(addr)),
+        }
+    }
+}
+
+impl FromStr for MacAddr {
+    type Err = NetworkAddressError;
+
+    /// Parses six hex bytes separated by `:` or `-`, such as `08:00:2b:01:02:03`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0u8; 6];
+        if parse_mac(s, &mut bytes) {
+            Ok(MacAddr(bytes))
+        } else {
+            Err(NetworkAddressError::InvalidValue(s.to_string()))
+        }
+    }
+}
+
+impl FromStr for MacAddr8 {
+    type Err = NetworkAddressError;
+
+    /// Parses eight hex bytes separated by `:` or `-`.  Like Postgres' `macaddr8_in`, six bytes
+    /// are accepted too, and converted to EUI-64.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0u8; 8];
+        if parse_mac(s, &mut bytes) {
+            return Ok(MacAddr8(bytes));
+        }
+        MacAddr::from_str(s).map(MacAddr8::from)
     }
 }
 
@@ -84,23 +279,15 @@ impl FromDatum for Inet {
         } else if datum == 0 {
             panic!("inet datum is declared non-null but Datum is zero");
         } else {
-            let cstr = direct_function_call::<&CStr>(pg_sys::inet_out, vec![Some(datum)]);
-            Some(Inet(
-                cstr.unwrap()
-                    .to_str()
-                    .expect("unable to convert &cstr inet into &str")
-                    .to_owned(),
-            ))
+            let (addr, netmask) = decode_inet(datum);
+            Some(Inet { addr, netmask })
         }
     }
 }
 
 impl IntoDatum for Inet {
     fn into_datum(self) -> Option<pg_sys::Datum> {
-        let cstr = std::ffi::CString::new(self.0).expect("failed to convert inet into CString");
-        unsafe {
-            direct_function_call_as_datum(pg_sys::inet_in, vec![cstr.as_c_str().into_datum()])
-        }
+        Some(encode_inet(&self.addr, self.netmask))
     }
 
     fn type_oid() -> u32 {
@@ -108,8 +295,272 @@ impl IntoDatum for Inet {
     }
 }
 
-impl Into<Inet> for String {
-    fn into(self) -> Inet {
-        Inet(self)
+impl FromDatum for Cidr {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Cidr> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("cidr datum is declared non-null but Datum is zero");
+        } else {
+            let (addr, netmask) = decode_inet(datum);
+            Some(Cidr { addr, netmask })
+        }
+    }
+}
+
+impl IntoDatum for Cidr {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(encode_inet(&self.addr, self.netmask))
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::CIDROID
+    }
+}
+
+impl FromDatum for MacAddr {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<MacAddr> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("macaddr datum is declared non-null but Datum is zero");
+        } else {
+            let mut bytes = [0u8; 6];
+            bytes.copy_from_slice(std::slice::from_raw_parts(datum as *const u8, 6));
+            Some(MacAddr(bytes))
+        }
+    }
+}
+
+impl IntoDatum for MacAddr {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let ptr = PgMemoryContexts::CurrentMemoryContext.palloc_slice::<u8>(6);
+        ptr.copy_from_slice(&self.0);
+        Some(ptr.as_ptr() as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::MACADDROID
+    }
+}
+
+impl FromDatum for MacAddr8 {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<MacAddr8> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("macaddr8 datum is declared non-null but Datum is zero");
+        } else {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(std::slice::from_raw_parts(datum as *const u8, 8));
+            Some(MacAddr8(bytes))
+        }
+    }
+}
+
+impl IntoDatum for MacAddr8 {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let ptr = PgMemoryContexts::CurrentMemoryContext.palloc_slice::<u8>(8);
+        ptr.copy_from_slice(&self.0);
+        Some(ptr.as_ptr() as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::MACADDR8OID
+    }
+}
+
+/// Implements `Serialize` and `Deserialize` through the type's `Display` and `FromStr`
+macro_rules! serde_via_str {
+    ($ty:ident, $expecting:literal) => {
+        impl Serialize for $ty {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct StrVisitor;
+                impl<'de> Visitor<'de> for StrVisitor {
+                    type Value = $ty;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        formatter.write_str($expecting)
+                    }
+
+                    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+                    where
+                        E: Error,
+                    {
+                        $ty::from_str(v).map_err(Error::custom)
+                    }
+                }
+
+                deserializer.deserialize_str(StrVisitor)
+            }
+        }
+    };
+}
+
+serde_via_str!(Inet, "a quoted JSON string in proper inet form");
+serde_via_str!(Cidr, "a quoted JSON string in proper cidr form");
+serde_via_str!(MacAddr, "a quoted JSON string in proper macaddr form");
+serde_via_str!(MacAddr8, "a quoted JSON string in proper macaddr8 form");
+
+/// Decode the `inet_struct` inside an `inet` or `cidr` varlena:
+///
+/// ```c
+/// typedef struct
+/// {
+///     unsigned char family;      /* PGSQL_AF_INET or PGSQL_AF_INET6 */
+///     unsigned char bits;        /* number of bits in netmask */
+///     unsigned char ipaddr[16];  /* up to 128 bits of address */
+/// } inet_struct;
+/// ```
+unsafe fn decode_inet(datum: pg_sys::Datum) -> (IpAddr, u8) {
+    let detoasted = pg_sys::pg_detoast_datum_packed(datum as *mut pg_sys::varlena);
+    let bytes = varlena::varlena_to_byte_slice(detoasted);
+    let (family, netmask, ipaddr) = (bytes[0], bytes[1], &bytes[2..]);
+
+    let addr = match family {
+        PGSQL_AF_INET => {
+            let mut octets = [0u8; 4];
+            octets.copy_from_slice(&ipaddr[..4]);
+            IpAddr::V4(Ipv4Addr::from(octets))
+        }
+        PGSQL_AF_INET6 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&ipaddr[..16]);
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        other => panic!("unrecognized inet address family: {}", other),
+    };
+
+    // don't leak the detoasted copy, if Postgres had to make one
+    if detoasted as usize != datum {
+        pg_sys::pfree(detoasted as *mut std::os::raw::c_void);
+    }
+
+    (addr, netmask)
+}
+
+/// Build an `inet` varlena, which is also the layout of `cidr`
+fn encode_inet(addr: &IpAddr, netmask: u8) -> pg_sys::Datum {
+    let (family, octets) = match addr {
+        IpAddr::V4(v4) => (PGSQL_AF_INET, v4.octets().to_vec()),
+        IpAddr::V6(v6) => (PGSQL_AF_INET6, v6.octets().to_vec()),
+    };
+    let size = pg_sys::VARHDRSZ + 2 + octets.len();
+
+    unsafe {
+        let varlena = pg_sys::palloc0(size) as *mut pg_sys::varlena;
+        varlena::set_varsize(varlena, size as i32);
+        let data = (varlena as *mut u8).add(pg_sys::VARHDRSZ);
+        *data = family;
+        *data.add(1) = netmask;
+        std::ptr::copy_nonoverlapping(octets.as_ptr(), data.add(2), octets.len());
+        varlena as pg_sys::Datum
+    }
+}
+
+fn max_netmask(addr: &IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+fn check_netmask(addr: &IpAddr, netmask: u8) -> Result<(), NetworkAddressError> {
+    if netmask > max_netmask(addr) {
+        Err(NetworkAddressError::InvalidNetmask(netmask))
+    } else {
+        Ok(())
+    }
+}
+
+/// The address' bits, left-aligned in a `u128` so both families compare bit-by-bit from the top
+fn addr_bits(addr: &IpAddr) -> u128 {
+    match addr {
+        IpAddr::V4(v4) => (u32::from(*v4) as u128) << 96,
+        IpAddr::V6(v6) => u128::from(*v6),
+    }
+}
+
+/// The first `nbits` bits of `bits`, with the rest zeroed
+fn prefix(bits: u128, nbits: u8) -> u128 {
+    if nbits == 0 {
+        0
+    } else {
+        bits & (!0u128 << (128 - nbits as u32))
+    }
+}
+
+/// `addr` with every bit to the right of `netmask` zeroed
+fn mask_addr(addr: &IpAddr, netmask: u8) -> IpAddr {
+    let masked = prefix(addr_bits(addr), netmask);
+    match addr {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from((masked >> 96) as u32)),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(masked)),
+    }
+}
+
+/// Port of Postgres' `network_cmp_internal()`: IPv4 sorts before IPv6, then by the common
+/// network prefix, then by netmask, then by the full address
+fn network_cmp(a: &IpAddr, a_netmask: u8, b: &IpAddr, b_netmask: u8) -> Ordering {
+    if a.is_ipv4() != b.is_ipv4() {
+        return b.is_ipv4().cmp(&a.is_ipv4());
+    }
+
+    let (a_bits, b_bits) = (addr_bits(a), addr_bits(b));
+    let common = a_netmask.min(b_netmask);
+    prefix(a_bits, common)
+        .cmp(&prefix(b_bits, common))
+        .then_with(|| a_netmask.cmp(&b_netmask))
+        .then_with(|| a_bits.cmp(&b_bits))
+}
+
+fn parse_addr(s: &str) -> Result<(IpAddr, Option<u8>), NetworkAddressError> {
+    let invalid = || NetworkAddressError::InvalidValue(s.to_string());
+    let mut parts = s.splitn(2, '/');
+    let addr = IpAddr::from_str(parts.next().unwrap_or_default().trim()).map_err(|_| invalid())?;
+    let netmask = match parts.next() {
+        Some(netmask) => Some(u8::from_str(netmask.trim()).map_err(|_| invalid())?),
+        None => None,
+    };
+    Ok((addr, netmask))
+}
+
+/// Parse exactly `bytes.len()` hex pairs separated by `:` or `-`
+fn parse_mac(s: &str, bytes: &mut [u8]) -> bool {
+    let parts = s.trim().split(|c| [':', '-'].contains(&c)).collect::<Vec<_>>();
+    if parts.len() != bytes.len() {
+        return false;
+    }
+    for (byte, part) in bytes.iter_mut().zip(parts) {
+        if part.len() != 2 {
+            return false;
+        }
+        match u8::from_str_radix(part, 16) {
+            Ok(value) => *byte = value,
+            Err(_) => return false,
+        }
+    }
+    true
+}
+
+fn write_mac(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    for (i, byte) in bytes.iter().enumerate() {
+        if i > 0 {
+            f.write_str(":")?;
+        }
+        write!(f, "{:02x}", byte)?;
     }
+    Ok(())
 }
@@ -0,0 +1,316 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{direct_function_call, pg_sys, FromDatum, IntoDatum, Timestamp, TimestampWithTimeZone};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Neg, Sub};
+use std::time::Duration;
+
+const USECS_PER_DAY: i64 = 86_400_000_000;
+const DAYS_PER_MONTH: i64 = 30;
+
+/// A Postgres `interval`, which keeps months, days, and microseconds separately because
+/// neither a month nor a day has a fixed length once time zones get involved.
+///
+/// Like Postgres, comparisons treat a month as 30 days and a day as 24 hours, so
+/// `'1 month'` is equal to `'30 days'`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Interval {
+    months: i32,
+    days: i32,
+    micros: i64,
+}
+
+/// Errors converting an [`Interval`] into a [`std::time::Duration`], or back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntervalConversionError {
+    /// The interval has a months or days component, which has no fixed length
+    VariableLength,
+
+    /// The interval is negative, which `Duration` can't represent
+    Negative,
+
+    /// The `Duration` has a sub-microsecond component, which an `interval` can't represent
+    SubMicrosecond,
+
+    /// The `Duration` is too large to fit in an `interval`
+    OutOfRange,
+}
+
+impl std::fmt::Display for IntervalConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntervalConversionError::VariableLength => {
+                write!(f, "interval has a months or days component")
+            }
+            IntervalConversionError::Negative => write!(f, "interval is negative"),
+            IntervalConversionError::SubMicrosecond => {
+                write!(f, "duration has sub-microsecond precision")
+            }
+            IntervalConversionError::OutOfRange => write!(f, "interval out of range"),
+        }
+    }
+}
+
+impl std::error::Error for IntervalConversionError {}
+
+impl Interval {
+    pub fn new(months: i32, days: i32, micros: i64) -> Self {
+        Interval {
+            months,
+            days,
+            micros,
+        }
+    }
+
+    pub fn from_months(months: i32) -> Self {
+        Interval::new(months, 0, 0)
+    }
+
+    pub fn from_days(days: i32) -> Self {
+        Interval::new(0, days, 0)
+    }
+
+    pub fn from_micros(micros: i64) -> Self {
+        Interval::new(0, 0, micros)
+    }
+
+    pub fn months(&self) -> i32 {
+        self.months
+    }
+
+    pub fn days(&self) -> i32 {
+        self.days
+    }
+
+    pub fn micros(&self) -> i64 {
+        self.micros
+    }
+
+    /// The length of this interval in microseconds, assuming 30-day months and 24-hour days,
+    /// which is how Postgres compares intervals
+    fn span(&self) -> i128 {
+        self.micros as i128
+            + self.days as i128 * USECS_PER_DAY as i128
+            + self.months as i128 * DAYS_PER_MONTH as i128 * USECS_PER_DAY as i128
+    }
+
+    fn checked_combine(
+        self,
+        other: Interval,
+        months: fn(i32, i32) -> Option<i32>,
+        days: fn(i32, i32) -> Option<i32>,
+        micros: fn(i64, i64) -> Option<i64>,
+    ) -> Interval {
+        match (
+            months(self.months, other.months),
+            days(self.days, other.days),
+            micros(self.micros, other.micros),
+        ) {
+            (Some(months), Some(days), Some(micros)) => Interval::new(months, days, micros),
+            _ => panic!("interval out of range"),
+        }
+    }
+}
+
+impl FromDatum for Interval {
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Interval> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("an interval Datum was flagged as non-null but the datum is zero");
+        } else {
+            let interval = &*(datum as *const pg_sys::Interval);
+            Some(Interval::new(interval.month, interval.day, interval.time))
+        }
+    }
+}
+
+impl IntoDatum for Interval {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        unsafe {
+            let interval =
+                pg_sys::palloc(std::mem::size_of::<pg_sys::Interval>()) as *mut pg_sys::Interval;
+            (*interval).month = self.months;
+            (*interval).day = self.days;
+            (*interval).time = self.micros;
+            Some(interval as pg_sys::Datum)
+        }
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::INTERVALOID
+    }
+}
+
+impl std::fmt::Display for Interval {
+    /// Formats the interval using Postgres' `interval_out`, which honors the `IntervalStyle` GUC
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = unsafe {
+            direct_function_call::<&std::ffi::CStr>(pg_sys::interval_out, vec![self.into_datum()])
+                .expect("interval_out returned null")
+        };
+        f.write_str(
+            output
+                .to_str()
+                .expect("interval_out returned invalid UTF-8"),
+        )
+    }
+}
+
+impl PartialEq for Interval {
+    fn eq(&self, other: &Self) -> bool {
+        self.span() == other.span()
+    }
+}
+
+impl Eq for Interval {}
+
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Interval {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.span().cmp(&other.span())
+    }
+}
+
+impl Hash for Interval {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.span().hash(state)
+    }
+}
+
+impl TryFrom<Interval> for Duration {
+    type Error = IntervalConversionError;
+
+    /// Only intervals without a months or days component convert, as those components don't
+    /// have a fixed length
+    fn try_from(interval: Interval) -> Result<Self, Self::Error> {
+        if interval.months != 0 || interval.days != 0 {
+            Err(IntervalConversionError::VariableLength)
+        } else if interval.micros < 0 {
+            Err(IntervalConversionError::Negative)
+        } else {
+            Ok(Duration::from_micros(interval.micros as u64))
+        }
+    }
+}
+
+impl TryFrom<Duration> for Interval {
+    type Error = IntervalConversionError;
+
+    /// The resulting interval only has a microseconds component
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        if duration.subsec_nanos() % 1000 != 0 {
+            return Err(IntervalConversionError::SubMicrosecond);
+        }
+
+        i64::try_from(duration.as_micros())
+            .map(Interval::from_micros)
+            .map_err(|_| IntervalConversionError::OutOfRange)
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    /// ## Panics
+    ///
+    /// If any of the fields overflow, just like Postgres raises "interval out of range"
+    fn add(self, rhs: Interval) -> Interval {
+        self.checked_combine(rhs, i32::checked_add, i32::checked_add, i64::checked_add)
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    /// ## Panics
+    ///
+    /// If any of the fields overflow, just like Postgres raises "interval out of range"
+    fn sub(self, rhs: Interval) -> Interval {
+        self.checked_combine(rhs, i32::checked_sub, i32::checked_sub, i64::checked_sub)
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+
+    fn neg(self) -> Interval {
+        Interval::default() - self
+    }
+}
+
+/// Applies one of Postgres' `timestamp`/`interval` operator functions
+unsafe fn timestamp_op<L: IntoDatum, R: IntoDatum, T: FromDatum>(
+    func: unsafe fn(pg_sys::FunctionCallInfo) -> pg_sys::Datum,
+    left: L,
+    right: R,
+) -> T {
+    direct_function_call::<T>(func, vec![left.into_datum(), right.into_datum()])
+        .expect("timestamp operator returned null")
+}
+
+impl Add<Interval> for Timestamp {
+    type Output = Timestamp;
+
+    /// Adds the months, then the days, then the microseconds, like Postgres' `timestamp + interval`
+    fn add(self, rhs: Interval) -> Timestamp {
+        unsafe { timestamp_op(pg_sys::timestamp_pl_interval, self, rhs) }
+    }
+}
+
+impl Sub<Interval> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, rhs: Interval) -> Timestamp {
+        unsafe { timestamp_op(pg_sys::timestamp_mi_interval, self, rhs) }
+    }
+}
+
+impl Sub for Timestamp {
+    type Output = Interval;
+
+    /// The difference as days and microseconds, like Postgres' `timestamp - timestamp`
+    fn sub(self, rhs: Timestamp) -> Interval {
+        unsafe { timestamp_op(pg_sys::timestamp_mi, self, rhs) }
+    }
+}
+
+impl Add<Interval> for TimestampWithTimeZone {
+    type Output = TimestampWithTimeZone;
+
+    /// Adds the months and days in the session's `TimeZone`, so that adding `'1 day'` across a
+    /// daylight saving time transition keeps the same local time, like Postgres'
+    /// `timestamptz + interval`
+    fn add(self, rhs: Interval) -> TimestampWithTimeZone {
+        unsafe { timestamp_op(pg_sys::timestamptz_pl_interval, self, rhs) }
+    }
+}
+
+impl Sub<Interval> for TimestampWithTimeZone {
+    type Output = TimestampWithTimeZone;
+
+    fn sub(self, rhs: Interval) -> TimestampWithTimeZone {
+        unsafe { timestamp_op(pg_sys::timestamptz_mi_interval, self, rhs) }
+    }
+}
+
+impl Sub for TimestampWithTimeZone {
+    type Output = Interval;
+
+    /// The difference as days and microseconds, like Postgres' `timestamptz - timestamptz`
+    fn sub(self, rhs: TimestampWithTimeZone) -> Interval {
+        // timestamptz and timestamp share the same representation, so Postgres uses
+        // `timestamp_mi` for both
+        unsafe { timestamp_op(pg_sys::timestamp_mi, self, rhs) }
+    }
+}
@@ -11,15 +11,19 @@ mod from;
 mod geo;
 mod inet;
 mod internal;
+mod interval;
 mod into;
 mod item_pointer_data;
 mod json;
 mod numeric;
+mod range;
+mod record;
 pub mod sql_entity_graph;
 mod time;
 mod time_stamp;
 mod time_stamp_with_timezone;
 mod time_with_timezone;
+mod tsearch;
 mod tuples;
 mod uuid;
 mod varlena;
@@ -34,16 +38,20 @@ pub use from::*;
 pub use geo::*;
 pub use inet::*;
 pub use internal::*;
+pub use interval::*;
 pub use into::*;
 pub use item_pointer_data::*;
 pub use json::*;
 pub use numeric::*;
+pub use range::*;
+pub use record::*;
 use once_cell::sync::Lazy;
 pub use sql_entity_graph::RustSqlMapping;
 use std::any::TypeId;
 pub use time_stamp::*;
 pub use time_stamp_with_timezone::*;
 pub use time_with_timezone::*;
+pub use tsearch::*;
 pub use tuples::*;
 pub use varlena::*;
 
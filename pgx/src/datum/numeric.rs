@@ -1,17 +1,594 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
-use crate::{
-    direct_function_call, direct_function_call_as_datum, pg_sys, void_mut_ptr, FromDatum, IntoDatum,
-};
-use pgx_pg_sys::pg_try;
+//! A native Rust representation of Postgres' `numeric` type.
+//!
+//! [`Numeric`] values are decoded directly from, and encoded directly to, Postgres' on-disk
+//! varlena format, so no round-trip through `numeric_in`/`numeric_out` is necessary.  All
+//! arithmetic is performed in Rust and follows Postgres' rules for the scale of the result.
+
+use crate::{ereport, pg_sys, varlena, FromDatum, IntoDatum, PgLogLevel, PgSqlErrorCode};
+use bigdecimal::BigDecimal;
+use num_bigint::{BigInt, Sign};
+use num_traits::{Signed, ToPrimitive, Zero};
 use serde::de::{Error, Visitor};
-use serde::{de, Deserialize, Deserializer, Serialize};
-use serde_json::Number;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::str::FromStr;
+
+// these mirror the definitions in Postgres' `utils/adt/numeric.c`
+const NBASE: u32 = 10000;
+const DEC_DIGITS: i64 = 4;
+
+const NUMERIC_SIGN_MASK: u16 = 0xC000;
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_SHORT: u16 = 0x8000;
+const NUMERIC_SPECIAL: u16 = 0xC000;
+
+const NUMERIC_EXT_SIGN_MASK: u16 = 0xF000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+
+const NUMERIC_DSCALE_MASK: u16 = 0x3FFF;
+const NUMERIC_DSCALE_MAX: i64 = NUMERIC_DSCALE_MASK as i64;
+
+const NUMERIC_SHORT_SIGN_MASK: u16 = 0x2000;
+const NUMERIC_SHORT_DSCALE_MASK: u16 = 0x1F80;
+const NUMERIC_SHORT_DSCALE_SHIFT: u16 = 7;
+const NUMERIC_SHORT_DSCALE_MAX: i64 =
+    (NUMERIC_SHORT_DSCALE_MASK >> NUMERIC_SHORT_DSCALE_SHIFT) as i64;
+const NUMERIC_SHORT_WEIGHT_SIGN_MASK: u16 = 0x0040;
+const NUMERIC_SHORT_WEIGHT_MASK: u16 = 0x003F;
+const NUMERIC_SHORT_WEIGHT_MAX: i64 = NUMERIC_SHORT_WEIGHT_MASK as i64;
+const NUMERIC_SHORT_WEIGHT_MIN: i64 = -(NUMERIC_SHORT_WEIGHT_MASK as i64) - 1;
+
+const NUMERIC_MAX_PRECISION: i32 = 1000;
+const NUMERIC_MAX_DISPLAY_SCALE: i64 = NUMERIC_MAX_PRECISION as i64;
+const NUMERIC_MIN_DISPLAY_SCALE: i64 = 0;
+const NUMERIC_MIN_SIG_DIGITS: i64 = 16;
+
+/// Errors that can occur when converting or constraining a [`Numeric`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NumericError {
+    /// The input string is not a valid `numeric` value
+    InvalidValue(String),
+
+    /// `NaN`, `Infinity`, or `-Infinity` can't be represented by the target type
+    NotFinite,
+
+    /// The value doesn't fit in the target type
+    OutOfRange,
+
+    /// The value doesn't fit in a `numeric(precision, scale)`
+    FieldOverflow { precision: i32, scale: i32 },
+}
+
+impl fmt::Display for NumericError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumericError::InvalidValue(value) => write!(f, "invalid Numeric value: {}", value),
+            NumericError::NotFinite => write!(f, "cannot convert NaN or infinity"),
+            NumericError::OutOfRange => write!(f, "value out of range"),
+            NumericError::FieldOverflow { precision, scale } => write!(
+                f,
+                "numeric field overflow: a field with precision {}, scale {} must round to an absolute value less than 10^{}",
+                precision,
+                scale,
+                precision - scale
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NumericError {}
+
+#[derive(Clone, Debug)]
+enum NumericValue {
+    /// always has a scale >= 0, which is the Postgres "display scale"
+    Finite(BigDecimal),
+    NaN,
+    Infinity,
+    NegInfinity,
+}
+
+/// A Postgres `numeric`: an arbitrary precision decimal, or one of the special values `NaN`,
+/// `Infinity`, and `-Infinity`.
+///
+/// Note that Postgres can only store the infinities starting with Postgres 14.  Trying to
+/// convert one into a Datum on earlier versions raises an ERROR.
+///
+/// Like Postgres, `NaN` is considered equal to itself and greater than every other value.
+#[derive(Clone, Debug)]
+pub struct Numeric(NumericValue);
+
+impl Numeric {
+    /// The `NaN` (not-a-number) value
+    pub fn nan() -> Self {
+        Numeric(NumericValue::NaN)
+    }
+
+    /// The `Infinity` value
+    pub fn infinity() -> Self {
+        Numeric(NumericValue::Infinity)
+    }
+
+    /// The `-Infinity` value
+    pub fn neg_infinity() -> Self {
+        Numeric(NumericValue::NegInfinity)
+    }
+
+    pub fn is_nan(&self) -> bool {
+        matches!(self.0, NumericValue::NaN)
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        matches!(self.0, NumericValue::Infinity | NumericValue::NegInfinity)
+    }
+
+    pub fn is_finite(&self) -> bool {
+        matches!(self.0, NumericValue::Finite(_))
+    }
+
+    /// The number of fractional digits Postgres would display for this value, or `None` if it
+    /// isn't finite
+    pub fn scale(&self) -> Option<u32> {
+        match &self.0 {
+            NumericValue::Finite(value) => Some(value.as_bigint_and_exponent().1 as u32),
+            _ => None,
+        }
+    }
+
+    /// The smallest precision a `numeric(precision, scale)` needs to hold this value at
+    /// its current [`Numeric::scale()`], or `None` if it isn't finite
+    pub fn precision(&self) -> Option<u32> {
+        match &self.0 {
+            NumericValue::Finite(value) => {
+                let (int, scale) = value.as_bigint_and_exponent();
+                let digits = int.abs().to_string().len() as i64;
+                let integral_digits = if int.is_zero() {
+                    0
+                } else {
+                    (digits - scale).max(0)
+                };
+                Some((integral_digits + scale).max(1) as u32)
+            }
+            _ => None,
+        }
+    }
+
+    /// Round to `scale` fractional digits, rounding half away from zero, just like Postgres'
+    /// `round(numeric, int)`.  A negative `scale` rounds to the left of the decimal point.
+    pub fn round(&self, scale: i32) -> Numeric {
+        self.map_finite(|value| round_half_away(value, scale as i64))
+    }
+
+    /// Truncate to `scale` fractional digits, just like Postgres' `trunc(numeric, int)`.
+    /// A negative `scale` truncates to the left of the decimal point.
+    pub fn trunc(&self, scale: i32) -> Numeric {
+        self.map_finite(|value| truncate(value, scale as i64))
+    }
+
+    /// Coerce this value into a `numeric(precision, scale)`, the same way Postgres does when
+    /// storing into a column with that type modifier.
+    ///
+    /// ## Panics
+    ///
+    /// If `precision` isn't between 1 and 1000, or `scale` isn't between 0 and `precision`
+    pub fn with_typmod(&self, precision: i32, scale: i32) -> Result<Numeric, NumericError> {
+        assert!(
+            (1..=NUMERIC_MAX_PRECISION).contains(&precision),
+            "NUMERIC precision {} must be between 1 and {}",
+            precision,
+            NUMERIC_MAX_PRECISION
+        );
+        assert!(
+            (0..=precision).contains(&scale),
+            "NUMERIC scale {} must be between 0 and precision {}",
+            scale,
+            precision
+        );
+
+        match &self.0 {
+            NumericValue::NaN => Ok(self.clone()),
+            NumericValue::Infinity | NumericValue::NegInfinity => {
+                Err(NumericError::FieldOverflow { precision, scale })
+            }
+            NumericValue::Finite(value) => {
+                let rounded = round_half_away(value, scale as i64);
+                let (int, _) = rounded.as_bigint_and_exponent();
+                if int.abs() >= pow10(precision as i64) {
+                    Err(NumericError::FieldOverflow { precision, scale })
+                } else {
+                    Ok(Numeric(NumericValue::Finite(rounded)))
+                }
+            }
+        }
+    }
+
+    /// Convert this value into an `f64`, which may lose precision
+    pub fn to_f64(&self) -> f64 {
+        match &self.0 {
+            NumericValue::Finite(_) => self.to_string().parse().unwrap(),
+            NumericValue::NaN => f64::NAN,
+            NumericValue::Infinity => f64::INFINITY,
+            NumericValue::NegInfinity => f64::NEG_INFINITY,
+        }
+    }
+
+    fn map_finite<F: FnOnce(&BigDecimal) -> BigDecimal>(&self, f: F) -> Numeric {
+        match &self.0 {
+            NumericValue::Finite(value) => Numeric(NumericValue::Finite(f(value))),
+            _ => self.clone(),
+        }
+    }
+
+    /// -1, 0, or 1, for everything but `NaN`
+    fn signum(&self) -> i32 {
+        match &self.0 {
+            NumericValue::Finite(value) => match value.sign() {
+                Sign::Minus => -1,
+                Sign::NoSign => 0,
+                Sign::Plus => 1,
+            },
+            NumericValue::Infinity => 1,
+            NumericValue::NegInfinity => -1,
+            NumericValue::NaN => panic!("NaN has no sign"),
+        }
+    }
+
+    fn infinity_with_sign(sign: i32) -> Numeric {
+        if sign < 0 {
+            Numeric::neg_infinity()
+        } else {
+            Numeric::infinity()
+        }
+    }
+
+    /// ordering of the different kinds of values, with `NaN` sorting last like in Postgres
+    fn rank(&self) -> u8 {
+        match self.0 {
+            NumericValue::NegInfinity => 0,
+            NumericValue::Finite(_) => 1,
+            NumericValue::Infinity => 2,
+            NumericValue::NaN => 3,
+        }
+    }
+}
+
+impl Default for Numeric {
+    fn default() -> Self {
+        Numeric(NumericValue::Finite(BigDecimal::zero()))
+    }
+}
+
+#[inline]
+fn pow10(exponent: i64) -> BigInt {
+    BigInt::from(10u8).pow(exponent as u32)
+}
+
+/// normalize a [`BigDecimal`] so that it has a non-negative scale
+#[inline]
+fn normalize(value: BigDecimal) -> BigDecimal {
+    if value.as_bigint_and_exponent().1 < 0 {
+        value.with_scale(0)
+    } else {
+        value
+    }
+}
+
+fn round_half_away(value: &BigDecimal, scale: i64) -> BigDecimal {
+    let (int, current) = value.as_bigint_and_exponent();
+    if current <= scale {
+        return value.with_scale(scale.max(0));
+    }
+
+    let divisor = pow10(current - scale);
+    let mut quotient = &int / &divisor;
+    let remainder = &int % &divisor;
+    if remainder.abs() * 2 >= divisor {
+        quotient += int.signum();
+    }
+    normalize(BigDecimal::new(quotient, scale))
+}
+
+fn truncate(value: &BigDecimal, scale: i64) -> BigDecimal {
+    let (int, current) = value.as_bigint_and_exponent();
+    if current <= scale {
+        return value.with_scale(scale.max(0));
+    }
+
+    normalize(BigDecimal::new(int / pow10(current - scale), scale))
+}
+
+/// The base-[`NBASE`] representation Postgres stores on disk
+struct NumericDigits {
+    negative: bool,
+    weight: i64,
+    dscale: i64,
+    digits: Vec<i16>,
+}
+
+impl NumericDigits {
+    fn from_decimal(value: &BigDecimal) -> Self {
+        let (int, dscale) = value.as_bigint_and_exponent();
+        let negative = int.sign() == Sign::Minus;
+
+        // pad the fractional digits so they fill up whole NBASE digits
+        let fractional_padding = (DEC_DIGITS - dscale % DEC_DIGITS) % DEC_DIGITS;
+        let mut decimal = int.abs().to_string();
+        decimal.extend(std::iter::repeat('0').take(fractional_padding as usize));
+        let fractional_digits = (dscale + fractional_padding) / DEC_DIGITS;
+
+        // and the integral digits too
+        let leading_padding = (DEC_DIGITS - decimal.len() as i64 % DEC_DIGITS) % DEC_DIGITS;
+        let decimal = "0".repeat(leading_padding as usize) + &decimal;
+
+        let mut digits = decimal
+            .as_bytes()
+            .chunks(DEC_DIGITS as usize)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0i16, |acc, digit| acc * 10 + (digit - b'0') as i16)
+            })
+            .collect::<Vec<_>>();
+        let mut weight = digits.len() as i64 - fractional_digits - 1;
+
+        // Postgres strips leading and trailing zeros
+        let leading_zeros = digits.iter().take_while(|digit| **digit == 0).count();
+        digits.drain(..leading_zeros);
+        weight -= leading_zeros as i64;
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+
+        if digits.is_empty() {
+            NumericDigits {
+                negative: false,
+                weight: 0,
+                dscale,
+                digits,
+            }
+        } else {
+            NumericDigits {
+                negative,
+                weight,
+                dscale,
+                digits,
+            }
+        }
+    }
+
+    fn into_decimal(self) -> BigDecimal {
+        let mut int = self
+            .digits
+            .iter()
+            .fold(BigInt::zero(), |acc, digit| acc * NBASE + *digit as u32);
+        if self.negative {
+            int = -int;
+        }
+
+        let exponent = (self.weight - self.digits.len() as i64 + 1) * DEC_DIGITS;
+        BigDecimal::new(int, -exponent).with_scale(self.dscale)
+    }
+}
+
+/// Decode the bytes following the varlena header of a Postgres `NumericData`
+fn decode(data: &[u8]) -> NumericValue {
+    let read_u16 = |offset: usize| u16::from_ne_bytes([data[offset], data[offset + 1]]);
+    let header = read_u16(0);
+
+    let (negative, dscale, weight, digits) = match header & NUMERIC_SIGN_MASK {
+        NUMERIC_SPECIAL => {
+            return match header & NUMERIC_EXT_SIGN_MASK {
+                NUMERIC_PINF => NumericValue::Infinity,
+                NUMERIC_NINF => NumericValue::NegInfinity,
+                _ => NumericValue::NaN,
+            };
+        }
+        NUMERIC_SHORT => {
+            let negative = header & NUMERIC_SHORT_SIGN_MASK != 0;
+            let dscale = (header & NUMERIC_SHORT_DSCALE_MASK) >> NUMERIC_SHORT_DSCALE_SHIFT;
+            let weight = if header & NUMERIC_SHORT_WEIGHT_SIGN_MASK != 0 {
+                (!NUMERIC_SHORT_WEIGHT_MASK | (header & NUMERIC_SHORT_WEIGHT_MASK)) as i16
+            } else {
+                (header & NUMERIC_SHORT_WEIGHT_MASK) as i16
+            };
+            (negative, dscale, weight, &data[2..])
+        }
+        sign => {
+            let dscale = header & NUMERIC_DSCALE_MASK;
+            let weight = read_u16(2) as i16;
+            (sign == NUMERIC_NEG, dscale, weight, &data[4..])
+        }
+    };
+
+    let digits = digits
+        .chunks_exact(2)
+        .map(|digit| i16::from_ne_bytes([digit[0], digit[1]]))
+        .collect();
+
+    NumericValue::Finite(
+        NumericDigits {
+            negative,
+            weight: weight as i64,
+            dscale: dscale as i64,
+            digits,
+        }
+        .into_decimal(),
+    )
+}
+
+/// Encode into the bytes following the varlena header of a Postgres `NumericData`
+fn encode(value: &NumericValue) -> Vec<u8> {
+    let special = match value {
+        NumericValue::Finite(_) => None,
+        NumericValue::NaN => Some(NUMERIC_NAN),
+        NumericValue::Infinity | NumericValue::NegInfinity if !cfg!(feature = "pg14") => {
+            ereport(
+                PgLogLevel::ERROR,
+                PgSqlErrorCode::ERRCODE_FEATURE_NOT_SUPPORTED,
+                "cannot convert infinity to numeric",
+                file!(),
+                line!(),
+                column!(),
+            );
+            unreachable!()
+        }
+        NumericValue::Infinity => Some(NUMERIC_PINF),
+        NumericValue::NegInfinity => Some(NUMERIC_NINF),
+    };
+
+    let value = match (special, value) {
+        (Some(header), _) => return header.to_ne_bytes().to_vec(),
+        (None, NumericValue::Finite(value)) => NumericDigits::from_decimal(value),
+        (None, _) => unreachable!(),
+    };
+
+    if value.dscale > NUMERIC_DSCALE_MAX || value.weight > i16::MAX as i64 {
+        ereport(
+            PgLogLevel::ERROR,
+            PgSqlErrorCode::ERRCODE_NUMERIC_VALUE_OUT_OF_RANGE,
+            "value overflows numeric format",
+            file!(),
+            line!(),
+            column!(),
+        );
+        unreachable!()
+    }
+
+    let mut bytes = Vec::with_capacity(4 + value.digits.len() * 2);
+    if value.dscale <= NUMERIC_SHORT_DSCALE_MAX
+        && (NUMERIC_SHORT_WEIGHT_MIN..=NUMERIC_SHORT_WEIGHT_MAX).contains(&value.weight)
+    {
+        let mut header = NUMERIC_SHORT
+            | ((value.dscale as u16) << NUMERIC_SHORT_DSCALE_SHIFT)
+            | (value.weight as u16 & (NUMERIC_SHORT_WEIGHT_SIGN_MASK | NUMERIC_SHORT_WEIGHT_MASK));
+        if value.negative {
+            header |= NUMERIC_SHORT_SIGN_MASK;
+        }
+        bytes.extend_from_slice(&header.to_ne_bytes());
+    } else {
+        let sign = if value.negative { NUMERIC_NEG } else { 0 };
+        let header = sign | (value.dscale as u16 & NUMERIC_DSCALE_MASK);
+        bytes.extend_from_slice(&header.to_ne_bytes());
+        bytes.extend_from_slice(&(value.weight as i16).to_ne_bytes());
+    }
+
+    for digit in value.digits {
+        bytes.extend_from_slice(&digit.to_ne_bytes());
+    }
+    bytes
+}
 
-#[derive(Serialize)]
-pub struct Numeric(pub String);
+impl FromDatum for Numeric {
+    unsafe fn from_datum(datum: usize, is_null: bool, _typoid: u32) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("a numeric Datum was flagged as non-null but the datum is zero");
+        } else {
+            let detoasted = pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena);
+            let value = decode(varlena::varlena_to_byte_slice(detoasted));
+
+            // don't leak the detoasted copy, if Postgres had to make one
+            if detoasted as usize != datum {
+                pg_sys::pfree(detoasted as *mut std::os::raw::c_void);
+            }
+
+            Some(Numeric(value))
+        }
+    }
+}
+
+impl IntoDatum for Numeric {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let bytes = encode(&self.0);
+        let size = pg_sys::VARHDRSZ + bytes.len();
+
+        unsafe {
+            let varlena = pg_sys::palloc(size) as *mut pg_sys::varlena;
+            varlena::set_varsize(varlena, size as i32);
+            std::ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                (varlena as *mut u8).add(pg_sys::VARHDRSZ),
+                bytes.len(),
+            );
+            Some(varlena as pg_sys::Datum)
+        }
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::NUMERICOID
+    }
+}
+
+impl fmt::Display for Numeric {
+    /// Formats the value exactly as Postgres' `numeric_out` would
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            NumericValue::NaN => f.write_str("NaN"),
+            NumericValue::Infinity => f.write_str("Infinity"),
+            NumericValue::NegInfinity => f.write_str("-Infinity"),
+            NumericValue::Finite(value) => {
+                let (int, scale) = value.as_bigint_and_exponent();
+                let scale = scale as usize;
+                let mut digits = int.abs().to_string();
+                if digits.len() <= scale {
+                    digits = "0".repeat(scale - digits.len() + 1) + &digits;
+                }
+
+                if int.sign() == Sign::Minus {
+                    f.write_str("-")?;
+                }
+                let (integral, fractional) = digits.split_at(digits.len() - scale);
+                f.write_str(integral)?;
+                if scale > 0 {
+                    f.write_str(".")?;
+                    f.write_str(fractional)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FromStr for Numeric {
+    type Err = NumericError;
+
+    /// Parses the same input Postgres' `numeric_in` accepts, including `NaN`, `Infinity`,
+    /// `-Infinity`, and scientific notation
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        match trimmed.to_ascii_lowercase().as_str() {
+            "nan" => return Ok(Numeric::nan()),
+            "infinity" | "+infinity" | "inf" | "+inf" => return Ok(Numeric::infinity()),
+            "-infinity" | "-inf" => return Ok(Numeric::neg_infinity()),
+            _ => {}
+        }
+
+        BigDecimal::from_str(trimmed)
+            .map(Numeric::from)
+            .map_err(|_| NumericError::InvalidValue(s.to_string()))
+    }
+}
+
+impl Serialize for Numeric {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
 
 impl<'de> Deserialize<'de> for Numeric {
     fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
@@ -42,12 +619,7 @@ impl<'de> Deserialize<'de> for Numeric {
             where
                 E: de::Error,
             {
-                let result =
-                    Number::from_f64(value).ok_or_else(|| de::Error::custom("not a JSON number"));
-                match result {
-                    Ok(num) => Ok(num.as_f64().unwrap().into()),
-                    Err(e) => Err(e),
-                }
+                Ok(value.into())
             }
 
             #[inline]
@@ -55,29 +627,7 @@ impl<'de> Deserialize<'de> for Numeric {
             where
                 E: Error,
             {
-                self.visit_string(v.to_owned())
-            }
-
-            #[inline]
-            fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
-            where
-                E: Error,
-            {
-                // try to convert the provided String value into a Postgres Numeric Datum
-                // if it doesn't raise an ERROR, then we're good
-                unsafe {
-                    pg_try(|| {
-                        // this might throw, but that's okay
-                        let datum = Numeric(v.clone()).into_datum().unwrap();
-
-                        // and don't leak the NumericData datum Postgres created
-                        pg_sys::pfree(datum as void_mut_ptr);
-
-                        // we have it as a valid String
-                        Ok(Numeric(v.clone()))
-                    })
-                    .unwrap_or(Err(Error::custom(format!("invalid Numeric value: {}", v))))
-                }
+                Numeric::from_str(v).map_err(Error::custom)
             }
         }
 
@@ -85,101 +635,301 @@ impl<'de> Deserialize<'de> for Numeric {
     }
 }
 
-impl Into<Numeric> for i8 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+impl From<BigDecimal> for Numeric {
+    fn from(value: BigDecimal) -> Self {
+        Numeric(NumericValue::Finite(normalize(value)))
     }
 }
 
-impl Into<Numeric> for i16 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+impl TryFrom<Numeric> for BigDecimal {
+    type Error = NumericError;
+
+    fn try_from(value: Numeric) -> Result<Self, Self::Error> {
+        match value.0 {
+            NumericValue::Finite(value) => Ok(value),
+            _ => Err(NumericError::NotFinite),
+        }
+    }
+}
+
+macro_rules! numeric_from_int {
+    ($($t:ty, $to:ident),*) => {
+        $(
+            impl From<$t> for Numeric {
+                fn from(value: $t) -> Self {
+                    Numeric(NumericValue::Finite(BigDecimal::new(BigInt::from(value), 0)))
+                }
+            }
+
+            impl TryFrom<Numeric> for $t {
+                type Error = NumericError;
+
+                /// Rounds half away from zero to the nearest integer, like Postgres' numeric
+                /// to integer casts
+                fn try_from(value: Numeric) -> Result<Self, Self::Error> {
+                    match value.0 {
+                        NumericValue::Finite(value) => round_half_away(&value, 0)
+                            .as_bigint_and_exponent()
+                            .0
+                            .$to()
+                            .ok_or(NumericError::OutOfRange),
+                        _ => Err(NumericError::NotFinite),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+numeric_from_int!(
+    i8, to_i8, i16, to_i16, i32, to_i32, i64, to_i64, i128, to_i128, isize, to_isize, u8, to_u8,
+    u16, to_u16, u32, to_u32, u64, to_u64, u128, to_u128, usize, to_usize
+);
+
+macro_rules! numeric_from_float {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Numeric {
+                /// Converts using the shortest decimal representation that round-trips back to
+                /// the same float.  `NaN` and the infinities map to their `numeric` counterparts.
+                fn from(value: $t) -> Self {
+                    if value.is_nan() {
+                        Numeric::nan()
+                    } else if value.is_infinite() {
+                        Numeric::infinity_with_sign(if value < 0.0 { -1 } else { 1 })
+                    } else {
+                        Numeric::from_str(&value.to_string()).expect("invalid float value")
+                    }
+                }
+            }
+
+            impl From<Numeric> for $t {
+                fn from(value: Numeric) -> Self {
+                    value.to_f64() as $t
+                }
+            }
+        )*
+    };
+}
+
+numeric_from_float!(f32, f64);
+
+impl PartialEq for Numeric {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
     }
 }
 
-impl Into<Numeric> for i32 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+impl Eq for Numeric {}
+
+impl PartialOrd for Numeric {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
-impl Into<Numeric> for i64 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+impl Ord for Numeric {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (&self.0, &other.0) {
+            (NumericValue::Finite(left), NumericValue::Finite(right)) => left.cmp(right),
+            _ => self.rank().cmp(&other.rank()),
+        }
     }
 }
 
-impl Into<Numeric> for u8 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+impl Hash for Numeric {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        if let NumericValue::Finite(value) = &self.0 {
+            // BigDecimal's hash ignores trailing fractional zeros, so it agrees with `Eq`
+            value.hash(state);
+        }
     }
 }
 
-impl Into<Numeric> for u16 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+fn division_by_zero() -> ! {
+    ereport(
+        PgLogLevel::ERROR,
+        PgSqlErrorCode::ERRCODE_DIVISION_BY_ZERO,
+        "division by zero",
+        file!(),
+        line!(),
+        column!(),
+    );
+    unreachable!()
+}
+
+/// Postgres' `select_div_scale()`: pick a result scale that gives at least
+/// [`NUMERIC_MIN_SIG_DIGITS`] significant digits, without losing the input scales
+fn select_div_scale(left: &BigDecimal, right: &BigDecimal) -> i64 {
+    let left = NumericDigits::from_decimal(left);
+    let right = NumericDigits::from_decimal(right);
+    let first_digit = |value: &NumericDigits| value.digits.first().copied().unwrap_or(0);
+
+    let mut quotient_weight = left.weight - right.weight;
+    if first_digit(&left) <= first_digit(&right) {
+        quotient_weight -= 1;
     }
+
+    (NUMERIC_MIN_SIG_DIGITS - quotient_weight * DEC_DIGITS)
+        .max(left.dscale)
+        .max(right.dscale)
+        .max(NUMERIC_MIN_DISPLAY_SCALE)
+        .min(NUMERIC_MAX_DISPLAY_SCALE)
 }
 
-impl Into<Numeric> for u32 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+impl<'a> Add<&'a Numeric> for &'a Numeric {
+    type Output = Numeric;
+
+    fn add(self, rhs: &'a Numeric) -> Numeric {
+        match (&self.0, &rhs.0) {
+            (NumericValue::Finite(left), NumericValue::Finite(right)) => {
+                Numeric(NumericValue::Finite(left + right))
+            }
+            (NumericValue::NaN, _) | (_, NumericValue::NaN) => Numeric::nan(),
+            (NumericValue::Infinity, NumericValue::NegInfinity)
+            | (NumericValue::NegInfinity, NumericValue::Infinity) => Numeric::nan(),
+            (NumericValue::Finite(_), _) => rhs.clone(),
+            _ => self.clone(),
+        }
     }
 }
 
-impl Into<Numeric> for u64 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+impl<'a> Sub<&'a Numeric> for &'a Numeric {
+    type Output = Numeric;
+
+    fn sub(self, rhs: &'a Numeric) -> Numeric {
+        self + &-rhs
     }
 }
 
-impl Into<Numeric> for f32 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+impl<'a> Mul<&'a Numeric> for &'a Numeric {
+    type Output = Numeric;
+
+    fn mul(self, rhs: &'a Numeric) -> Numeric {
+        match (&self.0, &rhs.0) {
+            (NumericValue::Finite(left), NumericValue::Finite(right)) => {
+                Numeric(NumericValue::Finite(left * right))
+            }
+            (NumericValue::NaN, _) | (_, NumericValue::NaN) => Numeric::nan(),
+            _ => match self.signum() * rhs.signum() {
+                // infinity times zero
+                0 => Numeric::nan(),
+                sign => Numeric::infinity_with_sign(sign),
+            },
+        }
     }
 }
 
-impl Into<Numeric> for f64 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+impl<'a> Div<&'a Numeric> for &'a Numeric {
+    type Output = Numeric;
+
+    /// Divides using the same result scale Postgres would pick, rounding half away from zero.
+    ///
+    /// ## Panics
+    ///
+    /// Raises a Postgres ERROR when dividing by zero
+    fn div(self, rhs: &'a Numeric) -> Numeric {
+        match (&self.0, &rhs.0) {
+            (NumericValue::NaN, _) | (_, NumericValue::NaN) => Numeric::nan(),
+            (_, NumericValue::Finite(right)) if right.is_zero() => division_by_zero(),
+            (NumericValue::Finite(left), NumericValue::Finite(right)) => {
+                let scale = select_div_scale(left, right);
+                let (left, left_scale) = left.as_bigint_and_exponent();
+                let (right, right_scale) = right.as_bigint_and_exponent();
+
+                // left / right * 10^scale, as a ratio of integers
+                let numerator = left * pow10(right_scale + scale);
+                let denominator = right * pow10(left_scale);
+                let mut quotient = &numerator / &denominator;
+                let remainder = &numerator % &denominator;
+                if remainder.abs() * 2 >= denominator.abs() {
+                    quotient += numerator.signum() * denominator.signum();
+                }
+                Numeric(NumericValue::Finite(BigDecimal::new(quotient, scale)))
+            }
+            (NumericValue::Finite(_), _) => Numeric::default(),
+            (_, NumericValue::Finite(_)) => {
+                Numeric::infinity_with_sign(self.signum() * rhs.signum())
+            }
+            // infinity divided by infinity
+            _ => Numeric::nan(),
+        }
     }
 }
 
-impl FromDatum for Numeric {
-    unsafe fn from_datum(datum: usize, is_null: bool, _typoid: u32) -> Option<Self>
-    where
-        Self: Sized,
-    {
-        if is_null {
-            None
-        } else {
-            let cstr =
-                direct_function_call::<&std::ffi::CStr>(pg_sys::numeric_out, vec![Some(datum)])
-                    .expect("numeric_out returned null");
-            Some(Numeric(cstr.to_str().unwrap().into()))
+impl<'a> Rem<&'a Numeric> for &'a Numeric {
+    type Output = Numeric;
+
+    /// The remainder of truncating division, with the sign of the dividend, like Postgres'
+    /// `mod(numeric, numeric)`.
+    ///
+    /// ## Panics
+    ///
+    /// Raises a Postgres ERROR when dividing by zero
+    fn rem(self, rhs: &'a Numeric) -> Numeric {
+        match (&self.0, &rhs.0) {
+            (NumericValue::NaN, _) | (_, NumericValue::NaN) => Numeric::nan(),
+            (_, NumericValue::Finite(right)) if right.is_zero() => division_by_zero(),
+            (NumericValue::Finite(left), NumericValue::Finite(right)) => {
+                let scale = left
+                    .as_bigint_and_exponent()
+                    .1
+                    .max(right.as_bigint_and_exponent().1);
+                let left = left.with_scale(scale).as_bigint_and_exponent().0;
+                let right = right.with_scale(scale).as_bigint_and_exponent().0;
+                Numeric(NumericValue::Finite(BigDecimal::new(left % right, scale)))
+            }
+            (NumericValue::Finite(_), _) => self.clone(),
+            // infinity modulo anything
+            _ => Numeric::nan(),
         }
     }
 }
 
-impl IntoDatum for Numeric {
-    fn into_datum(self) -> Option<pg_sys::Datum> {
-        let cstring =
-            std::ffi::CString::new(self.0).expect("failed to convert numeric string into CString");
-        let cstr = cstring.as_c_str();
+impl<'a> Neg for &'a Numeric {
+    type Output = Numeric;
 
-        unsafe {
-            direct_function_call_as_datum(
-                pg_sys::numeric_in,
-                vec![
-                    cstr.into_datum(),
-                    pg_sys::InvalidOid.into_datum(),
-                    0i32.into_datum(),
-                ],
-            )
+    fn neg(self) -> Numeric {
+        match &self.0 {
+            NumericValue::Finite(value) => Numeric(NumericValue::Finite(-value)),
+            NumericValue::NaN => Numeric::nan(),
+            NumericValue::Infinity => Numeric::neg_infinity(),
+            NumericValue::NegInfinity => Numeric::infinity(),
         }
     }
+}
 
-    fn type_oid() -> u32 {
-        pg_sys::NUMERICOID
+impl Neg for Numeric {
+    type Output = Numeric;
+
+    fn neg(self) -> Numeric {
+        -&self
     }
 }
+
+macro_rules! numeric_owned_ops {
+    ($($trait:ident, $method:ident),*) => {
+        $(
+            impl $trait for Numeric {
+                type Output = Numeric;
+
+                #[inline]
+                fn $method(self, rhs: Numeric) -> Numeric {
+                    (&self).$method(&rhs)
+                }
+            }
+
+            impl<'a> $trait<&'a Numeric> for Numeric {
+                type Output = Numeric;
+
+                #[inline]
+                fn $method(self, rhs: &'a Numeric) -> Numeric {
+                    (&self).$method(rhs)
+                }
+            }
+        )*
+    };
+}
+
+numeric_owned_ops!(Add, add, Sub, sub, Mul, mul, Div, div, Rem, rem);
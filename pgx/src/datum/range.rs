@@ -0,0 +1,340 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{
+    pg_getarg, pg_getarg_datum_raw, pg_sys, Date, FromDatum, IntoDatum, Numeric, Timestamp,
+    TimestampWithTimeZone,
+};
+
+/// One end of a [`Range`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RangeBound<T> {
+    /// The range is unbounded on this side
+    Infinite,
+    Inclusive(T),
+    Exclusive(T),
+}
+
+impl<T> RangeBound<T> {
+    /// The value of this bound, if it isn't [`RangeBound::Infinite`]
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            RangeBound::Infinite => None,
+            RangeBound::Inclusive(value) | RangeBound::Exclusive(value) => Some(value),
+        }
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        matches!(self, RangeBound::Infinite)
+    }
+
+    pub fn is_inclusive(&self) -> bool {
+        matches!(self, RangeBound::Inclusive(_))
+    }
+}
+
+/// A Postgres range type, such as `int4range` or `tstzrange`, over elements of type `T`.
+///
+/// Ranges decoded from Postgres are in their canonical form, so a discrete range such as
+/// `int4range '[1,3]'` comes back as `[1,4)`.  The same canonicalization happens when a
+/// `Range` is converted into a Datum.
+///
+/// User-defined range types can be created with `#[derive(PostgresRange)]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Range<T> {
+    lower: RangeBound<T>,
+    upper: RangeBound<T>,
+    empty: bool,
+}
+
+impl<T> Range<T> {
+    pub fn new(lower: RangeBound<T>, upper: RangeBound<T>) -> Self {
+        Range {
+            lower,
+            upper,
+            empty: false,
+        }
+    }
+
+    /// The range that contains no values, Postgres' `'empty'`
+    pub fn empty() -> Self {
+        Range {
+            lower: RangeBound::Infinite,
+            upper: RangeBound::Infinite,
+            empty: true,
+        }
+    }
+
+    /// The range that contains every value, Postgres' `'(,)'`
+    pub fn infinite() -> Self {
+        Range::new(RangeBound::Infinite, RangeBound::Infinite)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.empty
+    }
+
+    /// The lower bound, or `None` if this range is empty
+    pub fn lower(&self) -> Option<&RangeBound<T>> {
+        if self.empty {
+            None
+        } else {
+            Some(&self.lower)
+        }
+    }
+
+    /// The upper bound, or `None` if this range is empty
+    pub fn upper(&self) -> Option<&RangeBound<T>> {
+        if self.empty {
+            None
+        } else {
+            Some(&self.upper)
+        }
+    }
+
+    /// The lower and upper bounds, or `None` if this range is empty
+    pub fn into_bounds(self) -> Option<(RangeBound<T>, RangeBound<T>)> {
+        if self.empty {
+            None
+        } else {
+            Some((self.lower, self.upper))
+        }
+    }
+}
+
+impl<T: IntoDatum> Range<T> {
+    /// Convert into a Datum of the range type identified by `range_type_oid`, whose subtype
+    /// must be `T`.
+    ///
+    /// This is only necessary for user-defined range types: ranges over a [`RangeSubType`] can
+    /// use [`IntoDatum`] directly.
+    ///
+    /// ## Panics
+    ///
+    /// If `range_type_oid` isn't a range type, or if Postgres raises an ERROR because the lower
+    /// bound is greater than the upper bound
+    pub fn into_range_datum(self, range_type_oid: pg_sys::Oid) -> Option<pg_sys::Datum> {
+        self.make_range_datum(range_type_oid, true)
+    }
+
+    /// Like [`Range::into_range_datum`], but without calling the range type's canonical
+    /// function, which is what a canonical function itself must return
+    #[doc(hidden)]
+    pub fn into_range_datum_for_canonical(
+        self,
+        range_type_oid: pg_sys::Oid,
+    ) -> Option<pg_sys::Datum> {
+        self.make_range_datum(range_type_oid, false)
+    }
+
+    fn make_range_datum(
+        self,
+        range_type_oid: pg_sys::Oid,
+        canonicalize: bool,
+    ) -> Option<pg_sys::Datum> {
+        fn to_pg_bound<T: IntoDatum>(bound: RangeBound<T>, lower: bool) -> pg_sys::RangeBound {
+            let (val, infinite, inclusive) = match bound {
+                RangeBound::Infinite => (0, true, false),
+                RangeBound::Inclusive(value) => (into_bound_datum(value), false, true),
+                RangeBound::Exclusive(value) => (into_bound_datum(value), false, false),
+            };
+            pg_sys::RangeBound {
+                val,
+                infinite,
+                inclusive,
+                lower,
+            }
+        }
+
+        fn into_bound_datum<T: IntoDatum>(value: T) -> pg_sys::Datum {
+            value
+                .into_datum()
+                .expect("range bounds cannot be NULL, use RangeBound::Infinite instead")
+        }
+
+        unsafe {
+            let typcache = range_type_cache(range_type_oid);
+            let mut lower = to_pg_bound(self.lower, true);
+            let mut upper = to_pg_bound(self.upper, false);
+            let range = if canonicalize {
+                pg_sys::make_range(typcache, &mut lower, &mut upper, self.empty)
+            } else {
+                pg_sys::range_serialize(typcache, &mut lower, &mut upper, self.empty)
+            };
+            Some(range as pg_sys::Datum)
+        }
+    }
+}
+
+unsafe fn range_type_cache(range_type_oid: pg_sys::Oid) -> *mut pg_sys::TypeCacheEntry {
+    let typcache = pg_sys::lookup_type_cache(range_type_oid, pg_sys::TYPECACHE_RANGE_INFO as i32);
+    if (*typcache).rngelemtype.is_null() {
+        panic!("type with oid {} is not a range type", range_type_oid);
+    }
+    typcache
+}
+
+impl<T: FromDatum> FromDatum for Range<T> {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("a range Datum was flagged as non-null but the datum is zero");
+        } else {
+            // pass-by-reference bounds point into the detoasted copy, if Postgres had to make one,
+            // so it's left for the current memory context to free, like `Array::from_datum()` does
+            let detoasted = pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena);
+            Some(Range::from_range_type(detoasted as *mut pg_sys::RangeType))
+        }
+    }
+}
+
+impl<T: FromDatum> Range<T> {
+    /// Decode an already detoasted range, converting its bounds into `T`s
+    unsafe fn from_range_type(range: *mut pg_sys::RangeType) -> Self {
+        let typcache = range_type_cache((*range).rangetypid);
+        let element_type_oid = (*(*typcache).rngelemtype).type_id;
+
+        let mut lower = pg_sys::RangeBound::default();
+        let mut upper = pg_sys::RangeBound::default();
+        let mut empty = false;
+        pg_sys::range_deserialize(typcache, range, &mut lower, &mut upper, &mut empty);
+
+        if empty {
+            return Range::empty();
+        }
+
+        let from_pg_bound = |bound: pg_sys::RangeBound| {
+            if bound.infinite {
+                RangeBound::Infinite
+            } else {
+                let value = T::from_datum(bound.val, false, element_type_oid)
+                    .expect("range bound was NULL");
+                if bound.inclusive {
+                    RangeBound::Inclusive(value)
+                } else {
+                    RangeBound::Exclusive(value)
+                }
+            }
+        };
+
+        Range::new(from_pg_bound(lower), from_pg_bound(upper))
+    }
+}
+
+/// Calls a `#[derive(PostgresRange)]` type's `canonical` function on the range argument of
+/// `fcinfo`, which is what the generated `CANONICAL` wrapper does
+#[doc(hidden)]
+pub unsafe fn call_range_canonical<T: FromDatum + IntoDatum>(
+    fcinfo: pg_sys::FunctionCallInfo,
+    canonical: fn(Range<T>) -> Range<T>,
+) -> pg_sys::Datum {
+    let datum = pg_getarg_datum_raw(fcinfo, 0);
+    if datum == 0 {
+        panic!("range canonical function called with NULL");
+    }
+    let detoasted = pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena);
+    let range_type_oid = (*(detoasted as *mut pg_sys::RangeType)).rangetypid;
+    // as in `Range::from_datum()`, the bounds may point into `detoasted`, so it isn't freed
+    let range = Range::<T>::from_range_type(detoasted as *mut pg_sys::RangeType);
+
+    canonical(range)
+        .into_range_datum_for_canonical(range_type_oid)
+        .expect("range canonical function returned NULL")
+}
+
+/// Calls a `#[derive(PostgresRange)]` type's `subdiff` function on the two subtype arguments of
+/// `fcinfo`, which is what the generated `SUBTYPE_DIFF` wrapper does
+#[doc(hidden)]
+pub unsafe fn call_range_subdiff<T: FromDatum>(
+    fcinfo: pg_sys::FunctionCallInfo,
+    subdiff: fn(T, T) -> f64,
+) -> pg_sys::Datum {
+    let a = pg_getarg::<T>(fcinfo, 0).expect("range subdiff function called with NULL");
+    let b = pg_getarg::<T>(fcinfo, 1).expect("range subdiff function called with NULL");
+    subdiff(a, b).into_datum().expect("f64 is never NULL")
+}
+
+/// A type that is the subtype of one of Postgres' built-in range types
+pub trait RangeSubType: FromDatum + IntoDatum {
+    /// The oid of the built-in range type over this type
+    fn range_type_oid() -> pg_sys::Oid;
+}
+
+impl<T: RangeSubType> IntoDatum for Range<T> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        self.into_range_datum(T::range_type_oid())
+    }
+
+    fn type_oid() -> u32 {
+        T::range_type_oid()
+    }
+}
+
+macro_rules! range_subtype {
+    ($($t:ty => $oid:expr),*) => {
+        $(
+            impl RangeSubType for $t {
+                fn range_type_oid() -> pg_sys::Oid {
+                    $oid
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+range_subtype!(
+    i32 => pg_sys::INT4RANGEOID,
+    i64 => pg_sys::INT8RANGEOID,
+    Numeric => pg_sys::NUMRANGEOID,
+    Timestamp => pg_sys::TSRANGEOID,
+    TimestampWithTimeZone => pg_sys::TSTZRANGEOID,
+    Date => pg_sys::DATERANGEOID
+);
+
+// Postgres 10's catalog headers only #define int4range's oid, so look the others up by name
+#[cfg(feature = "pg10")]
+range_subtype!(
+    i32 => pg_sys::INT4RANGEOID,
+    i64 => crate::regtypein("pg_catalog.int8range"),
+    Numeric => crate::regtypein("pg_catalog.numrange"),
+    Timestamp => crate::regtypein("pg_catalog.tsrange"),
+    TimestampWithTimeZone => crate::regtypein("pg_catalog.tstzrange"),
+    Date => crate::regtypein("pg_catalog.daterange")
+);
+
+impl<T> From<std::ops::Range<T>> for Range<T> {
+    /// `start..end` becomes `[start,end)`
+    fn from(range: std::ops::Range<T>) -> Self {
+        Range::new(
+            RangeBound::Inclusive(range.start),
+            RangeBound::Exclusive(range.end),
+        )
+    }
+}
+
+impl<T> From<std::ops::RangeInclusive<T>> for Range<T> {
+    /// `start..=end` becomes `[start,end]`
+    fn from(range: std::ops::RangeInclusive<T>) -> Self {
+        let (start, end) = range.into_inner();
+        Range::new(RangeBound::Inclusive(start), RangeBound::Inclusive(end))
+    }
+}
+
+impl<T> From<std::ops::RangeFrom<T>> for Range<T> {
+    /// `start..` becomes `[start,)`
+    fn from(range: std::ops::RangeFrom<T>) -> Self {
+        Range::new(RangeBound::Inclusive(range.start), RangeBound::Infinite)
+    }
+}
+
+impl<T> From<std::ops::RangeTo<T>> for Range<T> {
+    /// `..end` becomes `(,end)`
+    fn from(range: std::ops::RangeTo<T>) -> Self {
+        Range::new(RangeBound::Infinite, RangeBound::Exclusive(range.end))
+    }
+}
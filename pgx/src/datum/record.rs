@@ -0,0 +1,232 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{
+    composite_row_type_make_tuple, format_type, heap_getattr, heap_getattr_raw,
+    heap_tuple_get_datum, heap_tuple_header_get_type_id, heap_tuple_header_get_typmod, pg_sys,
+    AllocatedByRust, FromDatum, IntoDatum, PgBox, PgTupleDesc,
+};
+use std::ffi::CString;
+
+/// A composite value of any row type -- a table's row type, a `CREATE TYPE ... AS (...)` type,
+/// or an anonymous `record` -- whose attributes are looked up by name at runtime
+///
+/// Its `TupleDesc` comes from the row type's entry in Postgres' type cache, via
+/// `lookup_rowtype_tupdesc()`.  Dropped attributes are skipped.
+///
+/// Build one with [`PgRecordBuilder`].
+pub struct PgRecord {
+    tupdesc: PgTupleDesc<'static>,
+    tuple: PgBox<pg_sys::HeapTupleData, AllocatedByRust>,
+}
+
+/// Collects named values into a [`PgRecord`]
+#[derive(Debug, Clone, Default)]
+pub struct PgRecordBuilder {
+    attributes: Vec<(String, Option<pg_sys::Datum>, pg_sys::Oid)>,
+}
+
+impl PgRecord {
+    pub fn builder() -> PgRecordBuilder {
+        PgRecordBuilder::default()
+    }
+
+    /// The oid of this record's composite type, or `RECORDOID` if it's anonymous
+    pub fn type_oid(&self) -> pg_sys::Oid {
+        self.tupdesc.oid()
+    }
+
+    /// For an anonymous record, the typmod that identifies its row type in this backend
+    pub fn typmod(&self) -> i32 {
+        self.tupdesc.typmod()
+    }
+
+    pub fn tupdesc(&self) -> &PgTupleDesc<'static> {
+        &self.tupdesc
+    }
+
+    /// The number of attributes, not counting dropped ones
+    pub fn len(&self) -> usize {
+        self.attributes().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The attribute names, in order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.attributes().map(|(_, attr)| attr.name())
+    }
+
+    /// Each attribute's `(name, value, type oid)`, in order.  A NULL attribute's value is `None`.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<pg_sys::Datum>, pg_sys::Oid)> {
+        self.attributes().map(move |(attno, attr)| {
+            // SAFETY:  our tuple and tupdesc describe the same row type, and attno is in range
+            let datum =
+                unsafe { heap_getattr_raw(self.tuple.as_ptr(), attno + 1, self.tupdesc.as_ptr()) };
+            (attr.name(), datum, attr.atttypid)
+        })
+    }
+
+    /// Get the attribute named `name`, or `None` if it's NULL
+    ///
+    /// ## Panics
+    ///
+    /// If this record has no attribute named `name`
+    pub fn get<T: FromDatum>(&self, name: &str) -> Option<T> {
+        let attno = self
+            .attributes()
+            .find(|(_, attr)| attr.name() == name)
+            .map(|(attno, _)| attno)
+            .unwrap_or_else(|| panic!("record has no attribute named \"{}\"", name));
+        heap_getattr(&self.tuple, attno + 1, &self.tupdesc)
+    }
+
+    /// Our non-dropped attributes, with their zero-based attribute numbers
+    fn attributes(&self) -> impl Iterator<Item = (usize, &pg_sys::FormData_pg_attribute)> {
+        self.tupdesc
+            .iter()
+            .enumerate()
+            .filter(|(_, attr)| !attr.is_dropped())
+    }
+}
+
+impl PgRecordBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an attribute whose type is `T`'s type
+    pub fn push<T: IntoDatum>(&mut self, name: &str, value: T) {
+        self.push_datum(name, value.into_datum(), T::type_oid());
+    }
+
+    /// Add an attribute of type `typoid`, with the value `datum`.  `None` is NULL.
+    pub fn push_datum(&mut self, name: &str, datum: Option<pg_sys::Datum>, typoid: pg_sys::Oid) {
+        self.attributes.push((name.to_string(), datum, typoid));
+    }
+
+    /// Build an anonymous `record` with the attributes in the order they were pushed
+    ///
+    /// Its row type is registered with Postgres (via `BlessTupleDesc()`), so it can be returned
+    /// from a function declared as `RETURNS record`, and understood by functions such as
+    /// `row_to_json()`, for the rest of this backend's life.
+    pub fn build(self) -> PgRecord {
+        unsafe {
+            let tupdesc = create_template_tupdesc(self.attributes.len());
+            for (idx, (name, _, typoid)) in self.attributes.iter().enumerate() {
+                let name =
+                    CString::new(name.as_str()).expect("attribute name contains a null byte");
+                pg_sys::TupleDescInitEntry(
+                    tupdesc,
+                    (idx + 1) as i16,
+                    name.as_ptr(),
+                    *typoid,
+                    -1,
+                    0,
+                );
+            }
+            let tupdesc = pg_sys::BlessTupleDesc(tupdesc);
+
+            let values = self.attributes.into_iter().map(|(_, datum, _)| datum);
+            let record = form_record(tupdesc, values.collect());
+
+            // the type cache has its own copy now
+            pg_sys::FreeTupleDesc(tupdesc);
+            record
+        }
+    }
+
+    /// Build a value of the composite type `typoid`, matching attributes by name.  Attributes
+    /// that weren't pushed are NULL.
+    ///
+    /// ## Panics
+    ///
+    /// If the type has no attribute with a pushed name, or if a pushed value's type isn't exactly
+    /// the attribute's type
+    pub fn build_as(self, typoid: pg_sys::Oid) -> PgRecord {
+        // SAFETY:  lookup_rowtype_tupdesc() raises an ERROR if typoid isn't a composite type
+        let tupdesc = unsafe { PgTupleDesc::from_pg(pg_sys::lookup_rowtype_tupdesc(typoid, -1)) };
+        let mut values = vec![None; tupdesc.len()];
+
+        for (name, datum, value_typoid) in self.attributes {
+            let (attno, attr) = tupdesc
+                .iter()
+                .enumerate()
+                .find(|(_, attr)| !attr.is_dropped() && attr.name() == name)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "type {} has no attribute named \"{}\"",
+                        format_type(typoid),
+                        name
+                    )
+                });
+            if attr.atttypid != value_typoid {
+                panic!(
+                    "attribute \"{}\" is of type {}, but the value is of type {}",
+                    name,
+                    format_type(attr.atttypid),
+                    format_type(value_typoid)
+                );
+            }
+            values[attno] = datum;
+        }
+
+        unsafe { form_record(tupdesc.as_ptr(), values) }
+    }
+}
+
+impl FromDatum for PgRecord {
+    const NEEDS_TYPID: bool = false;
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("record datum declared not null, but datum is zero")
+        } else {
+            let tuple = composite_row_type_make_tuple(datum);
+            let tupdesc = PgTupleDesc::from_pg(pg_sys::lookup_rowtype_tupdesc(
+                heap_tuple_header_get_type_id(tuple.t_data),
+                heap_tuple_header_get_typmod(tuple.t_data),
+            ));
+            Some(PgRecord { tupdesc, tuple })
+        }
+    }
+}
+
+impl IntoDatum for PgRecord {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(heap_tuple_get_datum(self.tuple.as_ptr()))
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::RECORDOID
+    }
+}
+
+/// Form a tuple of `tupdesc`'s row type from `values`, and wrap it as a `PgRecord`
+unsafe fn form_record(tupdesc: pg_sys::TupleDesc, values: Vec<Option<pg_sys::Datum>>) -> PgRecord {
+    let mut nulls = values.iter().map(Option::is_none).collect::<Vec<_>>();
+    let mut datums = values
+        .into_iter()
+        .map(|datum| datum.unwrap_or(0))
+        .collect::<Vec<_>>();
+    let heap_tuple = pg_sys::heap_form_tuple(tupdesc, datums.as_mut_ptr(), nulls.as_mut_ptr());
+
+    PgRecord::from_datum(heap_tuple_get_datum(heap_tuple), false, pg_sys::RECORDOID)
+        .expect("formed a NULL record")
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+unsafe fn create_template_tupdesc(natts: usize) -> pg_sys::TupleDesc {
+    pg_sys::CreateTemplateTupleDesc(natts as i32, false)
+}
+
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+unsafe fn create_template_tupdesc(natts: usize) -> pg_sys::TupleDesc {
+    pg_sys::CreateTemplateTupleDesc(natts as i32)
+}
@@ -0,0 +1,92 @@
+use super::{SqlGraphEntity, SqlGraphIdentifier, ToSql, ToSqlConfigEntity};
+use std::cmp::Ordering;
+
+/// The `TYPE` of a [`CREATE ACCESS METHOD`](https://www.postgresql.org/docs/current/sql-create-access-method.html).
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AccessMethodType {
+    Index,
+    Table,
+}
+
+impl ToSql for AccessMethodType {
+    fn to_sql(&self, _context: &super::PgxSql) -> eyre::Result<String> {
+        let value = match self {
+            AccessMethodType::Index => "INDEX",
+            AccessMethodType::Table => "TABLE",
+        };
+        Ok(String::from(value))
+    }
+}
+
+/// The output of a [`PgIndexAm`](crate::datum::sql_entity_graph::PgIndexAm) or
+/// [`PgTableAm`](crate::datum::sql_entity_graph::PgTableAm) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct PgAccessMethodEntity {
+    pub name: &'static str,
+    pub handler: &'static str,
+    pub kind: AccessMethodType,
+    pub file: &'static str,
+    pub line: u32,
+    pub full_path: &'static str,
+    pub module_path: &'static str,
+    pub to_sql_config: ToSqlConfigEntity,
+}
+
+impl Ord for PgAccessMethodEntity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.file
+            .cmp(other.file)
+            .then_with(|| self.line.cmp(&other.line))
+    }
+}
+
+impl PartialOrd for PgAccessMethodEntity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Into<SqlGraphEntity> for PgAccessMethodEntity {
+    fn into(self) -> SqlGraphEntity {
+        SqlGraphEntity::AccessMethod(self)
+    }
+}
+
+impl SqlGraphIdentifier for PgAccessMethodEntity {
+    fn dot_identifier(&self) -> String {
+        format!("access method {}", self.full_path)
+    }
+    fn rust_identifier(&self) -> String {
+        self.full_path.to_string()
+    }
+
+    fn file(&self) -> Option<&'static str> {
+        Some(self.file)
+    }
+
+    fn line(&self) -> Option<u32> {
+        Some(self.line)
+    }
+}
+
+impl ToSql for PgAccessMethodEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &super::PgxSql) -> eyre::Result<String> {
+        let self_index = context.access_methods[self];
+        let sql = format!("\n\
+                            -- {file}:{line}\n\
+                            -- {full_path}\n\
+                            CREATE ACCESS METHOD {name} TYPE {kind} HANDLER {schema}{handler};\
+                            ",
+                          name = self.name,
+                          kind = self.kind.to_sql(context)?,
+                          schema = context.schema_prefix_for(&self_index),
+                          handler = self.handler,
+                          full_path = self.full_path,
+                          file = self.file,
+                          line = self.line,
+        );
+        tracing::trace!(%sql);
+        Ok(sql)
+    }
+}
@@ -0,0 +1,75 @@
+use super::{SqlGraphEntity, SqlGraphIdentifier, ToSql, ToSqlConfigEntity};
+use std::cmp::Ordering;
+
+/// The output of a [`PgForeignDataWrapper`](crate::datum::sql_entity_graph::PgForeignDataWrapper) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct PgForeignDataWrapperEntity {
+    pub name: &'static str,
+    pub handler: &'static str,
+    pub validator: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    pub full_path: &'static str,
+    pub module_path: &'static str,
+    pub to_sql_config: ToSqlConfigEntity,
+}
+
+impl Ord for PgForeignDataWrapperEntity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.file
+            .cmp(other.file)
+            .then_with(|| self.line.cmp(&other.line))
+    }
+}
+
+impl PartialOrd for PgForeignDataWrapperEntity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Into<SqlGraphEntity> for PgForeignDataWrapperEntity {
+    fn into(self) -> SqlGraphEntity {
+        SqlGraphEntity::ForeignDataWrapper(self)
+    }
+}
+
+impl SqlGraphIdentifier for PgForeignDataWrapperEntity {
+    fn dot_identifier(&self) -> String {
+        format!("foreign data wrapper {}", self.full_path)
+    }
+    fn rust_identifier(&self) -> String {
+        self.full_path.to_string()
+    }
+
+    fn file(&self) -> Option<&'static str> {
+        Some(self.file)
+    }
+
+    fn line(&self) -> Option<u32> {
+        Some(self.line)
+    }
+}
+
+impl ToSql for PgForeignDataWrapperEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &super::PgxSql) -> eyre::Result<String> {
+        let self_index = context.foreign_data_wrappers[self];
+        let schema = context.schema_prefix_for(&self_index);
+        let sql = format!("\n\
+                            -- {file}:{line}\n\
+                            -- {full_path}\n\
+                            CREATE FOREIGN DATA WRAPPER {name} HANDLER {schema}{handler} VALIDATOR {schema}{validator};\
+                            ",
+                          name = self.name,
+                          schema = schema,
+                          handler = self.handler,
+                          validator = self.validator,
+                          full_path = self.full_path,
+                          file = self.file,
+                          line = self.line,
+        );
+        tracing::trace!(%sql);
+        Ok(sql)
+    }
+}
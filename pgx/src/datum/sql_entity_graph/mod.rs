@@ -3,15 +3,22 @@ pub use pgx_sql::PgxSql;
 
 pub mod aggregate;
 
+mod access_method;
+pub use access_method::{AccessMethodType, PgAccessMethodEntity};
+
 mod control_file;
 pub use control_file::ControlFile;
 
+mod foreign_data_wrapper;
+pub use foreign_data_wrapper::PgForeignDataWrapperEntity;
+
 mod schema;
 pub use schema::SchemaEntity;
 
 mod pg_extern;
 pub use pg_extern::{
-    PgExternArgumentEntity, PgExternEntity, PgExternReturnEntity, PgOperatorEntity,
+    PgEventTriggerEntity, PgExternArgumentEntity, PgExternEntity, PgExternReturnEntity,
+    PgOperatorEntity,
 };
 
 mod extension_sql;
@@ -20,6 +27,9 @@ pub use extension_sql::{ExtensionSqlEntity, SqlDeclaredEntity};
 mod postgres_enum;
 pub use postgres_enum::PostgresEnumEntity;
 
+mod postgres_range;
+pub use postgres_range::PostgresRangeEntity;
+
 mod postgres_type;
 pub use postgres_type::PostgresTypeEntity;
 
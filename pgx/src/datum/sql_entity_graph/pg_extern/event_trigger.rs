@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// The output of a [`PgEventTrigger`](crate::datum::sql_entity_graph::PgEventTrigger) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PgEventTriggerEntity {
+    pub event: Option<&'static str>,
+    pub tags: Vec<&'static str>,
+}
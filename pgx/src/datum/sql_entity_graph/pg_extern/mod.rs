@@ -1,10 +1,12 @@
 mod argument;
+mod event_trigger;
 mod operator;
 mod returning;
 
 use eyre::eyre;
 
 pub use argument::PgExternArgumentEntity;
+pub use event_trigger::PgEventTriggerEntity;
 pub use operator::PgOperatorEntity;
 pub use returning::PgExternReturnEntity;
 
@@ -29,6 +31,7 @@ pub struct PgExternEntity {
     pub fn_args: Vec<PgExternArgumentEntity>,
     pub fn_return: PgExternReturnEntity,
     pub operator: Option<PgOperatorEntity>,
+    pub event_trigger: Option<PgEventTriggerEntity>,
     pub to_sql_config: ToSqlConfigEntity,
 }
 
@@ -218,6 +221,7 @@ impl ToSql for PgExternEntity {
                                      format!("RETURNS TABLE ({}\n)", items)
                                  },
                                  PgExternReturnEntity::Trigger => String::from("RETURNS trigger"),
+                                 PgExternReturnEntity::EventTrigger => String::from("RETURNS event_trigger"),
                              },
                              search_path = if let Some(search_path) = &self.search_path {
                                  let retval = format!("SET search_path TO {}", search_path.join(", "));
@@ -346,6 +350,43 @@ impl ToSql for PgExternEntity {
                                 );
             tracing::trace!(sql = %operator_sql);
             ext_sql + &operator_sql
+        } else if let Some(PgEventTriggerEntity {
+            event: Some(event),
+            tags,
+        }) = &self.event_trigger
+        {
+            let event_trigger_sql = format!(
+                "\n\n\
+                    -- {file}:{line}\n\
+                    -- {module_path}::{unaliased_name}\n\
+                    CREATE EVENT TRIGGER \"{name}\" ON {event}\n\
+                    {when}\
+                    \tEXECUTE PROCEDURE {schema}\"{name}\"();\
+                ",
+                file = self.file,
+                line = self.line,
+                module_path = self.module_path,
+                unaliased_name = self.unaliased_name,
+                name = self.name,
+                event = event,
+                when = if tags.is_empty() {
+                    String::default()
+                } else {
+                    format!(
+                        "\tWHEN TAG IN ({})\n",
+                        tags.iter()
+                            .map(|tag| format!("'{}'", tag.replace('\'', "''")))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                },
+                schema = self
+                    .schema
+                    .map(|schema| format!("{}.", schema))
+                    .unwrap_or_else(|| context.schema_prefix_for(&self_index)),
+            );
+            tracing::trace!(sql = %event_trigger_sql);
+            ext_sql + &event_trigger_sql
         } else {
             ext_sql
         };
@@ -25,4 +25,5 @@ pub enum PgExternReturnEntity {
         )>,
     ),
     Trigger,
+    EventTrigger,
 }
@@ -5,9 +5,10 @@ use petgraph::{dot::Dot, graph::NodeIndex, stable_graph::StableGraph};
 use tracing::instrument;
 
 use super::{
-    aggregate::PgAggregateEntity, ControlFile, ExtensionSqlEntity, PgExternEntity,
+    aggregate::PgAggregateEntity, ControlFile, ExtensionSqlEntity, PgAccessMethodEntity,
+    PgExternEntity, PgForeignDataWrapperEntity,
     PgExternReturnEntity, PositioningRef, PostgresEnumEntity, PostgresHashEntity,
-    PostgresOrdEntity, PostgresTypeEntity, RustSourceOnlySqlMapping, RustSqlMapping, SchemaEntity,
+    PostgresOrdEntity, PostgresRangeEntity, PostgresTypeEntity, RustSourceOnlySqlMapping, RustSqlMapping, SchemaEntity,
     SqlDeclaredEntity, SqlGraphEntity, SqlGraphIdentifier, ToSql,
 };
 use pgx_utils::sql_entity_graph::SqlDeclared;
@@ -41,9 +42,12 @@ pub struct PgxSql {
     pub types: HashMap<PostgresTypeEntity, NodeIndex>,
     pub builtin_types: HashMap<String, NodeIndex>,
     pub enums: HashMap<PostgresEnumEntity, NodeIndex>,
+    pub ranges: HashMap<PostgresRangeEntity, NodeIndex>,
     pub ords: HashMap<PostgresOrdEntity, NodeIndex>,
     pub hashes: HashMap<PostgresHashEntity, NodeIndex>,
     pub aggregates: HashMap<PgAggregateEntity, NodeIndex>,
+    pub access_methods: HashMap<PgAccessMethodEntity, NodeIndex>,
+    pub foreign_data_wrappers: HashMap<PgForeignDataWrapperEntity, NodeIndex>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
@@ -71,9 +75,12 @@ impl PgxSql {
         let mut externs: Vec<PgExternEntity> = Vec::default();
         let mut types: Vec<PostgresTypeEntity> = Vec::default();
         let mut enums: Vec<PostgresEnumEntity> = Vec::default();
+        let mut ranges: Vec<PostgresRangeEntity> = Vec::default();
         let mut ords: Vec<PostgresOrdEntity> = Vec::default();
         let mut hashes: Vec<PostgresHashEntity> = Vec::default();
         let mut aggregates: Vec<PgAggregateEntity> = Vec::default();
+        let mut access_methods: Vec<PgAccessMethodEntity> = Vec::default();
+        let mut foreign_data_wrappers: Vec<PgForeignDataWrapperEntity> = Vec::default();
         for entity in entities {
             match entity {
                 SqlGraphEntity::ExtensionRoot(input_control) => {
@@ -95,6 +102,9 @@ impl PgxSql {
                 SqlGraphEntity::Enum(input_enum) => {
                     enums.push(input_enum);
                 }
+                SqlGraphEntity::Range(input_range) => {
+                    ranges.push(input_range);
+                }
                 SqlGraphEntity::Ord(input_ord) => {
                     ords.push(input_ord);
                 }
@@ -104,6 +114,12 @@ impl PgxSql {
                 SqlGraphEntity::Aggregate(input_hash) => {
                     aggregates.push(input_hash);
                 }
+                SqlGraphEntity::AccessMethod(input_access_method) => {
+                    access_methods.push(input_access_method);
+                }
+                SqlGraphEntity::ForeignDataWrapper(input_foreign_data_wrapper) => {
+                    foreign_data_wrappers.push(input_foreign_data_wrapper);
+                }
             }
         }
 
@@ -123,6 +139,7 @@ impl PgxSql {
         let mapped_schemas = initialize_schemas(&mut graph, bootstrap, finalize, schemas)?;
         let mapped_enums = initialize_enums(&mut graph, root, bootstrap, finalize, enums)?;
         let mapped_types = initialize_types(&mut graph, root, bootstrap, finalize, types)?;
+        let mapped_ranges = initialize_ranges(&mut graph, root, bootstrap, finalize, ranges)?;
         let (mapped_externs, mut mapped_builtin_types) = initialize_externs(
             &mut graph,
             root,
@@ -131,6 +148,7 @@ impl PgxSql {
             externs,
             &mapped_types,
             &mapped_enums,
+            &mapped_ranges,
         )?;
         let mapped_ords = initialize_ords(&mut graph, root, bootstrap, finalize, ords)?;
         let mapped_hashes = initialize_hashes(&mut graph, root, bootstrap, finalize, hashes)?;
@@ -144,6 +162,15 @@ impl PgxSql {
             &mapped_enums,
             &mapped_types,
         )?;
+        let mapped_access_methods =
+            initialize_access_methods(&mut graph, root, bootstrap, finalize, access_methods)?;
+        let mapped_foreign_data_wrappers = initialize_foreign_data_wrappers(
+            &mut graph,
+            root,
+            bootstrap,
+            finalize,
+            foreign_data_wrappers,
+        )?;
 
         // Now we can circle back and build up the edge sets.
         connect_schemas(&mut graph, &mapped_schemas, root);
@@ -157,12 +184,20 @@ impl PgxSql {
         )?;
         connect_enums(&mut graph, &mapped_enums, &mapped_schemas);
         connect_types(&mut graph, &mapped_types, &mapped_schemas);
+        connect_ranges(
+            &mut graph,
+            &mapped_ranges,
+            &mapped_schemas,
+            &mapped_types,
+            &mapped_enums,
+        );
         connect_externs(
             &mut graph,
             &mapped_externs,
             &mapped_schemas,
             &mapped_types,
             &mapped_enums,
+            &mapped_ranges,
             &mapped_builtin_types,
             &mapped_extension_sqls,
         )?;
@@ -191,6 +226,18 @@ impl PgxSql {
             &mapped_builtin_types,
             &mapped_externs,
         );
+        connect_access_methods(
+            &mut graph,
+            &mapped_access_methods,
+            &mapped_schemas,
+            &mapped_externs,
+        );
+        connect_foreign_data_wrappers(
+            &mut graph,
+            &mapped_foreign_data_wrappers,
+            &mapped_schemas,
+            &mapped_externs,
+        );
 
         let mut this = Self {
             type_mappings: type_mappings.map(|x| (x.id.clone(), x)).collect(),
@@ -202,9 +249,12 @@ impl PgxSql {
             types: mapped_types,
             builtin_types: mapped_builtin_types,
             enums: mapped_enums,
+            ranges: mapped_ranges,
             ords: mapped_ords,
             hashes: mapped_hashes,
             aggregates: mapped_aggregates,
+            access_methods: mapped_access_methods,
+            foreign_data_wrappers: mapped_foreign_data_wrappers,
             graph: graph,
             graph_root: root,
             graph_bootstrap: bootstrap,
@@ -276,6 +326,10 @@ impl PgxSql {
                         "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#C9A7C8\", weight = 5, shape = \"oval\"",
                         node.dot_identifier()
                     ),
+                    SqlGraphEntity::Range(_item) => format!(
+                        "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#C9A7C8\", weight = 5, shape = \"oval\"",
+                        node.dot_identifier()
+                    ),
                     SqlGraphEntity::Ord(_item) => format!(
                         "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#FFCFD3\", weight = 5, shape = \"diamond\"",
                         node.dot_identifier()
@@ -288,6 +342,14 @@ impl PgxSql {
                         "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#FFE4E0\", weight = 5, shape = \"diamond\"",
                         node.dot_identifier()
                     ),
+                    SqlGraphEntity::AccessMethod(_item) => format!(
+                        "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#DCD3E4\", weight = 5, shape = \"cds\"",
+                        node.dot_identifier()
+                    ),
+                    SqlGraphEntity::ForeignDataWrapper(_item) => format!(
+                        "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#D3E4DC\", weight = 5, shape = \"cylinder\"",
+                        node.dot_identifier()
+                    ),
                     SqlGraphEntity::CustomSql(_item) => format!(
                         "label = \"{}\", weight = 3, shape = \"signature\"",
                         node.dot_identifier()
@@ -378,6 +440,17 @@ impl PgxSql {
                 );
             }
         }
+        for (item, _index) in self.ranges.clone() {
+            for mapping in &item.mappings {
+                assert_eq!(
+                    self.type_mappings
+                        .insert(mapping.id.clone(), mapping.clone()),
+                    None,
+                    "Cannot map `{}` twice.",
+                    item.full_path,
+                );
+            }
+        }
     }
 
     pub fn has_sql_declared_entity(&self, identifier: &SqlDeclared) -> Option<&SqlDeclaredEntity> {
@@ -713,6 +786,54 @@ fn connect_types(
     }
 }
 
+#[tracing::instrument(level = "error", skip_all)]
+fn initialize_ranges(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    root: NodeIndex,
+    bootstrap: Option<NodeIndex>,
+    finalize: Option<NodeIndex>,
+    ranges: Vec<PostgresRangeEntity>,
+) -> eyre::Result<HashMap<PostgresRangeEntity, NodeIndex>> {
+    let mut mapped_ranges = HashMap::default();
+    for item in ranges {
+        let entity: SqlGraphEntity = item.clone().into();
+        let index = graph.add_node(entity);
+        mapped_ranges.insert(item, index);
+        build_base_edges(graph, index, root, bootstrap, finalize);
+    }
+    Ok(mapped_ranges)
+}
+
+#[tracing::instrument(level = "error", skip_all)]
+fn connect_ranges(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    ranges: &HashMap<PostgresRangeEntity, NodeIndex>,
+    schemas: &HashMap<SchemaEntity, NodeIndex>,
+    types: &HashMap<PostgresTypeEntity, NodeIndex>,
+    enums: &HashMap<PostgresEnumEntity, NodeIndex>,
+) {
+    for (item, &index) in ranges {
+        make_schema_connection(
+            graph,
+            "Range",
+            index,
+            &item.rust_identifier(),
+            item.module_path,
+            schemas,
+        );
+
+        make_type_or_enum_connection(
+            graph,
+            "Range",
+            index,
+            &item.rust_identifier(),
+            &item.subtype_id,
+            types,
+            enums,
+        );
+    }
+}
+
 #[tracing::instrument(level = "error", skip_all)]
 fn initialize_externs(
     graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
@@ -722,6 +843,7 @@ fn initialize_externs(
     externs: Vec<PgExternEntity>,
     mapped_types: &HashMap<PostgresTypeEntity, NodeIndex>,
     mapped_enums: &HashMap<PostgresEnumEntity, NodeIndex>,
+    mapped_ranges: &HashMap<PostgresRangeEntity, NodeIndex>,
 ) -> eyre::Result<(
     HashMap<PgExternEntity, NodeIndex>,
     HashMap<String, NodeIndex>,
@@ -748,6 +870,12 @@ fn initialize_externs(
                     break;
                 }
             }
+            for (ty_item, &_ty_index) in mapped_ranges {
+                if ty_item.id_matches(&arg.ty_id) {
+                    found = true;
+                    break;
+                }
+            }
             if !found {
                 mapped_builtin_types
                     .entry(arg.full_path.to_string())
@@ -758,7 +886,9 @@ fn initialize_externs(
         }
 
         match &item.fn_return {
-            PgExternReturnEntity::None | PgExternReturnEntity::Trigger => (),
+            PgExternReturnEntity::None
+            | PgExternReturnEntity::Trigger
+            | PgExternReturnEntity::EventTrigger => (),
             PgExternReturnEntity::Type { id, full_path, .. }
             | PgExternReturnEntity::SetOf { id, full_path, .. } => {
                 let mut found = false;
@@ -774,6 +904,12 @@ fn initialize_externs(
                         break;
                     }
                 }
+                for (ty_item, &_ty_index) in mapped_ranges {
+                    if ty_item.id_matches(id) {
+                        found = true;
+                        break;
+                    }
+                }
                 if !found {
                     mapped_builtin_types
                         .entry(full_path.to_string())
@@ -797,6 +933,12 @@ fn initialize_externs(
                             break;
                         }
                     }
+                    for (ty_item, &_ty_index) in mapped_ranges {
+                        if ty_item.id_matches(&iterated_return.0) {
+                            found = true;
+                            break;
+                        }
+                    }
                     if !found {
                         mapped_builtin_types
                             .entry(iterated_return.1.to_string())
@@ -820,6 +962,7 @@ fn connect_externs(
     schemas: &HashMap<SchemaEntity, NodeIndex>,
     types: &HashMap<PostgresTypeEntity, NodeIndex>,
     enums: &HashMap<PostgresEnumEntity, NodeIndex>,
+    ranges: &HashMap<PostgresRangeEntity, NodeIndex>,
     builtin_types: &HashMap<String, NodeIndex>,
     extension_sqls: &HashMap<ExtensionSqlEntity, NodeIndex>,
 ) -> eyre::Result<()> {
@@ -876,6 +1019,16 @@ fn connect_externs(
                     }
                 }
             }
+            if !found {
+                for (range_item, &range_index) in ranges {
+                    if range_item.id_matches(&arg.ty_id) {
+                        tracing::debug!(from = %item.rust_identifier(), to = %range_item.rust_identifier(), "Adding Extern after Range (due to argument) edge");
+                        graph.add_edge(range_index, index, SqlGraphRelationship::RequiredByArg);
+                        found = true;
+                        break;
+                    }
+                }
+            }
             if !found {
                 let builtin_index = builtin_types
                     .get(arg.full_path)
@@ -900,7 +1053,9 @@ fn connect_externs(
             }
         }
         match &item.fn_return {
-            PgExternReturnEntity::None | PgExternReturnEntity::Trigger => (),
+            PgExternReturnEntity::None
+            | PgExternReturnEntity::Trigger
+            | PgExternReturnEntity::EventTrigger => (),
             PgExternReturnEntity::Type { id, full_path, .. }
             | PgExternReturnEntity::SetOf { id, full_path, .. } => {
                 let mut found = false;
@@ -922,6 +1077,16 @@ fn connect_externs(
                         }
                     }
                 }
+                if !found {
+                    for (ty_item, &ty_index) in ranges {
+                        if ty_item.id_matches(id) {
+                            tracing::debug!(from = %item.rust_identifier(), to = %ty_item.rust_identifier(), "Adding Extern after Range (due to return) edge");
+                            graph.add_edge(ty_index, index, SqlGraphRelationship::RequiredByReturn);
+                            found = true;
+                            break;
+                        }
+                    }
+                }
                 if !found {
                     let builtin_index = builtin_types
                         .get(&full_path.to_string())
@@ -974,6 +1139,20 @@ fn connect_externs(
                             }
                         }
                     }
+                    if !found {
+                        for (ty_item, &ty_index) in ranges {
+                            if ty_item.id_matches(&iterated_return.0) {
+                                tracing::debug!(from = %item.rust_identifier(), to = %ty_item.rust_identifier(), "Adding Extern after Range (due to return) edge");
+                                graph.add_edge(
+                                    ty_index,
+                                    index,
+                                    SqlGraphRelationship::RequiredByReturn,
+                                );
+                                found = true;
+                                break;
+                            }
+                        }
+                    }
                     if !found {
                         let builtin_index = builtin_types
                             .get(&iterated_return.1.to_string())
@@ -1385,6 +1564,100 @@ fn connect_aggregates(
     }
 }
 
+#[tracing::instrument(level = "error", skip_all)]
+fn initialize_access_methods(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    root: NodeIndex,
+    bootstrap: Option<NodeIndex>,
+    finalize: Option<NodeIndex>,
+    access_methods: Vec<PgAccessMethodEntity>,
+) -> eyre::Result<HashMap<PgAccessMethodEntity, NodeIndex>> {
+    let mut mapped_access_methods = HashMap::default();
+    for item in access_methods {
+        let entity: SqlGraphEntity = item.clone().into();
+        let index = graph.add_node(entity);
+        mapped_access_methods.insert(item, index);
+        build_base_edges(graph, index, root, bootstrap, finalize);
+    }
+    Ok(mapped_access_methods)
+}
+
+#[tracing::instrument(level = "error", skip_all)]
+fn connect_access_methods(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    access_methods: &HashMap<PgAccessMethodEntity, NodeIndex>,
+    schemas: &HashMap<SchemaEntity, NodeIndex>,
+    externs: &HashMap<PgExternEntity, NodeIndex>,
+) {
+    for (item, &index) in access_methods {
+        make_schema_connection(
+            graph,
+            "Access Method",
+            index,
+            &item.rust_identifier(),
+            item.module_path,
+            schemas,
+        );
+
+        make_extern_connection(
+            graph,
+            "Access Method",
+            index,
+            &item.rust_identifier(),
+            &(item.module_path.to_string() + "::" + item.handler),
+            externs,
+        );
+    }
+}
+
+#[tracing::instrument(level = "error", skip_all)]
+fn initialize_foreign_data_wrappers(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    root: NodeIndex,
+    bootstrap: Option<NodeIndex>,
+    finalize: Option<NodeIndex>,
+    foreign_data_wrappers: Vec<PgForeignDataWrapperEntity>,
+) -> eyre::Result<HashMap<PgForeignDataWrapperEntity, NodeIndex>> {
+    let mut mapped_foreign_data_wrappers = HashMap::default();
+    for item in foreign_data_wrappers {
+        let entity: SqlGraphEntity = item.clone().into();
+        let index = graph.add_node(entity);
+        mapped_foreign_data_wrappers.insert(item, index);
+        build_base_edges(graph, index, root, bootstrap, finalize);
+    }
+    Ok(mapped_foreign_data_wrappers)
+}
+
+#[tracing::instrument(level = "error", skip_all)]
+fn connect_foreign_data_wrappers(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    foreign_data_wrappers: &HashMap<PgForeignDataWrapperEntity, NodeIndex>,
+    schemas: &HashMap<SchemaEntity, NodeIndex>,
+    externs: &HashMap<PgExternEntity, NodeIndex>,
+) {
+    for (item, &index) in foreign_data_wrappers {
+        make_schema_connection(
+            graph,
+            "Foreign Data Wrapper",
+            index,
+            &item.rust_identifier(),
+            item.module_path,
+            schemas,
+        );
+
+        for function in [item.handler, item.validator] {
+            make_extern_connection(
+                graph,
+                "Foreign Data Wrapper",
+                index,
+                &item.rust_identifier(),
+                &(item.module_path.to_string() + "::" + function),
+                externs,
+            );
+        }
+    }
+}
+
 fn make_schema_connection(
     graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
     kind: &str,
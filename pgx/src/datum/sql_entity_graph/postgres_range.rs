@@ -0,0 +1,148 @@
+use std::{
+    any::TypeId,
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+use eyre::eyre;
+
+use super::{SqlGraphEntity, SqlGraphIdentifier, ToSql, ToSqlConfigEntity};
+
+/// The output of a [`PostgresRange`](crate::datum::sql_entity_graph::PostgresRange) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostgresRangeEntity {
+    pub name: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    pub full_path: &'static str,
+    pub module_path: &'static str,
+    pub mappings: std::collections::HashSet<super::RustSqlMapping>,
+    pub subtype_id: TypeId,
+    pub subtype_source: &'static str,
+    pub subtype_full_path: &'static str,
+    /// The SQL name of the generated `CANONICAL` function, if any
+    pub canonical: Option<&'static str>,
+    /// The SQL name of the generated `SUBTYPE_DIFF` function, if any
+    pub subdiff: Option<&'static str>,
+    pub to_sql_config: ToSqlConfigEntity,
+}
+
+impl Hash for PostgresRangeEntity {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.full_path.hash(state);
+    }
+}
+
+impl Ord for PostgresRangeEntity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.file
+            .cmp(other.file)
+            .then_with(|| self.line.cmp(&other.line))
+    }
+}
+
+impl PartialOrd for PostgresRangeEntity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PostgresRangeEntity {
+    pub fn id_matches(&self, candidate: &core::any::TypeId) -> bool {
+        self.mappings.iter().any(|tester| *candidate == tester.id)
+    }
+}
+
+impl Into<SqlGraphEntity> for PostgresRangeEntity {
+    fn into(self) -> SqlGraphEntity {
+        SqlGraphEntity::Range(self)
+    }
+}
+
+impl SqlGraphIdentifier for PostgresRangeEntity {
+    fn dot_identifier(&self) -> String {
+        format!("range {}", self.full_path)
+    }
+    fn rust_identifier(&self) -> String {
+        self.full_path.to_string()
+    }
+
+    fn file(&self) -> Option<&'static str> {
+        Some(self.file)
+    }
+
+    fn line(&self) -> Option<u32> {
+        Some(self.line)
+    }
+}
+
+impl ToSql for PostgresRangeEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &super::PgxSql) -> eyre::Result<String> {
+        let self_index = context.ranges[self];
+        let schema = context.schema_prefix_for(&self_index);
+        let subtype = context
+            .rust_to_sql(self.subtype_id, self.subtype_source, self.subtype_full_path)
+            .ok_or_else(|| {
+                eyre!(
+                    "Failed to map range subtype `{}` to a SQL type while building range `{}`.",
+                    self.subtype_full_path,
+                    self.full_path
+                )
+            })?;
+
+        // The functions need to be present in a certain order:
+        // - CREATE TYPE; (the canonical function takes and returns the range type)
+        // - CREATE FUNCTION _canonical;
+        // - CREATE FUNCTION _subdiff;
+        // - CREATE TYPE AS RANGE (...);
+        let mut sql = format!(
+            "\n\
+                -- {file}:{line}\n\
+                -- {full_path}\
+            ",
+            file = self.file,
+            line = self.line,
+            full_path = self.full_path,
+        );
+        let mut options = vec![format!("\tSUBTYPE = {}", subtype)];
+
+        if let Some(canonical) = self.canonical {
+            sql.push_str(&format!(
+                "\n\
+                    CREATE TYPE {schema}{name};\n\
+                    CREATE FUNCTION {schema}{canonical}({schema}{name}) RETURNS {schema}{name} IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c /* Rust */ AS 'MODULE_PATHNAME', '{canonical}_wrapper';\
+                ",
+                schema = schema,
+                name = self.name,
+                canonical = canonical,
+            ));
+            options.push(format!("\tCANONICAL = {}{}", schema, canonical));
+        }
+
+        if let Some(subdiff) = self.subdiff {
+            sql.push_str(&format!(
+                "\n\
+                    CREATE FUNCTION {schema}{subdiff}({subtype}, {subtype}) RETURNS double precision IMMUTABLE STRICT PARALLEL SAFE LANGUAGE c /* Rust */ AS 'MODULE_PATHNAME', '{subdiff}_wrapper';\
+                ",
+                schema = schema,
+                subdiff = subdiff,
+                subtype = subtype,
+            ));
+            options.push(format!("\tSUBTYPE_DIFF = {}{}", schema, subdiff));
+        }
+
+        sql.push_str(&format!(
+            "\n\
+                CREATE TYPE {schema}{name} AS RANGE (\n\
+                    {options}\n\
+                );\
+            ",
+            schema = schema,
+            name = self.name,
+            options = options.join(",\n"),
+        ));
+        tracing::trace!(%sql);
+        Ok(sql)
+    }
+}
@@ -1,6 +1,7 @@
 use super::{
-    aggregate::PgAggregateEntity, ControlFile, ExtensionSqlEntity, PgExternEntity,
-    PostgresEnumEntity, PostgresHashEntity, PostgresOrdEntity, PostgresTypeEntity, SchemaEntity,
+    aggregate::PgAggregateEntity, ControlFile, ExtensionSqlEntity, PgAccessMethodEntity, PgExternEntity,
+    PgForeignDataWrapperEntity,
+    PostgresEnumEntity, PostgresHashEntity, PostgresOrdEntity, PostgresRangeEntity, PostgresTypeEntity, SchemaEntity,
     SqlGraphIdentifier, ToSql,
 };
 
@@ -14,9 +15,12 @@ pub enum SqlGraphEntity {
     Type(PostgresTypeEntity),
     BuiltinType(String),
     Enum(PostgresEnumEntity),
+    Range(PostgresRangeEntity),
     Ord(PostgresOrdEntity),
     Hash(PostgresHashEntity),
     Aggregate(PgAggregateEntity),
+    AccessMethod(PgAccessMethodEntity),
+    ForeignDataWrapper(PgForeignDataWrapperEntity),
 }
 
 impl SqlGraphEntity {
@@ -45,9 +49,12 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Type(item) => item.dot_identifier(),
             SqlGraphEntity::BuiltinType(item) => format!("preexisting type {}", item),
             SqlGraphEntity::Enum(item) => item.dot_identifier(),
+            SqlGraphEntity::Range(item) => item.dot_identifier(),
             SqlGraphEntity::Ord(item) => item.dot_identifier(),
             SqlGraphEntity::Hash(item) => item.dot_identifier(),
             SqlGraphEntity::Aggregate(item) => item.dot_identifier(),
+            SqlGraphEntity::AccessMethod(item) => item.dot_identifier(),
+            SqlGraphEntity::ForeignDataWrapper(item) => item.dot_identifier(),
             SqlGraphEntity::ExtensionRoot(item) => item.dot_identifier(),
         }
     }
@@ -60,9 +67,12 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Type(item) => item.rust_identifier(),
             SqlGraphEntity::BuiltinType(item) => item.to_string(),
             SqlGraphEntity::Enum(item) => item.rust_identifier(),
+            SqlGraphEntity::Range(item) => item.rust_identifier(),
             SqlGraphEntity::Ord(item) => item.rust_identifier(),
             SqlGraphEntity::Hash(item) => item.rust_identifier(),
             SqlGraphEntity::Aggregate(item) => item.rust_identifier(),
+            SqlGraphEntity::AccessMethod(item) => item.rust_identifier(),
+            SqlGraphEntity::ForeignDataWrapper(item) => item.rust_identifier(),
             SqlGraphEntity::ExtensionRoot(item) => item.rust_identifier(),
         }
     }
@@ -75,9 +85,12 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Type(item) => item.file(),
             SqlGraphEntity::BuiltinType(_item) => None,
             SqlGraphEntity::Enum(item) => item.file(),
+            SqlGraphEntity::Range(item) => item.file(),
             SqlGraphEntity::Ord(item) => item.file(),
             SqlGraphEntity::Hash(item) => item.file(),
             SqlGraphEntity::Aggregate(item) => item.file(),
+            SqlGraphEntity::AccessMethod(item) => item.file(),
+            SqlGraphEntity::ForeignDataWrapper(item) => item.file(),
             SqlGraphEntity::ExtensionRoot(item) => item.file(),
         }
     }
@@ -90,9 +103,12 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Type(item) => item.line(),
             SqlGraphEntity::BuiltinType(_item) => None,
             SqlGraphEntity::Enum(item) => item.line(),
+            SqlGraphEntity::Range(item) => item.line(),
             SqlGraphEntity::Ord(item) => item.line(),
             SqlGraphEntity::Hash(item) => item.line(),
             SqlGraphEntity::Aggregate(item) => item.line(),
+            SqlGraphEntity::AccessMethod(item) => item.line(),
+            SqlGraphEntity::ForeignDataWrapper(item) => item.line(),
             SqlGraphEntity::ExtensionRoot(item) => item.line(),
         }
     }
@@ -145,6 +161,10 @@ impl ToSql for SqlGraphEntity {
                 .to_sql_config
                 .to_sql(self, context)
                 .unwrap_or_else(|| item.to_sql(context)),
+            SqlGraphEntity::Range(item) => item
+                .to_sql_config
+                .to_sql(self, context)
+                .unwrap_or_else(|| item.to_sql(context)),
             SqlGraphEntity::Ord(item) => item
                 .to_sql_config
                 .to_sql(self, context)
@@ -157,6 +177,14 @@ impl ToSql for SqlGraphEntity {
                 .to_sql_config
                 .to_sql(self, context)
                 .unwrap_or_else(|| item.to_sql(context)),
+            SqlGraphEntity::AccessMethod(item) => item
+                .to_sql_config
+                .to_sql(self, context)
+                .unwrap_or_else(|| item.to_sql(context)),
+            SqlGraphEntity::ForeignDataWrapper(item) => item
+                .to_sql_config
+                .to_sql(self, context)
+                .unwrap_or_else(|| item.to_sql(context)),
             SqlGraphEntity::ExtensionRoot(item) => item.to_sql(context),
         }
     }
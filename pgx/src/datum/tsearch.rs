@@ -0,0 +1,698 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Postgres' full text search types: `tsvector` and `tsquery`
+//!
+//! Neither type's layout is in the generated bindings (they're bitfield-heavy structs from
+//! "tsearch/ts_type.h"), so they're decoded and encoded here byte-by-byte.
+
+use crate::datum::array::MAX_ALLOC_SIZE;
+use crate::{pg_sys, varlena, FromDatum, IntoDatum};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+
+/// from "tsearch/ts_type.h"
+const MAXSTRLEN: usize = (1 << 11) - 1;
+const MAXSTRPOS: usize = (1 << 20) - 1;
+const MAXENTRYPOS: u16 = 1 << 14;
+const MAXNUMPOS: usize = 256;
+const QI_VAL: i8 = 1;
+const QI_OPR: i8 = 2;
+const QI_VALSTOP: i8 = 3;
+const OP_NOT: i8 = 1;
+const OP_AND: i8 = 2;
+const OP_OR: i8 = 3;
+const OP_PHRASE: i8 = 4;
+
+/// `sizeof(QueryItem)`, the union of `QueryOperand` and `QueryOperator`
+const QUERY_ITEM_SIZE: usize = 12;
+
+extern "C" {
+    /// from "utils/pg_crc.h", the table behind `COMP_LEGACY_CRC32`
+    static pg_crc32_table: [u32; 256];
+}
+
+/// The weight of a lexeme position, from `A` (the highest) down to `D` (the default)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TsWeight {
+    D = 0,
+    C = 1,
+    B = 2,
+    A = 3,
+}
+
+/// Where a lexeme appears in a document, and with what weight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TsPosition {
+    pub position: u16,
+    pub weight: TsWeight,
+}
+
+/// One normalized word of a [`TsVector`], and its positions, if any
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TsLexeme {
+    word: String,
+    positions: Vec<TsPosition>,
+}
+
+/// A `tsvector`: a sorted list of distinct lexemes, each with its sorted list of positions
+///
+/// Build one with [`TsVectorBuilder`], which normalizes its input the same way Postgres'
+/// `tsvectorin` does.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TsVector {
+    lexemes: Vec<TsLexeme>,
+}
+
+/// Collects lexemes and positions, in any order, into a [`TsVector`]
+#[derive(Debug, Clone, Default)]
+pub struct TsVectorBuilder {
+    lexemes: BTreeMap<String, Vec<TsPosition>>,
+}
+
+/// A `tsquery`: a tree of lexemes and operators.  An empty query, such as one that only
+/// contained stopwords, has no tree.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TsQuery {
+    root: Option<TsQueryNode>,
+}
+
+/// A node of a [`TsQuery`]'s operator tree
+///
+/// Build trees by starting from [`TsQueryNode::lexeme`] or a [`TsQueryOperand`], and combining with [`TsQueryNode::and`],
+/// [`TsQueryNode::or`], [`TsQueryNode::negate`], and [`TsQueryNode::followed_by`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TsQueryNode {
+    Operand(TsQueryOperand),
+
+    /// `!node`
+    Not(Box<TsQueryNode>),
+
+    /// `left & right`
+    And(Box<TsQueryNode>, Box<TsQueryNode>),
+
+    /// `left | right`
+    Or(Box<TsQueryNode>, Box<TsQueryNode>),
+
+    /// `left <distance> right`, where `<->` is a distance of 1
+    Phrase(Box<TsQueryNode>, Box<TsQueryNode>, u16),
+}
+
+/// A lexeme in a [`TsQuery`], like `'word':*AB`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TsQueryOperand {
+    /// The lexeme to match
+    pub word: String,
+
+    /// Only match positions with one of these weights.  Empty matches any weight.
+    pub weights: Vec<TsWeight>,
+
+    /// Match any lexeme that starts with `word`
+    pub prefix: bool,
+}
+
+impl TsWeight {
+    fn from_bits(bits: u16) -> Self {
+        match bits & 0x3 {
+            0 => TsWeight::D,
+            1 => TsWeight::C,
+            2 => TsWeight::B,
+            _ => TsWeight::A,
+        }
+    }
+}
+
+impl TsLexeme {
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    /// The positions of this lexeme, sorted.  Lexemes of a `strip()`ped `tsvector`, or added
+    /// without positions, have none.
+    pub fn positions(&self) -> &[TsPosition] {
+        &self.positions
+    }
+}
+
+impl TsVector {
+    pub fn builder() -> TsVectorBuilder {
+        TsVectorBuilder::default()
+    }
+
+    /// The lexemes, sorted the way Postgres sorts them
+    pub fn lexemes(&self) -> &[TsLexeme] {
+        &self.lexemes
+    }
+
+    /// The number of lexemes, like Postgres' `length(tsvector)`
+    pub fn len(&self) -> usize {
+        self.lexemes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lexemes.is_empty()
+    }
+
+    pub fn get(&self, word: &str) -> Option<&TsLexeme> {
+        self.lexemes
+            .binary_search_by(|lexeme| lexeme.word.as_bytes().cmp(word.as_bytes()))
+            .ok()
+            .map(|idx| &self.lexemes[idx])
+    }
+}
+
+impl TsVectorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a lexeme, without adding a position for it
+    pub fn add_lexeme(&mut self, word: &str) {
+        check_word_len(word, "word is too long");
+        self.lexemes.entry(word.to_string()).or_default();
+    }
+
+    /// Add a lexeme at a position.  Positions start at 1, and like Postgres, positions past
+    /// 16383 are clamped to it.
+    pub fn add_position(&mut self, word: &str, position: u16, weight: TsWeight) {
+        if position == 0 {
+            panic!("wrong position info in tsvector: positions start at 1");
+        }
+        check_word_len(word, "word is too long");
+        self.lexemes
+            .entry(word.to_string())
+            .or_default()
+            .push(TsPosition {
+                position: position.min(MAXENTRYPOS - 1),
+                weight,
+            });
+    }
+
+    pub fn build(self) -> TsVector {
+        let lexemes = self
+            .lexemes
+            .into_iter()
+            .map(|(word, positions)| TsLexeme {
+                word,
+                positions: unique_positions(positions),
+            })
+            .collect();
+        TsVector { lexemes }
+    }
+}
+
+/// Port of Postgres' `uniquePos()`: sort by position, keep the highest weight of any duplicate,
+/// and keep no more than `MAXNUMPOS` positions
+fn unique_positions(mut positions: Vec<TsPosition>) -> Vec<TsPosition> {
+    positions.sort_by_key(|pos| pos.position);
+    let mut unique: Vec<TsPosition> = Vec::with_capacity(positions.len());
+    for pos in positions {
+        match unique.last_mut() {
+            Some(last) if last.position == pos.position => {
+                last.weight = last.weight.max(pos.weight)
+            }
+            _ => unique.push(pos),
+        }
+    }
+    unique.truncate(MAXNUMPOS);
+    unique
+}
+
+impl TsQuery {
+    /// A query with no lexemes, which matches nothing
+    pub fn empty() -> Self {
+        TsQuery::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn root(&self) -> Option<&TsQueryNode> {
+        self.root.as_ref()
+    }
+
+    pub fn into_root(self) -> Option<TsQueryNode> {
+        self.root
+    }
+}
+
+impl From<TsQueryNode> for TsQuery {
+    fn from(node: TsQueryNode) -> Self {
+        TsQuery { root: Some(node) }
+    }
+}
+
+impl From<TsQueryOperand> for TsQuery {
+    fn from(operand: TsQueryOperand) -> Self {
+        TsQuery::from(TsQueryNode::from(operand))
+    }
+}
+
+impl TsQueryOperand {
+    pub fn new(word: &str) -> Self {
+        TsQueryOperand {
+            word: word.to_string(),
+            weights: Vec::new(),
+            prefix: false,
+        }
+    }
+
+    /// Match any lexeme that starts with this one, like `'word':*`
+    pub fn prefix(mut self) -> Self {
+        self.prefix = true;
+        self
+    }
+
+    /// Only match positions with one of `weights`, like `'word':AB`
+    pub fn weights(mut self, weights: &[TsWeight]) -> Self {
+        self.weights = weights.to_vec();
+        self.weights.sort_unstable_by(|a, b| b.cmp(a));
+        self.weights.dedup();
+        self
+    }
+}
+
+impl From<TsQueryOperand> for TsQueryNode {
+    fn from(operand: TsQueryOperand) -> Self {
+        TsQueryNode::Operand(operand)
+    }
+}
+
+impl TsQueryNode {
+    /// A plain lexeme, which matches any weight
+    pub fn lexeme(word: &str) -> Self {
+        TsQueryNode::Operand(TsQueryOperand::new(word))
+    }
+
+    /// `self & other`
+    pub fn and(self, other: impl Into<TsQueryNode>) -> Self {
+        TsQueryNode::And(Box::new(self), Box::new(other.into()))
+    }
+
+    /// `self | other`
+    pub fn or(self, other: impl Into<TsQueryNode>) -> Self {
+        TsQueryNode::Or(Box::new(self), Box::new(other.into()))
+    }
+
+    /// `!self`
+    pub fn negate(self) -> Self {
+        TsQueryNode::Not(Box::new(self))
+    }
+
+    /// `self <distance> other`.  A `distance` of 1 is `self <-> other`.
+    pub fn followed_by(self, other: impl Into<TsQueryNode>, distance: u16) -> Self {
+        if distance > MAXENTRYPOS {
+            panic!(
+                "distance in phrase operator should not be greater than {}",
+                MAXENTRYPOS
+            );
+        }
+        TsQueryNode::Phrase(Box::new(self), Box::new(other.into()), distance)
+    }
+}
+
+impl FromDatum for TsVector {
+    const NEEDS_TYPID: bool = false;
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("tsvector datum declared not null, but datum is zero")
+        } else {
+            let detoasted = pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena);
+            let tsvector = decode_tsvector(varlena::varlena_to_byte_slice(detoasted));
+
+            // don't leak the detoasted copy, if Postgres had to make one
+            if detoasted as usize != datum {
+                pg_sys::pfree(detoasted as *mut std::os::raw::c_void);
+            }
+
+            Some(tsvector)
+        }
+    }
+}
+
+impl IntoDatum for TsVector {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(into_varlena(&encode_tsvector(&self)))
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::TSVECTOROID
+    }
+}
+
+impl FromDatum for TsQuery {
+    const NEEDS_TYPID: bool = false;
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("tsquery datum declared not null, but datum is zero")
+        } else {
+            let detoasted = pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena);
+            let tsquery = decode_tsquery(varlena::varlena_to_byte_slice(detoasted));
+
+            // don't leak the detoasted copy, if Postgres had to make one
+            if detoasted as usize != datum {
+                pg_sys::pfree(detoasted as *mut std::os::raw::c_void);
+            }
+
+            Some(tsquery)
+        }
+    }
+}
+
+impl IntoDatum for TsQuery {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(into_varlena(&encode_tsquery(&self)))
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::TSQUERYOID
+    }
+}
+
+fn check_word_len(word: &str, message: &str) {
+    if word.len() > MAXSTRLEN {
+        panic!(
+            "{} ({} bytes, max {} bytes)",
+            message,
+            word.len(),
+            MAXSTRLEN
+        );
+    }
+}
+
+/// Copy `data`, the bytes following a varlena header, into a new palloc'd varlena
+fn into_varlena(data: &[u8]) -> pg_sys::Datum {
+    let size = pg_sys::VARHDRSZ + data.len();
+    if size > MAX_ALLOC_SIZE {
+        panic!("value is too large ({} bytes)", size);
+    }
+
+    unsafe {
+        let varlena = pg_sys::palloc(size) as *mut pg_sys::varlena;
+        varlena::set_varsize(varlena, size as i32);
+        std::ptr::copy_nonoverlapping(
+            data.as_ptr(),
+            (varlena as *mut u8).add(pg_sys::VARHDRSZ),
+            data.len(),
+        );
+        varlena as pg_sys::Datum
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_ne_bytes(bytes[offset..offset + 2].try_into().unwrap())
+}
+
+fn read_i32(bytes: &[u8], offset: usize) -> i32 {
+    i32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn lexeme_string(bytes: &[u8]) -> String {
+    String::from_utf8(bytes.to_vec()).expect("lexeme is not valid UTF-8")
+}
+
+/// `WordEntry` is `uint32 haspos:1, len:11, pos:20`.  Returns `(haspos, len, pos)`.
+#[cfg(target_endian = "little")]
+fn unpack_word_entry(entry: u32) -> (bool, usize, usize) {
+    (
+        entry & 0x1 != 0,
+        ((entry >> 1) & 0x7ff) as usize,
+        (entry >> 12) as usize,
+    )
+}
+
+#[cfg(target_endian = "big")]
+fn unpack_word_entry(entry: u32) -> (bool, usize, usize) {
+    (
+        entry >> 31 != 0,
+        ((entry >> 20) & 0x7ff) as usize,
+        (entry & 0xfffff) as usize,
+    )
+}
+
+#[cfg(target_endian = "little")]
+fn pack_word_entry(haspos: bool, len: usize, pos: usize) -> u32 {
+    haspos as u32 | (len as u32) << 1 | (pos as u32) << 12
+}
+
+#[cfg(target_endian = "big")]
+fn pack_word_entry(haspos: bool, len: usize, pos: usize) -> u32 {
+    (haspos as u32) << 31 | (len as u32) << 20 | pos as u32
+}
+
+/// `QueryOperand` ends with `uint32 length:12, distance:20`.  Returns `(length, distance)`.
+#[cfg(target_endian = "little")]
+fn unpack_operand_location(bits: u32) -> (usize, usize) {
+    ((bits & 0xfff) as usize, (bits >> 12) as usize)
+}
+
+#[cfg(target_endian = "big")]
+fn unpack_operand_location(bits: u32) -> (usize, usize) {
+    ((bits >> 20) as usize, (bits & 0xfffff) as usize)
+}
+
+#[cfg(target_endian = "little")]
+fn pack_operand_location(length: usize, distance: usize) -> u32 {
+    length as u32 | (distance as u32) << 12
+}
+
+#[cfg(target_endian = "big")]
+fn pack_operand_location(length: usize, distance: usize) -> u32 {
+    (length as u32) << 20 | distance as u32
+}
+
+/// `SHORTALIGN()`
+fn short_align(offset: usize) -> usize {
+    (offset + 1) & !1
+}
+
+/// Decode a `TSVectorData`, without its varlena header:
+///
+/// - `int32 size`, the number of lexemes
+/// - `WordEntry entries[size]`
+/// - the lexemes' strings, each followed by its positions (if it has any) at the next even
+///   offset: `uint16 npos` and then `WordEntryPos pos[npos]`
+fn decode_tsvector(data: &[u8]) -> TsVector {
+    let size = read_i32(data, 0) as usize;
+    let entries_start = std::mem::size_of::<i32>();
+    let strings = &data[entries_start + size * 4..];
+
+    let lexemes = (0..size)
+        .map(|idx| {
+            let (haspos, len, pos) = unpack_word_entry(read_u32(data, entries_start + idx * 4));
+            let word = lexeme_string(&strings[pos..pos + len]);
+            let positions = if haspos {
+                let npos_offset = short_align(pos + len);
+                let npos = read_u16(strings, npos_offset) as usize;
+                (0..npos)
+                    .map(|i| {
+                        let wep = read_u16(strings, npos_offset + 2 + i * 2);
+                        TsPosition {
+                            position: wep & 0x3fff,
+                            weight: TsWeight::from_bits(wep >> 14),
+                        }
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            TsLexeme { word, positions }
+        })
+        .collect();
+
+    TsVector { lexemes }
+}
+
+fn encode_tsvector(tsvector: &TsVector) -> Vec<u8> {
+    let mut entries = Vec::with_capacity(tsvector.lexemes.len() * 4);
+    let mut strings = Vec::new();
+
+    for lexeme in &tsvector.lexemes {
+        check_word_len(&lexeme.word, "word is too long");
+        let pos = strings.len();
+        if pos > MAXSTRPOS {
+            panic!(
+                "string is too long for tsvector ({} bytes, max {} bytes)",
+                pos, MAXSTRPOS
+            );
+        }
+        strings.extend_from_slice(lexeme.word.as_bytes());
+
+        let haspos = !lexeme.positions.is_empty();
+        if haspos {
+            strings.resize(short_align(strings.len()), 0);
+            strings.extend_from_slice(&(lexeme.positions.len() as u16).to_ne_bytes());
+            for position in &lexeme.positions {
+                let wep = (position.weight as u16) << 14 | position.position & 0x3fff;
+                strings.extend_from_slice(&wep.to_ne_bytes());
+            }
+        }
+
+        entries.extend_from_slice(&pack_word_entry(haspos, lexeme.word.len(), pos).to_ne_bytes());
+    }
+
+    let mut data = Vec::with_capacity(4 + entries.len() + strings.len());
+    data.extend_from_slice(&(tsvector.lexemes.len() as i32).to_ne_bytes());
+    data.extend_from_slice(&entries);
+    data.extend_from_slice(&strings);
+    data
+}
+
+/// Decode a `TSQueryData`, without its varlena header:
+///
+/// - `int32 size`, the number of items
+/// - `QueryItem items[size]`, the tree in prefix order.  An operator's right operand is the
+///   item after it, and its left operand is `left` items after it.
+/// - the operands' strings, each followed by a `\0`
+fn decode_tsquery(data: &[u8]) -> TsQuery {
+    let size = read_i32(data, 0) as usize;
+    if size == 0 {
+        return TsQuery::empty();
+    }
+
+    let items_start = std::mem::size_of::<i32>();
+    let items = &data[items_start..items_start + size * QUERY_ITEM_SIZE];
+    let operands = &data[items_start + size * QUERY_ITEM_SIZE..];
+    TsQuery {
+        root: Some(decode_query_item(items, operands, 0)),
+    }
+}
+
+fn decode_query_item(items: &[u8], operands: &[u8], idx: usize) -> TsQueryNode {
+    let item = &items[idx * QUERY_ITEM_SIZE..(idx + 1) * QUERY_ITEM_SIZE];
+    match item[0] as i8 {
+        QI_VAL => {
+            // QueryOperand: int8 type, uint8 weight, bool prefix, int32 valcrc, uint32 length:12, distance:20
+            let weight_mask = item[1];
+            let (length, distance) = unpack_operand_location(read_u32(item, 8));
+            let weights = [TsWeight::A, TsWeight::B, TsWeight::C, TsWeight::D]
+                .iter()
+                .copied()
+                .filter(|weight| weight_mask & (1 << *weight as u8) != 0)
+                .collect();
+            TsQueryNode::Operand(TsQueryOperand {
+                word: lexeme_string(&operands[distance..distance + length]),
+                weights,
+                prefix: item[2] != 0,
+            })
+        }
+        QI_OPR => {
+            // QueryOperator: int8 type, int8 oper, int16 distance, uint32 left
+            let oper = item[1] as i8;
+            let right = || Box::new(decode_query_item(items, operands, idx + 1));
+            let left = || {
+                let left = read_u32(item, 4) as usize;
+                Box::new(decode_query_item(items, operands, idx + left))
+            };
+            match oper {
+                OP_NOT => TsQueryNode::Not(right()),
+                OP_AND => TsQueryNode::And(left(), right()),
+                OP_OR => TsQueryNode::Or(left(), right()),
+                OP_PHRASE => TsQueryNode::Phrase(left(), right(), read_u16(item, 2)),
+                other => panic!("unrecognized tsquery operator: {}", other),
+            }
+        }
+        QI_VALSTOP => panic!("tsquery contains an unresolved stopword"),
+        other => panic!("unrecognized tsquery item type: {}", other),
+    }
+}
+
+fn encode_tsquery(tsquery: &TsQuery) -> Vec<u8> {
+    let mut items = Vec::new();
+    let mut operands = Vec::new();
+    let mut nitems = 0;
+    if let Some(root) = &tsquery.root {
+        encode_query_item(root, &mut items, &mut operands, &mut nitems);
+    }
+
+    let mut data = Vec::with_capacity(4 + items.len() + operands.len());
+    data.extend_from_slice(&(nitems as i32).to_ne_bytes());
+    data.extend_from_slice(&items);
+    data.extend_from_slice(&operands);
+    data
+}
+
+fn encode_query_item(
+    node: &TsQueryNode,
+    items: &mut Vec<u8>,
+    operands: &mut Vec<u8>,
+    nitems: &mut usize,
+) {
+    let idx = *nitems;
+    let start = items.len();
+    items.resize(start + QUERY_ITEM_SIZE, 0);
+    *nitems += 1;
+
+    let (oper, distance, left, right) = match node {
+        TsQueryNode::Operand(operand) => {
+            check_word_len(&operand.word, "word is too long in tsquery");
+            let distance = operands.len();
+            if distance > MAXSTRPOS {
+                panic!("value is too big in tsquery");
+            }
+            operands.extend_from_slice(operand.word.as_bytes());
+            operands.push(0);
+
+            let weight_mask = operand
+                .weights
+                .iter()
+                .fold(0u8, |mask, weight| mask | 1 << *weight as u8);
+            let item = &mut items[start..];
+            item[0] = QI_VAL as u8;
+            item[1] = weight_mask;
+            item[2] = operand.prefix as u8;
+            item[4..8].copy_from_slice(&legacy_crc32(operand.word.as_bytes()).to_ne_bytes());
+            item[8..12].copy_from_slice(
+                &pack_operand_location(operand.word.len(), distance).to_ne_bytes(),
+            );
+            return;
+        }
+        TsQueryNode::Not(inner) => (OP_NOT, 0, None, inner),
+        TsQueryNode::And(left, right) => (OP_AND, 0, Some(left), right),
+        TsQueryNode::Or(left, right) => (OP_OR, 0, Some(left), right),
+        TsQueryNode::Phrase(left, right, distance) => (OP_PHRASE, *distance, Some(left), right),
+    };
+
+    encode_query_item(right, items, operands, nitems);
+    let left_offset = match left {
+        Some(left) => {
+            let left_offset = *nitems - idx;
+            encode_query_item(left, items, operands, nitems);
+            left_offset
+        }
+        // like Postgres' findoprnd(), which always points `!` at the next item
+        None => 1,
+    };
+
+    let item = &mut items[start..start + QUERY_ITEM_SIZE];
+    item[0] = QI_OPR as u8;
+    item[1] = oper as u8;
+    item[2..4].copy_from_slice(&(distance as i16).to_ne_bytes());
+    item[4..8].copy_from_slice(&(left_offset as u32).to_ne_bytes());
+}
+
+/// `INIT_LEGACY_CRC32`, `COMP_LEGACY_CRC32`, and `FIN_LEGACY_CRC32`, which is how Postgres
+/// computes a `QueryOperand`'s `valcrc`
+fn legacy_crc32(data: &[u8]) -> i32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        let index = ((crc >> 24) as u8 ^ byte) as usize;
+        crc = unsafe { pg_crc32_table[index] } ^ (crc << 8);
+    }
+    (crc ^ 0xFFFF_FFFF) as i32
+}
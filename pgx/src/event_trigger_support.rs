@@ -0,0 +1,204 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Helper functions and types for working with custom Rust event trigger functions
+
+use crate::{is_a, pg_sys, PgBox, Spi};
+use std::ffi::CStr;
+
+#[inline]
+pub unsafe fn called_as_event_trigger(fcinfo: pg_sys::FunctionCallInfo) -> bool {
+    let fcinfo = fcinfo.as_ref().expect("fcinfo was null");
+    !fcinfo.context.is_null() && is_a(fcinfo.context, pg_sys::NodeTag_T_EventTriggerData)
+}
+
+/// The event that fired an event trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgEventTriggerEvent {
+    DdlCommandStart,
+    DdlCommandEnd,
+    SqlDrop,
+    TableRewrite,
+}
+
+/// An object created or altered by a DDL command, as reported by
+/// `pg_event_trigger_ddl_commands()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DdlCommand {
+    /// The oid of the catalog the object is in
+    pub class_id: pg_sys::Oid,
+    pub object_id: pg_sys::Oid,
+    /// The column number, for a column, or zero
+    pub object_sub_id: i32,
+    pub command_tag: String,
+    pub object_type: String,
+    pub schema_name: Option<String>,
+    pub object_identity: String,
+    /// If the command is part of an extension script
+    pub in_extension: bool,
+}
+
+/// An object dropped by a DDL command, as reported by `pg_event_trigger_dropped_objects()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedObject {
+    /// The oid of the catalog the object was in
+    pub class_id: pg_sys::Oid,
+    pub object_id: pg_sys::Oid,
+    /// The column number, for a column, or zero
+    pub object_sub_id: i32,
+    /// If the object was one of the root objects of the deletion
+    pub original: bool,
+    /// If the object was reached through a normal dependency
+    pub normal: bool,
+    pub is_temporary: bool,
+    pub object_type: String,
+    pub schema_name: Option<String>,
+    pub object_name: Option<String>,
+    pub object_identity: String,
+    /// The object's address, as understood by `pg_get_object_address()`
+    pub address_names: Vec<String>,
+    pub address_args: Vec<String>,
+}
+
+/// A safe wrapper around the `pg_sys::EventTriggerData` Postgres passes to an event trigger
+/// function
+///
+/// This is what a `#[pg_event_trigger]` function receives:
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_event_trigger(event = "sql_drop")]
+/// fn log_drops(trigger: &PgEventTrigger) {
+///     for dropped in trigger.dropped_objects() {
+///         log!("{}: dropped {}", trigger.tag(), dropped.object_identity);
+///     }
+/// }
+/// ```
+pub struct PgEventTrigger {
+    trigdata: PgBox<pg_sys::EventTriggerData>,
+}
+
+impl PgEventTrigger {
+    /// Wrap the `pg_sys::EventTriggerData` Postgres passed to an event trigger function as
+    /// `fcinfo->context`
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as we cannot validate that `fcinfo` is valid
+    ///
+    /// ## Panics
+    ///
+    /// If the function wasn't called by the event trigger manager
+    pub unsafe fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        if !called_as_event_trigger(fcinfo) {
+            panic!("not fired by event trigger manager");
+        }
+
+        PgEventTrigger {
+            trigdata: PgBox::from_pg((*fcinfo).context as *mut pg_sys::EventTriggerData),
+        }
+    }
+
+    pub fn event(&self) -> PgEventTriggerEvent {
+        let event = unsafe { CStr::from_ptr(self.trigdata.event) };
+        match event.to_bytes() {
+            b"ddl_command_start" => PgEventTriggerEvent::DdlCommandStart,
+            b"ddl_command_end" => PgEventTriggerEvent::DdlCommandEnd,
+            b"sql_drop" => PgEventTriggerEvent::SqlDrop,
+            b"table_rewrite" => PgEventTriggerEvent::TableRewrite,
+            _ => panic!("unrecognized event trigger event: {:?}", event),
+        }
+    }
+
+    /// The command tag of the command that fired the trigger, such as `CREATE TABLE`
+    pub fn tag(&self) -> &str {
+        command_tag_name(&self.trigdata)
+            .to_str()
+            .expect("command tag is not valid UTF8")
+    }
+
+    /// The parse tree of the command that fired the trigger
+    pub fn parse_tree(&self) -> Option<PgBox<pg_sys::Node>> {
+        if self.trigdata.parsetree.is_null() {
+            None
+        } else {
+            Some(unsafe { PgBox::from_pg(self.trigdata.parsetree) })
+        }
+    }
+
+    /// The objects created or altered by the command, from `pg_event_trigger_ddl_commands()`
+    ///
+    /// Postgres only allows this in a `ddl_command_end` trigger, and raises an ERROR otherwise.
+    pub fn ddl_commands(&self) -> impl Iterator<Item = DdlCommand> {
+        let mut commands = Vec::new();
+        Spi::connect(|client| {
+            let rows = client.select(
+                "SELECT classid, objid, objsubid, command_tag, object_type, schema_name, \
+                        object_identity, in_extension \
+                   FROM pg_catalog.pg_event_trigger_ddl_commands()",
+                None,
+                None,
+            );
+            commands.extend(rows.map(|row| DdlCommand {
+                class_id: row["classid"].value().unwrap(),
+                object_id: row["objid"].value().unwrap(),
+                object_sub_id: row["objsubid"].value().unwrap(),
+                command_tag: row["command_tag"].value().unwrap(),
+                object_type: row["object_type"].value().unwrap(),
+                schema_name: row["schema_name"].value(),
+                object_identity: row["object_identity"].value().unwrap_or_default(),
+                in_extension: row["in_extension"].value().unwrap(),
+            }));
+            Ok(Some(()))
+        });
+        commands.into_iter()
+    }
+
+    /// The objects dropped by the command, from `pg_event_trigger_dropped_objects()`
+    ///
+    /// Postgres only allows this in a `sql_drop` trigger, and raises an ERROR otherwise.
+    pub fn dropped_objects(&self) -> impl Iterator<Item = DroppedObject> {
+        let mut dropped = Vec::new();
+        Spi::connect(|client| {
+            let rows = client.select(
+                "SELECT classid, objid, objsubid, original, normal, is_temporary, object_type, \
+                        schema_name, object_name, object_identity, address_names, address_args \
+                   FROM pg_catalog.pg_event_trigger_dropped_objects()",
+                None,
+                None,
+            );
+            dropped.extend(rows.map(|row| DroppedObject {
+                class_id: row["classid"].value().unwrap(),
+                object_id: row["objid"].value().unwrap(),
+                object_sub_id: row["objsubid"].value().unwrap(),
+                original: row["original"].value().unwrap(),
+                normal: row["normal"].value().unwrap(),
+                is_temporary: row["is_temporary"].value().unwrap(),
+                object_type: row["object_type"].value().unwrap(),
+                schema_name: row["schema_name"].value(),
+                object_name: row["object_name"].value(),
+                object_identity: row["object_identity"].value().unwrap_or_default(),
+                address_names: row["address_names"].value().unwrap_or_default(),
+                address_args: row["address_args"].value().unwrap_or_default(),
+            }));
+            Ok(Some(()))
+        });
+        dropped.into_iter()
+    }
+
+    /// The `pg_sys::EventTriggerData` we're wrapping
+    pub fn trigger_data(&self) -> &pg_sys::EventTriggerData {
+        &self.trigdata
+    }
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12"))]
+fn command_tag_name(trigdata: &pg_sys::EventTriggerData) -> &CStr {
+    unsafe { CStr::from_ptr(trigdata.tag) }
+}
+
+#[cfg(any(feature = "pg13", feature = "pg14"))]
+fn command_tag_name(trigdata: &pg_sys::EventTriggerData) -> &CStr {
+    unsafe { CStr::from_ptr(pg_sys::GetCommandTagName(trigdata.tag)) }
+}
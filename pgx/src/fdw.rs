@@ -0,0 +1,683 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+/*!
+
+[Foreign Data Wrapper](https://www.postgresql.org/docs/current/fdwhandler.html) support.
+
+Foreign data wrappers are created by implementing [`ForeignDataWrapper`] for a type and decorating
+the implementation with [`#[pg_foreign_data_wrapper]`](pgx_macros::pg_foreign_data_wrapper).
+
+The macro generates the `fdw_handler` function Postgres uses to discover the wrapper's callbacks,
+a validator function for its `OPTIONS`, and the
+[`CREATE FOREIGN DATA WRAPPER`](https://www.postgresql.org/docs/current/sql-createforeigndatawrapper.html)
+statement that registers them.
+
+# Example
+
+```rust,no_run
+use pgx::*;
+
+pub struct CountingFdw;
+
+#[pg_foreign_data_wrapper]
+impl ForeignDataWrapper for CountingFdw {
+    type ScanState = (i32, i32);
+    type Row = (i32, String);
+
+    fn begin_scan(_relation: &PgRelation, options: FdwOptions) -> Self::ScanState {
+        let limit = options
+            .get("rows")
+            .map(|rows| rows.parse().expect("rows must be an integer"))
+            .unwrap_or(10);
+        (0, limit)
+    }
+
+    fn iterate_scan(state: &mut Self::ScanState) -> Option<Self::Row> {
+        let (current, limit) = state;
+        if *current >= *limit {
+            return None;
+        }
+        *current += 1;
+        Some((*current, format!("row {}", current)))
+    }
+}
+```
+
+This creates SQL like so:
+
+```sql
+-- src/lib.rs:5
+-- fdw::CountingFdw
+CREATE FOREIGN DATA WRAPPER counting_fdw HANDLER counting_fdw_handler VALIDATOR counting_fdw_validator;
+```
+
+Which can be used like so:
+
+```sql
+CREATE SERVER counting_server FOREIGN DATA WRAPPER counting_fdw;
+CREATE FOREIGN TABLE counting (id int4, name text) SERVER counting_server OPTIONS (rows '3');
+SELECT * FROM counting;
+```
+
+Any of the optional functions (`validate`, `get_rel_size`, `get_paths`, `get_plan`, `rescan`,
+`end_scan`, `insert`, `update`, `delete`) can be left out, `#[pg_foreign_data_wrapper]` will
+create a stub and, where Postgres allows it, leave the corresponding `FdwRoutine` callback unset.
+*/
+use crate::{pg_sys, IntoDatum, PgBox, PgList, PgMemoryContexts, PgRelation};
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::{c_int, c_void};
+
+/// The `OPTIONS` of a foreign data wrapper, server or table.
+pub type FdwOptions = HashMap<String, String>;
+
+/// Converts a Rust tuple into the column values of a foreign table row.
+///
+/// Implemented for tuples of up to 16 [`IntoDatum`] values.  The tuple must have one value per
+/// column of the foreign table.
+pub trait IntoDatumRow {
+    fn into_datum_row(self) -> Vec<Option<pg_sys::Datum>>;
+}
+
+impl IntoDatumRow for Vec<Option<pg_sys::Datum>> {
+    fn into_datum_row(self) -> Vec<Option<pg_sys::Datum>> {
+        self
+    }
+}
+
+macro_rules! impl_into_datum_row {
+    ($($name:ident),+) => {
+        impl<$($name: IntoDatum),+> IntoDatumRow for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn into_datum_row(self) -> Vec<Option<pg_sys::Datum>> {
+                let ($($name,)+) = self;
+                vec![$($name.into_datum()),+]
+            }
+        }
+    };
+}
+
+impl_into_datum_row!(A);
+impl_into_datum_row!(A, B);
+impl_into_datum_row!(A, B, C);
+impl_into_datum_row!(A, B, C, D);
+impl_into_datum_row!(A, B, C, D, E);
+impl_into_datum_row!(A, B, C, D, E, F);
+impl_into_datum_row!(A, B, C, D, E, F, G);
+impl_into_datum_row!(A, B, C, D, E, F, G, H);
+impl_into_datum_row!(A, B, C, D, E, F, G, H, I);
+impl_into_datum_row!(A, B, C, D, E, F, G, H, I, J);
+impl_into_datum_row!(A, B, C, D, E, F, G, H, I, J, K);
+impl_into_datum_row!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_into_datum_row!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_into_datum_row!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_into_datum_row!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_into_datum_row!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+/// A foreign data wrapper.
+///
+/// Implementations should be decorated with
+/// [`#[pg_foreign_data_wrapper]`](pgx_macros::pg_foreign_data_wrapper), which generates the
+/// handler and validator functions and the `CREATE FOREIGN DATA WRAPPER` SQL.
+pub trait ForeignDataWrapper {
+    /// The name of the foreign data wrapper, as used in `CREATE SERVER ... FOREIGN DATA WRAPPER name`.
+    ///
+    /// **Optional:** This const can be skipped, `#[pg_foreign_data_wrapper]` will use the snake cased name of the type.
+    const NAME: &'static str;
+
+    /// Per-scan state, created in [`ForeignDataWrapper::begin_scan`].
+    ///
+    /// The state is dropped when the executor's per-query memory context is deleted.
+    ///
+    /// **Optional:** This type can be skipped, `#[pg_foreign_data_wrapper]` will use `()`.
+    type ScanState;
+
+    /// The rows produced by a scan, typically a tuple with one element per column.
+    type Row: IntoDatumRow;
+
+    /// The column which uniquely identifies a row, passed to [`ForeignDataWrapper::update`] and
+    /// [`ForeignDataWrapper::delete`].  Required for `UPDATE` and `DELETE` support.
+    const ROWID_COLUMN: Option<&'static str> = None;
+
+    /// Validate the `OPTIONS` of a `CREATE` or `ALTER` of a wrapper, server, user mapping or
+    /// foreign table.  `catalog` is the oid of the system catalog the options are for, such as
+    /// `pg_sys::ForeignTableRelationId`.
+    ///
+    /// Raise an error (for example, with `error!()`) to reject the options.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_foreign_data_wrapper]` will create a stub which accepts all options.
+    fn validate(options: FdwOptions, catalog: pg_sys::Oid);
+
+    /// Update `baserel.rows` (and optionally `baserel.reltarget`) with the estimated size of a scan.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_foreign_data_wrapper]` will create a stub
+    /// which keeps Postgres' default estimates.
+    fn get_rel_size(
+        root: &mut pg_sys::PlannerInfo,
+        baserel: &mut pg_sys::RelOptInfo,
+        foreign_table_oid: pg_sys::Oid,
+    );
+
+    /// Add the possible access paths for a scan to `baserel`.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_foreign_data_wrapper]` will create a stub
+    /// which calls [`default_foreign_paths`].
+    fn get_paths(
+        root: &mut pg_sys::PlannerInfo,
+        baserel: &mut pg_sys::RelOptInfo,
+        foreign_table_oid: pg_sys::Oid,
+    );
+
+    /// Create a `ForeignScan` plan node from the selected path.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_foreign_data_wrapper]` will create a stub
+    /// which calls [`default_foreign_plan`].
+    fn get_plan(
+        root: &mut pg_sys::PlannerInfo,
+        baserel: &mut pg_sys::RelOptInfo,
+        foreign_table_oid: pg_sys::Oid,
+        best_path: &mut pg_sys::ForeignPath,
+        tlist: PgList<pg_sys::TargetEntry>,
+        scan_clauses: PgList<pg_sys::RestrictInfo>,
+        outer_plan: *mut pg_sys::Plan,
+    ) -> PgBox<pg_sys::ForeignScan>;
+
+    /// Start a scan of the foreign table `relation`.
+    ///
+    /// `options` are the options of the wrapper, server and foreign table combined, where the
+    /// more specific object wins.
+    fn begin_scan(relation: &PgRelation, options: FdwOptions) -> Self::ScanState;
+
+    /// Fetch the next row of the scan, returning `None` if there are no more.
+    fn iterate_scan(state: &mut Self::ScanState) -> Option<Self::Row>;
+
+    /// Restart the scan from the beginning.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_foreign_data_wrapper]` will create a stub.
+    fn rescan(state: &mut Self::ScanState);
+
+    /// End the scan and release resources.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_foreign_data_wrapper]` will create a stub.
+    fn end_scan(state: &mut Self::ScanState);
+
+    /// Insert a row into the foreign table.  `values` has one entry per column, `None` for SQL `NULL`.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_foreign_data_wrapper]` will create a stub
+    /// and the foreign table will not support `INSERT`.
+    fn insert(relation: &PgRelation, options: &FdwOptions, values: &[Option<pg_sys::Datum>]);
+
+    /// Replace the row identified by `rowid`, the value of the
+    /// [`ForeignDataWrapper::ROWID_COLUMN`], with `values`.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_foreign_data_wrapper]` will create a stub
+    /// and the foreign table will not support `UPDATE`.
+    fn update(
+        relation: &PgRelation,
+        options: &FdwOptions,
+        rowid: Option<pg_sys::Datum>,
+        values: &[Option<pg_sys::Datum>],
+    );
+
+    /// Delete the row identified by `rowid`, the value of the [`ForeignDataWrapper::ROWID_COLUMN`].
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_foreign_data_wrapper]` will create a stub
+    /// and the foreign table will not support `DELETE`.
+    fn delete(relation: &PgRelation, options: &FdwOptions, rowid: Option<pg_sys::Datum>);
+}
+
+/// Convert a `List` of `DefElem`s, as found in the `options` of the foreign data catalogs, into
+/// [`FdwOptions`].
+///
+/// ## Safety
+///
+/// This function is unsafe as we cannot validate that `options` is a `List` of `DefElem`s.
+pub unsafe fn options_from_list(options: *mut pg_sys::List) -> FdwOptions {
+    PgList::<pg_sys::DefElem>::from_pg(options)
+        .iter_ptr()
+        .map(|def| {
+            let name = CStr::from_ptr((*def).defname);
+            let value = CStr::from_ptr(pg_sys::defGetString(def));
+            (
+                name.to_string_lossy().into_owned(),
+                value.to_string_lossy().into_owned(),
+            )
+        })
+        .collect()
+}
+
+/// The combined options of a foreign table, its server, and the server's foreign data wrapper.
+///
+/// When the same option is set on more than one of them, the more specific object wins.
+pub fn foreign_table_options(foreign_table_oid: pg_sys::Oid) -> FdwOptions {
+    unsafe {
+        let table = pg_sys::GetForeignTable(foreign_table_oid);
+        let server = pg_sys::GetForeignServer((*table).serverid);
+        let wrapper = pg_sys::GetForeignDataWrapper((*server).fdwid);
+
+        let mut options = options_from_list((*wrapper).options);
+        options.extend(options_from_list((*server).options));
+        options.extend(options_from_list((*table).options));
+        options
+    }
+}
+
+/// Add a single, unparameterized and unsorted, `ForeignPath` to `baserel`, costed as a full
+/// scan of `baserel.rows` rows.
+pub fn default_foreign_paths(root: &mut pg_sys::PlannerInfo, baserel: &mut pg_sys::RelOptInfo) {
+    let startup_cost = baserel.baserestrictcost.startup;
+    let total_cost = startup_cost + baserel.rows;
+    unsafe {
+        let path = pg_sys::create_foreignscan_path(
+            root,
+            baserel,
+            std::ptr::null_mut(),
+            baserel.rows,
+            startup_cost,
+            total_cost,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        );
+        pg_sys::add_path(baserel, path as *mut pg_sys::Path);
+    }
+}
+
+/// Create a `ForeignScan` which leaves all of `scan_clauses` to be checked by the executor.
+pub fn default_foreign_plan(
+    baserel: &mut pg_sys::RelOptInfo,
+    tlist: PgList<pg_sys::TargetEntry>,
+    scan_clauses: PgList<pg_sys::RestrictInfo>,
+    outer_plan: *mut pg_sys::Plan,
+) -> PgBox<pg_sys::ForeignScan> {
+    unsafe {
+        let scan_clauses = pg_sys::extract_actual_clauses(scan_clauses.into_pg(), false);
+        PgBox::from_pg(pg_sys::make_foreignscan(
+            tlist.into_pg(),
+            scan_clauses,
+            baserel.relid,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            outer_plan,
+        ))
+    }
+}
+
+/// Validate `options`, as given to the validator function `#[pg_foreign_data_wrapper]` generates.
+///
+/// Each option is formatted as `name=value`.
+pub fn fdw_validate<T: ForeignDataWrapper>(options: Vec<String>, catalog: pg_sys::Oid) {
+    let options = options
+        .into_iter()
+        .map(|option| match option.find('=') {
+            Some(idx) => (option[..idx].to_string(), option[idx + 1..].to_string()),
+            None => (option, String::new()),
+        })
+        .collect();
+    T::validate(options, catalog)
+}
+
+/// Create the `pg_sys::FdwRoutine` describing `T`.
+///
+/// This is called by the handler function `#[pg_foreign_data_wrapper]` generates.
+pub fn fdw_routine<T: ForeignDataWrapper>() -> PgBox<pg_sys::FdwRoutine> {
+    let mut routine = PgBox::<pg_sys::FdwRoutine>::alloc_node(pg_sys::NodeTag_T_FdwRoutine);
+
+    routine.GetForeignRelSize = Some(get_foreign_rel_size::<T>);
+    routine.GetForeignPaths = Some(get_foreign_paths::<T>);
+    routine.GetForeignPlan = Some(get_foreign_plan::<T>);
+    routine.BeginForeignScan = Some(begin_foreign_scan::<T>);
+    routine.IterateForeignScan = Some(iterate_foreign_scan::<T>);
+    routine.ReScanForeignScan = Some(rescan_foreign_scan::<T>);
+    routine.EndForeignScan = Some(end_foreign_scan::<T>);
+
+    routine.AddForeignUpdateTargets = Some(add_foreign_update_targets::<T>);
+    routine.BeginForeignModify = Some(begin_foreign_modify::<T>);
+    routine.ExecForeignInsert = Some(exec_foreign_insert::<T>);
+    routine.ExecForeignUpdate = Some(exec_foreign_update::<T>);
+    routine.ExecForeignDelete = Some(exec_foreign_delete::<T>);
+    routine.EndForeignModify = Some(end_foreign_modify);
+
+    routine.into_pg_boxed()
+}
+
+/// The state of an `INSERT`, `UPDATE` or `DELETE`, stored in `ResultRelInfo.ri_FdwState`.
+struct ModifyState {
+    options: FdwOptions,
+    rowid_attno: pg_sys::AttrNumber,
+}
+
+unsafe extern "C" fn get_foreign_rel_size<T: ForeignDataWrapper>(
+    root: *mut pg_sys::PlannerInfo,
+    baserel: *mut pg_sys::RelOptInfo,
+    foreign_table_oid: pg_sys::Oid,
+) {
+    pg_sys::guard::guard(|| T::get_rel_size(&mut *root, &mut *baserel, foreign_table_oid))
+}
+
+unsafe extern "C" fn get_foreign_paths<T: ForeignDataWrapper>(
+    root: *mut pg_sys::PlannerInfo,
+    baserel: *mut pg_sys::RelOptInfo,
+    foreign_table_oid: pg_sys::Oid,
+) {
+    pg_sys::guard::guard(|| T::get_paths(&mut *root, &mut *baserel, foreign_table_oid))
+}
+
+unsafe extern "C" fn get_foreign_plan<T: ForeignDataWrapper>(
+    root: *mut pg_sys::PlannerInfo,
+    baserel: *mut pg_sys::RelOptInfo,
+    foreign_table_oid: pg_sys::Oid,
+    best_path: *mut pg_sys::ForeignPath,
+    tlist: *mut pg_sys::List,
+    scan_clauses: *mut pg_sys::List,
+    outer_plan: *mut pg_sys::Plan,
+) -> *mut pg_sys::ForeignScan {
+    pg_sys::guard::guard(|| {
+        T::get_plan(
+            &mut *root,
+            &mut *baserel,
+            foreign_table_oid,
+            &mut *best_path,
+            PgList::from_pg(tlist),
+            PgList::from_pg(scan_clauses),
+            outer_plan,
+        )
+        .into_pg()
+    })
+}
+
+unsafe extern "C" fn begin_foreign_scan<T: ForeignDataWrapper>(
+    node: *mut pg_sys::ForeignScanState,
+    eflags: c_int,
+) {
+    pg_sys::guard::guard(|| {
+        // nothing may happen outside of the backend for EXPLAIN without ANALYZE
+        if eflags as u32 & pg_sys::EXEC_FLAG_EXPLAIN_ONLY != 0 {
+            return;
+        }
+
+        let relation = PgRelation::from_pg((*node).ss.ss_currentRelation);
+        let options = foreign_table_options(relation.oid());
+        let state = T::begin_scan(&relation, options);
+        (*node).fdw_state =
+            PgMemoryContexts::CurrentMemoryContext.leak_and_drop_on_delete(state) as *mut c_void;
+    })
+}
+
+unsafe extern "C" fn iterate_foreign_scan<T: ForeignDataWrapper>(
+    node: *mut pg_sys::ForeignScanState,
+) -> *mut pg_sys::TupleTableSlot {
+    pg_sys::guard::guard(|| {
+        let slot = (*node).ss.ss_ScanTupleSlot;
+        let state = &mut *((*node).fdw_state as *mut T::ScanState);
+        clear_slot(slot);
+        if let Some(row) = T::iterate_scan(state) {
+            store_row(slot, row.into_datum_row());
+        }
+        slot
+    })
+}
+
+unsafe extern "C" fn rescan_foreign_scan<T: ForeignDataWrapper>(
+    node: *mut pg_sys::ForeignScanState,
+) {
+    pg_sys::guard::guard(|| T::rescan(&mut *((*node).fdw_state as *mut T::ScanState)))
+}
+
+unsafe extern "C" fn end_foreign_scan<T: ForeignDataWrapper>(node: *mut pg_sys::ForeignScanState) {
+    pg_sys::guard::guard(|| {
+        // `begin_foreign_scan` doesn't create a state for EXPLAIN without ANALYZE
+        if !(*node).fdw_state.is_null() {
+            T::end_scan(&mut *((*node).fdw_state as *mut T::ScanState))
+        }
+    })
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+unsafe extern "C" fn add_foreign_update_targets<T: ForeignDataWrapper>(
+    parsetree: *mut pg_sys::Query,
+    _target_rte: *mut pg_sys::RangeTblEntry,
+    target_relation: pg_sys::Relation,
+) {
+    pg_sys::guard::guard(|| {
+        let (var, name) = rowid_var::<T>(
+            (*parsetree).resultRelation as pg_sys::Index,
+            target_relation,
+        );
+        let mut target_list = PgList::<pg_sys::TargetEntry>::from_pg((*parsetree).targetList);
+        let entry = pg_sys::makeTargetEntry(
+            var as *mut pg_sys::Expr,
+            (target_list.len() + 1) as pg_sys::AttrNumber,
+            name,
+            true,
+        );
+        target_list.push(entry);
+        (*parsetree).targetList = target_list.into_pg();
+    })
+}
+
+#[cfg(feature = "pg14")]
+unsafe extern "C" fn add_foreign_update_targets<T: ForeignDataWrapper>(
+    root: *mut pg_sys::PlannerInfo,
+    rtindex: pg_sys::Index,
+    _target_rte: *mut pg_sys::RangeTblEntry,
+    target_relation: pg_sys::Relation,
+) {
+    pg_sys::guard::guard(|| {
+        let (var, name) = rowid_var::<T>(rtindex, target_relation);
+        pg_sys::add_row_identity_var(root, var, rtindex, name);
+    })
+}
+
+/// Make a `Var` for `T::ROWID_COLUMN` of the relation at `rtindex`, and a palloc'd copy of its name.
+unsafe fn rowid_var<T: ForeignDataWrapper>(
+    rtindex: pg_sys::Index,
+    relation: pg_sys::Relation,
+) -> (*mut pg_sys::Var, *mut std::os::raw::c_char) {
+    let relation = PgRelation::from_pg(relation);
+    let rowid_column = T::ROWID_COLUMN.unwrap_or_else(|| {
+        panic!(
+            "the {} foreign data wrapper needs a ROWID_COLUMN to UPDATE or DELETE",
+            T::NAME
+        )
+    });
+
+    let tupdesc = relation.tuple_desc();
+    let (attno, attribute) = tupdesc
+        .iter()
+        .enumerate()
+        .find(|(_, attribute)| attribute.name() == rowid_column)
+        .unwrap_or_else(|| {
+            panic!(
+                "ROWID_COLUMN \"{}\" does not exist in foreign table \"{}\"",
+                rowid_column,
+                relation.name()
+            )
+        });
+
+    let var = pg_sys::makeVar(
+        rtindex,
+        (attno + 1) as pg_sys::AttrNumber,
+        attribute.atttypid,
+        attribute.atttypmod,
+        attribute.attcollation,
+        0,
+    );
+    let name = PgMemoryContexts::CurrentMemoryContext.pstrdup(rowid_column);
+    (var, name)
+}
+
+unsafe extern "C" fn begin_foreign_modify<T: ForeignDataWrapper>(
+    mtstate: *mut pg_sys::ModifyTableState,
+    rinfo: *mut pg_sys::ResultRelInfo,
+    _fdw_private: *mut pg_sys::List,
+    _subplan_index: c_int,
+    eflags: c_int,
+) {
+    pg_sys::guard::guard(|| {
+        if eflags as u32 & pg_sys::EXEC_FLAG_EXPLAIN_ONLY != 0 {
+            return;
+        }
+
+        let relation = PgRelation::from_pg((*rinfo).ri_RelationDesc);
+        let mut rowid_attno = pg_sys::InvalidAttrNumber as pg_sys::AttrNumber;
+        if (*mtstate).operation != pg_sys::CmdType_CMD_INSERT {
+            if let Some(rowid_column) = T::ROWID_COLUMN {
+                #[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+                let subplan = (**(*mtstate).mt_plans.add(_subplan_index as usize)).plan;
+                #[cfg(feature = "pg14")]
+                let subplan = (*(*mtstate).ps.lefttree).plan;
+
+                let rowid_column = std::ffi::CString::new(rowid_column).unwrap();
+                rowid_attno = pg_sys::ExecFindJunkAttributeInTlist(
+                    (*subplan).targetlist,
+                    rowid_column.as_ptr(),
+                );
+            }
+        }
+
+        let state = ModifyState {
+            options: foreign_table_options(relation.oid()),
+            rowid_attno,
+        };
+        (*rinfo).ri_FdwState =
+            PgMemoryContexts::CurrentMemoryContext.leak_and_drop_on_delete(state) as *mut c_void;
+    })
+}
+
+unsafe extern "C" fn exec_foreign_insert<T: ForeignDataWrapper>(
+    _estate: *mut pg_sys::EState,
+    rinfo: *mut pg_sys::ResultRelInfo,
+    slot: *mut pg_sys::TupleTableSlot,
+    _plan_slot: *mut pg_sys::TupleTableSlot,
+) -> *mut pg_sys::TupleTableSlot {
+    pg_sys::guard::guard(|| {
+        let state = &*((*rinfo).ri_FdwState as *const ModifyState);
+        let relation = PgRelation::from_pg((*rinfo).ri_RelationDesc);
+        T::insert(&relation, &state.options, &slot_values(slot));
+        slot
+    })
+}
+
+unsafe extern "C" fn exec_foreign_update<T: ForeignDataWrapper>(
+    _estate: *mut pg_sys::EState,
+    rinfo: *mut pg_sys::ResultRelInfo,
+    slot: *mut pg_sys::TupleTableSlot,
+    plan_slot: *mut pg_sys::TupleTableSlot,
+) -> *mut pg_sys::TupleTableSlot {
+    pg_sys::guard::guard(|| {
+        let state = &*((*rinfo).ri_FdwState as *const ModifyState);
+        let relation = PgRelation::from_pg((*rinfo).ri_RelationDesc);
+        let rowid = slot_value(plan_slot, state.rowid_attno);
+        T::update(&relation, &state.options, rowid, &slot_values(slot));
+        slot
+    })
+}
+
+unsafe extern "C" fn exec_foreign_delete<T: ForeignDataWrapper>(
+    _estate: *mut pg_sys::EState,
+    rinfo: *mut pg_sys::ResultRelInfo,
+    slot: *mut pg_sys::TupleTableSlot,
+    plan_slot: *mut pg_sys::TupleTableSlot,
+) -> *mut pg_sys::TupleTableSlot {
+    pg_sys::guard::guard(|| {
+        let state = &*((*rinfo).ri_FdwState as *const ModifyState);
+        let relation = PgRelation::from_pg((*rinfo).ri_RelationDesc);
+        let rowid = slot_value(plan_slot, state.rowid_attno);
+        T::delete(&relation, &state.options, rowid);
+        slot
+    })
+}
+
+unsafe extern "C" fn end_foreign_modify(
+    _estate: *mut pg_sys::EState,
+    _rinfo: *mut pg_sys::ResultRelInfo,
+) {
+    // the `ModifyState` is dropped along with the per-query memory context
+}
+
+pub(crate) unsafe fn clear_slot(slot: *mut pg_sys::TupleTableSlot) {
+    #[cfg(any(feature = "pg10", feature = "pg11"))]
+    pg_sys::ExecClearTuple(slot);
+
+    #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+    if let Some(clear) = (*(*slot).tts_ops).clear {
+        clear(slot);
+    }
+}
+
+pub(crate) unsafe fn store_row(slot: *mut pg_sys::TupleTableSlot, row: Vec<Option<pg_sys::Datum>>) {
+    let natts = (*(*slot).tts_tupleDescriptor).natts as usize;
+    if row.len() != natts {
+        panic!(
+            "tuple descriptor has {} columns, but the row has {} values",
+            natts,
+            row.len()
+        );
+    }
+
+    let values = std::slice::from_raw_parts_mut((*slot).tts_values, natts);
+    let isnull = std::slice::from_raw_parts_mut((*slot).tts_isnull, natts);
+    for (i, value) in row.into_iter().enumerate() {
+        values[i] = value.unwrap_or(0);
+        isnull[i] = value.is_none();
+    }
+    pg_sys::ExecStoreVirtualTuple(slot);
+}
+
+unsafe fn slot_values(slot: *mut pg_sys::TupleTableSlot) -> Vec<Option<pg_sys::Datum>> {
+    let natts = (*(*slot).tts_tupleDescriptor).natts as usize;
+
+    #[cfg(any(feature = "pg10", feature = "pg11"))]
+    pg_sys::slot_getallattrs(slot);
+
+    #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+    if ((*slot).tts_nvalid as usize) < natts {
+        pg_sys::slot_getsomeattrs_int(slot, natts as c_int);
+    }
+
+    let values = std::slice::from_raw_parts((*slot).tts_values, natts);
+    let isnull = std::slice::from_raw_parts((*slot).tts_isnull, natts);
+    values
+        .iter()
+        .zip(isnull.iter())
+        .map(|(&value, &isnull)| if isnull { None } else { Some(value) })
+        .collect()
+}
+
+unsafe fn slot_value(
+    slot: *mut pg_sys::TupleTableSlot,
+    attno: pg_sys::AttrNumber,
+) -> Option<pg_sys::Datum> {
+    if attno == pg_sys::InvalidAttrNumber as pg_sys::AttrNumber {
+        return None;
+    }
+
+    #[cfg(any(feature = "pg10", feature = "pg11"))]
+    let (value, isnull) = {
+        let mut isnull = false;
+        let value = pg_sys::slot_getattr(slot, attno as c_int, &mut isnull);
+        (value, isnull)
+    };
+
+    #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+    let (value, isnull) = {
+        if (*slot).tts_nvalid < attno {
+            pg_sys::slot_getsomeattrs_int(slot, attno as c_int);
+        }
+        (
+            *(*slot).tts_values.add(attno as usize - 1),
+            *(*slot).tts_isnull.add(attno as usize - 1),
+        )
+    };
+
+    if isnull {
+        None
+    } else {
+        Some(value)
+    }
+}
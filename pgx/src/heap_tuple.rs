@@ -0,0 +1,240 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Provides a safe wrapper around Postgres' `pg_sys::HeapTupleData` struct
+use crate::{format_type, heap_getattr, pg_sys, FromDatum, IntoDatum, PgBox, PgTupleDesc};
+
+/// A heap tuple paired with the `PgTupleDesc` that describes it, whose attributes can be read
+/// and replaced by name or by number
+///
+/// Tuples are allocated in the `CurrentMemoryContext`, or owned by whoever gave them to us, and
+/// are never `pfree()`'d by this type.  Replacing an attribute forms a new tuple with
+/// `heap_modify_tuple()`, leaving the original untouched.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn make_dog(name: &str) -> PgHeapTuple<'static> {
+///     // a type created with `CREATE TYPE dog AS (name text, age int)`
+///     let mut dog = PgHeapTuple::new_composite_type(regtypein("dog"));
+///     dog.set_by_name("name", name);
+///     dog.set_by_name("age", 3);
+///     dog
+/// }
+/// ```
+pub struct PgHeapTuple<'a> {
+    tupdesc: PgTupleDesc<'a>,
+    tuple: PgBox<pg_sys::HeapTupleData>,
+}
+
+impl<'a> PgHeapTuple<'a> {
+    /// Wrap a Postgres-provided `pg_sys::HeapTuple` described by `tupdesc`
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as we cannot validate that `tuple` is a valid heap tuple, nor that
+    /// `tupdesc` describes it.
+    pub unsafe fn from_heap_tuple(tupdesc: PgTupleDesc<'a>, tuple: pg_sys::HeapTuple) -> Self {
+        PgHeapTuple {
+            tupdesc,
+            tuple: PgBox::from_pg(tuple),
+        }
+    }
+
+    /// Form a tuple of `tupdesc`'s row type from `values`, one per attribute, including dropped
+    /// ones.  `None` is NULL.
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as we cannot validate that each value is of its attribute's type
+    ///
+    /// ## Panics
+    ///
+    /// If there isn't exactly one value per attribute
+    pub unsafe fn from_datums<I: IntoIterator<Item = Option<pg_sys::Datum>>>(
+        tupdesc: PgTupleDesc<'a>,
+        values: I,
+    ) -> Self {
+        let (mut datums, mut nulls): (Vec<_>, Vec<_>) = values
+            .into_iter()
+            .map(|value| (value.unwrap_or(0), value.is_none()))
+            .unzip();
+        if datums.len() != tupdesc.len() {
+            panic!(
+                "{} values were given for a tuple with {} attributes",
+                datums.len(),
+                tupdesc.len()
+            );
+        }
+
+        let tuple =
+            pg_sys::heap_form_tuple(tupdesc.as_ptr(), datums.as_mut_ptr(), nulls.as_mut_ptr());
+        Self::from_heap_tuple(tupdesc, tuple)
+    }
+
+    /// A tuple of the composite type `typoid`, with every attribute NULL
+    ///
+    /// Its `TupleDesc` comes from Postgres' type cache, via `lookup_rowtype_tupdesc()`, which
+    /// raises an ERROR if `typoid` isn't a composite type.
+    pub fn new_composite_type(typoid: pg_sys::Oid) -> PgHeapTuple<'static> {
+        unsafe {
+            let tupdesc = PgTupleDesc::from_pg(pg_sys::lookup_rowtype_tupdesc(typoid, -1));
+            let natts = tupdesc.len();
+            PgHeapTuple::from_datums(tupdesc, vec![None; natts])
+        }
+    }
+
+    pub fn tupdesc(&self) -> &PgTupleDesc<'a> {
+        &self.tupdesc
+    }
+
+    /// The number of attributes, including dropped ones
+    pub fn len(&self) -> usize {
+        self.tupdesc.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The attribute names, in order, skipping dropped attributes
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tupdesc
+            .iter()
+            .filter(|attr| !attr.is_dropped())
+            .map(|attr| attr.name())
+    }
+
+    /// Get the attribute numbered `attno`, or `None` if it's NULL
+    ///
+    /// `attno` is 1-based
+    ///
+    /// ## Panics
+    ///
+    /// If `attno` is out of range
+    pub fn get_by_index<T: FromDatum>(&self, attno: usize) -> Option<T> {
+        self.attr(attno);
+        heap_getattr(&self.tuple, attno, &self.tupdesc)
+    }
+
+    /// Get the attribute named `name`, or `None` if it's NULL
+    ///
+    /// ## Panics
+    ///
+    /// If this tuple has no attribute named `name`
+    pub fn get_by_name<T: FromDatum>(&self, name: &str) -> Option<T> {
+        self.get_by_index(self.attno(name))
+    }
+
+    /// Replace the attribute numbered `attno` with `value`.  `None` is NULL.
+    ///
+    /// `attno` is 1-based
+    ///
+    /// ## Panics
+    ///
+    /// If `attno` is out of range, or if `T`'s type isn't the attribute's type, nor binary
+    /// coercible to it
+    pub fn set_by_index<T: IntoDatum>(&mut self, attno: usize, value: T) {
+        let attr = self.attr(attno);
+        let (attname, atttypid) = (attr.name(), attr.atttypid);
+        let typoid = T::type_oid();
+        if typoid != atttypid && !unsafe { pg_sys::IsBinaryCoercible(typoid, atttypid) } {
+            panic!(
+                "attribute \"{}\" is of type {}, but the value is of type {}",
+                attname,
+                format_type(atttypid),
+                format_type(typoid)
+            );
+        }
+
+        // SAFETY:  we just checked that `value` is of the attribute's type
+        unsafe { self.set_datum_by_index(attno, value.into_datum()) }
+    }
+
+    /// Replace the attribute named `name` with `value`.  `None` is NULL.
+    ///
+    /// ## Panics
+    ///
+    /// If this tuple has no attribute named `name`, or if `T`'s type isn't the attribute's type,
+    /// nor binary coercible to it
+    pub fn set_by_name<T: IntoDatum>(&mut self, name: &str, value: T) {
+        self.set_by_index(self.attno(name), value)
+    }
+
+    /// Replace the attribute numbered `attno` with `datum`.  `None` is NULL.
+    ///
+    /// `attno` is 1-based
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as we cannot validate that `datum` is of the attribute's type
+    ///
+    /// ## Panics
+    ///
+    /// If `attno` is out of range
+    pub unsafe fn set_datum_by_index(&mut self, attno: usize, datum: Option<pg_sys::Datum>) {
+        self.attr(attno);
+
+        let natts = self.len();
+        let mut values = vec![0; natts];
+        let mut nulls = vec![false; natts];
+        let mut replace = vec![false; natts];
+        values[attno - 1] = datum.unwrap_or(0);
+        nulls[attno - 1] = datum.is_none();
+        replace[attno - 1] = true;
+
+        let tuple = pg_sys::heap_modify_tuple(
+            self.tuple.as_ptr(),
+            self.tupdesc.as_ptr(),
+            values.as_mut_ptr(),
+            nulls.as_mut_ptr(),
+            replace.as_mut_ptr(),
+        );
+        self.tuple = PgBox::from_pg(tuple);
+    }
+
+    /// Copy this tuple into a composite `Datum` of its row type, via `heap_copy_tuple_as_datum()`
+    ///
+    /// If the row type is an anonymous `record`, its `TupleDesc` must have been registered with
+    /// `BlessTupleDesc()` for the `Datum` to be understood.
+    pub fn into_composite_datum(self) -> pg_sys::Datum {
+        unsafe { pg_sys::heap_copy_tuple_as_datum(self.tuple.as_ptr(), self.tupdesc.as_ptr()) }
+    }
+
+    /// The underlying `pg_sys::HeapTuple`, which lives on after `self` is dropped
+    pub fn into_pg(self) -> pg_sys::HeapTuple {
+        self.tuple.into_pg()
+    }
+
+    /// The attribute numbered `attno`, which must be in range
+    fn attr(&self, attno: usize) -> &pg_sys::FormData_pg_attribute {
+        if attno == 0 || attno > self.len() {
+            panic!(
+                "attribute number {} is out of range for a tuple with {} attributes",
+                attno,
+                self.len()
+            );
+        }
+        self.tupdesc.get(attno - 1).unwrap()
+    }
+
+    /// The 1-based number of the non-dropped attribute named `name`
+    fn attno(&self, name: &str) -> usize {
+        self.tupdesc
+            .iter()
+            .position(|attr| !attr.is_dropped() && attr.name() == name)
+            .map(|idx| idx + 1)
+            .unwrap_or_else(|| panic!("tuple has no attribute named \"{}\"", name))
+    }
+}
+
+impl<'a> IntoDatum for PgHeapTuple<'a> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.into_composite_datum())
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::RECORDOID
+    }
+}
@@ -23,6 +23,19 @@ impl<T> Deref for HookResult<T> {
     }
 }
 
+/// Postgres' `JumbleState`, which [`PgHooks::post_parse_analyze`] is given while query
+/// identifiers are being computed
+#[cfg(feature = "pg14")]
+pub use pg_sys::JumbleState;
+
+/// Postgres' `JumbleState`, which only exists in Postgres 14 and later, so that
+/// [`PgHooks::post_parse_analyze`] has the same signature on every version
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+pub enum JumbleState {}
+
+/// Callbacks for Postgres' hook points, registered with [`register_hook`] or
+/// [`register_hook_points`].  Each is only called for instances registered for its
+/// [`PgHookPoints`].
 pub trait PgHooks {
     /// Hook for plugins to get control in ExecutorStart()
     fn executor_start(
@@ -133,6 +146,191 @@ pub trait PgHooks {
         prev_hook(parse, query_string, cursor_options, bound_params)
     }
 
+    /// Hook for plugins to add paths, such as custom scans, for a base relation
+    fn set_rel_pathlist(
+        &mut self,
+        root: PgBox<pg_sys::PlannerInfo>,
+        rel: PgBox<pg_sys::RelOptInfo>,
+        rti: pg_sys::Index,
+        rte: PgBox<pg_sys::RangeTblEntry>,
+        prev_hook: fn(
+            root: PgBox<pg_sys::PlannerInfo>,
+            rel: PgBox<pg_sys::RelOptInfo>,
+            rti: pg_sys::Index,
+            rte: PgBox<pg_sys::RangeTblEntry>,
+        ) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(root, rel, rti, rte)
+    }
+
+    /// Hook for plugins to add paths, such as custom scans, for a join relation
+    fn set_join_pathlist(
+        &mut self,
+        root: PgBox<pg_sys::PlannerInfo>,
+        joinrel: PgBox<pg_sys::RelOptInfo>,
+        outerrel: PgBox<pg_sys::RelOptInfo>,
+        innerrel: PgBox<pg_sys::RelOptInfo>,
+        jointype: pg_sys::JoinType,
+        extra: PgBox<pg_sys::JoinPathExtraData>,
+        prev_hook: fn(
+            root: PgBox<pg_sys::PlannerInfo>,
+            joinrel: PgBox<pg_sys::RelOptInfo>,
+            outerrel: PgBox<pg_sys::RelOptInfo>,
+            innerrel: PgBox<pg_sys::RelOptInfo>,
+            jointype: pg_sys::JoinType,
+            extra: PgBox<pg_sys::JoinPathExtraData>,
+        ) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(root, joinrel, outerrel, innerrel, jointype, extra)
+    }
+
+    /// Hook for plugins to get control at the end of parse analysis
+    ///
+    /// `jumble_state` is only ever given on Postgres 14 and later, and only while query
+    /// identifiers are being computed
+    fn post_parse_analyze(
+        &mut self,
+        pstate: PgBox<pg_sys::ParseState>,
+        query: PgBox<pg_sys::Query>,
+        jumble_state: Option<PgBox<JumbleState>>,
+        prev_hook: fn(
+            pstate: PgBox<pg_sys::ParseState>,
+            query: PgBox<pg_sys::Query>,
+            jumble_state: Option<PgBox<JumbleState>>,
+        ) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(pstate, query, jumble_state)
+    }
+
+    /// Hook for plugins to get control in ClientAuthentication(), once the client has passed, or
+    /// failed, authentication.  `status` is `pg_sys::STATUS_OK` or `pg_sys::STATUS_ERROR`.
+    ///
+    /// Only called for hooks registered in `_PG_init()` by a library in `shared_preload_libraries`
+    fn client_authentication(
+        &mut self,
+        port: PgBox<pg_sys::Port>,
+        status: i32,
+        prev_hook: fn(port: PgBox<pg_sys::Port>, status: i32) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(port, status)
+    }
+
+    /// Hook for plugins to get control in EmitErrorReport(), before a message is sent to the
+    /// server log and the client
+    fn emit_log(
+        &mut self,
+        error_data: PgBox<pg_sys::ErrorData>,
+        prev_hook: fn(error_data: PgBox<pg_sys::ErrorData>) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(error_data)
+    }
+
+    /// Hook for plugins to get control when a database object is created, altered, dropped or
+    /// otherwise accessed
+    fn object_access(
+        &mut self,
+        access: pg_sys::ObjectAccessType,
+        class_id: pg_sys::Oid,
+        object_id: pg_sys::Oid,
+        sub_id: i32,
+        arg: void_mut_ptr,
+        prev_hook: fn(
+            access: pg_sys::ObjectAccessType,
+            class_id: pg_sys::Oid,
+            object_id: pg_sys::Oid,
+            sub_id: i32,
+            arg: void_mut_ptr,
+        ) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(access, class_id, object_id, sub_id, arg)
+    }
+
+    /// Hook for plugins to get control in ExplainOneQuery(), to plan and explain a query
+    ///
+    /// `query_env` is always null on Postgres 10
+    fn explain_one_query(
+        &mut self,
+        query: PgBox<pg_sys::Query>,
+        cursor_options: i32,
+        into: PgBox<pg_sys::IntoClause>,
+        es: PgBox<pg_sys::ExplainState>,
+        query_string: *const std::os::raw::c_char,
+        params: PgBox<pg_sys::ParamListInfoData>,
+        query_env: PgBox<pg_sys::QueryEnvironment>,
+        prev_hook: fn(
+            query: PgBox<pg_sys::Query>,
+            cursor_options: i32,
+            into: PgBox<pg_sys::IntoClause>,
+            es: PgBox<pg_sys::ExplainState>,
+            query_string: *const std::os::raw::c_char,
+            params: PgBox<pg_sys::ParamListInfoData>,
+            query_env: PgBox<pg_sys::QueryEnvironment>,
+        ) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(
+            query,
+            cursor_options,
+            into,
+            es,
+            query_string,
+            params,
+            query_env,
+        )
+    }
+
+    /// Hook for plugins to get control in get_relation_info(), to edit what the planner knows
+    /// about a relation
+    fn get_relation_info(
+        &mut self,
+        root: PgBox<pg_sys::PlannerInfo>,
+        relation_oid: pg_sys::Oid,
+        inhparent: bool,
+        rel: PgBox<pg_sys::RelOptInfo>,
+        prev_hook: fn(
+            root: PgBox<pg_sys::PlannerInfo>,
+            relation_oid: pg_sys::Oid,
+            inhparent: bool,
+            rel: PgBox<pg_sys::RelOptInfo>,
+        ) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(root, relation_oid, inhparent, rel)
+    }
+
+    /// Hook for plugins to initialize their shared memory, once Postgres has created it
+    ///
+    /// Only called for hooks registered in `_PG_init()` by a library in
+    /// `shared_preload_libraries`.  There's no `shmem_request_hook` before Postgres 15, so
+    /// shared memory must be requested with `pg_sys::RequestAddinShmemSpace()` from `_PG_init()`.
+    fn shmem_startup(&mut self, prev_hook: fn() -> HookResult<()>) -> HookResult<()> {
+        prev_hook()
+    }
+
+    /// Hook for plugins to decide whether [`PgHooks::fmgr`] should get control around calls to
+    /// the function `fn_oid`
+    fn needs_fmgr(
+        &mut self,
+        fn_oid: pg_sys::Oid,
+        prev_hook: fn(fn_oid: pg_sys::Oid) -> HookResult<bool>,
+    ) -> HookResult<bool> {
+        prev_hook(fn_oid)
+    }
+
+    /// Hook for plugins to get control at the start and end of, or on an abort during, calls to
+    /// functions for which [`PgHooks::needs_fmgr`] returned true
+    fn fmgr(
+        &mut self,
+        event: pg_sys::FmgrHookEventType,
+        flinfo: PgBox<pg_sys::FmgrInfo>,
+        arg: *mut pg_sys::Datum,
+        prev_hook: fn(
+            event: pg_sys::FmgrHookEventType,
+            flinfo: PgBox<pg_sys::FmgrInfo>,
+            arg: *mut pg_sys::Datum,
+        ) -> HookResult<()>,
+    ) -> HookResult<()> {
+        prev_hook(event, flinfo, arg)
+    }
+
     /// Called when the transaction aborts
     fn abort(&mut self) {}
 
@@ -140,8 +338,55 @@ pub trait PgHooks {
     fn commit(&mut self) {}
 }
 
+bitflags! {
+    /// The hook points a `PgHooks` instance is registered for with [`register_hook_points`]
+    ///
+    /// pgx only installs its hook into a hook point once some instance is registered for it, so
+    /// that Postgres isn't slowed down by hooks nobody uses.  An instance is only called for the
+    /// hook points it's registered for, whatever other methods of [`PgHooks`] it implements.
+    pub struct PgHookPoints: u32 {
+        const EXECUTOR_START = 1 << 0;
+        const EXECUTOR_RUN = 1 << 1;
+        const EXECUTOR_FINISH = 1 << 2;
+        const EXECUTOR_END = 1 << 3;
+        const EXECUTOR_CHECK_PERMS = 1 << 4;
+        const PROCESS_UTILITY = 1 << 5;
+        const PLANNER = 1 << 6;
+        const SET_REL_PATHLIST = 1 << 7;
+        const SET_JOIN_PATHLIST = 1 << 8;
+        const POST_PARSE_ANALYZE = 1 << 9;
+        const CLIENT_AUTHENTICATION = 1 << 10;
+        const EMIT_LOG = 1 << 11;
+        const OBJECT_ACCESS = 1 << 12;
+        const EXPLAIN_ONE_QUERY = 1 << 13;
+        const GET_RELATION_INFO = 1 << 14;
+        const SHMEM_STARTUP = 1 << 15;
+        /// Both [`PgHooks::needs_fmgr`] and [`PgHooks::fmgr`]
+        const FMGR = 1 << 16;
+
+        /// The executor, utility and planner hook points that [`register_hook`] registers for
+        const EXECUTOR_AND_PLANNER = Self::EXECUTOR_START.bits
+            | Self::EXECUTOR_RUN.bits
+            | Self::EXECUTOR_FINISH.bits
+            | Self::EXECUTOR_END.bits
+            | Self::EXECUTOR_CHECK_PERMS.bits
+            | Self::PROCESS_UTILITY.bits
+            | Self::PLANNER.bits
+            | Self::SET_REL_PATHLIST.bits
+            | Self::SET_JOIN_PATHLIST.bits;
+    }
+}
+
+/// Identifies a `PgHooks` instance registered with [`register_hook`], so that it can later be
+/// passed to [`unregister_hook`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HookId(usize);
+
 struct Hooks {
-    current_hook: Box<&'static mut (dyn PgHooks)>,
+    /// `None` once unregistered, so that the indices in `RUNNING` stay valid
+    registered: Vec<Option<(HookId, PgHookPoints, &'static mut dyn PgHooks)>>,
+    next_id: usize,
+    installed: PgHookPoints,
     prev_executor_start_hook: pg_sys::ExecutorStart_hook_type,
     prev_executor_run_hook: pg_sys::ExecutorRun_hook_type,
     prev_executor_finish_hook: pg_sys::ExecutorFinish_hook_type,
@@ -149,70 +394,365 @@ struct Hooks {
     prev_executor_check_perms_hook: pg_sys::ExecutorCheckPerms_hook_type,
     prev_process_utility_hook: pg_sys::ProcessUtility_hook_type,
     prev_planner_hook: pg_sys::planner_hook_type,
+    prev_set_rel_pathlist_hook: pg_sys::set_rel_pathlist_hook_type,
+    prev_set_join_pathlist_hook: pg_sys::set_join_pathlist_hook_type,
+    prev_post_parse_analyze_hook: pg_sys::post_parse_analyze_hook_type,
+    prev_client_authentication_hook: pg_sys::ClientAuthentication_hook_type,
+    prev_emit_log_hook: pg_sys::emit_log_hook_type,
+    prev_object_access_hook: pg_sys::object_access_hook_type,
+    prev_explain_one_query_hook: pg_sys::ExplainOneQuery_hook_type,
+    prev_get_relation_info_hook: pg_sys::get_relation_info_hook_type,
+    prev_shmem_startup_hook: pg_sys::shmem_startup_hook_type,
+    prev_needs_fmgr_hook: pg_sys::needs_fmgr_hook_type,
+    prev_fmgr_hook: pg_sys::fmgr_hook_type,
 }
 
 static mut HOOKS: Option<Hooks> = None;
 
-/// Register a `PgHook` instance to respond to the various hook points
-pub unsafe fn register_hook(hook: &'static mut (dyn PgHooks)) {
-    if HOOKS.is_some() {
-        panic!("PgHook instance already registered");
-    }
-    HOOKS = Some(Hooks {
-        current_hook: Box::new(hook),
-        prev_executor_start_hook: pg_sys::ExecutorStart_hook
-            .replace(pgx_executor_start)
-            .or(Some(pgx_standard_executor_start_wrapper)),
-        prev_executor_run_hook: pg_sys::ExecutorRun_hook
-            .replace(pgx_executor_run)
-            .or(Some(pgx_standard_executor_run_wrapper)),
-        prev_executor_finish_hook: pg_sys::ExecutorFinish_hook
-            .replace(pgx_executor_finish)
-            .or(Some(pgx_standard_executor_finish_wrapper)),
-        prev_executor_end_hook: pg_sys::ExecutorEnd_hook
-            .replace(pgx_executor_end)
-            .or(Some(pgx_standard_executor_end_wrapper)),
-        prev_executor_check_perms_hook: pg_sys::ExecutorCheckPerms_hook
-            .replace(pgx_executor_check_perms)
-            .or(Some(pgx_standard_executor_check_perms_wrapper)),
-        prev_process_utility_hook: pg_sys::ProcessUtility_hook
-            .replace(pgx_process_utility)
-            .or(Some(pgx_standard_process_utility_wrapper)),
-        prev_planner_hook: pg_sys::planner_hook
-            .replace(pgx_planner)
-            .or(Some(pgx_standard_planner_wrapper)),
-    });
-
-    unsafe extern "C" fn xact_callback(event: pg_sys::XactEvent, _: void_mut_ptr) {
-        match event {
-            pg_sys::XactEvent_XACT_EVENT_ABORT => {
-                crate::guard(|| HOOKS.as_mut().unwrap().current_hook.abort());
+/// The index, into `Hooks::registered`, of the `PgHooks` instance running in each in-progress
+/// hook call, innermost last, so that its `prev_hook` knows which instance is next
+///
+/// An ERROR can longjmp past the `Running` guards that would pop them, so `RUNNING` is also
+/// cleared when the transaction aborts, and truncated back to where it was when a subtransaction
+/// that aborts was started
+static mut RUNNING: Vec<usize> = Vec::new();
+
+/// The length of `RUNNING` when each in-progress subtransaction was started, innermost last
+static mut SUBXACT_RUNNING: Vec<(pg_sys::SubTransactionId, usize)> = Vec::new();
+
+/// Register a `PgHook` instance to respond to the executor, utility and planner hook points,
+/// [`PgHookPoints::EXECUTOR_AND_PLANNER`].  Use [`register_hook_points`] for the others.
+///
+/// Any number of instances can be registered, and each hook point calls them in the order they
+/// were registered:  Postgres calls the first, whose `prev_hook` calls the second, and so on, until
+/// the last one's `prev_hook` calls whatever hook was installed before pgx's, or Postgres' own
+/// implementation.
+pub unsafe fn register_hook(hook: &'static mut (dyn PgHooks)) -> HookId {
+    register_hook_points(hook, PgHookPoints::EXECUTOR_AND_PLANNER)
+}
+
+/// Register a `PgHook` instance to respond to the given hook points, installing pgx's hook into
+/// any of them it isn't installed into yet
+///
+/// As with [`register_hook`], instances are called in the order they were registered.
+pub unsafe fn register_hook_points(hook: &'static mut dyn PgHooks, points: PgHookPoints) -> HookId {
+    let hooks = HOOKS.get_or_insert_with(|| Hooks::new());
+    hooks.install(points);
+    let id = HookId(hooks.next_id);
+    hooks.next_id += 1;
+    hooks.registered.push(Some((id, points, hook)));
+    id
+}
+
+/// Unregister a `PgHook` instance, so that it's skipped from now on.  Returns `false` if it had
+/// already been unregistered.
+///
+/// It can be called from within a hook, including one of the instance's own:  hook calls already
+/// in progress finish normally, and only later calls skip it.  Postgres' hooks stay pointed at
+/// pgx's, which call straight through to the previous hooks once every instance has been
+/// unregistered.
+pub unsafe fn unregister_hook(id: HookId) -> bool {
+    match HOOKS.as_mut() {
+        Some(hooks) => match hooks
+            .registered
+            .iter_mut()
+            .find(|entry| matches!(entry, Some((registered_id, _, _)) if *registered_id == id))
+        {
+            Some(entry) => {
+                *entry = None;
+                true
             }
-            pg_sys::XactEvent_XACT_EVENT_PRE_COMMIT => {
-                crate::guard(|| HOOKS.as_mut().unwrap().current_hook.commit());
+            None => false,
+        },
+        None => false,
+    }
+}
+
+impl Hooks {
+    unsafe fn new() -> Self {
+        unsafe extern "C" fn xact_callback(event: pg_sys::XactEvent, _: void_mut_ptr) {
+            match event {
+                pg_sys::XactEvent_XACT_EVENT_ABORT => {
+                    RUNNING.clear();
+                    SUBXACT_RUNNING.clear();
+                    crate::guard(|| {
+                        for (_, _, hook) in HOOKS.as_mut().unwrap().registered.iter_mut().flatten()
+                        {
+                            hook.abort();
+                        }
+                    });
+                }
+                pg_sys::XactEvent_XACT_EVENT_PRE_COMMIT => {
+                    crate::guard(|| {
+                        for (_, _, hook) in HOOKS.as_mut().unwrap().registered.iter_mut().flatten()
+                        {
+                            hook.commit();
+                        }
+                    });
+                }
+                _ => { /* noop */ }
             }
-            _ => { /* noop */ }
+        }
+
+        unsafe extern "C" fn subxact_callback(
+            event: pg_sys::SubXactEvent,
+            my_subid: pg_sys::SubTransactionId,
+            _: pg_sys::SubTransactionId,
+            _: void_mut_ptr,
+        ) {
+            match event {
+                pg_sys::SubXactEvent_SUBXACT_EVENT_START_SUB => {
+                    SUBXACT_RUNNING.push((my_subid, RUNNING.len()));
+                }
+                pg_sys::SubXactEvent_SUBXACT_EVENT_COMMIT_SUB
+                | pg_sys::SubXactEvent_SUBXACT_EVENT_ABORT_SUB => {
+                    if let Some(position) = SUBXACT_RUNNING
+                        .iter()
+                        .position(|(subid, _)| *subid == my_subid)
+                    {
+                        let (_, depth) = SUBXACT_RUNNING[position];
+                        SUBXACT_RUNNING.truncate(position);
+                        if event == pg_sys::SubXactEvent_SUBXACT_EVENT_ABORT_SUB {
+                            RUNNING.truncate(depth);
+                        }
+                    }
+                }
+                _ => { /* noop */ }
+            }
+        }
+
+        pg_sys::RegisterXactCallback(Some(xact_callback), std::ptr::null_mut());
+        pg_sys::RegisterSubXactCallback(Some(subxact_callback), std::ptr::null_mut());
+
+        Hooks {
+            registered: Vec::new(),
+            next_id: 0,
+            installed: PgHookPoints::empty(),
+            prev_executor_start_hook: None,
+            prev_executor_run_hook: None,
+            prev_executor_finish_hook: None,
+            prev_executor_end_hook: None,
+            prev_executor_check_perms_hook: None,
+            prev_process_utility_hook: None,
+            prev_planner_hook: None,
+            prev_set_rel_pathlist_hook: None,
+            prev_set_join_pathlist_hook: None,
+            prev_post_parse_analyze_hook: None,
+            prev_client_authentication_hook: None,
+            prev_emit_log_hook: None,
+            prev_object_access_hook: None,
+            prev_explain_one_query_hook: None,
+            prev_get_relation_info_hook: None,
+            prev_shmem_startup_hook: None,
+            prev_needs_fmgr_hook: None,
+            prev_fmgr_hook: None,
+        }
+    }
+
+    /// Install pgx's hook into each of `points` it isn't already installed into, remembering the
+    /// hook that was there before, or Postgres' standard implementation if there wasn't one
+    unsafe fn install(&mut self, points: PgHookPoints) {
+        macro_rules! install {
+            ($point:ident, $prev:ident, $pg_hook:ident, $hook:ident, $standard:ident) => {
+                if points.contains(PgHookPoints::$point)
+                    && !self.installed.contains(PgHookPoints::$point)
+                {
+                    self.$prev = pg_sys::$pg_hook.replace($hook).or(Some($standard));
+                }
+            };
+        }
+
+        install!(
+            EXECUTOR_START,
+            prev_executor_start_hook,
+            ExecutorStart_hook,
+            pgx_executor_start,
+            pgx_standard_executor_start_wrapper
+        );
+        install!(
+            EXECUTOR_RUN,
+            prev_executor_run_hook,
+            ExecutorRun_hook,
+            pgx_executor_run,
+            pgx_standard_executor_run_wrapper
+        );
+        install!(
+            EXECUTOR_FINISH,
+            prev_executor_finish_hook,
+            ExecutorFinish_hook,
+            pgx_executor_finish,
+            pgx_standard_executor_finish_wrapper
+        );
+        install!(
+            EXECUTOR_END,
+            prev_executor_end_hook,
+            ExecutorEnd_hook,
+            pgx_executor_end,
+            pgx_standard_executor_end_wrapper
+        );
+        install!(
+            EXECUTOR_CHECK_PERMS,
+            prev_executor_check_perms_hook,
+            ExecutorCheckPerms_hook,
+            pgx_executor_check_perms,
+            pgx_standard_executor_check_perms_wrapper
+        );
+        install!(
+            PROCESS_UTILITY,
+            prev_process_utility_hook,
+            ProcessUtility_hook,
+            pgx_process_utility,
+            pgx_standard_process_utility_wrapper
+        );
+        install!(
+            PLANNER,
+            prev_planner_hook,
+            planner_hook,
+            pgx_planner,
+            pgx_standard_planner_wrapper
+        );
+        install!(
+            SET_REL_PATHLIST,
+            prev_set_rel_pathlist_hook,
+            set_rel_pathlist_hook,
+            pgx_set_rel_pathlist,
+            pgx_standard_set_rel_pathlist_wrapper
+        );
+        install!(
+            SET_JOIN_PATHLIST,
+            prev_set_join_pathlist_hook,
+            set_join_pathlist_hook,
+            pgx_set_join_pathlist,
+            pgx_standard_set_join_pathlist_wrapper
+        );
+        install!(
+            POST_PARSE_ANALYZE,
+            prev_post_parse_analyze_hook,
+            post_parse_analyze_hook,
+            pgx_post_parse_analyze,
+            pgx_standard_post_parse_analyze_wrapper
+        );
+        install!(
+            CLIENT_AUTHENTICATION,
+            prev_client_authentication_hook,
+            ClientAuthentication_hook,
+            pgx_client_authentication,
+            pgx_standard_client_authentication_wrapper
+        );
+        install!(
+            EMIT_LOG,
+            prev_emit_log_hook,
+            emit_log_hook,
+            pgx_emit_log,
+            pgx_standard_emit_log_wrapper
+        );
+        install!(
+            OBJECT_ACCESS,
+            prev_object_access_hook,
+            object_access_hook,
+            pgx_object_access,
+            pgx_standard_object_access_wrapper
+        );
+        install!(
+            EXPLAIN_ONE_QUERY,
+            prev_explain_one_query_hook,
+            ExplainOneQuery_hook,
+            pgx_explain_one_query,
+            pgx_standard_explain_one_query_wrapper
+        );
+        install!(
+            GET_RELATION_INFO,
+            prev_get_relation_info_hook,
+            get_relation_info_hook,
+            pgx_get_relation_info,
+            pgx_standard_get_relation_info_wrapper
+        );
+        install!(
+            SHMEM_STARTUP,
+            prev_shmem_startup_hook,
+            shmem_startup_hook,
+            pgx_shmem_startup,
+            pgx_standard_shmem_startup_wrapper
+        );
+        install!(
+            FMGR,
+            prev_needs_fmgr_hook,
+            needs_fmgr_hook,
+            pgx_needs_fmgr,
+            pgx_standard_needs_fmgr_wrapper
+        );
+        install!(
+            FMGR,
+            prev_fmgr_hook,
+            fmgr_hook,
+            pgx_fmgr,
+            pgx_standard_fmgr_wrapper
+        );
+
+        self.installed |= points;
+    }
+}
+
+/// Truncates `RUNNING` back to the length it had before its index was pushed when the hook it
+/// was pushed for returns, or unwinds
+struct Running(usize);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        unsafe {
+            RUNNING.truncate(self.0);
         }
     }
+}
+
+/// The first still registered `PgHooks` instance registered for `point`, starting from `index`,
+/// marked as running until the returned `Running` is dropped
+unsafe fn hook_at(
+    index: usize,
+    point: PgHookPoints,
+) -> Option<(&'static mut dyn PgHooks, Running)> {
+    let registered = &mut HOOKS.as_mut().unwrap().registered;
+    let (index, (_, _, hook)) = registered
+        .iter_mut()
+        .enumerate()
+        .skip(index)
+        .filter_map(|(index, entry)| entry.as_mut().map(|entry| (index, entry)))
+        .find(|(_, (_, points, _))| points.contains(point))?;
+    let depth = RUNNING.len();
+    RUNNING.push(index);
+    Some((&mut **hook, Running(depth)))
+}
 
-    pg_sys::RegisterXactCallback(Some(xact_callback), std::ptr::null_mut());
+/// The index of the `PgHooks` instance after the one whose `prev_hook` is being called
+unsafe fn next_index() -> usize {
+    RUNNING.last().map_or(0, |index| index + 1)
 }
 
 #[pg_guard]
 unsafe extern "C" fn pgx_executor_start(query_desc: *mut pg_sys::QueryDesc, eflags: i32) {
+    executor_start(0, PgBox::from_pg(query_desc), eflags);
+}
+
+unsafe fn executor_start(
+    index: usize,
+    query_desc: PgBox<pg_sys::QueryDesc>,
+    eflags: i32,
+) -> HookResult<()> {
     fn prev(query_desc: PgBox<pg_sys::QueryDesc>, eflags: i32) -> HookResult<()> {
-        unsafe {
+        unsafe { executor_start(next_index(), query_desc, eflags) }
+    }
+    match hook_at(index, PgHookPoints::EXECUTOR_START) {
+        Some((hook, _running)) => hook.executor_start(query_desc, eflags, prev),
+        None => {
             (HOOKS
                 .as_mut()
                 .unwrap()
                 .prev_executor_start_hook
                 .as_ref()
-                .unwrap())(query_desc.into_pg(), eflags)
+                .unwrap())(query_desc.into_pg(), eflags);
+            HookResult::new(())
         }
-        HookResult::new(())
     }
-    let hook = &mut HOOKS.as_mut().unwrap().current_hook;
-    hook.executor_start(PgBox::from_pg(query_desc), eflags, prev);
 }
 
 #[pg_guard]
@@ -222,64 +762,90 @@ unsafe extern "C" fn pgx_executor_run(
     count: u64,
     execute_once: bool,
 ) {
+    executor_run(
+        0,
+        PgBox::from_pg(query_desc),
+        direction,
+        count,
+        execute_once,
+    );
+}
+
+unsafe fn executor_run(
+    index: usize,
+    query_desc: PgBox<pg_sys::QueryDesc>,
+    direction: pg_sys::ScanDirection,
+    count: u64,
+    execute_once: bool,
+) -> HookResult<()> {
     fn prev(
         query_desc: PgBox<pg_sys::QueryDesc>,
         direction: pg_sys::ScanDirection,
         count: u64,
         execute_once: bool,
     ) -> HookResult<()> {
-        unsafe {
+        unsafe { executor_run(next_index(), query_desc, direction, count, execute_once) }
+    }
+    match hook_at(index, PgHookPoints::EXECUTOR_RUN) {
+        Some((hook, _running)) => {
+            hook.executor_run(query_desc, direction, count, execute_once, prev)
+        }
+        None => {
             (HOOKS
                 .as_mut()
                 .unwrap()
                 .prev_executor_run_hook
                 .as_ref()
-                .unwrap())(query_desc.into_pg(), direction, count, execute_once)
+                .unwrap())(query_desc.into_pg(), direction, count, execute_once);
+            HookResult::new(())
         }
-        HookResult::new(())
     }
-    let hook = &mut HOOKS.as_mut().unwrap().current_hook;
-    hook.executor_run(
-        PgBox::from_pg(query_desc),
-        direction,
-        count,
-        execute_once,
-        prev,
-    );
 }
 
 #[pg_guard]
 unsafe extern "C" fn pgx_executor_finish(query_desc: *mut pg_sys::QueryDesc) {
+    executor_finish(0, PgBox::from_pg(query_desc));
+}
+
+unsafe fn executor_finish(index: usize, query_desc: PgBox<pg_sys::QueryDesc>) -> HookResult<()> {
     fn prev(query_desc: PgBox<pg_sys::QueryDesc>) -> HookResult<()> {
-        unsafe {
+        unsafe { executor_finish(next_index(), query_desc) }
+    }
+    match hook_at(index, PgHookPoints::EXECUTOR_FINISH) {
+        Some((hook, _running)) => hook.executor_finish(query_desc, prev),
+        None => {
             (HOOKS
                 .as_mut()
                 .unwrap()
                 .prev_executor_finish_hook
                 .as_ref()
-                .unwrap())(query_desc.into_pg())
+                .unwrap())(query_desc.into_pg());
+            HookResult::new(())
         }
-        HookResult::new(())
     }
-    let hook = &mut HOOKS.as_mut().unwrap().current_hook;
-    hook.executor_finish(PgBox::from_pg(query_desc), prev);
 }
 
 #[pg_guard]
 unsafe extern "C" fn pgx_executor_end(query_desc: *mut pg_sys::QueryDesc) {
+    executor_end(0, PgBox::from_pg(query_desc));
+}
+
+unsafe fn executor_end(index: usize, query_desc: PgBox<pg_sys::QueryDesc>) -> HookResult<()> {
     fn prev(query_desc: PgBox<pg_sys::QueryDesc>) -> HookResult<()> {
-        unsafe {
+        unsafe { executor_end(next_index(), query_desc) }
+    }
+    match hook_at(index, PgHookPoints::EXECUTOR_END) {
+        Some((hook, _running)) => hook.executor_end(query_desc, prev),
+        None => {
             (HOOKS
                 .as_mut()
                 .unwrap()
                 .prev_executor_end_hook
                 .as_ref()
-                .unwrap())(query_desc.into_pg())
+                .unwrap())(query_desc.into_pg());
+            HookResult::new(())
         }
-        HookResult::new(())
     }
-    let hook = &mut HOOKS.as_mut().unwrap().current_hook;
-    hook.executor_end(PgBox::from_pg(query_desc), prev);
 }
 
 #[pg_guard]
@@ -287,22 +853,33 @@ unsafe extern "C" fn pgx_executor_check_perms(
     range_table: *mut pg_sys::List,
     ereport_on_violation: bool,
 ) -> bool {
+    executor_check_perms(0, PgList::from_pg(range_table), ereport_on_violation).inner
+}
+
+unsafe fn executor_check_perms(
+    index: usize,
+    range_table: PgList<*mut pg_sys::RangeTblEntry>,
+    ereport_on_violation: bool,
+) -> HookResult<bool> {
     fn prev(
         range_table: PgList<*mut pg_sys::RangeTblEntry>,
         ereport_on_violation: bool,
     ) -> HookResult<bool> {
-        HookResult::new(unsafe {
-            (HOOKS
-                .as_mut()
-                .unwrap()
-                .prev_executor_check_perms_hook
-                .as_ref()
-                .unwrap())(range_table.into_pg(), ereport_on_violation)
-        })
+        unsafe { executor_check_perms(next_index(), range_table, ereport_on_violation) }
+    }
+    match hook_at(index, PgHookPoints::EXECUTOR_CHECK_PERMS) {
+        Some((hook, _running)) => {
+            hook.executor_check_perms(range_table, ereport_on_violation, prev)
+        }
+        None => HookResult::new((HOOKS
+            .as_mut()
+            .unwrap()
+            .prev_executor_check_perms_hook
+            .as_ref()
+            .unwrap())(
+            range_table.into_pg(), ereport_on_violation
+        )),
     }
-    let hook = &mut HOOKS.as_mut().unwrap().current_hook;
-    hook.executor_check_perms(PgList::from_pg(range_table), ereport_on_violation, prev)
-        .inner
 }
 
 #[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
@@ -316,36 +893,8 @@ unsafe extern "C" fn pgx_process_utility(
     dest: *mut pg_sys::DestReceiver,
     completion_tag: *mut pg_sys::QueryCompletion,
 ) {
-    fn prev(
-        pstmt: PgBox<pg_sys::PlannedStmt>,
-        query_string: &std::ffi::CStr,
-        _read_only_tree: Option<bool>,
-        context: pg_sys::ProcessUtilityContext,
-        params: PgBox<pg_sys::ParamListInfoData>,
-        query_env: PgBox<pg_sys::QueryEnvironment>,
-        dest: PgBox<pg_sys::DestReceiver>,
-        completion_tag: *mut pg_sys::QueryCompletion,
-    ) -> HookResult<()> {
-        HookResult::new(unsafe {
-            (HOOKS
-                .as_mut()
-                .unwrap()
-                .prev_process_utility_hook
-                .as_ref()
-                .unwrap())(
-                pstmt.into_pg(),
-                query_string.as_ptr(),
-                context,
-                params.into_pg(),
-                query_env.into_pg(),
-                dest.into_pg(),
-                completion_tag,
-            )
-        })
-    }
-
-    let hook = &mut HOOKS.as_mut().unwrap().current_hook;
-    hook.process_utility_hook(
+    process_utility(
+        0,
         PgBox::from_pg(pstmt),
         std::ffi::CStr::from_ptr(query_string),
         None,
@@ -354,10 +903,10 @@ unsafe extern "C" fn pgx_process_utility(
         PgBox::from_pg(query_env),
         PgBox::from_pg(dest),
         completion_tag,
-        prev,
     )
     .inner
 }
+
 #[cfg(feature = "pg14")]
 #[pg_guard]
 unsafe extern "C" fn pgx_process_utility(
@@ -370,6 +919,31 @@ unsafe extern "C" fn pgx_process_utility(
     dest: *mut pg_sys::DestReceiver,
     completion_tag: *mut pg_sys::QueryCompletion,
 ) {
+    process_utility(
+        0,
+        PgBox::from_pg(pstmt),
+        std::ffi::CStr::from_ptr(query_string),
+        Some(read_only_tree),
+        context,
+        PgBox::from_pg(params),
+        PgBox::from_pg(query_env),
+        PgBox::from_pg(dest),
+        completion_tag,
+    )
+    .inner
+}
+
+unsafe fn process_utility(
+    index: usize,
+    pstmt: PgBox<pg_sys::PlannedStmt>,
+    query_string: &std::ffi::CStr,
+    read_only_tree: Option<bool>,
+    context: pg_sys::ProcessUtilityContext,
+    params: PgBox<pg_sys::ParamListInfoData>,
+    query_env: PgBox<pg_sys::QueryEnvironment>,
+    dest: PgBox<pg_sys::DestReceiver>,
+    completion_tag: *mut pg_sys::QueryCompletion,
+) -> HookResult<()> {
     fn prev(
         pstmt: PgBox<pg_sys::PlannedStmt>,
         query_string: &std::ffi::CStr,
@@ -380,38 +954,72 @@ unsafe extern "C" fn pgx_process_utility(
         dest: PgBox<pg_sys::DestReceiver>,
         completion_tag: *mut pg_sys::QueryCompletion,
     ) -> HookResult<()> {
-        HookResult::new(unsafe {
-            (HOOKS
-                .as_mut()
-                .unwrap()
-                .prev_process_utility_hook
-                .as_ref()
-                .unwrap())(
-                pstmt.into_pg(),
-                query_string.as_ptr(),
-                read_only_tree.unwrap(),
+        unsafe {
+            process_utility(
+                next_index(),
+                pstmt,
+                query_string,
+                read_only_tree,
                 context,
-                params.into_pg(),
-                query_env.into_pg(),
-                dest.into_pg(),
+                params,
+                query_env,
+                dest,
                 completion_tag,
             )
-        })
+        }
     }
+    match hook_at(index, PgHookPoints::PROCESS_UTILITY) {
+        Some((hook, _running)) => hook.process_utility_hook(
+            pstmt,
+            query_string,
+            read_only_tree,
+            context,
+            params,
+            query_env,
+            dest,
+            completion_tag,
+            prev,
+        ),
+        None => {
+            #[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+            {
+                (HOOKS
+                    .as_mut()
+                    .unwrap()
+                    .prev_process_utility_hook
+                    .as_ref()
+                    .unwrap())(
+                    pstmt.into_pg(),
+                    query_string.as_ptr(),
+                    context,
+                    params.into_pg(),
+                    query_env.into_pg(),
+                    dest.into_pg(),
+                    completion_tag,
+                )
+            }
 
-    let hook = &mut HOOKS.as_mut().unwrap().current_hook;
-    hook.process_utility_hook(
-        PgBox::from_pg(pstmt),
-        std::ffi::CStr::from_ptr(query_string),
-        Some(read_only_tree),
-        context,
-        PgBox::from_pg(params),
-        PgBox::from_pg(query_env),
-        PgBox::from_pg(dest),
-        completion_tag,
-        prev,
-    )
-    .inner
+            #[cfg(feature = "pg14")]
+            {
+                (HOOKS
+                    .as_mut()
+                    .unwrap()
+                    .prev_process_utility_hook
+                    .as_ref()
+                    .unwrap())(
+                    pstmt.into_pg(),
+                    query_string.as_ptr(),
+                    read_only_tree.unwrap(),
+                    context,
+                    params.into_pg(),
+                    query_env.into_pg(),
+                    dest.into_pg(),
+                    completion_tag,
+                )
+            }
+            HookResult::new(())
+        }
+    }
 }
 
 #[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12"))]
@@ -421,7 +1029,14 @@ unsafe extern "C" fn pgx_planner(
     cursor_options: i32,
     bound_params: pg_sys::ParamListInfo,
 ) -> *mut pg_sys::PlannedStmt {
-    pgx_planner_impl(parse, std::ptr::null(), cursor_options, bound_params)
+    planner(
+        0,
+        PgBox::from_pg(parse),
+        std::ptr::null(),
+        cursor_options,
+        PgBox::from_pg(bound_params),
+    )
+    .inner
 }
 
 #[cfg(any(feature = "pg13", feature = "pg14"))]
@@ -432,23 +1047,44 @@ unsafe extern "C" fn pgx_planner(
     cursor_options: i32,
     bound_params: pg_sys::ParamListInfo,
 ) -> *mut pg_sys::PlannedStmt {
-    pgx_planner_impl(parse, query_string, cursor_options, bound_params)
+    planner(
+        0,
+        PgBox::from_pg(parse),
+        query_string,
+        cursor_options,
+        PgBox::from_pg(bound_params),
+    )
+    .inner
 }
 
-#[pg_guard]
-unsafe extern "C" fn pgx_planner_impl(
-    parse: *mut pg_sys::Query,
+unsafe fn planner(
+    index: usize,
+    parse: PgBox<pg_sys::Query>,
     query_string: *const ::std::os::raw::c_char,
     cursor_options: i32,
-    bound_params: pg_sys::ParamListInfo,
-) -> *mut pg_sys::PlannedStmt {
+    bound_params: PgBox<pg_sys::ParamListInfoData>,
+) -> HookResult<*mut pg_sys::PlannedStmt> {
     fn prev(
         parse: PgBox<pg_sys::Query>,
-        #[allow(unused_variables)] query_string: *const ::std::os::raw::c_char,
+        query_string: *const ::std::os::raw::c_char,
         cursor_options: i32,
         bound_params: PgBox<pg_sys::ParamListInfoData>,
     ) -> HookResult<*mut pg_sys::PlannedStmt> {
-        HookResult::new(unsafe {
+        unsafe {
+            planner(
+                next_index(),
+                parse,
+                query_string,
+                cursor_options,
+                bound_params,
+            )
+        }
+    }
+    match hook_at(index, PgHookPoints::PLANNER) {
+        Some((hook, _running)) => {
+            hook.planner(parse, query_string, cursor_options, bound_params, prev)
+        }
+        None => HookResult::new({
             #[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12"))]
             {
                 (HOOKS.as_mut().unwrap().prev_planner_hook.as_ref().unwrap())(
@@ -467,17 +1103,538 @@ unsafe extern "C" fn pgx_planner_impl(
                     bound_params.into_pg(),
                 )
             }
-        })
+        }),
     }
-    let hook = &mut HOOKS.as_mut().unwrap().current_hook;
-    hook.planner(
-        PgBox::from_pg(parse),
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_set_rel_pathlist(
+    root: *mut pg_sys::PlannerInfo,
+    rel: *mut pg_sys::RelOptInfo,
+    rti: pg_sys::Index,
+    rte: *mut pg_sys::RangeTblEntry,
+) {
+    set_rel_pathlist(
+        0,
+        PgBox::from_pg(root),
+        PgBox::from_pg(rel),
+        rti,
+        PgBox::from_pg(rte),
+    );
+}
+
+unsafe fn set_rel_pathlist(
+    index: usize,
+    root: PgBox<pg_sys::PlannerInfo>,
+    rel: PgBox<pg_sys::RelOptInfo>,
+    rti: pg_sys::Index,
+    rte: PgBox<pg_sys::RangeTblEntry>,
+) -> HookResult<()> {
+    fn prev(
+        root: PgBox<pg_sys::PlannerInfo>,
+        rel: PgBox<pg_sys::RelOptInfo>,
+        rti: pg_sys::Index,
+        rte: PgBox<pg_sys::RangeTblEntry>,
+    ) -> HookResult<()> {
+        unsafe { set_rel_pathlist(next_index(), root, rel, rti, rte) }
+    }
+    match hook_at(index, PgHookPoints::SET_REL_PATHLIST) {
+        Some((hook, _running)) => hook.set_rel_pathlist(root, rel, rti, rte, prev),
+        None => {
+            (HOOKS
+                .as_mut()
+                .unwrap()
+                .prev_set_rel_pathlist_hook
+                .as_ref()
+                .unwrap())(root.into_pg(), rel.into_pg(), rti, rte.into_pg());
+            HookResult::new(())
+        }
+    }
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_set_join_pathlist(
+    root: *mut pg_sys::PlannerInfo,
+    joinrel: *mut pg_sys::RelOptInfo,
+    outerrel: *mut pg_sys::RelOptInfo,
+    innerrel: *mut pg_sys::RelOptInfo,
+    jointype: pg_sys::JoinType,
+    extra: *mut pg_sys::JoinPathExtraData,
+) {
+    set_join_pathlist(
+        0,
+        PgBox::from_pg(root),
+        PgBox::from_pg(joinrel),
+        PgBox::from_pg(outerrel),
+        PgBox::from_pg(innerrel),
+        jointype,
+        PgBox::from_pg(extra),
+    );
+}
+
+unsafe fn set_join_pathlist(
+    index: usize,
+    root: PgBox<pg_sys::PlannerInfo>,
+    joinrel: PgBox<pg_sys::RelOptInfo>,
+    outerrel: PgBox<pg_sys::RelOptInfo>,
+    innerrel: PgBox<pg_sys::RelOptInfo>,
+    jointype: pg_sys::JoinType,
+    extra: PgBox<pg_sys::JoinPathExtraData>,
+) -> HookResult<()> {
+    fn prev(
+        root: PgBox<pg_sys::PlannerInfo>,
+        joinrel: PgBox<pg_sys::RelOptInfo>,
+        outerrel: PgBox<pg_sys::RelOptInfo>,
+        innerrel: PgBox<pg_sys::RelOptInfo>,
+        jointype: pg_sys::JoinType,
+        extra: PgBox<pg_sys::JoinPathExtraData>,
+    ) -> HookResult<()> {
+        unsafe {
+            set_join_pathlist(
+                next_index(),
+                root,
+                joinrel,
+                outerrel,
+                innerrel,
+                jointype,
+                extra,
+            )
+        }
+    }
+    match hook_at(index, PgHookPoints::SET_JOIN_PATHLIST) {
+        Some((hook, _running)) => {
+            hook.set_join_pathlist(root, joinrel, outerrel, innerrel, jointype, extra, prev)
+        }
+        None => {
+            (HOOKS
+                .as_mut()
+                .unwrap()
+                .prev_set_join_pathlist_hook
+                .as_ref()
+                .unwrap())(
+                root.into_pg(),
+                joinrel.into_pg(),
+                outerrel.into_pg(),
+                innerrel.into_pg(),
+                jointype,
+                extra.into_pg(),
+            );
+            HookResult::new(())
+        }
+    }
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+#[pg_guard]
+unsafe extern "C" fn pgx_post_parse_analyze(
+    pstate: *mut pg_sys::ParseState,
+    query: *mut pg_sys::Query,
+) {
+    post_parse_analyze(0, PgBox::from_pg(pstate), PgBox::from_pg(query), None);
+}
+
+#[cfg(feature = "pg14")]
+#[pg_guard]
+unsafe extern "C" fn pgx_post_parse_analyze(
+    pstate: *mut pg_sys::ParseState,
+    query: *mut pg_sys::Query,
+    jstate: *mut pg_sys::JumbleState,
+) {
+    let jumble_state = if jstate.is_null() {
+        None
+    } else {
+        Some(PgBox::from_pg(jstate))
+    };
+    post_parse_analyze(
+        0,
+        PgBox::from_pg(pstate),
+        PgBox::from_pg(query),
+        jumble_state,
+    );
+}
+
+unsafe fn post_parse_analyze(
+    index: usize,
+    pstate: PgBox<pg_sys::ParseState>,
+    query: PgBox<pg_sys::Query>,
+    jumble_state: Option<PgBox<JumbleState>>,
+) -> HookResult<()> {
+    fn prev(
+        pstate: PgBox<pg_sys::ParseState>,
+        query: PgBox<pg_sys::Query>,
+        jumble_state: Option<PgBox<JumbleState>>,
+    ) -> HookResult<()> {
+        unsafe { post_parse_analyze(next_index(), pstate, query, jumble_state) }
+    }
+    match hook_at(index, PgHookPoints::POST_PARSE_ANALYZE) {
+        Some((hook, _running)) => hook.post_parse_analyze(pstate, query, jumble_state, prev),
+        None => {
+            #[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+            {
+                (HOOKS
+                    .as_mut()
+                    .unwrap()
+                    .prev_post_parse_analyze_hook
+                    .as_ref()
+                    .unwrap())(pstate.into_pg(), query.into_pg())
+            }
+
+            #[cfg(feature = "pg14")]
+            {
+                (HOOKS
+                    .as_mut()
+                    .unwrap()
+                    .prev_post_parse_analyze_hook
+                    .as_ref()
+                    .unwrap())(
+                    pstate.into_pg(),
+                    query.into_pg(),
+                    jumble_state.map_or(std::ptr::null_mut(), |jstate| jstate.into_pg()),
+                )
+            }
+            HookResult::new(())
+        }
+    }
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_client_authentication(port: *mut pg_sys::Port, status: i32) {
+    client_authentication(0, PgBox::from_pg(port), status);
+}
+
+unsafe fn client_authentication(
+    index: usize,
+    port: PgBox<pg_sys::Port>,
+    status: i32,
+) -> HookResult<()> {
+    fn prev(port: PgBox<pg_sys::Port>, status: i32) -> HookResult<()> {
+        unsafe { client_authentication(next_index(), port, status) }
+    }
+    match hook_at(index, PgHookPoints::CLIENT_AUTHENTICATION) {
+        Some((hook, _running)) => hook.client_authentication(port, status, prev),
+        None => {
+            (HOOKS
+                .as_mut()
+                .unwrap()
+                .prev_client_authentication_hook
+                .as_ref()
+                .unwrap())(port.into_pg(), status);
+            HookResult::new(())
+        }
+    }
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_emit_log(error_data: *mut pg_sys::ErrorData) {
+    emit_log(0, PgBox::from_pg(error_data));
+}
+
+unsafe fn emit_log(index: usize, error_data: PgBox<pg_sys::ErrorData>) -> HookResult<()> {
+    fn prev(error_data: PgBox<pg_sys::ErrorData>) -> HookResult<()> {
+        unsafe { emit_log(next_index(), error_data) }
+    }
+    match hook_at(index, PgHookPoints::EMIT_LOG) {
+        Some((hook, _running)) => hook.emit_log(error_data, prev),
+        None => {
+            (HOOKS.as_mut().unwrap().prev_emit_log_hook.as_ref().unwrap())(error_data.into_pg());
+            HookResult::new(())
+        }
+    }
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_object_access(
+    access: pg_sys::ObjectAccessType,
+    class_id: pg_sys::Oid,
+    object_id: pg_sys::Oid,
+    sub_id: i32,
+    arg: void_mut_ptr,
+) {
+    object_access(0, access, class_id, object_id, sub_id, arg);
+}
+
+unsafe fn object_access(
+    index: usize,
+    access: pg_sys::ObjectAccessType,
+    class_id: pg_sys::Oid,
+    object_id: pg_sys::Oid,
+    sub_id: i32,
+    arg: void_mut_ptr,
+) -> HookResult<()> {
+    fn prev(
+        access: pg_sys::ObjectAccessType,
+        class_id: pg_sys::Oid,
+        object_id: pg_sys::Oid,
+        sub_id: i32,
+        arg: void_mut_ptr,
+    ) -> HookResult<()> {
+        unsafe { object_access(next_index(), access, class_id, object_id, sub_id, arg) }
+    }
+    match hook_at(index, PgHookPoints::OBJECT_ACCESS) {
+        Some((hook, _running)) => {
+            hook.object_access(access, class_id, object_id, sub_id, arg, prev)
+        }
+        None => {
+            (HOOKS
+                .as_mut()
+                .unwrap()
+                .prev_object_access_hook
+                .as_ref()
+                .unwrap())(access, class_id, object_id, sub_id, arg);
+            HookResult::new(())
+        }
+    }
+}
+
+#[cfg(feature = "pg10")]
+#[pg_guard]
+unsafe extern "C" fn pgx_explain_one_query(
+    query: *mut pg_sys::Query,
+    cursor_options: i32,
+    into: *mut pg_sys::IntoClause,
+    es: *mut pg_sys::ExplainState,
+    query_string: *const ::std::os::raw::c_char,
+    params: pg_sys::ParamListInfo,
+) {
+    explain_one_query(
+        0,
+        PgBox::from_pg(query),
+        cursor_options,
+        PgBox::from_pg(into),
+        PgBox::from_pg(es),
         query_string,
+        PgBox::from_pg(params),
+        PgBox::from_pg(std::ptr::null_mut()),
+    );
+}
+
+#[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+#[pg_guard]
+unsafe extern "C" fn pgx_explain_one_query(
+    query: *mut pg_sys::Query,
+    cursor_options: i32,
+    into: *mut pg_sys::IntoClause,
+    es: *mut pg_sys::ExplainState,
+    query_string: *const ::std::os::raw::c_char,
+    params: pg_sys::ParamListInfo,
+    query_env: *mut pg_sys::QueryEnvironment,
+) {
+    explain_one_query(
+        0,
+        PgBox::from_pg(query),
         cursor_options,
-        PgBox::from_pg(bound_params),
-        prev,
-    )
-    .inner
+        PgBox::from_pg(into),
+        PgBox::from_pg(es),
+        query_string,
+        PgBox::from_pg(params),
+        PgBox::from_pg(query_env),
+    );
+}
+
+unsafe fn explain_one_query(
+    index: usize,
+    query: PgBox<pg_sys::Query>,
+    cursor_options: i32,
+    into: PgBox<pg_sys::IntoClause>,
+    es: PgBox<pg_sys::ExplainState>,
+    query_string: *const ::std::os::raw::c_char,
+    params: PgBox<pg_sys::ParamListInfoData>,
+    query_env: PgBox<pg_sys::QueryEnvironment>,
+) -> HookResult<()> {
+    fn prev(
+        query: PgBox<pg_sys::Query>,
+        cursor_options: i32,
+        into: PgBox<pg_sys::IntoClause>,
+        es: PgBox<pg_sys::ExplainState>,
+        query_string: *const ::std::os::raw::c_char,
+        params: PgBox<pg_sys::ParamListInfoData>,
+        query_env: PgBox<pg_sys::QueryEnvironment>,
+    ) -> HookResult<()> {
+        unsafe {
+            explain_one_query(
+                next_index(),
+                query,
+                cursor_options,
+                into,
+                es,
+                query_string,
+                params,
+                query_env,
+            )
+        }
+    }
+    match hook_at(index, PgHookPoints::EXPLAIN_ONE_QUERY) {
+        Some((hook, _running)) => hook.explain_one_query(
+            query,
+            cursor_options,
+            into,
+            es,
+            query_string,
+            params,
+            query_env,
+            prev,
+        ),
+        None => {
+            #[cfg(feature = "pg10")]
+            {
+                (HOOKS
+                    .as_mut()
+                    .unwrap()
+                    .prev_explain_one_query_hook
+                    .as_ref()
+                    .unwrap())(
+                    query.into_pg(),
+                    cursor_options,
+                    into.into_pg(),
+                    es.into_pg(),
+                    query_string,
+                    params.into_pg(),
+                )
+            }
+
+            #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+            {
+                (HOOKS
+                    .as_mut()
+                    .unwrap()
+                    .prev_explain_one_query_hook
+                    .as_ref()
+                    .unwrap())(
+                    query.into_pg(),
+                    cursor_options,
+                    into.into_pg(),
+                    es.into_pg(),
+                    query_string,
+                    params.into_pg(),
+                    query_env.into_pg(),
+                )
+            }
+            HookResult::new(())
+        }
+    }
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_get_relation_info(
+    root: *mut pg_sys::PlannerInfo,
+    relation_oid: pg_sys::Oid,
+    inhparent: bool,
+    rel: *mut pg_sys::RelOptInfo,
+) {
+    get_relation_info(
+        0,
+        PgBox::from_pg(root),
+        relation_oid,
+        inhparent,
+        PgBox::from_pg(rel),
+    );
+}
+
+unsafe fn get_relation_info(
+    index: usize,
+    root: PgBox<pg_sys::PlannerInfo>,
+    relation_oid: pg_sys::Oid,
+    inhparent: bool,
+    rel: PgBox<pg_sys::RelOptInfo>,
+) -> HookResult<()> {
+    fn prev(
+        root: PgBox<pg_sys::PlannerInfo>,
+        relation_oid: pg_sys::Oid,
+        inhparent: bool,
+        rel: PgBox<pg_sys::RelOptInfo>,
+    ) -> HookResult<()> {
+        unsafe { get_relation_info(next_index(), root, relation_oid, inhparent, rel) }
+    }
+    match hook_at(index, PgHookPoints::GET_RELATION_INFO) {
+        Some((hook, _running)) => hook.get_relation_info(root, relation_oid, inhparent, rel, prev),
+        None => {
+            (HOOKS
+                .as_mut()
+                .unwrap()
+                .prev_get_relation_info_hook
+                .as_ref()
+                .unwrap())(root.into_pg(), relation_oid, inhparent, rel.into_pg());
+            HookResult::new(())
+        }
+    }
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_shmem_startup() {
+    shmem_startup(0);
+}
+
+unsafe fn shmem_startup(index: usize) -> HookResult<()> {
+    fn prev() -> HookResult<()> {
+        unsafe { shmem_startup(next_index()) }
+    }
+    match hook_at(index, PgHookPoints::SHMEM_STARTUP) {
+        Some((hook, _running)) => hook.shmem_startup(prev),
+        None => {
+            (HOOKS
+                .as_mut()
+                .unwrap()
+                .prev_shmem_startup_hook
+                .as_ref()
+                .unwrap())();
+            HookResult::new(())
+        }
+    }
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_needs_fmgr(fn_oid: pg_sys::Oid) -> bool {
+    needs_fmgr(0, fn_oid).inner
+}
+
+unsafe fn needs_fmgr(index: usize, fn_oid: pg_sys::Oid) -> HookResult<bool> {
+    fn prev(fn_oid: pg_sys::Oid) -> HookResult<bool> {
+        unsafe { needs_fmgr(next_index(), fn_oid) }
+    }
+    match hook_at(index, PgHookPoints::FMGR) {
+        Some((hook, _running)) => hook.needs_fmgr(fn_oid, prev),
+        None => HookResult::new((HOOKS
+            .as_mut()
+            .unwrap()
+            .prev_needs_fmgr_hook
+            .as_ref()
+            .unwrap())(fn_oid)),
+    }
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_fmgr(
+    event: pg_sys::FmgrHookEventType,
+    flinfo: *mut pg_sys::FmgrInfo,
+    arg: *mut pg_sys::Datum,
+) {
+    fmgr(0, event, PgBox::from_pg(flinfo), arg);
+}
+
+unsafe fn fmgr(
+    index: usize,
+    event: pg_sys::FmgrHookEventType,
+    flinfo: PgBox<pg_sys::FmgrInfo>,
+    arg: *mut pg_sys::Datum,
+) -> HookResult<()> {
+    fn prev(
+        event: pg_sys::FmgrHookEventType,
+        flinfo: PgBox<pg_sys::FmgrInfo>,
+        arg: *mut pg_sys::Datum,
+    ) -> HookResult<()> {
+        unsafe { fmgr(next_index(), event, flinfo, arg) }
+    }
+    match hook_at(index, PgHookPoints::FMGR) {
+        Some((hook, _running)) => hook.fmgr(event, flinfo, arg, prev),
+        None => {
+            (HOOKS.as_mut().unwrap().prev_fmgr_hook.as_ref().unwrap())(
+                event,
+                flinfo.into_pg(),
+                arg,
+            );
+            HookResult::new(())
+        }
+    }
 }
 
 #[pg_guard]
@@ -582,3 +1739,140 @@ unsafe extern "C" fn pgx_standard_planner_wrapper(
 ) -> *mut pg_sys::PlannedStmt {
     pg_sys::standard_planner(parse, query_string, cursor_options, bound_params)
 }
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_set_rel_pathlist_wrapper(
+    _root: *mut pg_sys::PlannerInfo,
+    _rel: *mut pg_sys::RelOptInfo,
+    _rti: pg_sys::Index,
+    _rte: *mut pg_sys::RangeTblEntry,
+) {
+    // Postgres has no standard implementation of this hook
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_set_join_pathlist_wrapper(
+    _root: *mut pg_sys::PlannerInfo,
+    _joinrel: *mut pg_sys::RelOptInfo,
+    _outerrel: *mut pg_sys::RelOptInfo,
+    _innerrel: *mut pg_sys::RelOptInfo,
+    _jointype: pg_sys::JoinType,
+    _extra: *mut pg_sys::JoinPathExtraData,
+) {
+    // Postgres has no standard implementation of this hook
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_post_parse_analyze_wrapper(
+    _pstate: *mut pg_sys::ParseState,
+    _query: *mut pg_sys::Query,
+) {
+    // Postgres has no standard implementation of this hook
+}
+
+#[cfg(feature = "pg14")]
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_post_parse_analyze_wrapper(
+    _pstate: *mut pg_sys::ParseState,
+    _query: *mut pg_sys::Query,
+    _jstate: *mut pg_sys::JumbleState,
+) {
+    // Postgres has no standard implementation of this hook
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_client_authentication_wrapper(
+    _port: *mut pg_sys::Port,
+    _status: i32,
+) {
+    // Postgres has no standard implementation of this hook
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_emit_log_wrapper(_error_data: *mut pg_sys::ErrorData) {
+    // Postgres has no standard implementation of this hook
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_object_access_wrapper(
+    _access: pg_sys::ObjectAccessType,
+    _class_id: pg_sys::Oid,
+    _object_id: pg_sys::Oid,
+    _sub_id: i32,
+    _arg: void_mut_ptr,
+) {
+    // Postgres has no standard implementation of this hook
+}
+
+#[cfg(feature = "pg10")]
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_explain_one_query_wrapper(
+    query: *mut pg_sys::Query,
+    cursor_options: i32,
+    into: *mut pg_sys::IntoClause,
+    es: *mut pg_sys::ExplainState,
+    query_string: *const ::std::os::raw::c_char,
+    params: pg_sys::ParamListInfo,
+) {
+    pg_sys::pgx_standard_ExplainOneQuery(
+        query,
+        cursor_options,
+        into,
+        es,
+        query_string,
+        params,
+        std::ptr::null_mut(),
+    )
+}
+
+#[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_explain_one_query_wrapper(
+    query: *mut pg_sys::Query,
+    cursor_options: i32,
+    into: *mut pg_sys::IntoClause,
+    es: *mut pg_sys::ExplainState,
+    query_string: *const ::std::os::raw::c_char,
+    params: pg_sys::ParamListInfo,
+    query_env: *mut pg_sys::QueryEnvironment,
+) {
+    pg_sys::pgx_standard_ExplainOneQuery(
+        query,
+        cursor_options,
+        into,
+        es,
+        query_string,
+        params,
+        query_env,
+    )
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_get_relation_info_wrapper(
+    _root: *mut pg_sys::PlannerInfo,
+    _relation_oid: pg_sys::Oid,
+    _inhparent: bool,
+    _rel: *mut pg_sys::RelOptInfo,
+) {
+    // Postgres has no standard implementation of this hook
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_shmem_startup_wrapper() {
+    // Postgres has no standard implementation of this hook
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_needs_fmgr_wrapper(_fn_oid: pg_sys::Oid) -> bool {
+    false
+}
+
+#[pg_guard]
+unsafe extern "C" fn pgx_standard_fmgr_wrapper(
+    _event: pg_sys::FmgrHookEventType,
+    _flinfo: *mut pg_sys::FmgrInfo,
+    _arg: *mut pg_sys::Datum,
+) {
+    // Postgres has no standard implementation of this hook
+}
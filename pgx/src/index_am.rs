@@ -0,0 +1,658 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+/*!
+
+[Index Access Method](https://www.postgresql.org/docs/current/indexam.html) support.
+
+Index access methods are created by implementing [`IndexAm`] for a type and decorating the
+implementation with [`#[pg_index_am]`](pgx_macros::pg_index_am).
+
+The macro generates the `index_am_handler` function Postgres uses to discover the access method's
+callbacks, along with the [`CREATE ACCESS METHOD`](https://www.postgresql.org/docs/current/sql-create-access-method.html)
+statement that registers it.
+
+# Example
+
+```rust,no_run
+use pgx::*;
+
+pub struct NoopAm;
+
+#[pg_index_am]
+impl IndexAm for NoopAm {
+    const NAME: &'static str = "noop";
+
+    fn build(
+        _heap_relation: &PgRelation,
+        _index_relation: &PgRelation,
+        _index_info: &mut pg_sys::IndexInfo,
+    ) -> IndexBuildResult {
+        IndexBuildResult::default()
+    }
+
+    fn build_empty(_index_relation: &PgRelation) {}
+
+    fn insert(
+        _index_relation: &PgRelation,
+        _values: &[Option<pg_sys::Datum>],
+        _heap_tid: pg_sys::ItemPointerData,
+        _heap_relation: &PgRelation,
+        _check_unique: pg_sys::IndexUniqueCheck,
+        _index_info: &mut pg_sys::IndexInfo,
+    ) -> bool {
+        false
+    }
+
+    fn bulk_delete(
+        _info: &pg_sys::IndexVacuumInfo,
+        stats: Option<PgBox<pg_sys::IndexBulkDeleteResult>>,
+        _callback: BulkDeleteCallback,
+    ) -> Option<PgBox<pg_sys::IndexBulkDeleteResult>> {
+        stats
+    }
+
+    fn vacuum_cleanup(
+        _info: &pg_sys::IndexVacuumInfo,
+        stats: Option<PgBox<pg_sys::IndexBulkDeleteResult>>,
+    ) -> Option<PgBox<pg_sys::IndexBulkDeleteResult>> {
+        stats
+    }
+
+    fn cost_estimate(
+        root: &mut pg_sys::PlannerInfo,
+        path: &mut pg_sys::IndexPath,
+        loop_count: f64,
+    ) -> IndexCosts {
+        IndexCosts::generic(root, path, loop_count)
+    }
+
+    fn begin_scan(_index_relation: &PgRelation, _nkeys: i32, _norderbys: i32) -> Self::ScanState {}
+
+    fn rescan(_scan: &mut PgIndexScan<Self::ScanState>) {}
+
+    fn get_tuple(_scan: &mut PgIndexScan<Self::ScanState>, _direction: pg_sys::ScanDirection) -> bool {
+        false
+    }
+}
+```
+
+This creates SQL like so:
+
+```sql
+-- src/lib.rs:5
+-- index_am::NoopAm
+CREATE ACCESS METHOD noop TYPE INDEX HANDLER noop_am_handler;
+```
+
+Any of the optional functions (`get_tuple`, `get_bitmap`, `end_scan`, `options`, `validate`) can
+be left out, `#[pg_index_am]` will create a stub and, where Postgres allows it, leave the
+corresponding `IndexAmRoutine` callback unset.
+*/
+use crate::{pg_sys, PgBox, PgMemoryContexts, PgRelation};
+use std::marker::PhantomData;
+
+/// The statistics an [`IndexAm::build`] reports back to Postgres.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct IndexBuildResult {
+    /// The number of tuples in the heap.
+    pub heap_tuples: f64,
+    /// The number of tuples added to the index.
+    pub index_tuples: f64,
+}
+
+/// The costs an [`IndexAm::cost_estimate`] reports back to the planner.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct IndexCosts {
+    pub startup_cost: pg_sys::Cost,
+    pub total_cost: pg_sys::Cost,
+    pub selectivity: pg_sys::Selectivity,
+    pub correlation: f64,
+    pub pages: f64,
+}
+
+impl IndexCosts {
+    /// Estimate costs using Postgres' `genericcostestimate()`, which is what most of the built-in
+    /// index access methods start from.
+    pub fn generic(root: &mut pg_sys::PlannerInfo, path: &mut pg_sys::IndexPath, loop_count: f64) -> Self {
+        let costs = PgBox::<pg_sys::GenericCosts>::alloc0();
+        unsafe {
+            #[cfg(any(feature = "pg10", feature = "pg11"))]
+            pg_sys::genericcostestimate(
+                root,
+                path,
+                loop_count,
+                pg_sys::deconstruct_indexquals(path),
+                costs.as_ptr(),
+            );
+
+            #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+            pg_sys::genericcostestimate(root, path, loop_count, costs.as_ptr());
+        }
+
+        IndexCosts {
+            startup_cost: costs.indexStartupCost,
+            total_cost: costs.indexTotalCost,
+            selectivity: costs.indexSelectivity,
+            correlation: costs.indexCorrelation,
+            pages: costs.numIndexPages,
+        }
+    }
+}
+
+/// The callback Postgres hands to [`IndexAm::bulk_delete`] to decide which index entries point
+/// to dead heap tuples.
+pub struct BulkDeleteCallback {
+    callback: pg_sys::IndexBulkDeleteCallback,
+    state: *mut std::os::raw::c_void,
+}
+
+impl BulkDeleteCallback {
+    /// Returns `true` if the index entry pointing at `heap_tid` should be removed.
+    pub fn is_dead(&self, heap_tid: &mut pg_sys::ItemPointerData) -> bool {
+        match self.callback {
+            Some(callback) => unsafe { callback(heap_tid, self.state) },
+            None => false,
+        }
+    }
+}
+
+/// A `pg_sys::TIDBitmap` being filled by [`IndexAm::get_bitmap`].
+pub struct PgTidBitmap(*mut pg_sys::TIDBitmap);
+
+impl PgTidBitmap {
+    /// Add the specified heap tuple ids to the bitmap.
+    ///
+    /// Set `recheck` if the index conditions must be rechecked against the heap tuples.
+    pub fn add(&mut self, heap_tids: &[pg_sys::ItemPointerData], recheck: bool) {
+        unsafe {
+            pg_sys::tbm_add_tuples(
+                self.0,
+                heap_tids.as_ptr() as *mut _,
+                heap_tids.len() as i32,
+                recheck,
+            )
+        }
+    }
+
+    pub fn as_ptr(&self) -> *mut pg_sys::TIDBitmap {
+        self.0
+    }
+}
+
+/// A safe wrapper around an in-progress index scan (`pg_sys::IndexScanDesc`).
+///
+/// `S` is the [`IndexAm::ScanState`] returned by [`IndexAm::begin_scan`].
+pub struct PgIndexScan<S> {
+    desc: PgBox<pg_sys::IndexScanDescData>,
+    __marker: PhantomData<S>,
+}
+
+impl<S> PgIndexScan<S> {
+    unsafe fn from_pg(desc: pg_sys::IndexScanDesc) -> Self {
+        PgIndexScan {
+            desc: PgBox::from_pg(desc),
+            __marker: PhantomData,
+        }
+    }
+
+    /// The index being scanned.
+    pub fn index_relation(&self) -> PgRelation {
+        unsafe { PgRelation::from_pg(self.desc.indexRelation) }
+    }
+
+    /// The heap the index belongs to, if Postgres provided one.
+    pub fn heap_relation(&self) -> Option<PgRelation> {
+        if self.desc.heapRelation.is_null() {
+            None
+        } else {
+            Some(unsafe { PgRelation::from_pg(self.desc.heapRelation) })
+        }
+    }
+
+    /// The scan keys for the current (re)scan.
+    pub fn keys(&self) -> &[pg_sys::ScanKeyData] {
+        unsafe { scan_keys(self.desc.keyData, self.desc.numberOfKeys) }
+    }
+
+    /// The `ORDER BY` keys for the current (re)scan.
+    pub fn order_bys(&self) -> &[pg_sys::ScanKeyData] {
+        unsafe { scan_keys(self.desc.orderByData, self.desc.numberOfOrderBys) }
+    }
+
+    /// The state returned by [`IndexAm::begin_scan`].
+    pub fn state(&self) -> &S {
+        unsafe { &*(self.desc.opaque as *const S) }
+    }
+
+    /// The state returned by [`IndexAm::begin_scan`].
+    pub fn state_mut(&mut self) -> &mut S {
+        unsafe { &mut *(self.desc.opaque as *mut S) }
+    }
+
+    /// Set the heap tuple id the scan returns from [`IndexAm::get_tuple`].
+    pub fn set_heap_tid(&mut self, heap_tid: pg_sys::ItemPointerData) {
+        #[cfg(any(feature = "pg10", feature = "pg11"))]
+        {
+            self.desc.xs_ctup.t_self = heap_tid;
+        }
+
+        #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+        {
+            self.desc.xs_heaptid = heap_tid;
+        }
+    }
+
+    /// Tell the executor the index conditions must be rechecked against the heap tuple.
+    pub fn set_recheck(&mut self, recheck: bool) {
+        self.desc.xs_recheck = recheck;
+    }
+
+    pub fn as_ptr(&self) -> pg_sys::IndexScanDesc {
+        self.desc.as_ptr()
+    }
+}
+
+unsafe fn scan_keys<'a>(keys: *mut pg_sys::ScanKeyData, nkeys: i32) -> &'a [pg_sys::ScanKeyData] {
+    if keys.is_null() || nkeys <= 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(keys, nkeys as usize)
+    }
+}
+
+/// An index access method.
+///
+/// Implementations should be decorated with [`#[pg_index_am]`](pgx_macros::pg_index_am), which
+/// generates the handler function and the `CREATE ACCESS METHOD` SQL.
+pub trait IndexAm {
+    /// The name of the access method, as used in `CREATE INDEX ... USING name`.
+    ///
+    /// **Optional:** This const can be skipped, `#[pg_index_am]` will use the snake cased name of the type.
+    const NAME: &'static str;
+
+    /// Per-scan state, created in [`IndexAm::begin_scan`].
+    ///
+    /// The state is dropped when the memory context the scan was started in is deleted.
+    ///
+    /// **Optional:** This type can be skipped, `#[pg_index_am]` will use `()`.
+    type ScanState;
+
+    /// Total number of strategies (operators) by which we can traverse/search this AM.
+    /// Zero if AM does not have a fixed set of strategy assignments.
+    const STRATEGIES: u16 = 0;
+    /// Total number of support functions that this AM uses.
+    const SUPPORT: u16 = 0;
+    /// Does AM support ORDER BY indexed column's value?
+    const CAN_ORDER: bool = false;
+    /// Does AM support ORDER BY result of an operator on indexed column?
+    const CAN_ORDER_BY_OP: bool = false;
+    /// Does AM support backward scanning?
+    const CAN_BACKWARD: bool = false;
+    /// Does AM support UNIQUE indexes?
+    const CAN_UNIQUE: bool = false;
+    /// Does AM support multi-column indexes?
+    const CAN_MULTI_COL: bool = false;
+    /// Does AM require scans to have a constraint on the first index column?
+    const OPTIONAL_KEY: bool = false;
+    /// Does AM handle ScalarArrayOpExpr quals?
+    const SEARCH_ARRAY: bool = false;
+    /// Does AM handle IS NULL/IS NOT NULL quals?
+    const SEARCH_NULLS: bool = false;
+    /// Can index storage data type differ from column data type?
+    const STORAGE: bool = false;
+    /// Can an index of this type be clustered on?
+    const CLUSTERABLE: bool = false;
+    /// Does AM handle predicate locks?
+    const PRED_LOCKS: bool = false;
+    /// Does AM support parallel scan?
+    const CAN_PARALLEL: bool = false;
+    /// Does AM support columns included with clause INCLUDE?
+    ///
+    /// Ignored on Postgres 10, which doesn't support `INCLUDE`.
+    const CAN_INCLUDE: bool = false;
+    /// Type of data stored in index, or `InvalidOid` if variable.
+    const KEY_TYPE: pg_sys::Oid = pg_sys::InvalidOid;
+
+    /// Build a new index over the existing contents of `heap_relation`.
+    fn build(
+        heap_relation: &PgRelation,
+        index_relation: &PgRelation,
+        index_info: &mut pg_sys::IndexInfo,
+    ) -> IndexBuildResult;
+
+    /// Build an empty index, and write it to the initialization fork of an unlogged index.
+    fn build_empty(index_relation: &PgRelation);
+
+    /// Insert a new tuple into an existing index.
+    ///
+    /// `values` has one entry per index column, `None` for SQL `NULL`.
+    fn insert(
+        index_relation: &PgRelation,
+        values: &[Option<pg_sys::Datum>],
+        heap_tid: pg_sys::ItemPointerData,
+        heap_relation: &PgRelation,
+        check_unique: pg_sys::IndexUniqueCheck,
+        index_info: &mut pg_sys::IndexInfo,
+    ) -> bool;
+
+    /// Delete tuples from the index, as directed by `callback`.
+    fn bulk_delete(
+        info: &pg_sys::IndexVacuumInfo,
+        stats: Option<PgBox<pg_sys::IndexBulkDeleteResult>>,
+        callback: BulkDeleteCallback,
+    ) -> Option<PgBox<pg_sys::IndexBulkDeleteResult>>;
+
+    /// Clean up after a `VACUUM` operation.
+    ///
+    /// `stats` is whatever the last [`IndexAm::bulk_delete`] returned, if it was called at all.
+    fn vacuum_cleanup(
+        info: &pg_sys::IndexVacuumInfo,
+        stats: Option<PgBox<pg_sys::IndexBulkDeleteResult>>,
+    ) -> Option<PgBox<pg_sys::IndexBulkDeleteResult>>;
+
+    /// Estimate the costs of an index scan.
+    fn cost_estimate(
+        root: &mut pg_sys::PlannerInfo,
+        path: &mut pg_sys::IndexPath,
+        loop_count: f64,
+    ) -> IndexCosts;
+
+    /// Parse and validate the `reloptions` of an index.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_index_am]` will create a stub which ignores any options.
+    fn options(reloptions: pg_sys::Datum, validate: bool) -> Option<PgBox<pg_sys::bytea>>;
+
+    /// Validate the operator class `opclass_oid`.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_index_am]` will create a stub which accepts all operator classes.
+    fn validate(opclass_oid: pg_sys::Oid) -> bool;
+
+    /// Prepare for an index scan.
+    fn begin_scan(index_relation: &PgRelation, nkeys: i32, norderbys: i32) -> Self::ScanState;
+
+    /// Start or restart an index scan, possibly with new scan keys.
+    fn rescan(scan: &mut PgIndexScan<Self::ScanState>);
+
+    /// Fetch the next tuple in the given scan, returning `false` if there are no more.
+    ///
+    /// Call [`PgIndexScan::set_heap_tid`] with the heap tuple id of a match.
+    ///
+    /// **Optional:** This function can be skipped, but at least one of `get_tuple` and `get_bitmap` must be provided.
+    fn get_tuple(scan: &mut PgIndexScan<Self::ScanState>, direction: pg_sys::ScanDirection) -> bool;
+
+    /// Fetch all tuples in the given scan and add them to `bitmap`, returning how many were found.
+    ///
+    /// **Optional:** This function can be skipped, but at least one of `get_tuple` and `get_bitmap` must be provided.
+    fn get_bitmap(scan: &mut PgIndexScan<Self::ScanState>, bitmap: &mut PgTidBitmap) -> i64;
+
+    /// End a scan and release resources.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_index_am]` will create a stub.
+    fn end_scan(scan: &mut PgIndexScan<Self::ScanState>);
+}
+
+/// Create the `pg_sys::IndexAmRoutine` describing `T`.
+///
+/// This is called by the handler function `#[pg_index_am]` generates.
+pub fn index_am_routine<T: IndexAm>() -> PgBox<pg_sys::IndexAmRoutine> {
+    let mut routine =
+        PgBox::<pg_sys::IndexAmRoutine>::alloc_node(pg_sys::NodeTag_T_IndexAmRoutine);
+
+    routine.amstrategies = T::STRATEGIES;
+    routine.amsupport = T::SUPPORT;
+    routine.amcanorder = T::CAN_ORDER;
+    routine.amcanorderbyop = T::CAN_ORDER_BY_OP;
+    routine.amcanbackward = T::CAN_BACKWARD;
+    routine.amcanunique = T::CAN_UNIQUE;
+    routine.amcanmulticol = T::CAN_MULTI_COL;
+    routine.amoptionalkey = T::OPTIONAL_KEY;
+    routine.amsearcharray = T::SEARCH_ARRAY;
+    routine.amsearchnulls = T::SEARCH_NULLS;
+    routine.amstorage = T::STORAGE;
+    routine.amclusterable = T::CLUSTERABLE;
+    routine.ampredlocks = T::PRED_LOCKS;
+    routine.amcanparallel = T::CAN_PARALLEL;
+    #[cfg(not(feature = "pg10"))]
+    {
+        routine.amcaninclude = T::CAN_INCLUDE;
+    }
+    routine.amkeytype = T::KEY_TYPE;
+
+    routine.ambuild = Some(ambuild::<T>);
+    routine.ambuildempty = Some(ambuildempty::<T>);
+    routine.aminsert = Some(aminsert::<T>);
+    routine.ambulkdelete = Some(ambulkdelete::<T>);
+    routine.amvacuumcleanup = Some(amvacuumcleanup::<T>);
+    routine.amcostestimate = Some(amcostestimate::<T>);
+    routine.amoptions = Some(amoptions::<T>);
+    routine.amvalidate = Some(amvalidate::<T>);
+    routine.ambeginscan = Some(ambeginscan::<T>);
+    routine.amrescan = Some(amrescan::<T>);
+    routine.amgettuple = Some(amgettuple::<T>);
+    routine.amgetbitmap = Some(amgetbitmap::<T>);
+    routine.amendscan = Some(amendscan::<T>);
+
+    routine.into_pg_boxed()
+}
+
+unsafe extern "C" fn ambuild<T: IndexAm>(
+    heap_relation: pg_sys::Relation,
+    index_relation: pg_sys::Relation,
+    index_info: *mut pg_sys::IndexInfo,
+) -> *mut pg_sys::IndexBuildResult {
+    pg_sys::guard::guard(|| {
+        let heap_relation = PgRelation::from_pg(heap_relation);
+        let index_relation = PgRelation::from_pg(index_relation);
+        let built = T::build(&heap_relation, &index_relation, &mut *index_info);
+
+        let mut result = PgBox::<pg_sys::IndexBuildResult>::alloc0();
+        result.heap_tuples = built.heap_tuples;
+        result.index_tuples = built.index_tuples;
+        result.into_pg()
+    })
+}
+
+unsafe extern "C" fn ambuildempty<T: IndexAm>(index_relation: pg_sys::Relation) {
+    pg_sys::guard::guard(|| T::build_empty(&PgRelation::from_pg(index_relation)))
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12", feature = "pg13"))]
+unsafe extern "C" fn aminsert<T: IndexAm>(
+    index_relation: pg_sys::Relation,
+    values: *mut pg_sys::Datum,
+    isnull: *mut bool,
+    heap_tid: pg_sys::ItemPointer,
+    heap_relation: pg_sys::Relation,
+    check_unique: pg_sys::IndexUniqueCheck,
+    index_info: *mut pg_sys::IndexInfo,
+) -> bool {
+    pg_sys::guard::guard(|| {
+        insert::<T>(
+            index_relation,
+            values,
+            isnull,
+            heap_tid,
+            heap_relation,
+            check_unique,
+            index_info,
+        )
+    })
+}
+
+#[cfg(feature = "pg14")]
+unsafe extern "C" fn aminsert<T: IndexAm>(
+    index_relation: pg_sys::Relation,
+    values: *mut pg_sys::Datum,
+    isnull: *mut bool,
+    heap_tid: pg_sys::ItemPointer,
+    heap_relation: pg_sys::Relation,
+    check_unique: pg_sys::IndexUniqueCheck,
+    _index_unchanged: bool,
+    index_info: *mut pg_sys::IndexInfo,
+) -> bool {
+    pg_sys::guard::guard(|| {
+        insert::<T>(
+            index_relation,
+            values,
+            isnull,
+            heap_tid,
+            heap_relation,
+            check_unique,
+            index_info,
+        )
+    })
+}
+
+unsafe fn insert<T: IndexAm>(
+    index_relation: pg_sys::Relation,
+    values: *mut pg_sys::Datum,
+    isnull: *mut bool,
+    heap_tid: pg_sys::ItemPointer,
+    heap_relation: pg_sys::Relation,
+    check_unique: pg_sys::IndexUniqueCheck,
+    index_info: *mut pg_sys::IndexInfo,
+) -> bool {
+    let index_relation = PgRelation::from_pg(index_relation);
+    let heap_relation = PgRelation::from_pg(heap_relation);
+    let natts = index_relation.tuple_desc().len();
+    let values = std::slice::from_raw_parts(values, natts);
+    let isnull = std::slice::from_raw_parts(isnull, natts);
+    let values = values
+        .iter()
+        .zip(isnull.iter())
+        .map(|(&value, &isnull)| if isnull { None } else { Some(value) })
+        .collect::<Vec<_>>();
+
+    T::insert(
+        &index_relation,
+        &values,
+        *heap_tid,
+        &heap_relation,
+        check_unique,
+        &mut *index_info,
+    )
+}
+
+unsafe extern "C" fn ambulkdelete<T: IndexAm>(
+    info: *mut pg_sys::IndexVacuumInfo,
+    stats: *mut pg_sys::IndexBulkDeleteResult,
+    callback: pg_sys::IndexBulkDeleteCallback,
+    callback_state: *mut std::os::raw::c_void,
+) -> *mut pg_sys::IndexBulkDeleteResult {
+    pg_sys::guard::guard(|| {
+        let stats = if stats.is_null() {
+            None
+        } else {
+            Some(PgBox::from_pg(stats))
+        };
+        let callback = BulkDeleteCallback {
+            callback,
+            state: callback_state,
+        };
+        T::bulk_delete(&*info, stats, callback)
+            .map(|stats| stats.as_ptr())
+            .unwrap_or(std::ptr::null_mut())
+    })
+}
+
+unsafe extern "C" fn amvacuumcleanup<T: IndexAm>(
+    info: *mut pg_sys::IndexVacuumInfo,
+    stats: *mut pg_sys::IndexBulkDeleteResult,
+) -> *mut pg_sys::IndexBulkDeleteResult {
+    pg_sys::guard::guard(|| {
+        let stats = if stats.is_null() {
+            None
+        } else {
+            Some(PgBox::from_pg(stats))
+        };
+        T::vacuum_cleanup(&*info, stats)
+            .map(|stats| stats.as_ptr())
+            .unwrap_or(std::ptr::null_mut())
+    })
+}
+
+unsafe extern "C" fn amcostestimate<T: IndexAm>(
+    root: *mut pg_sys::PlannerInfo,
+    path: *mut pg_sys::IndexPath,
+    loop_count: f64,
+    index_startup_cost: *mut pg_sys::Cost,
+    index_total_cost: *mut pg_sys::Cost,
+    index_selectivity: *mut pg_sys::Selectivity,
+    index_correlation: *mut f64,
+    index_pages: *mut f64,
+) {
+    pg_sys::guard::guard(|| {
+        let costs = T::cost_estimate(&mut *root, &mut *path, loop_count);
+        *index_startup_cost = costs.startup_cost;
+        *index_total_cost = costs.total_cost;
+        *index_selectivity = costs.selectivity;
+        *index_correlation = costs.correlation;
+        *index_pages = costs.pages;
+    })
+}
+
+unsafe extern "C" fn amoptions<T: IndexAm>(
+    reloptions: pg_sys::Datum,
+    validate: bool,
+) -> *mut pg_sys::bytea {
+    pg_sys::guard::guard(|| {
+        T::options(reloptions, validate)
+            .map(|options| options.as_ptr())
+            .unwrap_or(std::ptr::null_mut())
+    })
+}
+
+unsafe extern "C" fn amvalidate<T: IndexAm>(opclass_oid: pg_sys::Oid) -> bool {
+    pg_sys::guard::guard(|| T::validate(opclass_oid))
+}
+
+unsafe extern "C" fn ambeginscan<T: IndexAm>(
+    index_relation: pg_sys::Relation,
+    nkeys: std::os::raw::c_int,
+    norderbys: std::os::raw::c_int,
+) -> pg_sys::IndexScanDesc {
+    pg_sys::guard::guard(|| {
+        let scan = pg_sys::RelationGetIndexScan(index_relation, nkeys, norderbys);
+        let state = T::begin_scan(&PgRelation::from_pg(index_relation), nkeys, norderbys);
+        (*scan).opaque = PgMemoryContexts::CurrentMemoryContext.leak_and_drop_on_delete(state)
+            as *mut std::os::raw::c_void;
+        scan
+    })
+}
+
+unsafe extern "C" fn amrescan<T: IndexAm>(
+    scan: pg_sys::IndexScanDesc,
+    keys: pg_sys::ScanKey,
+    nkeys: std::os::raw::c_int,
+    orderbys: pg_sys::ScanKey,
+    norderbys: std::os::raw::c_int,
+) {
+    pg_sys::guard::guard(|| {
+        if !keys.is_null() && nkeys > 0 {
+            std::ptr::copy(keys, (*scan).keyData, nkeys as usize);
+        }
+        if !orderbys.is_null() && norderbys > 0 {
+            std::ptr::copy(orderbys, (*scan).orderByData, norderbys as usize);
+        }
+        T::rescan(&mut PgIndexScan::from_pg(scan))
+    })
+}
+
+unsafe extern "C" fn amgettuple<T: IndexAm>(
+    scan: pg_sys::IndexScanDesc,
+    direction: pg_sys::ScanDirection,
+) -> bool {
+    pg_sys::guard::guard(|| T::get_tuple(&mut PgIndexScan::from_pg(scan), direction))
+}
+
+unsafe extern "C" fn amgetbitmap<T: IndexAm>(
+    scan: pg_sys::IndexScanDesc,
+    tbm: *mut pg_sys::TIDBitmap,
+) -> i64 {
+    pg_sys::guard::guard(|| T::get_bitmap(&mut PgIndexScan::from_pg(scan), &mut PgTidBitmap(tbm)))
+}
+
+unsafe extern "C" fn amendscan<T: IndexAm>(scan: pg_sys::IndexScanDesc) {
+    pg_sys::guard::guard(|| T::end_scan(&mut PgIndexScan::from_pg(scan)))
+}
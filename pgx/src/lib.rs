@@ -29,12 +29,17 @@ pub use pgx_macros::*;
 
 pub mod aggregate;
 pub mod callbacks;
+pub mod custom_scan;
 pub mod datum;
 pub mod enum_helper;
+pub mod event_trigger_support;
 pub mod fcinfo;
+pub mod fdw;
 pub mod guc;
+pub mod heap_tuple;
 pub mod hooks;
 pub mod htup;
+pub mod index_am;
 pub mod inoutfuncs;
 pub mod itemptr;
 pub mod list;
@@ -47,11 +52,14 @@ pub mod memcxt;
 pub mod misc;
 pub mod namespace;
 pub mod nodes;
+pub mod output_plugin;
 pub mod pgbox;
 pub mod rel;
 pub mod shmem;
 pub mod spi;
 pub mod stringinfo;
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+pub mod table_am;
 pub mod trigger_support;
 pub mod tupdesc;
 pub mod varlena;
@@ -61,16 +69,24 @@ pub mod xid;
 #[doc(hidden)]
 pub use once_cell;
 
+/// The arbitrary-precision decimal crate [`Numeric`] converts to and from
+pub use bigdecimal;
+
 pub use aggregate::*;
 pub use atomics::*;
 pub use callbacks::*;
+pub use custom_scan::*;
 use datum::sql_entity_graph::{RustSourceOnlySqlMapping, RustSqlMapping};
 pub use datum::*;
 pub use enum_helper::*;
+pub use event_trigger_support::*;
 pub use fcinfo::*;
+pub use fdw::*;
 pub use guc::*;
+pub use heap_tuple::*;
 pub use hooks::*;
 pub use htup::*;
+pub use index_am::*;
 pub use inoutfuncs::*;
 pub use itemptr::*;
 pub use list::*;
@@ -79,11 +95,14 @@ pub use lwlock::*;
 pub use memcxt::*;
 pub use namespace::*;
 pub use nodes::*;
+pub use output_plugin::*;
 pub use pgbox::*;
 pub use rel::*;
 pub use shmem::*;
 pub use spi::*;
 pub use stringinfo::*;
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+pub use table_am::*;
 pub use trigger_support::*;
 pub use tupdesc::*;
 pub use varlena::*;
@@ -211,6 +230,11 @@ pub static DEFAULT_TYPEID_SQL_MAPPING: Lazy<HashSet<RustSqlMapping>> = Lazy::new
     map_type!(m, pgx_pg_sys::ItemPointerData, "tid");
     map_type!(m, pgx_pg_sys::Point, "point");
     map_type!(m, pgx_pg_sys::BOX, "box");
+    map_type!(m, pgx_pg_sys::LSEG, "lseg");
+    map_type!(m, pgx_pg_sys::LINE, "line");
+    map_type!(m, pgx_pg_sys::CIRCLE, "circle");
+    map_type!(m, datum::Path, "path");
+    map_type!(m, datum::Polygon, "polygon");
     map_type!(m, Date, "date");
     map_type!(m, Time, "time");
     map_type!(m, TimeWithTimeZone, "time with time zone");
@@ -218,13 +242,30 @@ pub static DEFAULT_TYPEID_SQL_MAPPING: Lazy<HashSet<RustSqlMapping>> = Lazy::new
     map_type!(m, TimestampWithTimeZone, "timestamp with time zone");
     map_type!(m, pgx_pg_sys::PlannerInfo, "internal");
     map_type!(m, datum::Internal, "internal");
-    map_type!(m, pgbox::PgBox<pgx_pg_sys::IndexAmRoutine>, "internal");
+    map_type!(m, pgbox::PgBox<pgx_pg_sys::FdwRoutine>, "fdw_handler");
+    map_type!(m, pgbox::PgBox<pgx_pg_sys::IndexAmRoutine>, "index_am_handler");
+    #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+    map_type!(m, pgbox::PgBox<pgx_pg_sys::TableAmRoutine>, "table_am_handler");
     map_type!(m, rel::PgRelation, "regclass");
     map_type!(m, datum::Numeric, "numeric");
+    map_type!(m, datum::Interval, "interval");
+    map_type!(m, datum::Range<i32>, "int4range");
+    map_type!(m, datum::Range<i64>, "int8range");
+    map_type!(m, datum::Range<datum::Numeric>, "numrange");
+    map_type!(m, datum::Range<Timestamp>, "tsrange");
+    map_type!(m, datum::Range<TimestampWithTimeZone>, "tstzrange");
+    map_type!(m, datum::Range<Date>, "daterange");
+    map_type!(m, datum::PgRecord, "record");
+    map_type!(m, heap_tuple::PgHeapTuple<'static>, "record");
     map_type!(m, datum::AnyElement, "anyelement");
     map_type!(m, datum::AnyArray, "anyarray");
     map_type!(m, datum::Inet, "inet");
+    map_type!(m, datum::Cidr, "cidr");
+    map_type!(m, datum::MacAddr, "macaddr");
+    map_type!(m, datum::MacAddr8, "macaddr8");
     map_type!(m, datum::Uuid, "uuid");
+    map_type!(m, datum::TsVector, "tsvector");
+    map_type!(m, datum::TsQuery, "tsquery");
 
     m
 });
@@ -0,0 +1,481 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+/*!
+
+[Logical Decoding Output Plugin](https://www.postgresql.org/docs/current/logicaldecoding-output-plugin.html) support.
+
+Output plugins are created by implementing [`OutputPlugin`] for a type and decorating the
+implementation with [`#[pg_output_plugin]`](pgx_macros::pg_output_plugin), which generates the
+`_PG_output_plugin_init` function Postgres looks for when a replication slot uses the extension's
+shared library as its plugin.  As such, an extension can only contain one output plugin.
+
+The value returned by [`OutputPlugin::startup`] lives for the whole decoding session, and each
+callback is handed the [`PgLogicalDecodingContext`] used to write the plugin's output.
+
+# Example
+
+```rust,no_run
+use pgx::*;
+
+pub struct ChangeCounter {
+    changes: usize,
+}
+
+#[pg_output_plugin]
+impl OutputPlugin for ChangeCounter {
+    fn startup(_ctx: &mut PgLogicalDecodingContext, _is_init: bool) -> Self {
+        ChangeCounter { changes: 0 }
+    }
+
+    fn change(
+        &mut self,
+        _ctx: &mut PgLogicalDecodingContext,
+        _txn: &pg_sys::ReorderBufferTXN,
+        _relation: &PgRelation,
+        _change: &DecodedChange,
+    ) {
+        self.changes += 1;
+    }
+
+    fn commit(
+        &mut self,
+        ctx: &mut PgLogicalDecodingContext,
+        txn: &pg_sys::ReorderBufferTXN,
+        _commit_lsn: pg_sys::XLogRecPtr,
+    ) {
+        ctx.write_str(&format!("{} changes in {}", self.changes, txn.xid));
+        self.changes = 0;
+    }
+}
+```
+
+Pass `'pgx_example'` (the name of the extension's shared library) as the plugin when creating the
+replication slot:
+
+```sql
+SELECT pg_create_logical_replication_slot('example_slot', 'pgx_example');
+```
+*/
+use crate::{
+    heap_getattr_raw, pg_sys, varlena, FromDatum, PgList, PgMemoryContexts, PgRelation, PgTupleDesc,
+};
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr};
+use std::os::raw::c_char;
+
+/// The options given to the decoding session, such as the variadic options of
+/// `pg_logical_slot_get_changes()`.
+///
+/// Options given without a value map to `None`.
+pub type OutputPluginOptions = HashMap<String, Option<String>>;
+
+/// The kind of output produced by an [`OutputPlugin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputType {
+    /// The output is text in the database encoding, as required by `pg_logical_slot_get_changes()`.
+    Text,
+    /// The output is arbitrary bytes, readable with `pg_logical_slot_get_binary_changes()`.
+    Binary,
+}
+
+/// A logical decoding output plugin.
+///
+/// Only [`OutputPlugin::startup`] and [`OutputPlugin::change`] are required, the other callbacks
+/// do nothing by default.
+pub trait OutputPlugin: Sized {
+    /// Start a decoding session.  `is_init` is `true` when the replication slot is being created.
+    fn startup(ctx: &mut PgLogicalDecodingContext, is_init: bool) -> Self;
+
+    /// The kind of output the plugin produces, asked once right after [`OutputPlugin::startup`].
+    fn output_type(&self) -> OutputType {
+        OutputType::Text
+    }
+
+    /// Called before the changes of the committed transaction `txn` are decoded.
+    fn begin(&mut self, _ctx: &mut PgLogicalDecodingContext, _txn: &pg_sys::ReorderBufferTXN) {}
+
+    /// Called for each row inserted, updated or deleted in `relation` by `txn`.
+    fn change(
+        &mut self,
+        ctx: &mut PgLogicalDecodingContext,
+        txn: &pg_sys::ReorderBufferTXN,
+        relation: &PgRelation,
+        change: &DecodedChange,
+    );
+
+    /// Called when `relations` are truncated by `txn`.
+    ///
+    /// Decoding of `TRUNCATE` requires Postgres 11 or newer.
+    fn truncate(
+        &mut self,
+        _ctx: &mut PgLogicalDecodingContext,
+        _txn: &pg_sys::ReorderBufferTXN,
+        _relations: &[PgRelation],
+        _cascade: bool,
+        _restart_seqs: bool,
+    ) {
+    }
+
+    /// Called after all the changes of `txn` have been decoded.
+    fn commit(
+        &mut self,
+        _ctx: &mut PgLogicalDecodingContext,
+        _txn: &pg_sys::ReorderBufferTXN,
+        _commit_lsn: pg_sys::XLogRecPtr,
+    ) {
+    }
+
+    /// Called for each message emitted by `pg_logical_emit_message()`.
+    ///
+    /// `txn` is `None` for non-transactional messages.
+    fn message(
+        &mut self,
+        _ctx: &mut PgLogicalDecodingContext,
+        _txn: Option<&pg_sys::ReorderBufferTXN>,
+        _message_lsn: pg_sys::XLogRecPtr,
+        _transactional: bool,
+        _prefix: &str,
+        _message: &[u8],
+    ) {
+    }
+
+    /// Return `true` to skip the changes replayed from `origin_id`.
+    fn filter_by_origin(
+        &mut self,
+        _ctx: &mut PgLogicalDecodingContext,
+        _origin_id: pg_sys::RepOriginId,
+    ) -> bool {
+        false
+    }
+
+    /// Called when the decoding session ends.
+    fn shutdown(&mut self, _ctx: &mut PgLogicalDecodingContext) {}
+}
+
+/// A Postgres `LogicalDecodingContext`.
+pub struct PgLogicalDecodingContext {
+    ctx: *mut pg_sys::LogicalDecodingContext,
+}
+
+impl PgLogicalDecodingContext {
+    /// Wrap a Postgres-provided `pg_sys::LogicalDecodingContext`.
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as it cannot validate that `ctx` is a valid pointer.
+    pub unsafe fn from_pg(ctx: *mut pg_sys::LogicalDecodingContext) -> Self {
+        PgLogicalDecodingContext { ctx }
+    }
+
+    /// The options given to the decoding session.
+    pub fn options(&self) -> OutputPluginOptions {
+        unsafe {
+            PgList::<pg_sys::DefElem>::from_pg(
+                pg_sys::pgx_LogicalDecodingContext_output_plugin_options(self.ctx),
+            )
+            .iter_ptr()
+            .map(|def| {
+                let name = CStr::from_ptr((*def).defname);
+                let value = if (*def).arg.is_null() {
+                    None
+                } else {
+                    let value = CStr::from_ptr(pg_sys::defGetString(def));
+                    Some(value.to_string_lossy().into_owned())
+                };
+                (name.to_string_lossy().into_owned(), value)
+            })
+            .collect()
+        }
+    }
+
+    /// Write `data` as one complete message of the plugin's output.
+    pub fn write(&mut self, data: &[u8]) {
+        unsafe {
+            pg_sys::OutputPluginPrepareWrite(self.ctx, true);
+            pg_sys::appendBinaryStringInfo(
+                pg_sys::pgx_LogicalDecodingContext_out(self.ctx),
+                data.as_ptr() as *const c_char,
+                data.len() as i32,
+            );
+            pg_sys::OutputPluginWrite(self.ctx, true);
+        }
+    }
+
+    /// Write `s` as one complete message of the plugin's output.
+    pub fn write_str(&mut self, s: &str) {
+        self.write(s.as_bytes())
+    }
+
+    pub fn as_ptr(&self) -> *mut pg_sys::LogicalDecodingContext {
+        self.ctx
+    }
+}
+
+/// What a [`DecodedChange`] did to its row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeAction {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// A row change handed to [`OutputPlugin::change`].
+pub struct DecodedChange<'a> {
+    change: &'a pg_sys::ReorderBufferChange,
+    tupdesc: PgTupleDesc<'a>,
+}
+
+impl<'a> DecodedChange<'a> {
+    pub fn action(&self) -> ChangeAction {
+        match self.change.action {
+            pg_sys::ReorderBufferChangeType_REORDER_BUFFER_CHANGE_INSERT => ChangeAction::Insert,
+            pg_sys::ReorderBufferChangeType_REORDER_BUFFER_CHANGE_UPDATE => ChangeAction::Update,
+            pg_sys::ReorderBufferChangeType_REORDER_BUFFER_CHANGE_DELETE => ChangeAction::Delete,
+            other => panic!("unexpected ReorderBufferChangeType: {}", other),
+        }
+    }
+
+    /// The LSN of the WAL record of the change.
+    pub fn lsn(&self) -> pg_sys::XLogRecPtr {
+        self.change.lsn
+    }
+
+    /// The row before an `UPDATE` or `DELETE`.
+    ///
+    /// Depending on the relation's `REPLICA IDENTITY`, this might only contain the key columns,
+    /// or not be available at all.
+    pub fn old_tuple(&self) -> Option<DecodedTuple<'_>> {
+        unsafe { self.tuple(self.change.data.tp.oldtuple) }
+    }
+
+    /// The row after an `INSERT` or `UPDATE`.
+    pub fn new_tuple(&self) -> Option<DecodedTuple<'_>> {
+        unsafe { self.tuple(self.change.data.tp.newtuple) }
+    }
+
+    unsafe fn tuple(&self, buf: *mut pg_sys::ReorderBufferTupleBuf) -> Option<DecodedTuple<'_>> {
+        if buf.is_null() {
+            None
+        } else {
+            Some(DecodedTuple {
+                tuple: &(*buf).tuple,
+                tupdesc: &self.tupdesc,
+            })
+        }
+    }
+}
+
+/// An old or new row of a [`DecodedChange`].
+pub struct DecodedTuple<'a> {
+    tuple: &'a pg_sys::HeapTupleData,
+    tupdesc: &'a PgTupleDesc<'a>,
+}
+
+impl<'a> DecodedTuple<'a> {
+    /// The descriptor of the relation the row belongs to.
+    pub fn tuple_desc(&self) -> &PgTupleDesc<'a> {
+        self.tupdesc
+    }
+
+    /// The value of the attribute `attno`, which is 1-based.  `None` means `NULL`.
+    ///
+    /// Unchanged TOASTed values can't be read during decoding, check for them with
+    /// [`DecodedTuple::is_unchanged_toast`] first.
+    pub fn get<T: FromDatum>(&self, attno: usize) -> Option<T> {
+        if attno < 1 {
+            panic!("attribute {} does not exist", attno);
+        }
+        let attribute = self
+            .tupdesc
+            .get(attno - 1)
+            .unwrap_or_else(|| panic!("attribute {} does not exist", attno));
+        if attribute.is_dropped() {
+            return None;
+        }
+        unsafe {
+            let datum = heap_getattr_raw(self.tuple, attno, self.tupdesc.as_ptr())?;
+            T::from_datum(datum, false, attribute.type_oid().value())
+        }
+    }
+
+    /// The value of the attribute called `name`.  `None` means `NULL`.
+    pub fn get_by_name<T: FromDatum>(&self, name: &str) -> Option<T> {
+        let attno = self
+            .tupdesc
+            .iter()
+            .position(|attribute| !attribute.is_dropped() && attribute.name() == name)
+            .unwrap_or_else(|| panic!("attribute \"{}\" does not exist", name));
+        self.get(attno + 1)
+    }
+
+    /// Is the attribute `attno`, which is 1-based, a TOASTed value left unchanged by an `UPDATE`?
+    pub fn is_unchanged_toast(&self, attno: usize) -> bool {
+        if attno < 1 {
+            return false;
+        }
+        match self.tupdesc.get(attno - 1) {
+            Some(attribute) if attribute.attlen == -1 => unsafe {
+                match heap_getattr_raw(self.tuple, attno, self.tupdesc.as_ptr()) {
+                    Some(datum) => {
+                        let ptr = datum as *const pg_sys::varlena;
+                        varlena::varatt_is_1b_e(ptr)
+                            && varlena::vartag_1b_e(ptr) as u32
+                                == pg_sys::vartag_external_VARTAG_ONDISK
+                    }
+                    None => false,
+                }
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Fill in `callbacks` with the callbacks of `T`.
+///
+/// This is called by the `_PG_output_plugin_init` function `#[pg_output_plugin]` generates.
+pub unsafe fn output_plugin_init<T: OutputPlugin>(callbacks: *mut pg_sys::OutputPluginCallbacks) {
+    let callbacks = &mut *callbacks;
+    callbacks.startup_cb = Some(startup::<T>);
+    callbacks.begin_cb = Some(begin::<T>);
+    callbacks.change_cb = Some(change::<T>);
+    #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+    {
+        callbacks.truncate_cb = Some(truncate::<T>);
+    }
+    callbacks.commit_cb = Some(commit::<T>);
+    callbacks.message_cb = Some(message::<T>);
+    callbacks.filter_by_origin_cb = Some(filter_by_origin::<T>);
+    callbacks.shutdown_cb = Some(shutdown::<T>);
+}
+
+unsafe fn plugin<'a, T: OutputPlugin>(ctx: *mut pg_sys::LogicalDecodingContext) -> &'a mut T {
+    &mut *(pg_sys::pgx_LogicalDecodingContext_output_plugin_private(ctx) as *mut T)
+}
+
+unsafe extern "C" fn startup<T: OutputPlugin>(
+    ctx: *mut pg_sys::LogicalDecodingContext,
+    options: *mut pg_sys::OutputPluginOptions,
+    is_init: bool,
+) {
+    pg_sys::guard::guard(|| {
+        let plugin = T::startup(&mut PgLogicalDecodingContext::from_pg(ctx), is_init);
+        (*options).output_type = match plugin.output_type() {
+            OutputType::Text => pg_sys::OutputPluginOutputType_OUTPUT_PLUGIN_TEXTUAL_OUTPUT,
+            OutputType::Binary => pg_sys::OutputPluginOutputType_OUTPUT_PLUGIN_BINARY_OUTPUT,
+        };
+
+        // the current memory context is the decoding context's, which lives as long as the session
+        let plugin = PgMemoryContexts::CurrentMemoryContext.leak_and_drop_on_delete(plugin);
+        pg_sys::pgx_LogicalDecodingContext_set_output_plugin_private(ctx, plugin as *mut c_void);
+    })
+}
+
+unsafe extern "C" fn begin<T: OutputPlugin>(
+    ctx: *mut pg_sys::LogicalDecodingContext,
+    txn: *mut pg_sys::ReorderBufferTXN,
+) {
+    pg_sys::guard::guard(|| {
+        plugin::<T>(ctx).begin(&mut PgLogicalDecodingContext::from_pg(ctx), &*txn)
+    })
+}
+
+unsafe extern "C" fn change<T: OutputPlugin>(
+    ctx: *mut pg_sys::LogicalDecodingContext,
+    txn: *mut pg_sys::ReorderBufferTXN,
+    relation: pg_sys::Relation,
+    change: *mut pg_sys::ReorderBufferChange,
+) {
+    pg_sys::guard::guard(|| {
+        let relation = PgRelation::from_pg(relation);
+        let change = DecodedChange {
+            change: &*change,
+            tupdesc: PgTupleDesc::from_relation(&relation),
+        };
+        plugin::<T>(ctx).change(
+            &mut PgLogicalDecodingContext::from_pg(ctx),
+            &*txn,
+            &relation,
+            &change,
+        )
+    })
+}
+
+#[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+unsafe extern "C" fn truncate<T: OutputPlugin>(
+    ctx: *mut pg_sys::LogicalDecodingContext,
+    txn: *mut pg_sys::ReorderBufferTXN,
+    nrelations: std::os::raw::c_int,
+    relations: *mut pg_sys::Relation,
+    change: *mut pg_sys::ReorderBufferChange,
+) {
+    pg_sys::guard::guard(|| {
+        let relations = std::slice::from_raw_parts(relations, nrelations as usize)
+            .iter()
+            .map(|&relation| PgRelation::from_pg(relation))
+            .collect::<Vec<_>>();
+        let truncate = (*change).data.truncate;
+        plugin::<T>(ctx).truncate(
+            &mut PgLogicalDecodingContext::from_pg(ctx),
+            &*txn,
+            &relations,
+            truncate.cascade,
+            truncate.restart_seqs,
+        )
+    })
+}
+
+unsafe extern "C" fn commit<T: OutputPlugin>(
+    ctx: *mut pg_sys::LogicalDecodingContext,
+    txn: *mut pg_sys::ReorderBufferTXN,
+    commit_lsn: pg_sys::XLogRecPtr,
+) {
+    pg_sys::guard::guard(|| {
+        plugin::<T>(ctx).commit(
+            &mut PgLogicalDecodingContext::from_pg(ctx),
+            &*txn,
+            commit_lsn,
+        )
+    })
+}
+
+unsafe extern "C" fn message<T: OutputPlugin>(
+    ctx: *mut pg_sys::LogicalDecodingContext,
+    txn: *mut pg_sys::ReorderBufferTXN,
+    message_lsn: pg_sys::XLogRecPtr,
+    transactional: bool,
+    prefix: *const c_char,
+    message_size: pg_sys::Size,
+    message: *const c_char,
+) {
+    pg_sys::guard::guard(|| {
+        let prefix = CStr::from_ptr(prefix).to_string_lossy();
+        let message = std::slice::from_raw_parts(message as *const u8, message_size);
+        plugin::<T>(ctx).message(
+            &mut PgLogicalDecodingContext::from_pg(ctx),
+            txn.as_ref(),
+            message_lsn,
+            transactional,
+            &prefix,
+            message,
+        )
+    })
+}
+
+unsafe extern "C" fn filter_by_origin<T: OutputPlugin>(
+    ctx: *mut pg_sys::LogicalDecodingContext,
+    origin_id: pg_sys::RepOriginId,
+) -> bool {
+    pg_sys::guard::guard(|| {
+        plugin::<T>(ctx).filter_by_origin(&mut PgLogicalDecodingContext::from_pg(ctx), origin_id)
+    })
+}
+
+unsafe extern "C" fn shutdown<T: OutputPlugin>(ctx: *mut pg_sys::LogicalDecodingContext) {
+    pg_sys::guard::guard(|| {
+        // the plugin itself is dropped along with the decoding context's memory context
+        if !pg_sys::pgx_LogicalDecodingContext_output_plugin_private(ctx).is_null() {
+            plugin::<T>(ctx).shutdown(&mut PgLogicalDecodingContext::from_pg(ctx))
+        }
+    })
+}
@@ -1,5 +1,7 @@
 use crate::lwlock::*;
 use crate::{pg_sys, PgAtomic};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use uuid::Uuid;
 
 /// Custom types that want to participate in shared memory must implement this marker trait
@@ -213,3 +215,430 @@ unsafe impl<K: Eq + hash32::Hash, V: Default, S, const N: usize> PGXSharedMemory
     for heapless::IndexMap<K, V, S, N>
 {
 }
+
+/// A dynamic shared memory segment, created with [`PgDsmSegment::create()`] by one backend and
+/// attached to, via its [`PgDsmSegment::handle()`], by others (typically dynamic background workers).
+///
+/// Unlike the memory set up by `pg_shmem_init!()`, dynamic segments can be created at any time
+/// and don't require the extension to be in `shared_preload_libraries`.
+///
+/// The segment is detached when this value is dropped.  Postgres tracks segment mappings with the
+/// current resource owner, which detaches them itself when it's released, so unless
+/// [`PgDsmSegment::pin_mapping()`] is called, a `PgDsmSegment` must not outlive the resource owner
+/// that was current when it was created or attached.  That's why creating or attaching is `unsafe`.
+pub struct PgDsmSegment {
+    seg: *mut pg_sys::dsm_segment,
+}
+
+impl PgDsmSegment {
+    /// Create a new segment of `size` bytes, raising an ERROR if it can't be created
+    ///
+    /// # Safety
+    ///
+    /// The result must be dropped before the current resource owner is released, typically at the
+    /// end of the current transaction, unless [`PgDsmSegment::pin_mapping()`] is called first
+    pub unsafe fn create(size: usize) -> Self {
+        let seg = pg_sys::dsm_create(size, 0);
+        PgDsmSegment { seg }
+    }
+
+    /// Create a new segment of `size` bytes, or `None` if the system has already reached its
+    /// maximum number of dynamic shared memory segments
+    ///
+    /// # Safety
+    ///
+    /// The result must be dropped before the current resource owner is released, typically at the
+    /// end of the current transaction, unless [`PgDsmSegment::pin_mapping()`] is called first
+    pub unsafe fn try_create(size: usize) -> Option<Self> {
+        let seg = pg_sys::dsm_create(
+            size,
+            pg_sys::DSM_CREATE_NULL_IF_MAXSEGMENTS as std::os::raw::c_int,
+        );
+        if seg.is_null() {
+            None
+        } else {
+            Some(PgDsmSegment { seg })
+        }
+    }
+
+    /// Attach to the segment identified by `handle`, or `None` if it no longer exists.
+    ///
+    /// Postgres raises an ERROR if this backend is already attached to the segment.
+    ///
+    /// # Safety
+    ///
+    /// The result must be dropped before the current resource owner is released, typically at the
+    /// end of the current transaction, unless [`PgDsmSegment::pin_mapping()`] is called first
+    pub unsafe fn attach(handle: pg_sys::dsm_handle) -> Option<Self> {
+        let seg = pg_sys::dsm_attach(handle);
+        if seg.is_null() {
+            None
+        } else {
+            Some(PgDsmSegment { seg })
+        }
+    }
+
+    /// Wrap a segment Postgres has already mapped.  It'll be detached when the result is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `seg` must be a valid, attached segment that nothing else will detach
+    pub unsafe fn from_pg(seg: *mut pg_sys::dsm_segment) -> Self {
+        assert!(!seg.is_null(), "dsm_segment is NULL");
+        PgDsmSegment { seg }
+    }
+
+    /// The handle other backends use to [`PgDsmSegment::attach()`] to this segment
+    pub fn handle(&self) -> pg_sys::dsm_handle {
+        unsafe { pg_sys::dsm_segment_handle(self.seg) }
+    }
+
+    /// The size, in bytes, of this segment's mapping
+    pub fn len(&self) -> usize {
+        unsafe { pg_sys::dsm_segment_map_length(self.seg) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The address at which this segment is mapped in the current backend
+    pub fn address(&self) -> *mut std::os::raw::c_void {
+        unsafe { pg_sys::dsm_segment_address(self.seg) }
+    }
+
+    /// Keep this segment mapped until the backend exits, rather than until the end of the
+    /// current resource owner.  It is still detached when this value is dropped.
+    pub fn pin_mapping(&self) {
+        unsafe { pg_sys::dsm_pin_mapping(self.seg) }
+    }
+
+    pub fn as_ptr(&self) -> *mut pg_sys::dsm_segment {
+        self.seg
+    }
+
+    /// Give up ownership of the segment mapping without detaching it
+    pub fn into_pg(self) -> *mut pg_sys::dsm_segment {
+        let seg = self.seg;
+        std::mem::forget(self);
+        seg
+    }
+}
+
+impl Drop for PgDsmSegment {
+    fn drop(&mut self) {
+        unsafe { pg_sys::dsm_detach(self.seg) }
+    }
+}
+
+/// Computes the size of a segment needed to hold a [`PgShmToc`] with a given set of chunks
+pub struct PgShmTocEstimator {
+    estimator: pg_sys::shm_toc_estimator,
+}
+
+impl Default for PgShmTocEstimator {
+    fn default() -> Self {
+        PgShmTocEstimator::new()
+    }
+}
+
+impl PgShmTocEstimator {
+    pub fn new() -> Self {
+        PgShmTocEstimator {
+            estimator: pg_sys::shm_toc_estimator {
+                space_for_chunks: 0,
+                number_of_keys: 0,
+            },
+        }
+    }
+
+    /// Account for a chunk of `size` bytes that'll be [`PgShmToc::allocate()`]d
+    pub fn add_chunk(&mut self, size: usize) -> &mut Self {
+        let align = pg_sys::ALIGNOF_BUFFER as usize;
+        let size = (size + align - 1) & !(align - 1);
+        self.estimator.space_for_chunks =
+            unsafe { pg_sys::add_size(self.estimator.space_for_chunks, size) };
+        self
+    }
+
+    /// Account for `count` keys that'll be [`PgShmToc::insert()`]ed
+    pub fn add_keys(&mut self, count: usize) -> &mut Self {
+        self.estimator.number_of_keys =
+            unsafe { pg_sys::add_size(self.estimator.number_of_keys, count) };
+        self
+    }
+
+    /// The total size, in bytes, the segment needs to be
+    pub fn size(&mut self) -> usize {
+        unsafe { pg_sys::shm_toc_estimate(&mut self.estimator) }
+    }
+}
+
+/// A table of contents laid out at the start of a [`PgDsmSegment`], mapping `u64` keys to chunks
+/// of the segment so that other backends can find them after attaching
+pub struct PgShmToc<'seg> {
+    toc: *mut pg_sys::shm_toc,
+    segment: &'seg PgDsmSegment,
+}
+
+impl<'seg> PgShmToc<'seg> {
+    /// Lay out a new table of contents, identified by `magic`, covering all of `segment`
+    pub fn create(magic: u64, segment: &'seg PgDsmSegment) -> Self {
+        let toc = unsafe { pg_sys::shm_toc_create(magic, segment.address(), segment.len()) };
+        PgShmToc { toc, segment }
+    }
+
+    /// Find the table of contents [`PgShmToc::create()`]d in `segment`, or `None` if the
+    /// segment doesn't start with one identified by `magic`
+    pub fn attach(magic: u64, segment: &'seg PgDsmSegment) -> Option<Self> {
+        let toc = unsafe { pg_sys::shm_toc_attach(magic, segment.address()) };
+        if toc.is_null() {
+            None
+        } else {
+            Some(PgShmToc { toc, segment })
+        }
+    }
+
+    pub fn segment(&self) -> &'seg PgDsmSegment {
+        self.segment
+    }
+
+    /// Carve `size` bytes out of the segment, raising an ERROR if it's out of space
+    pub fn allocate(&self, size: usize) -> *mut std::os::raw::c_void {
+        unsafe { pg_sys::shm_toc_allocate(self.toc, size) }
+    }
+
+    /// Publish a chunk previously returned by [`PgShmToc::allocate()`] under `key`
+    pub fn insert(&self, key: u64, address: *mut std::os::raw::c_void) {
+        unsafe { pg_sys::shm_toc_insert(self.toc, key, address) }
+    }
+
+    /// Find the chunk published under `key`
+    pub fn lookup(&self, key: u64) -> Option<*mut std::os::raw::c_void> {
+        let address = unsafe { pg_sys::shm_toc_lookup(self.toc, key, true) };
+        if address.is_null() {
+            None
+        } else {
+            Some(address)
+        }
+    }
+
+    /// Copy `value` into a new chunk of the segment and publish it under `key`
+    pub fn insert_value<T: PGXSharedMemory + Copy>(&self, key: u64, value: T) -> &'seg T {
+        unsafe {
+            let address = self.allocate(std::mem::size_of::<T>()) as *mut T;
+            address.write(value);
+            self.insert(key, address as *mut std::os::raw::c_void);
+            &*address
+        }
+    }
+
+    /// Find the value [`PgShmToc::insert_value()`]ed under `key`
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure the value under `key` really is a `T`
+    pub unsafe fn lookup_value<T: PGXSharedMemory + Copy>(&self, key: u64) -> Option<&'seg T> {
+        self.lookup(key).map(|address| &*(address as *const T))
+    }
+
+    /// Create a new message queue of `size` bytes in the segment and publish it under `key`
+    pub fn create_mq(&self, key: u64, size: usize) -> PgShmMq<'seg> {
+        let size = size.max(unsafe { pg_sys::shm_mq_minimum_size });
+        let address = self.allocate(size);
+        let mq = unsafe { pg_sys::shm_mq_create(address, size) };
+        self.insert(key, address);
+        PgShmMq {
+            mq,
+            segment: self.segment,
+        }
+    }
+
+    /// Find the message queue [`PgShmToc::create_mq()`]d under `key`
+    pub fn lookup_mq(&self, key: u64) -> Option<PgShmMq<'seg>> {
+        self.lookup(key).map(|address| PgShmMq {
+            mq: address as *mut pg_sys::shm_mq,
+            segment: self.segment,
+        })
+    }
+
+    pub fn as_ptr(&self) -> *mut pg_sys::shm_toc {
+        self.toc
+    }
+}
+
+/// Errors from sending or receiving through a [`PgShmMqHandle`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShmMqError {
+    /// The queue is full (when sending) or empty (when receiving), and we were asked not to wait
+    WouldBlock,
+    /// The other side of the queue has detached, or never attached
+    Detached,
+    /// A message couldn't be serialized or deserialized
+    Serialization(String),
+}
+
+impl std::fmt::Display for ShmMqError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShmMqError::WouldBlock => write!(f, "shm_mq operation would block"),
+            ShmMqError::Detached => write!(f, "shm_mq counterparty has detached"),
+            ShmMqError::Serialization(e) => write!(f, "shm_mq message serialization failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ShmMqError {}
+
+fn shm_mq_result(result: pg_sys::shm_mq_result) -> Result<(), ShmMqError> {
+    match result {
+        pg_sys::shm_mq_result_SHM_MQ_SUCCESS => Ok(()),
+        pg_sys::shm_mq_result_SHM_MQ_WOULD_BLOCK => Err(ShmMqError::WouldBlock),
+        pg_sys::shm_mq_result_SHM_MQ_DETACHED => Err(ShmMqError::Detached),
+        unknown => panic!("unrecognized shm_mq_result: {}", unknown),
+    }
+}
+
+/// A single-reader, single-writer message queue living in a [`PgDsmSegment`].
+///
+/// Exactly one process must become the sender and one the receiver, after which each
+/// [`PgShmMq::attach()`]es to get a [`PgShmMqHandle`] for its side of the queue.
+pub struct PgShmMq<'seg> {
+    mq: *mut pg_sys::shm_mq,
+    segment: &'seg PgDsmSegment,
+}
+
+impl<'seg> PgShmMq<'seg> {
+    /// Make the current backend the queue's sender
+    pub fn set_sender(&self) {
+        unsafe { pg_sys::shm_mq_set_sender(self.mq, pg_sys::MyProc) }
+    }
+
+    /// Make the current backend the queue's receiver
+    pub fn set_receiver(&self) {
+        unsafe { pg_sys::shm_mq_set_receiver(self.mq, pg_sys::MyProc) }
+    }
+
+    /// Attach to the queue.  The handle is detached when dropped, which the counterparty
+    /// sees as [`ShmMqError::Detached`]
+    pub fn attach(&self) -> PgShmMqHandle<'seg> {
+        let mqh =
+            unsafe { pg_sys::shm_mq_attach(self.mq, self.segment.as_ptr(), std::ptr::null_mut()) };
+        PgShmMqHandle {
+            mqh,
+            _segment: self.segment,
+        }
+    }
+
+    /// Become the queue's sender and attach to it
+    pub fn attach_as_sender(&self) -> PgShmMqHandle<'seg> {
+        self.set_sender();
+        self.attach()
+    }
+
+    /// Become the queue's receiver and attach to it
+    pub fn attach_as_receiver(&self) -> PgShmMqHandle<'seg> {
+        self.set_receiver();
+        self.attach()
+    }
+
+    pub fn as_ptr(&self) -> *mut pg_sys::shm_mq {
+        self.mq
+    }
+}
+
+/// One side of an attached [`PgShmMq`].
+///
+/// Blocking sends and receives wait on the process latch, so they remain responsive to
+/// query cancellation and postmaster death.
+pub struct PgShmMqHandle<'seg> {
+    mqh: *mut pg_sys::shm_mq_handle,
+    _segment: &'seg PgDsmSegment,
+}
+
+impl<'seg> PgShmMqHandle<'seg> {
+    /// Associate the background worker expected to attach to the other side of the queue, so
+    /// that waiting on it fails with [`ShmMqError::Detached`] if that worker dies first
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid `BackgroundWorkerHandle` that outlives this queue handle
+    pub unsafe fn set_worker_handle(&mut self, handle: *mut pg_sys::BackgroundWorkerHandle) {
+        pg_sys::shm_mq_set_handle(self.mqh, handle)
+    }
+
+    /// Wait for the counterparty to attach to the queue
+    pub fn wait_for_attach(&mut self) -> Result<(), ShmMqError> {
+        shm_mq_result(unsafe { pg_sys::shm_mq_wait_for_attach(self.mqh) })
+    }
+
+    /// Send `data` as one message, waiting for room in the queue if necessary
+    pub fn send(&mut self, data: &[u8]) -> Result<(), ShmMqError> {
+        self.send_internal(data, false)
+    }
+
+    /// Send `data` as one message without waiting.
+    ///
+    /// On [`ShmMqError::WouldBlock`] part of the message may have been written, and the same
+    /// `data` must be passed again to finish sending it.
+    pub fn try_send(&mut self, data: &[u8]) -> Result<(), ShmMqError> {
+        self.send_internal(data, true)
+    }
+
+    fn send_internal(&mut self, data: &[u8], nowait: bool) -> Result<(), ShmMqError> {
+        shm_mq_result(unsafe {
+            pg_sys::shm_mq_send(
+                self.mqh,
+                data.len(),
+                data.as_ptr() as *const std::os::raw::c_void,
+                nowait,
+            )
+        })
+    }
+
+    /// Receive the next message, waiting for one to arrive if necessary.  The returned slice
+    /// is only valid until the next receive.
+    pub fn receive(&mut self) -> Result<&[u8], ShmMqError> {
+        self.receive_internal(false)
+    }
+
+    /// Receive the next message without waiting
+    pub fn try_receive(&mut self) -> Result<&[u8], ShmMqError> {
+        self.receive_internal(true)
+    }
+
+    fn receive_internal(&mut self, nowait: bool) -> Result<&[u8], ShmMqError> {
+        let mut nbytes = 0;
+        let mut data = std::ptr::null_mut();
+        shm_mq_result(unsafe { pg_sys::shm_mq_receive(self.mqh, &mut nbytes, &mut data, nowait) })?;
+
+        if nbytes == 0 {
+            Ok(&[])
+        } else {
+            Ok(unsafe { std::slice::from_raw_parts(data as *const u8, nbytes) })
+        }
+    }
+
+    /// Serialize `value` with CBOR and send it as one message
+    pub fn send_serialized<T: Serialize>(&mut self, value: &T) -> Result<(), ShmMqError> {
+        let data =
+            serde_cbor::to_vec(value).map_err(|e| ShmMqError::Serialization(e.to_string()))?;
+        self.send(&data)
+    }
+
+    /// Receive the next message and deserialize it from CBOR
+    pub fn receive_deserialized<T: DeserializeOwned>(&mut self) -> Result<T, ShmMqError> {
+        let data = self.receive()?;
+        serde_cbor::from_slice(data).map_err(|e| ShmMqError::Serialization(e.to_string()))
+    }
+
+    pub fn as_ptr(&self) -> *mut pg_sys::shm_mq_handle {
+        self.mqh
+    }
+}
+
+impl<'seg> Drop for PgShmMqHandle<'seg> {
+    fn drop(&mut self) {
+        unsafe { pg_sys::shm_mq_detach(self.mqh) }
+    }
+}
@@ -3,14 +3,15 @@
 
 //! Safe access to Postgres' *Server Programming Interface* (SPI).
 
-use crate::{pg_sys, FromDatum, IntoDatum, Json, PgMemoryContexts, PgOid};
+use crate::{pg_sys, FromDatum, IntoDatum, Json, PgMemoryContexts, PgOid, PgSqlErrorCode};
 use enum_primitive_derive::*;
 use num_traits::FromPrimitive;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::marker::PhantomData;
 use std::ops::{Index, IndexMut};
 
-#[derive(Debug, Primitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Primitive)]
 pub enum SpiOk {
     Connect = 1,
     Finish = 2,
@@ -31,25 +32,178 @@ pub enum SpiOk {
     TdRegister = 17,
 }
 
-#[derive(Debug, Primitive)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SpiError {
-    // NB:  These are #define'd as negative, but we redefine them as positive so that
-    // #[derive(Primitive)] will work.  We just need to negate result codes from the
+    // NB:  These are #define'd as negative, but we redefine them as positive to match
+    // the FromPrimitive impl below.  We just need to negate result codes from the
     // various SPI_xxx functions when looking for errors
-    Connect = 1,
-    Copy = 2,
-    Opunknown = 3,
-    Unconnected = 4,
+    Connect,
+    Copy,
+    Opunknown,
+    Unconnected,
     #[allow(dead_code)]
-    Cursor = 5, /* not used anymore */
-    Argument = 6,
-    Param = 7,
-    Transaction = 8,
-    Noattribute = 9,
-    Nooutfunc = 10,
-    Typunknown = 11,
-    RelDuplicate = 12,
-    RelNotFound = 13,
+    Cursor, /* not used anymore */
+    Argument,
+    Param,
+    Transaction,
+    Noattribute,
+    Nooutfunc,
+    Typunknown,
+    RelDuplicate,
+    RelNotFound,
+
+    /// The result has no column with this name
+    ColumnNotFound {
+        column: String,
+    },
+    /// The column's type can't be read as the requested Rust type
+    TypeMismatch {
+        column: String,
+        expected: pg_sys::Oid,
+        found: pg_sys::Oid,
+    },
+    /// The column is NULL, but was read into a non-`Option` value
+    UnexpectedNull {
+        column: String,
+    },
+
+    /// An ERROR raised while running a statement, as caught by the `try_` family of functions
+    Sql {
+        /// The five-character SQLSTATE code, such as `23505` for a unique violation
+        sqlstate: String,
+        message: String,
+        detail: Option<String>,
+        hint: Option<String>,
+    },
+}
+
+impl SpiError {
+    /// The SQLSTATE of an `SpiError::Sql`
+    pub fn sqlstate(&self) -> Option<&str> {
+        match self {
+            SpiError::Sql { sqlstate, .. } => Some(sqlstate),
+            _ => None,
+        }
+    }
+
+    /// Is this an `SpiError::Sql` with the specified error code?
+    pub fn is_sqlstate(&self, code: PgSqlErrorCode) -> bool {
+        self.sqlstate() == Some(&unpack_sqlstate(code as i32))
+    }
+
+    fn from_caught_error(caught: pg_sys::CaughtError) -> Self {
+        match caught {
+            pg_sys::CaughtError::PostgresError(edata) => unsafe {
+                let e = SpiError::Sql {
+                    sqlstate: unpack_sqlstate((*edata).sqlerrcode),
+                    message: cstr_to_string((*edata).message).unwrap_or_default(),
+                    detail: cstr_to_string((*edata).detail),
+                    hint: cstr_to_string((*edata).hint),
+                };
+                pg_sys::FreeErrorData(edata);
+                e
+            },
+            pg_sys::CaughtError::RustPanic(message) => SpiError::Sql {
+                sqlstate: unpack_sqlstate(PgSqlErrorCode::ERRCODE_INTERNAL_ERROR as i32),
+                message,
+                detail: None,
+                hint: None,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for SpiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpiError::ColumnNotFound { column } => {
+                write!(f, "column \"{}\" does not exist", column)
+            }
+            SpiError::TypeMismatch {
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "column \"{}\" has type oid {}, which is not compatible with type oid {}",
+                column, found, expected
+            ),
+            SpiError::UnexpectedNull { column } => write!(f, "column \"{}\" is NULL", column),
+            SpiError::Sql {
+                sqlstate, message, ..
+            } => write!(f, "{} (SQLSTATE {})", message, sqlstate),
+            other => write!(f, "SPI error: {:?}", other),
+        }
+    }
+}
+
+impl std::error::Error for SpiError {}
+
+/// Turn an error code made with `MAKE_SQLSTATE()` back into its five characters
+fn unpack_sqlstate(sqlerrcode: i32) -> String {
+    (0..5)
+        .map(|i| (((sqlerrcode >> (6 * i)) & 0x3F) as u8 + b'0') as char)
+        .collect()
+}
+
+unsafe fn cstr_to_string(s: *const std::os::raw::c_char) -> Option<String> {
+    if s.is_null() {
+        None
+    } else {
+        Some(std::ffi::CStr::from_ptr(s).to_string_lossy().into_owned())
+    }
+}
+
+impl FromPrimitive for SpiError {
+    fn from_i64(n: i64) -> Option<Self> {
+        match n {
+            1 => Some(SpiError::Connect),
+            2 => Some(SpiError::Copy),
+            3 => Some(SpiError::Opunknown),
+            4 => Some(SpiError::Unconnected),
+            5 => Some(SpiError::Cursor),
+            6 => Some(SpiError::Argument),
+            7 => Some(SpiError::Param),
+            8 => Some(SpiError::Transaction),
+            9 => Some(SpiError::Noattribute),
+            10 => Some(SpiError::Nooutfunc),
+            11 => Some(SpiError::Typunknown),
+            12 => Some(SpiError::RelDuplicate),
+            13 => Some(SpiError::RelNotFound),
+            _ => None,
+        }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Self::from_i64(n as i64)
+    }
+}
+
+/// Types that can be built from one row of an SPI result, matching columns by name.
+///
+/// Usually implemented with `#[derive(FromSpiRow)]`, which maps each field of a struct onto the
+/// column of the same name.  `Option<T>` fields accept NULLs, and any other field raises
+/// `SpiError::UnexpectedNull` if its column is NULL.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[derive(FromSpiRow)]
+/// struct Dog {
+///     name: String,
+///     age: Option<i32>,
+/// }
+///
+/// let dogs = Spi::connect(|client| {
+///     for dog in client.select("SELECT name, age FROM dogs", None, None).into_rows::<Dog>() {
+///         let dog = dog?;
+///         info!("{} is {:?}", dog.name, dog.age);
+///     }
+///     Ok(Some(()))
+/// });
+/// ```
+pub trait FromSpiRow: Sized {
+    fn from_spi_row(row: &SpiHeapTupleData) -> std::result::Result<Self, SpiError>;
 }
 
 pub struct Spi;
@@ -66,6 +220,52 @@ pub struct SpiTupleTable {
     current: isize,
 }
 
+/// The name of a cursor left open by [`SpiCursor::detach_into_name()`]
+pub type CursorName = String;
+
+/// A cursor over the results of a query, opened with [`SpiClient::open_cursor()`]
+///
+/// Rather than materializing every row like [`SpiClient::select()`], rows are pulled from the
+/// underlying portal as needed, in batches of [`SpiCursor::set_batch_size()`] rows when walking
+/// it with [`SpiCursor::next_row()`] or [`SpiCursor::rows()`].  Only the most recently fetched
+/// batch is held onto: it's freed by the next fetch, so the raw rows handed out by
+/// [`SpiCursor::fetch()`] and [`SpiCursor::next_row()`] borrow from the cursor, and can't be
+/// produced by an `Iterator`, whose items must outlive later calls to `next()`.
+/// [`SpiCursor::rows()`] is an `Iterator` because it converts each row into an owned value
+/// before its batch is freed.
+///
+/// The cursor is closed when dropped, unless it's detached with [`SpiCursor::detach_into_name()`]
+/// so that it can be picked up again with [`SpiClient::find_cursor()`] later in the transaction.
+pub struct SpiCursor<'client> {
+    portal: pg_sys::Portal,
+    batch_size: i64,
+    table: *mut pg_sys::SPITupleTable,
+    rows: Vec<SpiHeapTupleData>,
+    position: usize,
+    _client: PhantomData<&'client SpiClient>,
+}
+
+/// An `Iterator` over the rest of a [`SpiCursor`]'s rows as `T`s, created by [`SpiCursor::rows()`]
+pub struct SpiCursorRows<'cursor, 'client, T> {
+    cursor: &'cursor mut SpiCursor<'client>,
+    _marker: PhantomData<T>,
+}
+
+/// A query planned once by [`SpiClient::prepare()`] and then executed any number of times, with
+/// different arguments, through [`SpiClient::select_prepared()`] or [`SpiClient::update_prepared()`]
+///
+/// By default the plan only lives as long as the current SPI connection, so the statement
+/// borrows the [`SpiClient`] that prepared it.  Call [`PreparedStatement::keep()`] to hold onto
+/// it for the lifetime of the backend, such as in a `static` cache.  A kept plan is freed when
+/// the statement is dropped, otherwise it's left for SPI to free along with the rest of the
+/// connection.
+pub struct PreparedStatement<'client> {
+    plan: pg_sys::SPIPlanPtr,
+    argtypes: Vec<PgOid>,
+    kept: bool,
+    _client: PhantomData<&'client SpiClient>,
+}
+
 /// Represents a single `pg_sys::Datum` inside a `SpiHeapTupleData`
 pub struct SpiHeapTupleDataEntry {
     datum: Option<pg_sys::Datum>,
@@ -197,6 +397,103 @@ impl Spi {
     >(
         f: F,
     ) -> Option<R> {
+        match Spi::connect_internal(f) {
+            Ok(result) => result,
+
+            // closure returned an error
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    /// Like [`Spi::connect()`], but any ERROR raised while connected, or `Err` returned by the
+    /// closure, rolls back everything the closure did and is returned as an `Err`, rather than
+    /// aborting the entire transaction.
+    ///
+    /// The closure runs in its own subtransaction, so that the calling transaction can carry on
+    /// after an error, for example to retry after a unique constraint violation.
+    pub fn try_connect<
+        R: FromDatum + IntoDatum,
+        F: FnOnce(SpiClient) -> std::result::Result<Option<R>, SpiError>,
+    >(
+        f: F,
+    ) -> std::result::Result<Option<R>, SpiError> {
+        Spi::catch_in_subtransaction(|| Spi::connect_internal(f))
+    }
+
+    /// Like [`Spi::get_one()`], but returns an `Err` rather than aborting the transaction if the
+    /// query raises an ERROR
+    pub fn try_get_one<A: FromDatum + IntoDatum>(
+        query: &str,
+    ) -> std::result::Result<Option<A>, SpiError> {
+        Spi::try_connect(|client| Ok(client.select(query, Some(1), None).first().get_one()))
+    }
+
+    /// Like [`Spi::get_one_with_args()`], but returns an `Err` rather than aborting the
+    /// transaction if the query raises an ERROR
+    pub fn try_get_one_with_args<A: FromDatum + IntoDatum>(
+        query: &str,
+        args: Vec<(PgOid, Option<pg_sys::Datum>)>,
+    ) -> std::result::Result<Option<A>, SpiError> {
+        Spi::try_connect(|client| Ok(client.select(query, Some(1), Some(args)).first().get_one()))
+    }
+
+    /// Like [`Spi::run()`], but returns an `Err` rather than aborting the transaction if the
+    /// statement raises an ERROR
+    pub fn try_run(query: &str) -> std::result::Result<(), SpiError> {
+        Spi::try_connect(|mut client| {
+            client.update(query, None, None);
+            Ok(Some(()))
+        })
+        .map(|_| ())
+    }
+
+    /// Run `f` in a new subtransaction, which is committed if `f` returns `Ok` and rolled back
+    /// if it returns `Err`, raises an ERROR, or panics.  Errors are returned as `SpiError::Sql`
+    fn catch_in_subtransaction<R, F: FnOnce() -> std::result::Result<R, SpiError>>(
+        f: F,
+    ) -> std::result::Result<R, SpiError> {
+        unsafe {
+            let oldcontext = pg_sys::CurrentMemoryContext;
+            let oldowner = pg_sys::CurrentResourceOwner;
+
+            pg_sys::BeginInternalSubTransaction(std::ptr::null_mut());
+            // run `f` in the caller's memory context, not the subtransaction's
+            pg_sys::CurrentMemoryContext = oldcontext;
+
+            // if `f` fails, the subtransaction is rolled back and anything `f` was
+            // in the middle of doing is discarded, so it's fine to assume it's unwind safe
+            let result = pg_sys::pg_try(std::panic::AssertUnwindSafe(f));
+
+            // an ERROR leaves us in Postgres' ErrorContext
+            pg_sys::CurrentMemoryContext = oldcontext;
+            let result = match result.catch_error() {
+                Ok(Ok(result)) => {
+                    pg_sys::ReleaseCurrentSubTransaction();
+                    Ok(result)
+                }
+                Ok(Err(e)) => {
+                    pg_sys::RollbackAndReleaseCurrentSubTransaction();
+                    Err(e)
+                }
+                Err(caught) => {
+                    let e = SpiError::from_caught_error(caught);
+                    pg_sys::RollbackAndReleaseCurrentSubTransaction();
+                    Err(e)
+                }
+            };
+
+            pg_sys::CurrentMemoryContext = oldcontext;
+            pg_sys::CurrentResourceOwner = oldowner;
+            result
+        }
+    }
+
+    fn connect_internal<
+        R: FromDatum + IntoDatum,
+        F: FnOnce(SpiClient) -> std::result::Result<Option<R>, SpiError>,
+    >(
+        f: F,
+    ) -> std::result::Result<Option<R>, SpiError> {
         let outer_memory_context =
             PgMemoryContexts::For(PgMemoryContexts::CurrentMemoryContext.value());
 
@@ -252,25 +549,33 @@ impl Spi {
                     None => None,
                 };
 
-                copied_datum
+                Ok(copied_datum)
             }
 
             // closure returned an error
-            Err(e) => panic!("{:?}", e),
+            Err(e) => Err(e),
         }
     }
 
     pub fn check_status(status_code: i32) -> SpiOk {
+        match Spi::try_check_status(status_code) {
+            Ok(ok) => ok,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    /// Like [`Spi::check_status()`], but returns SPI's error codes as an `Err`
+    pub fn try_check_status(status_code: i32) -> std::result::Result<SpiOk, SpiError> {
         if status_code > 0 {
             let status_enum = SpiOk::from_i32(status_code);
             match status_enum {
-                Some(ok) => ok,
+                Some(ok) => Ok(ok),
                 None => panic!("unrecognized SPI status code {}", status_code),
             }
         } else {
             let status_enum = SpiError::from_i32(-status_code);
             match status_enum {
-                Some(e) => panic!("{:?}", e),
+                Some(e) => Err(e),
                 None => panic!("unrecognized SPI status code {}", status_code),
             }
         }
@@ -309,6 +614,29 @@ impl SpiClient {
         SpiClient::execute(query, false, limit, args)
     }
 
+    /// Like [`SpiClient::select()`], but returns an `Err` rather than aborting the transaction if
+    /// the query raises an ERROR.  The query runs in its own subtransaction.
+    pub fn try_select(
+        &self,
+        query: &str,
+        limit: Option<i64>,
+        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
+    ) -> std::result::Result<SpiTupleTable, SpiError> {
+        Spi::catch_in_subtransaction(|| Ok(SpiClient::execute(query, false, limit, args)))
+    }
+
+    /// Like [`SpiClient::update()`], but returns an `Err` rather than aborting the transaction if
+    /// the statement raises an ERROR.  The statement runs in its own subtransaction, so the
+    /// `SpiClient` remains usable afterwards
+    pub fn try_update(
+        &mut self,
+        query: &str,
+        limit: Option<i64>,
+        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
+    ) -> std::result::Result<SpiTupleTable, SpiError> {
+        Spi::catch_in_subtransaction(|| Ok(SpiClient::execute(query, false, limit, args)))
+    }
+
     fn execute(
         query: &str,
         read_only: bool,
@@ -323,27 +651,7 @@ impl SpiClient {
         let status_code = match args {
             Some(args) => {
                 let nargs = args.len();
-                let mut argtypes = vec![];
-                let mut datums = vec![];
-                let mut nulls = vec![];
-
-                for (argtype, datum) in args {
-                    argtypes.push(argtype.value());
-
-                    match datum {
-                        Some(datum) => {
-                            // ' ' here means that the datum is not null
-                            datums.push(datum);
-                            nulls.push(' ' as std::os::raw::c_char);
-                        }
-
-                        None => {
-                            // 'n' here means that the datum is null
-                            datums.push(0);
-                            nulls.push('n' as std::os::raw::c_char);
-                        }
-                    }
-                }
+                let (mut argtypes, mut datums, nulls) = SpiClient::split_args(args);
 
                 unsafe {
                     pg_sys::SPI_execute_with_args(
@@ -360,8 +668,343 @@ impl SpiClient {
             None => unsafe { pg_sys::SPI_execute(src.as_ptr(), read_only, limit.unwrap_or(0)) },
         };
 
+        SpiTupleTable::from_spi(Spi::check_status(status_code))
+    }
+
+    /// Plan `query`, whose parameters `$1`, `$2`, etc are of the types in `args`, for later execution
+    pub fn prepare(&self, query: &str, args: &[PgOid]) -> PreparedStatement<'_> {
+        let src = std::ffi::CString::new(query).expect("query contained a null byte");
+        let mut argtypes = args.iter().map(|oid| oid.value()).collect::<Vec<_>>();
+
+        let plan = unsafe {
+            pg_sys::SPI_prepare(src.as_ptr(), argtypes.len() as i32, argtypes.as_mut_ptr())
+        };
+        if plan.is_null() {
+            panic!(
+                "SPI_prepare failed: {:?}",
+                SpiError::from_i32(-unsafe { pg_sys::SPI_result })
+            );
+        }
+
+        PreparedStatement {
+            plan,
+            argtypes: args.to_vec(),
+            kept: false,
+            _client: PhantomData,
+        }
+    }
+
+    /// execute a `PreparedStatement` that only reads from the database
+    pub fn select_prepared(
+        &self,
+        statement: &PreparedStatement<'_>,
+        limit: Option<i64>,
+        args: Vec<Option<pg_sys::Datum>>,
+    ) -> SpiTupleTable {
+        statement.execute(false, limit, args)
+    }
+
+    /// execute a `PreparedStatement` that modifies the database in some way
+    ///
+    /// Unlike [`SpiClient::update()`] this only borrows the client, as `statement` already does
+    pub fn update_prepared(
+        &self,
+        statement: &PreparedStatement<'_>,
+        limit: Option<i64>,
+        args: Vec<Option<pg_sys::Datum>>,
+    ) -> SpiTupleTable {
+        statement.execute(false, limit, args)
+    }
+
+    /// Open a cursor over the results of `query`, without running it to completion
+    pub fn open_cursor(
+        &self,
+        query: &str,
+        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
+    ) -> SpiCursor<'_> {
+        SpiClient::open_cursor_internal(None, query, args)
+    }
+
+    /// Open a cursor with a specific name, which must not already be in use in this transaction
+    pub fn open_named_cursor(
+        &self,
+        name: &str,
+        query: &str,
+        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
+    ) -> SpiCursor<'_> {
+        SpiClient::open_cursor_internal(Some(name), query, args)
+    }
+
+    /// Find a cursor that's still open in this transaction, such as one left open by
+    /// [`SpiCursor::detach_into_name()`] or declared with SQL's `DECLARE`
+    pub fn find_cursor(&self, name: &str) -> Option<SpiCursor<'_>> {
+        let name = std::ffi::CString::new(name).expect("cursor name contained a null byte");
+        let portal = unsafe { pg_sys::SPI_cursor_find(name.as_ptr()) };
+        if portal.is_null() {
+            None
+        } else {
+            Some(SpiCursor::new(portal))
+        }
+    }
+
+    fn open_cursor_internal<'client>(
+        name: Option<&str>,
+        query: &str,
+        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
+    ) -> SpiCursor<'client> {
+        let name = name
+            .map(|name| std::ffi::CString::new(name).expect("cursor name contained a null byte"));
+        let src = std::ffi::CString::new(query).expect("query contained a null byte");
+        let args = args.unwrap_or_default();
+        let nargs = args.len();
+        let (mut argtypes, mut datums, nulls) = SpiClient::split_args(args);
+
+        let portal = unsafe {
+            pg_sys::SPI_cursor_open_with_args(
+                name.as_ref().map_or(std::ptr::null(), |name| name.as_ptr()),
+                src.as_ptr(),
+                nargs as i32,
+                argtypes.as_mut_ptr(),
+                datums.as_mut_ptr(),
+                nulls.as_ptr(),
+                false,
+                0,
+            )
+        };
+        if portal.is_null() {
+            panic!(
+                "SPI_cursor_open_with_args failed: {:?}",
+                SpiError::from_i32(-unsafe { pg_sys::SPI_result })
+            );
+        }
+
+        SpiCursor::new(portal)
+    }
+
+    /// Split `args` into the parallel type, value, and null arrays SPI wants
+    fn split_args(
+        args: Vec<(PgOid, Option<pg_sys::Datum>)>,
+    ) -> (
+        Vec<pg_sys::Oid>,
+        Vec<pg_sys::Datum>,
+        Vec<std::os::raw::c_char>,
+    ) {
+        let mut argtypes = vec![];
+        let mut datums = vec![];
+        let mut nulls = vec![];
+
+        for (argtype, datum) in args {
+            argtypes.push(argtype.value());
+
+            match datum {
+                Some(datum) => {
+                    // ' ' here means that the datum is not null
+                    datums.push(datum);
+                    nulls.push(' ' as std::os::raw::c_char);
+                }
+
+                None => {
+                    // 'n' here means that the datum is null
+                    datums.push(0);
+                    nulls.push('n' as std::os::raw::c_char);
+                }
+            }
+        }
+
+        (argtypes, datums, nulls)
+    }
+}
+
+impl<'client> SpiCursor<'client> {
+    const DEFAULT_BATCH_SIZE: i64 = 100;
+
+    fn new(portal: pg_sys::Portal) -> Self {
+        SpiCursor {
+            portal,
+            batch_size: SpiCursor::DEFAULT_BATCH_SIZE,
+            table: std::ptr::null_mut(),
+            rows: Vec::new(),
+            position: 0,
+            _client: PhantomData,
+        }
+    }
+
+    /// How many rows to fetch at a time when walking this cursor with [`SpiCursor::next_row()`]
+    pub fn set_batch_size(mut self, batch_size: i64) -> Self {
+        assert!(batch_size > 0, "batch size must be positive");
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// The name of the underlying portal
+    pub fn name(&self) -> &str {
+        unsafe { std::ffi::CStr::from_ptr((*self.portal).name) }
+            .to_str()
+            .expect("cursor name is not valid UTF8")
+    }
+
+    /// Fetch up to `count` more rows.  The result is empty once the cursor is exhausted.
+    ///
+    /// The rows of the previous fetch are freed first, which is why they can't be held onto
+    /// across calls.
+    pub fn fetch(&mut self, count: i64) -> &[SpiHeapTupleData] {
+        self.free_batch();
+        unsafe {
+            pg_sys::SPI_tuptable = std::ptr::null_mut();
+            pg_sys::SPI_cursor_fetch(self.portal, true, count as std::os::raw::c_long);
+
+            self.table = pg_sys::SPI_tuptable;
+            if !self.table.is_null() {
+                let tupdesc = (*self.table).tupdesc;
+                let vals =
+                    std::slice::from_raw_parts((*self.table).vals, pg_sys::SPI_processed as usize);
+                self.rows = vals
+                    .iter()
+                    .map(|htup| SpiHeapTupleData::new(tupdesc, *htup))
+                    .collect();
+            }
+        }
+        &self.rows
+    }
+
+    /// The next row of the cursor, fetching another batch when the current one runs out.
+    /// `None` once the cursor is exhausted
+    pub fn next_row(&mut self) -> Option<&SpiHeapTupleData> {
+        if self.position >= self.rows.len() && self.fetch(self.batch_size).is_empty() {
+            return None;
+        }
+        self.position += 1;
+        self.rows.get(self.position - 1)
+    }
+
+    /// Iterate over the rest of the cursor's rows as `T`s, fetching them in batches of
+    /// [`SpiCursor::set_batch_size()`] rows
+    pub fn rows<T: FromSpiRow>(&mut self) -> SpiCursorRows<'_, 'client, T> {
+        SpiCursorRows {
+            cursor: self,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Skip over up to `count` rows without fetching them, returning how many were skipped
+    pub fn move_forward(&mut self, count: i64) -> usize {
+        unsafe {
+            pg_sys::SPI_cursor_move(self.portal, true, count as std::os::raw::c_long);
+            pg_sys::SPI_processed as usize
+        }
+    }
+
+    /// Leave the cursor open for the rest of the transaction, returning its name for use
+    /// with [`SpiClient::find_cursor()`]
+    pub fn detach_into_name(mut self) -> CursorName {
+        let name = self.name().to_string();
+        self.free_batch();
+        std::mem::forget(self);
+        name
+    }
+
+    /// Hand the current batch back to SPI
+    fn free_batch(&mut self) {
+        self.rows.clear();
+        self.position = 0;
+        if !self.table.is_null() {
+            unsafe { pg_sys::SPI_freetuptable(self.table) }
+            self.table = std::ptr::null_mut();
+        }
+    }
+}
+
+impl<T: FromSpiRow> Iterator for SpiCursorRows<'_, '_, T> {
+    type Item = std::result::Result<T, SpiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cursor.next_row().map(T::from_spi_row)
+    }
+}
+
+impl Drop for SpiCursor<'_> {
+    fn drop(&mut self) {
+        self.free_batch();
+        unsafe { pg_sys::SPI_cursor_close(self.portal) }
+    }
+}
+
+impl<'client> PreparedStatement<'client> {
+    /// Move the plan out of the current SPI connection's memory so that it outlives it
+    pub fn keep(mut self) -> PreparedStatement<'static> {
+        if !self.kept {
+            Spi::check_status(unsafe { pg_sys::SPI_keepplan(self.plan) });
+        }
+
+        // the plan now belongs to the returned statement, so `self` mustn't free it
+        self.kept = false;
+        PreparedStatement {
+            plan: self.plan,
+            argtypes: std::mem::take(&mut self.argtypes),
+            kept: true,
+            _client: PhantomData,
+        }
+    }
+
+    /// The types of the arguments this statement expects
+    pub fn argtypes(&self) -> &[PgOid] {
+        &self.argtypes
+    }
+
+    pub fn as_ptr(&self) -> pg_sys::SPIPlanPtr {
+        self.plan
+    }
+
+    fn execute(
+        &self,
+        read_only: bool,
+        limit: Option<i64>,
+        args: Vec<Option<pg_sys::Datum>>,
+    ) -> SpiTupleTable {
+        if args.len() != self.argtypes.len() {
+            panic!(
+                "prepared statement expects {} arguments, but {} were given",
+                self.argtypes.len(),
+                args.len()
+            );
+        }
+
+        let args = self.argtypes.iter().cloned().zip(args).collect::<Vec<_>>();
+        let (_, mut datums, nulls) = SpiClient::split_args(args);
+
+        unsafe {
+            pg_sys::SPI_tuptable = std::ptr::null_mut();
+        }
+        let status_code = unsafe {
+            pg_sys::SPI_execute_plan(
+                self.plan,
+                datums.as_mut_ptr(),
+                nulls.as_ptr(),
+                read_only,
+                limit.unwrap_or(0),
+            )
+        };
+
+        SpiTupleTable::from_spi(Spi::check_status(status_code))
+    }
+}
+
+impl Drop for PreparedStatement<'_> {
+    fn drop(&mut self) {
+        // a plan that wasn't kept belongs to the SPI connection, which may already be finished
+        if self.kept {
+            unsafe {
+                pg_sys::SPI_freeplan(self.plan);
+            }
+        }
+    }
+}
+
+impl SpiTupleTable {
+    /// Wrap whatever the last SPI call left in `SPI_tuptable`
+    fn from_spi(status_code: SpiOk) -> Self {
         SpiTupleTable {
-            status_code: Spi::check_status(status_code),
+            status_code,
             table: unsafe { pg_sys::SPI_tuptable },
             size: unsafe { pg_sys::SPI_processed as usize },
             tupdesc: if unsafe { pg_sys::SPI_tuptable }.is_null() {
@@ -372,9 +1015,7 @@ impl SpiClient {
             current: -1,
         }
     }
-}
 
-impl SpiTupleTable {
     /// `SpiTupleTable`s are positioned before the start, for iteration purposes.
     ///
     /// This method moves the position to the first row.  If there are no rows, this
@@ -412,11 +1053,19 @@ impl SpiTupleTable {
         (a, b, c)
     }
 
+    /// Iterate over the rows of this table as `T`s, starting from the first row
+    pub fn into_rows<T: FromSpiRow>(
+        mut self,
+    ) -> impl Iterator<Item = std::result::Result<T, SpiError>> {
+        self.current = -1;
+        self.map(|row| T::from_spi_row(&row))
+    }
+
     pub fn get_heap_tuple(&self) -> Option<SpiHeapTupleData> {
         if self.current < 0 {
             panic!("SpiTupleTable positioned before start")
         }
-        if self.current as u64 >= unsafe { pg_sys::SPI_processed } {
+        if self.current as usize >= self.size {
             None
         } else {
             match self.tupdesc {
@@ -436,7 +1085,7 @@ impl SpiTupleTable {
         if self.current < 0 {
             panic!("SpiTupleTable positioned before start")
         }
-        if self.current as u64 >= unsafe { pg_sys::SPI_processed } {
+        if self.current as usize >= self.size {
             None
         } else {
             match self.tupdesc {
@@ -525,6 +1174,37 @@ impl SpiHeapTupleData {
         }
     }
 
+    /// Get a typed Datum value from this HeapTuple by its field name, first checking that the
+    /// field's type, as reported by `SPI_gettypeid`, can be read as a `T`.
+    ///
+    /// The type is compatible if it's the same as `T`'s, or binary coercible to it, such
+    /// as a `varchar` being read as a `String`
+    pub fn get_by_name<T: FromDatum + IntoDatum>(
+        &self,
+        name: &str,
+    ) -> std::result::Result<Option<T>, SpiError> {
+        use crate::pg_sys::AsPgCStr;
+
+        let fnumber = unsafe { pg_sys::SPI_fnumber(self.tupdesc, name.as_pg_cstr()) };
+        if fnumber == pg_sys::SPI_ERROR_NOATTRIBUTE {
+            return Err(SpiError::ColumnNotFound {
+                column: name.to_string(),
+            });
+        }
+
+        let expected = T::type_oid();
+        let found = unsafe { pg_sys::SPI_gettypeid(self.tupdesc, fnumber) };
+        if found != expected && !unsafe { pg_sys::IsBinaryCoercible(found, expected) } {
+            return Err(SpiError::TypeMismatch {
+                column: name.to_string(),
+                expected,
+                found,
+            });
+        }
+
+        Ok(self.by_ordinal(fnumber as usize)?.value::<T>())
+    }
+
     /// Get a mutable typed Datum value from this HeapTuple by its ordinal position.  
     ///
     /// The ordinal position is 1-based.
@@ -0,0 +1,956 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+/*!
+
+[Table Access Method](https://www.postgresql.org/docs/current/tableam.html) support.
+
+Table access methods are created by implementing [`TableAm`] for a type and decorating the
+implementation with [`#[pg_table_am]`](pgx_macros::pg_table_am).  They require Postgres 12 or newer.
+
+The macro generates the `table_am_handler` function Postgres uses to discover the access method's
+callbacks, along with the [`CREATE ACCESS METHOD`](https://www.postgresql.org/docs/current/sql-create-access-method.html)
+statement that registers it.
+
+Only the callbacks needed for sequential scans and basic DML are exposed.  The remaining
+`TableAmRoutine` callbacks (index fetches, `CLUSTER`, sampling, ...) raise an error saying the
+operation isn't supported.
+
+The planner is free to choose a parallel sequential scan of any table.  Its participants share a
+flag, and only the first of them to fetch a tuple runs [`TableAm::scan_getnextslot`], over the
+whole relation, while the others return no tuples.
+
+# Example
+
+```rust,no_run
+use pgx::*;
+
+pub struct BlackholeAm;
+
+#[pg_table_am]
+impl TableAm for BlackholeAm {
+    const NAME: &'static str = "blackhole";
+
+    fn scan_begin(
+        _relation: &PgRelation,
+        _snapshot: pg_sys::Snapshot,
+        _keys: &[pg_sys::ScanKeyData],
+        _flags: u32,
+    ) -> Self::ScanState {
+    }
+
+    fn scan_getnextslot(
+        _scan: &mut PgTableScan<Self::ScanState>,
+        _direction: pg_sys::ScanDirection,
+        _slot: &mut PgTupleTableSlot,
+    ) -> bool {
+        false
+    }
+
+    fn tuple_insert(
+        _relation: &PgRelation,
+        _slot: &mut PgTupleTableSlot,
+        _cid: pg_sys::CommandId,
+        _options: i32,
+    ) {
+    }
+
+    fn relation_set_new_filenode(
+        _relation: &PgRelation,
+        _new_rnode: &pg_sys::RelFileNode,
+        _persistence: std::os::raw::c_char,
+    ) -> (pg_sys::TransactionId, pg_sys::MultiXactId) {
+        (pg_sys::InvalidTransactionId, 0)
+    }
+
+    fn relation_estimate_size(_relation: &PgRelation) -> TableSizeEstimate {
+        TableSizeEstimate::default()
+    }
+}
+```
+
+This creates SQL like so:
+
+```sql
+-- src/lib.rs:5
+-- table_am::BlackholeAm
+CREATE ACCESS METHOD blackhole TYPE TABLE HANDLER blackhole_am_handler;
+```
+
+Any of the optional functions (`scan_rescan`, `scan_end`, `tuple_delete`, `tuple_update`,
+`tuple_lock`, `relation_vacuum`, `relation_size`) can be left out, `#[pg_table_am]` will create a
+stub.
+*/
+use crate::{pg_sys, FromDatum, PgBox, PgMemoryContexts, PgRelation, PgTupleDesc};
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int, c_void};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The size estimate a [`TableAm::relation_estimate_size`] reports back to the planner.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TableSizeEstimate {
+    /// The number of pages in the relation.
+    pub pages: pg_sys::BlockNumber,
+    /// The number of tuples in the relation.
+    pub tuples: f64,
+    /// The fraction of pages which are marked all-visible.
+    pub allvisfrac: f64,
+}
+
+/// A safe wrapper around a `pg_sys::TupleTableSlot`.
+///
+/// The slot is owned by Postgres, this only borrows it for the duration of a callback.
+pub struct PgTupleTableSlot {
+    slot: *mut pg_sys::TupleTableSlot,
+}
+
+impl PgTupleTableSlot {
+    /// Wrap a Postgres-provided `pg_sys::TupleTableSlot`.
+    ///
+    /// ## Safety
+    ///
+    /// This method is unsafe as we cannot validate that the provided pointer is a valid slot.
+    pub unsafe fn from_pg(slot: *mut pg_sys::TupleTableSlot) -> Self {
+        PgTupleTableSlot { slot }
+    }
+
+    /// The tuple descriptor of the slot.
+    pub fn tuple_desc(&self) -> PgTupleDesc<'static> {
+        unsafe {
+            let tupdesc = (*self.slot).tts_tupleDescriptor;
+            // `PgTupleDesc` releases a reference counted descriptor when dropped, so take one out
+            if (*tupdesc).tdrefcount >= 0 {
+                pg_sys::IncrTupleDescRefCount(tupdesc);
+            }
+            PgTupleDesc::from_pg(tupdesc)
+        }
+    }
+
+    /// The number of attributes in the slot's tuple descriptor.
+    pub fn natts(&self) -> usize {
+        unsafe { (*(*self.slot).tts_tupleDescriptor).natts as usize }
+    }
+
+    /// Is the slot empty?
+    pub fn is_empty(&self) -> bool {
+        unsafe { (*self.slot).tts_flags as u32 & pg_sys::TTS_FLAG_EMPTY != 0 }
+    }
+
+    /// Clear the contents of the slot.
+    pub fn clear(&mut self) {
+        unsafe {
+            if let Some(clear) = (*(*self.slot).tts_ops).clear {
+                clear(self.slot);
+            }
+        }
+    }
+
+    /// Store a virtual tuple made of `values` in the slot, replacing whatever it held.
+    ///
+    /// `values` must have one entry per attribute, `None` for SQL `NULL`.  Pass-by-reference
+    /// Datums must stay valid for as long as the slot holds them.
+    pub fn store_virtual(&mut self, values: &[Option<pg_sys::Datum>]) {
+        let natts = self.natts();
+        if values.len() != natts {
+            panic!(
+                "slot has {} attributes, but {} values were provided",
+                natts,
+                values.len()
+            );
+        }
+
+        self.clear();
+        unsafe {
+            let slot_values = std::slice::from_raw_parts_mut((*self.slot).tts_values, natts);
+            let slot_isnull = std::slice::from_raw_parts_mut((*self.slot).tts_isnull, natts);
+            for (i, value) in values.iter().enumerate() {
+                slot_values[i] = value.unwrap_or(0);
+                slot_isnull[i] = value.is_none();
+            }
+            pg_sys::ExecStoreVirtualTuple(self.slot);
+        }
+    }
+
+    /// The values of all attributes in the slot, `None` for SQL `NULL`.
+    pub fn values(&mut self) -> Vec<Option<pg_sys::Datum>> {
+        let natts = self.natts();
+        unsafe {
+            if ((*self.slot).tts_nvalid as usize) < natts {
+                pg_sys::slot_getsomeattrs_int(self.slot, natts as c_int);
+            }
+            let values = std::slice::from_raw_parts((*self.slot).tts_values, natts);
+            let isnull = std::slice::from_raw_parts((*self.slot).tts_isnull, natts);
+            values
+                .iter()
+                .zip(isnull.iter())
+                .map(|(&value, &isnull)| if isnull { None } else { Some(value) })
+                .collect()
+        }
+    }
+
+    /// Get a typed attribute value from the slot.
+    ///
+    /// The `attno` argument is 1-based, like Postgres' attribute numbers.
+    pub fn get<T: FromDatum>(&mut self, attno: usize) -> Option<T> {
+        let natts = self.natts();
+        if attno < 1 || attno > natts {
+            panic!("attribute number {} is out of bounds", attno);
+        }
+
+        unsafe {
+            if ((*self.slot).tts_nvalid as usize) < attno {
+                pg_sys::slot_getsomeattrs_int(self.slot, attno as c_int);
+            }
+            let tupdesc = (*self.slot).tts_tupleDescriptor;
+            let typoid = (*(*tupdesc).attrs.as_ptr().add(attno - 1)).atttypid;
+            let datum = *(*self.slot).tts_values.add(attno - 1);
+            let isnull = *(*self.slot).tts_isnull.add(attno - 1);
+            T::from_datum(datum, isnull, typoid)
+        }
+    }
+
+    /// The tuple id of the tuple in the slot.
+    pub fn tid(&self) -> pg_sys::ItemPointerData {
+        unsafe { (*self.slot).tts_tid }
+    }
+
+    /// Set the tuple id of the tuple in the slot.
+    pub fn set_tid(&mut self, tid: pg_sys::ItemPointerData) {
+        unsafe { (*self.slot).tts_tid = tid }
+    }
+
+    pub fn as_ptr(&self) -> *mut pg_sys::TupleTableSlot {
+        self.slot
+    }
+}
+
+/// The scan descriptor handed to Postgres.  It must start with the `TableScanDescData` so
+/// Postgres can use it as one.
+#[repr(C)]
+struct TableScanDescWithState {
+    base: pg_sys::TableScanDescData,
+    state: *mut c_void,
+    /// For a parallel scan, whether this participant claimed it, decided by the first fetch
+    /// after the scan is started or restarted
+    participating: Option<bool>,
+}
+
+/// The parallel scan descriptor shared by a parallel scan's participants.  It must start with
+/// the `ParallelTableScanDescData` so Postgres can use it as one.
+#[repr(C)]
+struct ParallelTableScanDescWithClaim {
+    base: pg_sys::ParallelTableScanDescData,
+    /// Set by the participant that scans the relation
+    claimed: AtomicBool,
+}
+
+/// A safe wrapper around an in-progress table scan (`pg_sys::TableScanDesc`).
+///
+/// `S` is the [`TableAm::ScanState`] returned by [`TableAm::scan_begin`].
+pub struct PgTableScan<S> {
+    desc: *mut TableScanDescWithState,
+    __marker: PhantomData<S>,
+}
+
+impl<S> PgTableScan<S> {
+    unsafe fn from_pg(desc: pg_sys::TableScanDesc) -> Self {
+        PgTableScan {
+            desc: desc as *mut TableScanDescWithState,
+            __marker: PhantomData,
+        }
+    }
+
+    /// The relation being scanned.
+    pub fn relation(&self) -> PgRelation {
+        unsafe { PgRelation::from_pg((*self.desc).base.rs_rd) }
+    }
+
+    /// The snapshot the scan was started with.
+    pub fn snapshot(&self) -> pg_sys::Snapshot {
+        unsafe { (*self.desc).base.rs_snapshot }
+    }
+
+    /// The scan keys for the current (re)scan.
+    pub fn keys(&self) -> &[pg_sys::ScanKeyData] {
+        unsafe { scan_keys((*self.desc).base.rs_key, (*self.desc).base.rs_nkeys) }
+    }
+
+    /// The `pg_sys::ScanOptions` flags the scan was started with.
+    pub fn flags(&self) -> u32 {
+        unsafe { (*self.desc).base.rs_flags }
+    }
+
+    /// The state returned by [`TableAm::scan_begin`].
+    pub fn state(&self) -> &S {
+        unsafe { &*((*self.desc).state as *const S) }
+    }
+
+    /// The state returned by [`TableAm::scan_begin`].
+    pub fn state_mut(&mut self) -> &mut S {
+        unsafe { &mut *((*self.desc).state as *mut S) }
+    }
+
+    pub fn as_ptr(&self) -> pg_sys::TableScanDesc {
+        self.desc as pg_sys::TableScanDesc
+    }
+}
+
+unsafe fn scan_keys<'a>(keys: *mut pg_sys::ScanKeyData, nkeys: i32) -> &'a [pg_sys::ScanKeyData] {
+    if keys.is_null() || nkeys <= 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(keys, nkeys as usize)
+    }
+}
+
+/// A table access method.
+///
+/// Implementations should be decorated with [`#[pg_table_am]`](pgx_macros::pg_table_am), which
+/// generates the handler function and the `CREATE ACCESS METHOD` SQL.
+///
+/// Tuples are exchanged with Postgres through virtual [`PgTupleTableSlot`]s.
+pub trait TableAm {
+    /// The name of the access method, as used in `CREATE TABLE ... USING name`.
+    ///
+    /// **Optional:** This const can be skipped, `#[pg_table_am]` will use the snake cased name of the type.
+    const NAME: &'static str;
+
+    /// Per-scan state, created in [`TableAm::scan_begin`].
+    ///
+    /// The state is dropped when the memory context the scan was started in is deleted.
+    ///
+    /// **Optional:** This type can be skipped, `#[pg_table_am]` will use `()`.
+    type ScanState;
+
+    /// Start a scan of `relation`.
+    ///
+    /// `flags` is a bitmask of `pg_sys::ScanOptions`.
+    fn scan_begin(
+        relation: &PgRelation,
+        snapshot: pg_sys::Snapshot,
+        keys: &[pg_sys::ScanKeyData],
+        flags: u32,
+    ) -> Self::ScanState;
+
+    /// Restart a scan, possibly with new scan keys (see [`PgTableScan::keys`]).
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_table_am]` will create a stub.
+    fn scan_rescan(scan: &mut PgTableScan<Self::ScanState>);
+
+    /// Store the next tuple of the scan in `slot`, returning `false` if there are no more.
+    fn scan_getnextslot(
+        scan: &mut PgTableScan<Self::ScanState>,
+        direction: pg_sys::ScanDirection,
+        slot: &mut PgTupleTableSlot,
+    ) -> bool;
+
+    /// End a scan and release resources.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_table_am]` will create a stub.
+    fn scan_end(scan: &mut PgTableScan<Self::ScanState>);
+
+    /// Insert the tuple in `slot` into `relation`.
+    ///
+    /// Call [`PgTupleTableSlot::set_tid`] with the tuple id of the new tuple if the table is
+    /// expected to have indexes.
+    fn tuple_insert(
+        relation: &PgRelation,
+        slot: &mut PgTupleTableSlot,
+        cid: pg_sys::CommandId,
+        options: i32,
+    );
+
+    /// Delete the tuple identified by `tid`.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_table_am]` will create a stub which raises an error.
+    fn tuple_delete(
+        relation: &PgRelation,
+        tid: pg_sys::ItemPointerData,
+        cid: pg_sys::CommandId,
+        snapshot: pg_sys::Snapshot,
+        wait: bool,
+        failure_data: &mut pg_sys::TM_FailureData,
+    ) -> pg_sys::TM_Result;
+
+    /// Replace the tuple identified by `old_tid` with the tuple in `slot`.
+    ///
+    /// Set `update_indexes` if the new tuple needs new index entries.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_table_am]` will create a stub which raises an error.
+    fn tuple_update(
+        relation: &PgRelation,
+        old_tid: pg_sys::ItemPointerData,
+        slot: &mut PgTupleTableSlot,
+        cid: pg_sys::CommandId,
+        snapshot: pg_sys::Snapshot,
+        wait: bool,
+        failure_data: &mut pg_sys::TM_FailureData,
+        update_indexes: &mut bool,
+    ) -> pg_sys::TM_Result;
+
+    /// Lock the tuple identified by `tid` and store its latest version in `slot`.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_table_am]` will create a stub which raises an error.
+    fn tuple_lock(
+        relation: &PgRelation,
+        tid: pg_sys::ItemPointerData,
+        snapshot: pg_sys::Snapshot,
+        slot: &mut PgTupleTableSlot,
+        cid: pg_sys::CommandId,
+        mode: pg_sys::LockTupleMode,
+        wait_policy: pg_sys::LockWaitPolicy,
+        failure_data: &mut pg_sys::TM_FailureData,
+    ) -> pg_sys::TM_Result;
+
+    /// Create new storage for `relation`, as for `CREATE TABLE` or `TRUNCATE`.
+    ///
+    /// Returns the relation's new `relfrozenxid` and `relminmxid`.
+    fn relation_set_new_filenode(
+        relation: &PgRelation,
+        new_rnode: &pg_sys::RelFileNode,
+        persistence: c_char,
+    ) -> (pg_sys::TransactionId, pg_sys::MultiXactId);
+
+    /// `VACUUM` the relation.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_table_am]` will create a stub which does nothing.
+    fn relation_vacuum(relation: &PgRelation, params: &mut pg_sys::VacuumParams);
+
+    /// The size of the given fork of `relation`, in bytes.
+    ///
+    /// **Optional:** This function can be skipped, `#[pg_table_am]` will create a stub which returns `0`.
+    fn relation_size(relation: &PgRelation, fork: pg_sys::ForkNumber) -> u64;
+
+    /// Estimate the size of `relation` for the planner.
+    fn relation_estimate_size(relation: &PgRelation) -> TableSizeEstimate;
+}
+
+/// Create the `pg_sys::TableAmRoutine` describing `T`.
+///
+/// This is called by the handler function `#[pg_table_am]` generates.
+pub fn table_am_routine<T: TableAm>() -> PgBox<pg_sys::TableAmRoutine> {
+    let mut routine = PgBox::<pg_sys::TableAmRoutine>::alloc_node(pg_sys::NodeTag_T_TableAmRoutine);
+
+    routine.slot_callbacks = Some(slot_callbacks);
+
+    routine.scan_begin = Some(scan_begin::<T>);
+    routine.scan_end = Some(scan_end::<T>);
+    routine.scan_rescan = Some(scan_rescan::<T>);
+    routine.scan_getnextslot = Some(scan_getnextslot::<T>);
+
+    routine.parallelscan_estimate = Some(parallelscan_estimate);
+    routine.parallelscan_initialize = Some(parallelscan_initialize);
+    routine.parallelscan_reinitialize = Some(parallelscan_reinitialize);
+
+    routine.index_fetch_begin = Some(index_fetch_begin);
+    routine.index_fetch_reset = Some(index_fetch_reset);
+    routine.index_fetch_end = Some(index_fetch_end);
+    routine.index_fetch_tuple = Some(index_fetch_tuple);
+
+    routine.tuple_fetch_row_version = Some(tuple_fetch_row_version);
+    routine.tuple_tid_valid = Some(tuple_tid_valid);
+    routine.tuple_get_latest_tid = Some(tuple_get_latest_tid);
+    routine.tuple_satisfies_snapshot = Some(tuple_satisfies_snapshot);
+    #[cfg(any(feature = "pg12", feature = "pg13"))]
+    {
+        routine.compute_xid_horizon_for_tuples = Some(compute_xid_horizon_for_tuples);
+    }
+    #[cfg(feature = "pg14")]
+    {
+        routine.index_delete_tuples = Some(index_delete_tuples);
+    }
+
+    routine.tuple_insert = Some(tuple_insert::<T>);
+    routine.tuple_insert_speculative = Some(tuple_insert_speculative);
+    routine.tuple_complete_speculative = Some(tuple_complete_speculative);
+    routine.multi_insert = Some(multi_insert::<T>);
+    routine.tuple_delete = Some(tuple_delete::<T>);
+    routine.tuple_update = Some(tuple_update::<T>);
+    routine.tuple_lock = Some(tuple_lock::<T>);
+
+    routine.relation_set_new_filenode = Some(relation_set_new_filenode::<T>);
+    routine.relation_nontransactional_truncate = Some(relation_nontransactional_truncate);
+    routine.relation_copy_data = Some(relation_copy_data);
+    routine.relation_copy_for_cluster = Some(relation_copy_for_cluster);
+    routine.relation_vacuum = Some(relation_vacuum::<T>);
+    routine.scan_analyze_next_block = Some(scan_analyze_next_block);
+    routine.scan_analyze_next_tuple = Some(scan_analyze_next_tuple);
+    routine.index_build_range_scan = Some(index_build_range_scan);
+    routine.index_validate_scan = Some(index_validate_scan);
+
+    routine.relation_size = Some(relation_size::<T>);
+    routine.relation_needs_toast_table = Some(relation_needs_toast_table);
+    routine.relation_estimate_size = Some(relation_estimate_size::<T>);
+
+    routine.scan_sample_next_block = Some(scan_sample_next_block);
+    routine.scan_sample_next_tuple = Some(scan_sample_next_tuple);
+
+    routine.into_pg_boxed()
+}
+
+fn unsupported(what: &str) -> ! {
+    panic!("{} is not supported by this table access method", what)
+}
+
+unsafe extern "C" fn slot_callbacks(
+    _relation: pg_sys::Relation,
+) -> *const pg_sys::TupleTableSlotOps {
+    &pg_sys::TTSOpsVirtual
+}
+
+unsafe extern "C" fn scan_begin<T: TableAm>(
+    relation: pg_sys::Relation,
+    snapshot: pg_sys::Snapshot,
+    nkeys: c_int,
+    keys: *mut pg_sys::ScanKeyData,
+    parallel_scan: pg_sys::ParallelTableScanDesc,
+    flags: pg_sys::uint32,
+) -> pg_sys::TableScanDesc {
+    pg_sys::guard::guard(|| {
+        let desc = pg_sys::palloc0(std::mem::size_of::<TableScanDescWithState>())
+            as *mut TableScanDescWithState;
+        (*desc).base.rs_rd = relation;
+        (*desc).base.rs_snapshot = snapshot;
+        (*desc).base.rs_nkeys = nkeys;
+        (*desc).base.rs_flags = flags;
+        (*desc).base.rs_parallel = parallel_scan;
+        (*desc).participating = None;
+        if nkeys > 0 {
+            let size = std::mem::size_of::<pg_sys::ScanKeyData>() * nkeys as usize;
+            (*desc).base.rs_key = pg_sys::palloc(size) as *mut pg_sys::ScanKeyData;
+            if !keys.is_null() {
+                std::ptr::copy(keys, (*desc).base.rs_key, nkeys as usize);
+            }
+        }
+
+        let state = T::scan_begin(
+            &PgRelation::from_pg(relation),
+            snapshot,
+            scan_keys(keys, nkeys),
+            flags,
+        );
+        (*desc).state =
+            PgMemoryContexts::CurrentMemoryContext.leak_and_drop_on_delete(state) as *mut c_void;
+        desc as pg_sys::TableScanDesc
+    })
+}
+
+unsafe extern "C" fn scan_end<T: TableAm>(scan: pg_sys::TableScanDesc) {
+    pg_sys::guard::guard(|| {
+        T::scan_end(&mut PgTableScan::from_pg(scan));
+        // a parallel scan's participants are given a copy of the leader's snapshot to release
+        if (*scan).rs_flags & pg_sys::ScanOptions_SO_TEMP_SNAPSHOT != 0 {
+            pg_sys::UnregisterSnapshot((*scan).rs_snapshot);
+        }
+        if !(*scan).rs_key.is_null() {
+            pg_sys::pfree((*scan).rs_key as *mut c_void);
+        }
+        pg_sys::pfree(scan as *mut c_void);
+    })
+}
+
+unsafe extern "C" fn scan_rescan<T: TableAm>(
+    scan: pg_sys::TableScanDesc,
+    keys: *mut pg_sys::ScanKeyData,
+    _set_params: bool,
+    _allow_strat: bool,
+    _allow_sync: bool,
+    _allow_pagemode: bool,
+) {
+    pg_sys::guard::guard(|| {
+        if !keys.is_null() && (*scan).rs_nkeys > 0 {
+            std::ptr::copy(keys, (*scan).rs_key, (*scan).rs_nkeys as usize);
+        }
+        (*(scan as *mut TableScanDescWithState)).participating = None;
+        T::scan_rescan(&mut PgTableScan::from_pg(scan))
+    })
+}
+
+unsafe extern "C" fn scan_getnextslot<T: TableAm>(
+    scan: pg_sys::TableScanDesc,
+    direction: pg_sys::ScanDirection,
+    slot: *mut pg_sys::TupleTableSlot,
+) -> bool {
+    pg_sys::guard::guard(|| {
+        let parallel_scan = (*scan).rs_parallel as *mut ParallelTableScanDescWithClaim;
+        if !parallel_scan.is_null() {
+            let participating = (*(scan as *mut TableScanDescWithState))
+                .participating
+                .get_or_insert_with(|| !(*parallel_scan).claimed.swap(true, Ordering::SeqCst));
+            if !*participating {
+                PgTupleTableSlot::from_pg(slot).clear();
+                return false;
+            }
+        }
+
+        T::scan_getnextslot(
+            &mut PgTableScan::from_pg(scan),
+            direction,
+            &mut PgTupleTableSlot::from_pg(slot),
+        )
+    })
+}
+
+unsafe extern "C" fn parallelscan_estimate(_relation: pg_sys::Relation) -> pg_sys::Size {
+    std::mem::size_of::<ParallelTableScanDescWithClaim>()
+}
+
+unsafe extern "C" fn parallelscan_initialize(
+    relation: pg_sys::Relation,
+    parallel_scan: pg_sys::ParallelTableScanDesc,
+) -> pg_sys::Size {
+    let parallel_scan = parallel_scan as *mut ParallelTableScanDescWithClaim;
+    (*parallel_scan).base.phs_relid = (*relation).rd_id;
+    (*parallel_scan).base.phs_syncscan = false;
+    std::ptr::write(&mut (*parallel_scan).claimed, AtomicBool::new(false));
+    std::mem::size_of::<ParallelTableScanDescWithClaim>()
+}
+
+unsafe extern "C" fn parallelscan_reinitialize(
+    _relation: pg_sys::Relation,
+    parallel_scan: pg_sys::ParallelTableScanDesc,
+) {
+    (*(parallel_scan as *mut ParallelTableScanDescWithClaim))
+        .claimed
+        .store(false, Ordering::SeqCst);
+}
+
+unsafe extern "C" fn index_fetch_begin(
+    _relation: pg_sys::Relation,
+) -> *mut pg_sys::IndexFetchTableData {
+    pg_sys::guard::guard(|| unsupported("index scan"))
+}
+
+unsafe extern "C" fn index_fetch_reset(_data: *mut pg_sys::IndexFetchTableData) {
+    pg_sys::guard::guard(|| unsupported("index scan"))
+}
+
+unsafe extern "C" fn index_fetch_end(_data: *mut pg_sys::IndexFetchTableData) {
+    pg_sys::guard::guard(|| unsupported("index scan"))
+}
+
+unsafe extern "C" fn index_fetch_tuple(
+    _scan: *mut pg_sys::IndexFetchTableData,
+    _tid: pg_sys::ItemPointer,
+    _snapshot: pg_sys::Snapshot,
+    _slot: *mut pg_sys::TupleTableSlot,
+    _call_again: *mut bool,
+    _all_dead: *mut bool,
+) -> bool {
+    pg_sys::guard::guard(|| unsupported("index scan"))
+}
+
+unsafe extern "C" fn tuple_fetch_row_version(
+    _relation: pg_sys::Relation,
+    _tid: pg_sys::ItemPointer,
+    _snapshot: pg_sys::Snapshot,
+    _slot: *mut pg_sys::TupleTableSlot,
+) -> bool {
+    pg_sys::guard::guard(|| unsupported("fetching a tuple by tid"))
+}
+
+unsafe extern "C" fn tuple_tid_valid(
+    _scan: pg_sys::TableScanDesc,
+    _tid: pg_sys::ItemPointer,
+) -> bool {
+    pg_sys::guard::guard(|| unsupported("tid scan"))
+}
+
+unsafe extern "C" fn tuple_get_latest_tid(_scan: pg_sys::TableScanDesc, _tid: pg_sys::ItemPointer) {
+    pg_sys::guard::guard(|| unsupported("tid scan"))
+}
+
+unsafe extern "C" fn tuple_satisfies_snapshot(
+    _relation: pg_sys::Relation,
+    _slot: *mut pg_sys::TupleTableSlot,
+    _snapshot: pg_sys::Snapshot,
+) -> bool {
+    pg_sys::guard::guard(|| unsupported("checking tuple visibility"))
+}
+
+#[cfg(any(feature = "pg12", feature = "pg13"))]
+unsafe extern "C" fn compute_xid_horizon_for_tuples(
+    _relation: pg_sys::Relation,
+    _items: *mut pg_sys::ItemPointerData,
+    _nitems: c_int,
+) -> pg_sys::TransactionId {
+    pg_sys::guard::guard(|| unsupported("index tuple deletion"))
+}
+
+#[cfg(feature = "pg14")]
+unsafe extern "C" fn index_delete_tuples(
+    _relation: pg_sys::Relation,
+    _delete_state: *mut pg_sys::TM_IndexDeleteOp,
+) -> pg_sys::TransactionId {
+    pg_sys::guard::guard(|| unsupported("index tuple deletion"))
+}
+
+unsafe extern "C" fn tuple_insert<T: TableAm>(
+    relation: pg_sys::Relation,
+    slot: *mut pg_sys::TupleTableSlot,
+    cid: pg_sys::CommandId,
+    options: c_int,
+    _bistate: *mut pg_sys::BulkInsertStateData,
+) {
+    pg_sys::guard::guard(|| {
+        (*slot).tts_tableOid = (*relation).rd_id;
+        T::tuple_insert(
+            &PgRelation::from_pg(relation),
+            &mut PgTupleTableSlot::from_pg(slot),
+            cid,
+            options,
+        )
+    })
+}
+
+unsafe extern "C" fn tuple_insert_speculative(
+    _relation: pg_sys::Relation,
+    _slot: *mut pg_sys::TupleTableSlot,
+    _cid: pg_sys::CommandId,
+    _options: c_int,
+    _bistate: *mut pg_sys::BulkInsertStateData,
+    _spec_token: pg_sys::uint32,
+) {
+    pg_sys::guard::guard(|| unsupported("INSERT ... ON CONFLICT"))
+}
+
+unsafe extern "C" fn tuple_complete_speculative(
+    _relation: pg_sys::Relation,
+    _slot: *mut pg_sys::TupleTableSlot,
+    _spec_token: pg_sys::uint32,
+    _succeeded: bool,
+) {
+    pg_sys::guard::guard(|| unsupported("INSERT ... ON CONFLICT"))
+}
+
+unsafe extern "C" fn multi_insert<T: TableAm>(
+    relation: pg_sys::Relation,
+    slots: *mut *mut pg_sys::TupleTableSlot,
+    nslots: c_int,
+    cid: pg_sys::CommandId,
+    options: c_int,
+    _bistate: *mut pg_sys::BulkInsertStateData,
+) {
+    pg_sys::guard::guard(|| {
+        let pg_relation = PgRelation::from_pg(relation);
+        for &slot in std::slice::from_raw_parts(slots, nslots as usize) {
+            (*slot).tts_tableOid = (*relation).rd_id;
+            T::tuple_insert(
+                &pg_relation,
+                &mut PgTupleTableSlot::from_pg(slot),
+                cid,
+                options,
+            );
+        }
+    })
+}
+
+unsafe extern "C" fn tuple_delete<T: TableAm>(
+    relation: pg_sys::Relation,
+    tid: pg_sys::ItemPointer,
+    cid: pg_sys::CommandId,
+    snapshot: pg_sys::Snapshot,
+    _crosscheck: pg_sys::Snapshot,
+    wait: bool,
+    failure_data: *mut pg_sys::TM_FailureData,
+    _changing_part: bool,
+) -> pg_sys::TM_Result {
+    pg_sys::guard::guard(|| {
+        T::tuple_delete(
+            &PgRelation::from_pg(relation),
+            *tid,
+            cid,
+            snapshot,
+            wait,
+            &mut *failure_data,
+        )
+    })
+}
+
+unsafe extern "C" fn tuple_update<T: TableAm>(
+    relation: pg_sys::Relation,
+    old_tid: pg_sys::ItemPointer,
+    slot: *mut pg_sys::TupleTableSlot,
+    cid: pg_sys::CommandId,
+    snapshot: pg_sys::Snapshot,
+    _crosscheck: pg_sys::Snapshot,
+    wait: bool,
+    failure_data: *mut pg_sys::TM_FailureData,
+    lock_mode: *mut pg_sys::LockTupleMode,
+    update_indexes: *mut bool,
+) -> pg_sys::TM_Result {
+    pg_sys::guard::guard(|| {
+        (*slot).tts_tableOid = (*relation).rd_id;
+        *lock_mode = pg_sys::LockTupleMode_LockTupleExclusive;
+        *update_indexes = false;
+        T::tuple_update(
+            &PgRelation::from_pg(relation),
+            *old_tid,
+            &mut PgTupleTableSlot::from_pg(slot),
+            cid,
+            snapshot,
+            wait,
+            &mut *failure_data,
+            &mut *update_indexes,
+        )
+    })
+}
+
+unsafe extern "C" fn tuple_lock<T: TableAm>(
+    relation: pg_sys::Relation,
+    tid: pg_sys::ItemPointer,
+    snapshot: pg_sys::Snapshot,
+    slot: *mut pg_sys::TupleTableSlot,
+    cid: pg_sys::CommandId,
+    mode: pg_sys::LockTupleMode,
+    wait_policy: pg_sys::LockWaitPolicy,
+    _flags: pg_sys::uint8,
+    failure_data: *mut pg_sys::TM_FailureData,
+) -> pg_sys::TM_Result {
+    pg_sys::guard::guard(|| {
+        T::tuple_lock(
+            &PgRelation::from_pg(relation),
+            *tid,
+            snapshot,
+            &mut PgTupleTableSlot::from_pg(slot),
+            cid,
+            mode,
+            wait_policy,
+            &mut *failure_data,
+        )
+    })
+}
+
+unsafe extern "C" fn relation_set_new_filenode<T: TableAm>(
+    relation: pg_sys::Relation,
+    new_rnode: *const pg_sys::RelFileNode,
+    persistence: c_char,
+    freeze_xid: *mut pg_sys::TransactionId,
+    min_multi: *mut pg_sys::MultiXactId,
+) {
+    pg_sys::guard::guard(|| {
+        let (xid, multi) =
+            T::relation_set_new_filenode(&PgRelation::from_pg(relation), &*new_rnode, persistence);
+        *freeze_xid = xid;
+        *min_multi = multi;
+    })
+}
+
+unsafe extern "C" fn relation_nontransactional_truncate(_relation: pg_sys::Relation) {
+    pg_sys::guard::guard(|| unsupported("non-transactional TRUNCATE"))
+}
+
+unsafe extern "C" fn relation_copy_data(
+    _relation: pg_sys::Relation,
+    _new_rnode: *const pg_sys::RelFileNode,
+) {
+    pg_sys::guard::guard(|| unsupported("ALTER TABLE ... SET TABLESPACE"))
+}
+
+unsafe extern "C" fn relation_copy_for_cluster(
+    _new_table: pg_sys::Relation,
+    _old_table: pg_sys::Relation,
+    _old_index: pg_sys::Relation,
+    _use_sort: bool,
+    _oldest_xmin: pg_sys::TransactionId,
+    _xid_cutoff: *mut pg_sys::TransactionId,
+    _multi_cutoff: *mut pg_sys::MultiXactId,
+    _num_tuples: *mut f64,
+    _tups_vacuumed: *mut f64,
+    _tups_recently_dead: *mut f64,
+) {
+    pg_sys::guard::guard(|| unsupported("CLUSTER and VACUUM FULL"))
+}
+
+unsafe extern "C" fn relation_vacuum<T: TableAm>(
+    relation: pg_sys::Relation,
+    params: *mut pg_sys::VacuumParams,
+    _bstrategy: pg_sys::BufferAccessStrategy,
+) {
+    pg_sys::guard::guard(|| T::relation_vacuum(&PgRelation::from_pg(relation), &mut *params))
+}
+
+unsafe extern "C" fn scan_analyze_next_block(
+    _scan: pg_sys::TableScanDesc,
+    _blockno: pg_sys::BlockNumber,
+    _bstrategy: pg_sys::BufferAccessStrategy,
+) -> bool {
+    false
+}
+
+unsafe extern "C" fn scan_analyze_next_tuple(
+    _scan: pg_sys::TableScanDesc,
+    _oldest_xmin: pg_sys::TransactionId,
+    _liverows: *mut f64,
+    _deadrows: *mut f64,
+    _slot: *mut pg_sys::TupleTableSlot,
+) -> bool {
+    false
+}
+
+unsafe extern "C" fn index_build_range_scan(
+    _table_relation: pg_sys::Relation,
+    _index_relation: pg_sys::Relation,
+    _index_info: *mut pg_sys::IndexInfo,
+    _allow_sync: bool,
+    _any_visible: bool,
+    _progress: bool,
+    _start_blockno: pg_sys::BlockNumber,
+    _numblocks: pg_sys::BlockNumber,
+    _callback: pg_sys::IndexBuildCallback,
+    _callback_state: *mut c_void,
+    _scan: pg_sys::TableScanDesc,
+) -> f64 {
+    pg_sys::guard::guard(|| unsupported("CREATE INDEX"))
+}
+
+unsafe extern "C" fn index_validate_scan(
+    _table_relation: pg_sys::Relation,
+    _index_relation: pg_sys::Relation,
+    _index_info: *mut pg_sys::IndexInfo,
+    _snapshot: pg_sys::Snapshot,
+    _state: *mut pg_sys::ValidateIndexState,
+) {
+    pg_sys::guard::guard(|| unsupported("CREATE INDEX CONCURRENTLY"))
+}
+
+unsafe extern "C" fn relation_size<T: TableAm>(
+    relation: pg_sys::Relation,
+    fork: pg_sys::ForkNumber,
+) -> pg_sys::uint64 {
+    pg_sys::guard::guard(|| T::relation_size(&PgRelation::from_pg(relation), fork))
+}
+
+unsafe extern "C" fn relation_needs_toast_table(_relation: pg_sys::Relation) -> bool {
+    false
+}
+
+unsafe extern "C" fn relation_estimate_size<T: TableAm>(
+    relation: pg_sys::Relation,
+    _attr_widths: *mut pg_sys::int32,
+    pages: *mut pg_sys::BlockNumber,
+    tuples: *mut f64,
+    allvisfrac: *mut f64,
+) {
+    pg_sys::guard::guard(|| {
+        let estimate = T::relation_estimate_size(&PgRelation::from_pg(relation));
+        *pages = estimate.pages;
+        *tuples = estimate.tuples;
+        *allvisfrac = estimate.allvisfrac;
+    })
+}
+
+unsafe extern "C" fn scan_sample_next_block(
+    _scan: pg_sys::TableScanDesc,
+    _scan_state: *mut pg_sys::SampleScanState,
+) -> bool {
+    pg_sys::guard::guard(|| unsupported("TABLESAMPLE"))
+}
+
+unsafe extern "C" fn scan_sample_next_tuple(
+    _scan: pg_sys::TableScanDesc,
+    _scan_state: *mut pg_sys::SampleScanState,
+    _slot: *mut pg_sys::TupleTableSlot,
+) -> bool {
+    pg_sys::guard::guard(|| unsupported("TABLESAMPLE"))
+}
@@ -3,7 +3,180 @@
 
 //! Helper functions for working with custom Rust trigger functions
 
-use crate::{is_a, pg_sys};
+use crate::{is_a, pg_sys, PgBox, PgHeapTuple, PgRelation, PgTupleDesc};
+use std::ffi::CStr;
+
+/// When a trigger fired, relative to the event that fired it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgTriggerWhen {
+    Before,
+    After,
+    InsteadOf,
+}
+
+/// Whether a trigger fired once per row or once per statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgTriggerLevel {
+    Row,
+    Statement,
+}
+
+/// The operation that fired a trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgTriggerOperation {
+    Insert,
+    Update,
+    Delete,
+    Truncate,
+}
+
+/// A safe wrapper around the `pg_sys::TriggerData` Postgres passes to a trigger function
+///
+/// This is what a `#[pg_trigger]` function receives:
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_trigger]
+/// fn keep_old_rows(trigger: &PgTrigger) -> Option<PgHeapTuple<'_>> {
+///     match trigger.op() {
+///         // skip the delete
+///         PgTriggerOperation::Delete => None,
+///         _ => trigger.new(),
+///     }
+/// }
+/// ```
+pub struct PgTrigger {
+    trigdata: PgBox<pg_sys::TriggerData>,
+    relation: PgRelation,
+}
+
+impl PgTrigger {
+    /// Wrap the `pg_sys::TriggerData` Postgres passed to a trigger function as `fcinfo->context`
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as we cannot validate that `fcinfo` is valid
+    ///
+    /// ## Panics
+    ///
+    /// If the function wasn't called by the trigger manager
+    pub unsafe fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        if !called_as_trigger(fcinfo) {
+            panic!("not called by trigger manager");
+        }
+
+        let trigdata = PgBox::from_pg((*fcinfo).context as *mut pg_sys::TriggerData);
+        let relation = PgRelation::from_pg(trigdata.tg_relation);
+        PgTrigger { trigdata, relation }
+    }
+
+    /// The trigger's name, from `CREATE TRIGGER`
+    pub fn name(&self) -> &str {
+        unsafe { CStr::from_ptr(self.trigger().tgname) }
+            .to_str()
+            .expect("trigger name is not valid UTF8")
+    }
+
+    pub fn when(&self) -> PgTriggerWhen {
+        let event = self.trigdata.tg_event;
+        if trigger_fired_before(event) {
+            PgTriggerWhen::Before
+        } else if trigger_fired_instead(event) {
+            PgTriggerWhen::InsteadOf
+        } else {
+            PgTriggerWhen::After
+        }
+    }
+
+    pub fn level(&self) -> PgTriggerLevel {
+        if trigger_fired_for_row(self.trigdata.tg_event) {
+            PgTriggerLevel::Row
+        } else {
+            PgTriggerLevel::Statement
+        }
+    }
+
+    pub fn op(&self) -> PgTriggerOperation {
+        let event = self.trigdata.tg_event;
+        if trigger_fired_by_insert(event) {
+            PgTriggerOperation::Insert
+        } else if trigger_fired_by_update(event) {
+            PgTriggerOperation::Update
+        } else if trigger_fired_by_delete(event) {
+            PgTriggerOperation::Delete
+        } else if trigger_fired_by_truncate(event) {
+            PgTriggerOperation::Truncate
+        } else {
+            panic!("unrecognized trigger event: {}", event)
+        }
+    }
+
+    /// The arguments given to the trigger function in `CREATE TRIGGER`
+    pub fn args(&self) -> Vec<&str> {
+        let trigger = self.trigger();
+        (0..trigger.tgnargs as usize)
+            .map(|i| {
+                unsafe { CStr::from_ptr(*trigger.tgargs.add(i)) }
+                    .to_str()
+                    .expect("trigger argument is not valid UTF8")
+            })
+            .collect()
+    }
+
+    /// The table (or view) the trigger fired on
+    pub fn relation(&self) -> &PgRelation {
+        &self.relation
+    }
+
+    /// For a row-level `UPDATE` or `DELETE`, the row as it was before the operation
+    pub fn old(&self) -> Option<PgHeapTuple<'_>> {
+        match self.op() {
+            PgTriggerOperation::Update | PgTriggerOperation::Delete => {
+                self.heap_tuple(self.trigdata.tg_trigtuple)
+            }
+            _ => None,
+        }
+    }
+
+    /// For a row-level `INSERT` or `UPDATE`, the row as it will be after the operation
+    ///
+    /// Return this, or a modified copy of it, from a `BEFORE` trigger to let the operation
+    /// proceed.
+    #[allow(clippy::new_ret_no_self)] // it's `NEW`, as in PL/pgSQL
+    pub fn new(&self) -> Option<PgHeapTuple<'_>> {
+        match self.op() {
+            PgTriggerOperation::Insert => self.heap_tuple(self.trigdata.tg_trigtuple),
+            PgTriggerOperation::Update => self.heap_tuple(self.trigdata.tg_newtuple),
+            _ => None,
+        }
+    }
+
+    /// The `pg_sys::TriggerData` we're wrapping
+    pub fn trigger_data(&self) -> &pg_sys::TriggerData {
+        &self.trigdata
+    }
+
+    fn trigger(&self) -> &pg_sys::Trigger {
+        unsafe {
+            self.trigdata
+                .tg_trigger
+                .as_ref()
+                .expect("TriggerData has no trigger")
+        }
+    }
+
+    fn heap_tuple(&self, tuple: pg_sys::HeapTuple) -> Option<PgHeapTuple<'_>> {
+        if tuple.is_null() {
+            None
+        } else {
+            // SAFETY:  the trigger manager gave us this tuple, and it's of our relation's row type
+            Some(unsafe {
+                PgHeapTuple::from_heap_tuple(PgTupleDesc::from_relation(&self.relation), tuple)
+            })
+        }
+    }
+}
 
 #[inline]
 pub unsafe fn called_as_trigger(fcinfo: pg_sys::FunctionCallInfo) -> bool {
@@ -13,6 +13,16 @@ pub fn regtypein(type_name: &str) -> pg_sys::Oid {
     }
 }
 
+/// A helper function for Postgres' `format_type_be` function to get the SQL name of a type,
+/// such as `integer` or `character varying`
+///
+/// Will panic if Postgres can't find the type
+pub fn format_type(typoid: pg_sys::Oid) -> String {
+    unsafe { std::ffi::CStr::from_ptr(pg_sys::format_type_be(typoid)) }
+        .to_string_lossy()
+        .into_owned()
+}
+
 /// A helper function for Postgres' `regtypein` function to lookup a type using the name of a Rust type
 ///
 /// We truncate the type name to its last value, unless its a primitive type.